@@ -2,28 +2,36 @@ use candid::{Principal, Nat};
 use ic_stable_structures::memory_manager::MemoryId;
 use ic_stable_structures::{StableBTreeMap, StableCell};
 use std::cell::RefCell;
+use std::collections::HashSet;
 use std::time::Duration;
 // ckUSDT types defined in types.rs
-use crate::types::{Account, TransferFromArgs, TransferFromError, TransferArg, TransferError, CKUSDT_CANISTER_ID, CKUSDT_TRANSFER_FEE};
+use crate::types::{Account, TransferFromArgs, TransferFromError, TransferArg, TransferError, CKUSDT_CANISTER_ID};
 
 use crate::{MEMORY_MANAGER, Memory};
 use super::liquidity_pool;
-use super::types::{PendingWithdrawal, WithdrawalType, AuditEntry, AuditEvent};
+use super::deposit_keys::{self, DepositKeyStart};
+use super::types::{DepositReceipt, GameError, GameEvent, PendingWithdrawal, WithdrawalType, AuditEntry, AuditEvent, ReconcileReport, TokenInfo, WithdrawalState, WithdrawalStatusDetail};
 
 use super::memory_ids::{
     USER_BALANCES_MEMORY_ID,
     PENDING_WITHDRAWALS_MEMORY_ID,
     AUDIT_LOG_MAP_MEMORY_ID,
     AUDIT_LOG_COUNTER_MEMORY_ID,
+    LEDGER_CANISTER_MEMORY_ID,
+    TOKEN_INFO_MEMORY_ID,
+    AUDIT_RETENTION_MEMORY_ID,
 };
 
 // Constants
-const MIN_DEPOSIT: u64 = 1_000_000; // 1 USDT
-const MIN_WITHDRAW: u64 = 1_000_000; // 1 USDT
-const MAX_AUDIT_ENTRIES: u64 = 1000; // Retention limit
-/// Minimum balance before triggering automatic weekly withdrawal to parent canister.
-/// Set to 10 USDT to minimize gas costs while ensuring timely fee collection.
-const PARENT_AUTO_WITHDRAW_THRESHOLD: u64 = 10_000_000; // 10 USDT
+// Minimums in whole tokens, scaled by the ledger's decimals (see `token_unit`)
+const MIN_DEPOSIT_TOKENS: u64 = 1; // 1 USDT
+const MIN_WITHDRAW_TOKENS: u64 = 1; // 1 USDT
+/// Audit log retention: admin-configurable within these bounds (oldest entries pruned first)
+pub(crate) const DEFAULT_AUDIT_RETENTION: u64 = 1000;
+pub(crate) const MIN_AUDIT_RETENTION: u64 = 100;
+pub(crate) const MAX_AUDIT_RETENTION: u64 = 100_000;
+/// ICRC ledger deduplication window: retries with an older `created_at` fail as TooOld
+pub const LEDGER_DEDUP_WINDOW_NANOS: u64 = 24 * 60 * 60 * 1_000_000_000;
 
 thread_local! {
     pub(crate) static USER_BALANCES_STABLE: RefCell<StableBTreeMap<Principal, u64, Memory>> = RefCell::new(
@@ -39,7 +47,7 @@ thread_local! {
     );
 
     // Audit trail with automatic pruning
-    // Stores up to 1,000 entries using BTreeMap with sequential keys
+    // Stores up to the configured retention (default 1,000) using BTreeMap with sequential keys
     // Oldest entries are automatically removed when limit is exceeded
     static AUDIT_LOG_MAP: RefCell<StableBTreeMap<u64, AuditEntry, Memory>> = RefCell::new(
         StableBTreeMap::init(
@@ -54,11 +62,136 @@ thread_local! {
         )
     );
 
+    static AUDIT_RETENTION: RefCell<StableCell<u64, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(AUDIT_RETENTION_MEMORY_ID))),
+            DEFAULT_AUDIT_RETENTION
+        )
+    );
+
+    // Ledger metadata (symbol, decimals, fee), fetched once at init
+    static TOKEN_INFO: RefCell<StableCell<TokenInfo, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(TOKEN_INFO_MEMORY_ID))),
+            TokenInfo::default()
+        )
+    );
+
+    // ICRC ledger for all token movements: ckUSDT unless an admin points it at a test ledger
+    static LEDGER_CANISTER_ID: RefCell<StableCell<Principal, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(LEDGER_CANISTER_MEMORY_ID))),
+            Principal::from_text(CKUSDT_CANISTER_ID).expect("Invalid principal constant")
+        )
+    );
+
     static CACHED_CANISTER_BALANCE: RefCell<u64> = const { RefCell::new(0) };
+
+    // Users whose withdrawal transfer is awaiting the ledger (heap only: nothing is in flight after an upgrade)
+    pub(crate) static WITHDRAWALS_IN_FLIGHT: RefCell<HashSet<Principal>> = RefCell::new(HashSet::new());
     static PARENT_TIMER: RefCell<Option<ic_cdk_timers::TimerId>> = const { RefCell::new(None) };
     static RECONCILIATION_TIMER: RefCell<Option<ic_cdk_timers::TimerId>> = const { RefCell::new(None) };
 }
 
+// =============================================================================
+// TOKEN METADATA
+// =============================================================================
+
+pub fn get_token_info() -> TokenInfo {
+    TOKEN_INFO.with(|t| t.borrow().get().clone())
+}
+
+pub fn get_token_decimals() -> u8 {
+    TOKEN_INFO.with(|t| t.borrow().get().decimals)
+}
+
+/// Smallest-unit amount of one whole token (10^decimals)
+pub(crate) fn token_unit() -> u64 {
+    10u64.saturating_pow(get_token_decimals() as u32)
+}
+
+pub(crate) fn min_deposit() -> u64 {
+    MIN_DEPOSIT_TOKENS.saturating_mul(token_unit())
+}
+
+pub(crate) fn min_withdraw() -> u64 {
+    MIN_WITHDRAW_TOKENS.saturating_mul(token_unit())
+}
+
+/// Ledger fee charged on every transfer we send or pull
+pub(crate) fn transfer_fee() -> u64 {
+    TOKEN_INFO.with(|t| t.borrow().get().fee)
+}
+
+#[cfg(test)]
+pub(crate) fn set_token_info(info: TokenInfo) {
+    TOKEN_INFO.with(|t| t.borrow_mut().set(info));
+}
+
+/// Smallest-unit `amount` in whole tokens for messages, e.g. "1.5 ckUSDT"
+pub(crate) fn format_token_amount(amount: u64) -> String {
+    let info = get_token_info();
+    let unit = token_unit();
+    let fraction = amount % unit;
+    if fraction == 0 {
+        return format!("{} {}", amount / unit, info.symbol);
+    }
+    let digits = format!("{:0width$}", fraction, width = info.decimals as usize);
+    format!("{}.{} {}", amount / unit, digits.trim_end_matches('0'), info.symbol)
+}
+
+pub fn get_ledger_canister_id() -> Principal {
+    LEDGER_CANISTER_ID.with(|l| *l.borrow().get())
+}
+
+/// Point deposits, withdrawals and balance checks at another ICRC ledger (admin check is
+/// done by the caller in `admin_query`). Balances are denominated in the current ledger's
+/// token, so switching is only allowed while nothing is owed.
+pub(crate) fn set_ledger_canister_id(ledger: Principal) -> Result<(), String> {
+    // Canister ids are opaque principals (class byte 0x01); this also rejects the anonymous principal
+    if ledger.as_slice().last() != Some(&0x01) {
+        return Err(format!("{} is not a canister id", ledger));
+    }
+    if super::pool_drain::get_pending_drain().is_some() {
+        return Err("Ledger cannot change while a pool drain is unresolved".to_string());
+    }
+    let total_deposits = calculate_total_deposits();
+    let pool_reserve = liquidity_pool::get_pool_reserve();
+    let pending = PENDING_WITHDRAWALS.with(|p| p.borrow().len());
+    if total_deposits > 0 || pool_reserve > 0 || pending > 0 {
+        return Err(format!(
+            "Ledger can only change while nothing is owed (deposits {}, pool reserve {}, pending withdrawals {})",
+            total_deposits, pool_reserve, pending
+        ));
+    }
+    LEDGER_CANISTER_ID.with(|l| l.borrow_mut().set(ledger));
+    Ok(())
+}
+
+/// Fetch symbol, decimals and fee from the ledger and cache them in stable memory.
+/// On any call failure the previously cached values are kept.
+#[allow(deprecated)]
+pub async fn refresh_token_info() -> TokenInfo {
+    let ledger = get_ledger_canister_id();
+
+    let symbol: Result<(String,), _> = ic_cdk::api::call::call(ledger, "icrc1_symbol", ()).await;
+    let decimals: Result<(u8,), _> = ic_cdk::api::call::call(ledger, "icrc1_decimals", ()).await;
+    let fee: Result<(Nat,), _> = ic_cdk::api::call::call(ledger, "icrc1_fee", ()).await;
+
+    match (symbol, decimals, fee) {
+        (Ok((symbol,)), Ok((decimals,)), Ok((fee,))) => {
+            let info = TokenInfo {
+                symbol,
+                decimals,
+                fee: fee.0.try_into().unwrap_or(u64::MAX),
+            };
+            TOKEN_INFO.with(|t| t.borrow_mut().set(info.clone()));
+            info
+        }
+        _ => get_token_info(),
+    }
+}
+
 // =============================================================================
 // CACHED BALANCE TRACKING
 // =============================================================================
@@ -91,7 +224,21 @@ pub(crate) enum TransferResult {
 // HELPER FUNCTIONS
 // =============================================================================
 
+/// Reject the anonymous principal. Every play, deposit and withdraw path calls this
+/// first, so an unauthenticated call fails clearly before any state is read or written.
+pub fn require_authenticated(caller: Principal) -> Result<(), String> {
+    if caller == Principal::anonymous() {
+        return Err("Authentication required".to_string());
+    }
+    Ok(())
+}
+
 pub(crate) fn log_audit(event: AuditEvent) {
+    log_audit_at(event, ic_cdk::api::time());
+}
+
+/// `log_audit` with an explicit timestamp (for code paths that are tested natively)
+pub(crate) fn log_audit_at(event: AuditEvent, timestamp: u64) {
     // Get next counter value and increment (saturating_add prevents overflow)
     let idx = AUDIT_LOG_COUNTER.with(|counter| {
         let mut cell = counter.borrow_mut();
@@ -102,7 +249,7 @@ pub(crate) fn log_audit(event: AuditEvent) {
 
     // Create and insert entry
     let entry = AuditEntry {
-        timestamp: ic_cdk::api::time(),
+        timestamp,
         event,
     };
 
@@ -112,11 +259,39 @@ pub(crate) fn log_audit(event: AuditEvent) {
 
     // Prune if over limit (using saturating_sub for safety)
     let len = AUDIT_LOG_MAP.with(|log| log.borrow().len());
-    if len > MAX_AUDIT_ENTRIES {
-        prune_oldest_audit_entries(len.saturating_sub(MAX_AUDIT_ENTRIES));
+    let retention = get_audit_retention();
+    if len > retention {
+        prune_oldest_audit_entries(len.saturating_sub(retention));
     }
 }
 
+/// Maximum number of audit entries kept
+pub(crate) fn get_audit_retention() -> u64 {
+    AUDIT_RETENTION.with(|r| *r.borrow().get())
+}
+
+pub(crate) fn validate_audit_retention(limit: u64) -> Result<(), String> {
+    if !(MIN_AUDIT_RETENTION..=MAX_AUDIT_RETENTION).contains(&limit) {
+        return Err(format!(
+            "Audit retention {} out of range ({}..={})",
+            limit, MIN_AUDIT_RETENTION, MAX_AUDIT_RETENTION
+        ));
+    }
+    Ok(())
+}
+
+/// Change the audit retention limit (admin check is done by the caller in `admin_query`).
+/// Lowering it prunes the oldest entries immediately; raising it just allows growth.
+pub(crate) fn set_audit_retention(limit: u64) -> Result<(), String> {
+    validate_audit_retention(limit)?;
+    AUDIT_RETENTION.with(|r| r.borrow_mut().set(limit));
+    let len = get_audit_count();
+    if len > limit {
+        prune_oldest_audit_entries(len - limit);
+    }
+    Ok(())
+}
+
 fn prune_oldest_audit_entries(count: u64) {
     AUDIT_LOG_MAP.with(|log| {
         let mut log = log.borrow_mut();
@@ -144,23 +319,37 @@ fn calculate_total_deposits() -> u64 {
 // DEPOSIT FUNCTION (ICRC-2)
 // =============================================================================
 
+/// Deposit `amount` via ICRC-2 `transfer_from`.
+///
+/// With an `idempotency_key`, retries of the same deposit never transfer twice:
+/// see `deposit_keys` for the retry semantics.
+pub async fn deposit(amount: u64, idempotency_key: Option<[u8; 16]>) -> Result<DepositReceipt, GameError> {
+    deposit_internal(ic_cdk::api::msg_caller(), amount, idempotency_key).await
+}
+
 #[allow(deprecated)]
-pub async fn deposit(amount: u64) -> Result<u64, String> {
-    if amount < MIN_DEPOSIT {
-        return Err(format!("Minimum deposit is {} USDT", MIN_DEPOSIT / 1_000_000));
+pub(crate) async fn deposit_internal(caller: Principal, amount: u64, idempotency_key: Option<[u8; 16]>) -> Result<DepositReceipt, GameError> {
+    require_authenticated(caller).map_err(|_| GameError::Unauthorized)?;
+
+    if amount < min_deposit() {
+        return Err(GameError::BelowMinimum { amount, minimum: min_deposit() });
     }
 
     // Maximum user deposit: 1B USDT. Higher than LP limit (100M) - no share calculations.
     const MAX_USER_DEPOSIT: u64 = 1_000_000_000_000;
     if amount > MAX_USER_DEPOSIT {
-        return Err(format!(
-            "Maximum deposit is {} USDT (overflow protection)",
-            MAX_USER_DEPOSIT / 1_000_000
-        ));
+        return Err(GameError::AboveMaximum { amount, maximum: MAX_USER_DEPOSIT });
     }
 
-    let caller = ic_cdk::api::msg_caller();
-    let ck_usdt_principal = Principal::from_text(CKUSDT_CANISTER_ID).expect("Invalid principal constant");
+    let ck_usdt_principal = get_ledger_canister_id();
+
+    let created_at_time = match idempotency_key {
+        Some(key) => match deposit_keys::begin(caller, key, amount, ic_cdk::api::time())? {
+            DepositKeyStart::Completed(receipt) => return Ok(receipt),
+            DepositKeyStart::Proceed { created_at } => Some(created_at),
+        },
+        None => None,
+    };
 
     let args = TransferFromArgs {
         spender_subaccount: None,
@@ -170,98 +359,193 @@ pub async fn deposit(amount: u64) -> Result<u64, String> {
         // Explicitly charge the fee to the sender.
         // This prevents the protocol from "eating" the fee (insolvency risk).
         // If the ledger creates a surplus from this, it is Protocol Profit (safe).
-        fee: Some(Nat::from(CKUSDT_TRANSFER_FEE)), 
-        memo: None,
-        created_at_time: None,
+        fee: Some(Nat::from(transfer_fee())),
+        // Memo + created_at_time let the ledger deduplicate keyed retries
+        memo: idempotency_key.map(|k| k.to_vec()),
+        created_at_time,
     };
 
     let (result,): (Result<Nat, TransferFromError>,) =
         ic_cdk::api::call::call(ck_usdt_principal, "icrc2_transfer_from", (args,))
         .await
-        .map_err(|(code, msg)| format!("Call failed: {:?} {}", code, msg))?;
+        .map_err(|(code, msg)| {
+            // Uncertain outcome: keep the key so a retry is deduplicated by the ledger
+            if let Some(key) = idempotency_key {
+                deposit_keys::mark_uncertain(caller, key);
+            }
+            format!("Call failed: {:?} {}", code, msg)
+        })?;
+
+    // A duplicate of our own keyed transfer means an earlier attempt reached the
+    // ledger but was never credited here, so it is credited now
+    let result = match (result, idempotency_key) {
+        (Err(TransferFromError::Duplicate { duplicate_of }), Some(_)) => Ok(duplicate_of),
+        (result, _) => result,
+    };
 
     match result {
-        Ok(_block_index) => {
-            // Credit user with the full amount
-            // ICRC-2 transfer_from ACTUAL behavior:
-            // - User pays: amount + fee (debited from user's account)
-            // - Canister receives: amount (full amount)
-            // - Fee is burned/collected by the ledger
-            //
-            // Net Canister Balance: +amount (user already paid the fee)
-            // User Balance Credit: amount (full amount received)
-
-            let new_balance = USER_BALANCES_STABLE.with(|balances| {
-                let mut balances = balances.borrow_mut();
-                let current = balances.get(&caller).unwrap_or(0);
-                let new_bal = current.checked_add(amount)
-                    .ok_or_else(|| "Balance overflow".to_string())?;
-                balances.insert(caller, new_bal);
-                Ok::<u64, String>(new_bal)
-            })?;
-
-            // Update cached canister balance (canister received `amount`)
-            increment_cached_balance(amount);
-
-            Ok(new_balance)
+        Ok(block_index) => {
+            let block_index = block_index.0.try_into().unwrap_or(0);
+            credit_deposit(caller, amount, block_index, idempotency_key, ic_cdk::api::time())
+        }
+        Err(e) => {
+            if let Some(key) = idempotency_key {
+                deposit_keys::abort(caller, key);
+            }
+            Err(format!("Transfer failed: {:?}", e).into())
         }
-        Err(e) => Err(format!("Transfer failed: {:?}", e)),
     }
 }
 
+/// Credit a deposit whose `transfer_from` landed in ledger block `block_index`
+pub(crate) fn credit_deposit(
+    caller: Principal,
+    amount: u64,
+    block_index: u64,
+    idempotency_key: Option<[u8; 16]>,
+    now: u64,
+) -> Result<DepositReceipt, GameError> {
+    // Credit user with the full amount
+    // ICRC-2 transfer_from ACTUAL behavior:
+    // - User pays: amount + fee (debited from user's account)
+    // - Canister receives: amount (full amount)
+    // - Fee is burned/collected by the ledger
+    //
+    // Net Canister Balance: +amount (user already paid the fee)
+    // User Balance Credit: amount (full amount received)
+
+    let new_balance = USER_BALANCES_STABLE.with(|balances| {
+        let mut balances = balances.borrow_mut();
+        let current = balances.get(&caller).unwrap_or(0);
+        let new_bal = current.checked_add(amount)
+            .ok_or_else(|| "Balance overflow".to_string())?;
+        balances.insert(caller, new_bal);
+        Ok::<u64, String>(new_bal)
+    })?;
+
+    // Update cached canister balance (canister received `amount`)
+    increment_cached_balance(amount);
+    log_audit_at(AuditEvent::BalanceCredited { user: caller, amount, new_balance, block_index: Some(block_index) }, now);
+    super::events::notify(GameEvent::Deposit { user: caller, amount, new_balance });
+
+    let receipt = DepositReceipt { new_balance, block_index };
+    if let Some(key) = idempotency_key {
+        deposit_keys::complete(caller, key, &receipt);
+    }
+    Ok(receipt)
+}
+
+/// Run `deposit`, then `play` with the credited funds (`deposit_and_play` endpoints).
+///
+/// Futures do nothing until awaited, so the bet starts only after the deposit is credited.
+/// Bets debit the balance only once they are placed, so a bet that fails leaves the
+/// full deposit in the caller's balance; the error says so.
+pub(crate) async fn deposit_and_play<T, E: std::fmt::Display>(
+    deposit: impl std::future::Future<Output = Result<DepositReceipt, GameError>>,
+    play: impl std::future::Future<Output = Result<T, E>>,
+) -> Result<T, String> {
+    let receipt = deposit.await.map_err(String::from)?;
+    play.await.map_err(|e| format!(
+        "Bet not placed: {}. Deposit credited: balance {} decimals (ledger block {})",
+        e, receipt.new_balance, receipt.block_index
+    ))
+}
+
 // =============================================================================
 // WITHDRAW FUNCTION
 // =============================================================================
 
-pub async fn withdraw_all() -> Result<u64, String> {
+pub async fn withdraw_all() -> Result<u64, GameError> {
     let caller = ic_cdk::api::msg_caller();
     withdraw_internal(caller).await
 }
 
-pub(crate) async fn withdraw_internal(user: Principal) -> Result<u64, String> {
+/// Withdraw everything above `keep_amount`, leaving exactly `keep_amount` deposited.
+pub async fn withdraw_keeping(keep_amount: u64) -> Result<u64, GameError> {
+    let caller = ic_cdk::api::msg_caller();
+    withdraw_keeping_internal(caller, keep_amount).await
+}
+
+pub(crate) async fn withdraw_internal(user: Principal) -> Result<u64, GameError> {
+    require_authenticated(user).map_err(|_| GameError::Unauthorized)?;
+
     // Check if already pending (prevents concurrent withdrawals)
     if PENDING_WITHDRAWALS.with(|p| p.borrow().contains_key(&user)) {
-        return Err("Withdrawal already pending. Call retry_withdrawal() to retry or abandon_withdrawal() to cancel.".to_string());
+        return Err(GameError::WithdrawalPending);
     }
 
+    // Also covers an empty balance
     let balance = get_balance_internal(user);
+    if balance < min_withdraw() {
+        return Err(GameError::BelowMinimum { amount: balance, minimum: min_withdraw() });
+    }
+    check_covers_transfer_fee(balance)?;
+
+    execute_user_withdrawal(user, balance).await.map_err(GameError::from)
+}
 
-    if balance == 0 {
-        return Err("No balance to withdraw".to_string());
+pub(crate) async fn withdraw_keeping_internal(user: Principal, keep_amount: u64) -> Result<u64, GameError> {
+    require_authenticated(user).map_err(|_| GameError::Unauthorized)?;
+
+    if PENDING_WITHDRAWALS.with(|p| p.borrow().contains_key(&user)) {
+        return Err(GameError::WithdrawalPending);
     }
 
-    if balance < MIN_WITHDRAW {
-        return Err(format!("Balance {} decimals is below minimum withdrawal of {} USDT",
-                          balance, MIN_WITHDRAW / 1_000_000));
+    let amount = calculate_withdraw_keeping(get_balance_internal(user), keep_amount)?;
+    execute_user_withdrawal(user, amount).await.map_err(GameError::from)
+}
+
+/// Amount withdrawable from `balance` while leaving `keep_amount` deposited.
+/// Rejects if nothing would be withdrawn or the remainder is below the minimum withdrawal.
+pub(crate) fn calculate_withdraw_keeping(balance: u64, keep_amount: u64) -> Result<u64, GameError> {
+    if keep_amount >= balance {
+        return Err(format!("Keep amount {} must be less than balance {}", keep_amount, balance).into());
     }
 
-    // ATOMIC: Create pending FIRST, then zero balance
+    let amount = balance - keep_amount;
+    if amount < min_withdraw() {
+        return Err(GameError::BelowMinimum { amount, minimum: min_withdraw() });
+    }
+    check_covers_transfer_fee(amount)?;
+
+    Ok(amount)
+}
+
+/// Three-phase user withdrawal of `amount`: create pending, deduct balance, transfer.
+/// Caller must have checked there is no pending withdrawal, that `amount` <= balance and
+/// that it covers the transfer fee.
+async fn execute_user_withdrawal(user: Principal, amount: u64) -> Result<u64, String> {
+    // ATOMIC: Create pending FIRST, then deduct from balance
     // This ordering is critical for atomicity:
     // - If inserting pending fails (e.g., memory full), balance remains untouched
     // - IC stable structures auto-rollback on trap, so partial state is impossible
-    // - Only after pending is successfully created do we zero the balance
+    // - Only after pending is successfully created do we deduct the balance
     let created_at = ic_cdk::api::time();
     let pending = PendingWithdrawal {
-        withdrawal_type: WithdrawalType::User { amount: balance },
+        withdrawal_type: WithdrawalType::User { amount },
         created_at,
     };
 
     PENDING_WITHDRAWALS.with(|p| p.borrow_mut().insert(user, pending));
 
-    // Now that pending is created, zero the balance
+    // Now that pending is created, deduct the amount (no await since the caller checked
+    // the balance, so it still covers `amount`)
     USER_BALANCES_STABLE.with(|balances| {
-        balances.borrow_mut().insert(user, 0);
+        let mut balances = balances.borrow_mut();
+        let current = balances.get(&user).unwrap_or(0);
+        balances.insert(user, current.saturating_sub(amount));
     });
 
-    log_audit(AuditEvent::WithdrawalInitiated { user, amount: balance });
+    log_audit(AuditEvent::WithdrawalInitiated { user, amount });
 
-    match attempt_transfer(user, balance, created_at).await {
+    match attempt_transfer(user, amount, created_at).await {
         TransferResult::Success(_block) => {
             PENDING_WITHDRAWALS.with(|p| p.borrow_mut().remove(&user));
-            log_audit(AuditEvent::WithdrawalCompleted { user, amount: balance });
-            // Update cached canister balance (canister sent `balance`)
-            decrement_cached_balance(balance);
-            Ok(balance)
+            log_audit(AuditEvent::WithdrawalCompleted { user, amount });
+            super::events::notify(GameEvent::Withdrawal { user, amount });
+            // Update cached canister balance (canister sent `amount`)
+            decrement_cached_balance(amount);
+            Ok(amount)
         }
         TransferResult::DefiniteError(err) => {
             // DESIGN NOTE FOR AUDITORS:
@@ -270,7 +554,7 @@ pub(crate) async fn withdraw_internal(user: Principal) -> Result<u64, String> {
             // 2. DefiniteError = ledger definitely rejected the transaction
             // 3. No prior UncertainError = we KNOW it never succeeded
             rollback_withdrawal(user)?;
-            log_audit(AuditEvent::WithdrawalFailed { user, amount: balance });
+            log_audit(AuditEvent::WithdrawalFailed { user, amount });
             Err(err)
         }
         TransferResult::UncertainError(msg) => {
@@ -299,8 +583,9 @@ pub(crate) async fn withdraw_internal(user: Principal) -> Result<u64, String> {
 /// * `fee` - Protocol fee to credit to parent on successful transfer (not on rollback)
 pub fn schedule_lp_withdrawal(user: Principal, shares: Nat, reserve: Nat, amount: u64, fee: u64) -> Result<u64, String> {
     if PENDING_WITHDRAWALS.with(|p| p.borrow().contains_key(&user)) {
-        return Err("Withdrawal already pending. Call retry_withdrawal() to retry or abandon_withdrawal() to cancel.".to_string());
+        return Err(GameError::WithdrawalPending.into());
     }
+    check_covers_transfer_fee(amount)?;
 
     let created_at = ic_cdk::api::time();
     let pending = PendingWithdrawal {
@@ -323,19 +608,16 @@ pub fn schedule_lp_withdrawal(user: Principal, shares: Nat, reserve: Nat, amount
 // Refactoring to `Call::unbounded_wait` requires dependency updates and significant changes.
 #[allow(deprecated)]
 pub(crate) async fn attempt_transfer(user: Principal, amount: u64, created_at: u64) -> TransferResult {
-    let ck_usdt_principal = Principal::from_text(CKUSDT_CANISTER_ID).expect("Invalid principal constant");
-
-    let args = TransferArg {
-        from_subaccount: None,
-        to: Account { owner: user, subaccount: None },
-        amount: Nat::from(amount - CKUSDT_TRANSFER_FEE),
-        fee: Some(Nat::from(CKUSDT_TRANSFER_FEE)),
-        memo: None,
-        created_at_time: Some(created_at),
+    // Nothing is sent, so this can never have reached the ledger
+    let (ledger, args) = match build_withdrawal_transfer(user, amount, created_at) {
+        Ok(transfer) => transfer,
+        Err(e) => return TransferResult::DefiniteError(e),
     };
 
+    WITHDRAWALS_IN_FLIGHT.with(|f| f.borrow_mut().insert(user));
     let call_result: Result<(Result<Nat, TransferError>,), _> = 
-        ic_cdk::api::call::call(ck_usdt_principal, "icrc1_transfer", (args,)).await;
+        ic_cdk::api::call::call(ledger, "icrc1_transfer", (args,)).await;
+    WITHDRAWALS_IN_FLIGHT.with(|f| f.borrow_mut().remove(&user));
 
     match call_result {
         Ok((Ok(block_index),)) => {
@@ -347,6 +629,32 @@ pub(crate) async fn attempt_transfer(user: Principal, amount: u64, created_at: u
     }
 }
 
+/// Target ledger and `icrc1_transfer` args for paying out `amount` (the ledger fee is taken from it).
+/// Errors if `amount` does not cover the fee.
+pub(crate) fn build_withdrawal_transfer(user: Principal, amount: u64, created_at: u64) -> Result<(Principal, TransferArg), String> {
+    let fee = transfer_fee();
+    let net_amount = amount.checked_sub(fee).filter(|&net| net > 0)
+        .ok_or_else(|| format!("Amount {} does not cover the transfer fee {}", amount, fee))?;
+    let args = TransferArg {
+        from_subaccount: None,
+        to: super::parent::payout_account(user),
+        amount: Nat::from(net_amount),
+        fee: Some(Nat::from(fee)),
+        memo: None,
+        created_at_time: Some(created_at),
+    };
+    Ok((get_ledger_canister_id(), args))
+}
+
+/// A payout must leave something after the ledger fee (the fee is read from the ledger
+/// at runtime, so it can exceed the configured minimums)
+fn check_covers_transfer_fee(amount: u64) -> Result<(), GameError> {
+    if amount <= transfer_fee() {
+        return Err(GameError::BelowMinimum { amount, minimum: transfer_fee() + 1 });
+    }
+    Ok(())
+}
+
 pub(crate) fn rollback_withdrawal(user: Principal) -> Result<(), String> {
     let pending = PENDING_WITHDRAWALS.with(|p| p.borrow().get(&user))
         .ok_or("No pending withdrawal")?;
@@ -375,6 +683,7 @@ pub(crate) fn rollback_withdrawal(user: Principal) -> Result<(), String> {
 pub(crate) fn complete_withdrawal(user: Principal, amount: u64) {
     PENDING_WITHDRAWALS.with(|p| p.borrow_mut().remove(&user));
     log_audit(AuditEvent::WithdrawalCompleted { user, amount });
+    super::events::notify(GameEvent::Withdrawal { user, amount });
 }
 
 
@@ -402,11 +711,11 @@ async fn auto_withdraw_parent() {
 
      // SAFETY: TOCTOU race is acceptable here because withdraw_internal()
      // performs its own balance checks atomically. Worst case is the timer
-     // attempts a withdrawal that immediately fails with "Withdrawal already pending"
-     // or "No balance to withdraw", which is harmless.
+     // attempts a withdrawal that immediately fails with WithdrawalPending
+     // or BelowMinimum, which is harmless.
      let balance = get_balance_internal(parent);
 
-     if balance > PARENT_AUTO_WITHDRAW_THRESHOLD {
+     if super::parent::parent_withdrawal_due(balance) {
          // Use withdraw_internal directly
          match withdraw_internal(parent).await {
              Ok(amount) => {
@@ -468,11 +777,15 @@ pub fn start_balance_reconciliation_timer() {
 /// TooOld only means "I can't process THIS retry" - it says nothing about whether
 /// a PRIOR attempt succeeded. Auto-rollback here would cause double-spend if the
 /// original transfer actually went through.
-pub async fn retry_withdrawal() -> Result<u64, String> {
-    let caller = ic_cdk::api::msg_caller();
+pub async fn retry_withdrawal() -> Result<u64, GameError> {
+    retry_withdrawal_internal(ic_cdk::api::msg_caller()).await
+}
+
+pub(crate) async fn retry_withdrawal_internal(caller: Principal) -> Result<u64, GameError> {
+    require_authenticated(caller).map_err(|_| GameError::Unauthorized)?;
 
     let pending = PENDING_WITHDRAWALS.with(|p| p.borrow().get(&caller))
-        .ok_or("No pending withdrawal to retry")?;
+        .ok_or(GameError::NotFound)?;
 
     let amount = pending.get_amount();
 
@@ -496,6 +809,7 @@ pub async fn retry_withdrawal() -> Result<u64, String> {
             }
             PENDING_WITHDRAWALS.with(|p| p.borrow_mut().remove(&caller));
             log_audit(AuditEvent::WithdrawalCompleted { user: caller, amount });
+            super::events::notify(GameEvent::Withdrawal { user: caller, amount });
             // Update cached canister balance (canister sent `amount`)
             decrement_cached_balance(amount);
             Ok(amount)
@@ -509,10 +823,10 @@ pub async fn retry_withdrawal() -> Result<u64, String> {
                  Check your on-chain ckUSDT balance. \
                  If you received funds, call abandon_withdrawal(). \
                  Otherwise, you may retry again or abandon.", e
-            ))
+            ).into())
         }
         TransferResult::UncertainError(msg) => {
-            Err(format!("Transfer uncertain: {}. Please retry.", msg))
+            Err(format!("Transfer uncertain: {}. Please retry.", msg).into())
         }
     }
 }
@@ -546,11 +860,15 @@ pub async fn retry_withdrawal() -> Result<u64, String> {
 /// If a user abandons without receiving funds, those funds remain in the canister's
 /// ckUSDT balance but are not credited to any user. This is a "surplus" that keeps
 /// the system solvent. An admin recovery mechanism could be added later if needed.
-pub fn abandon_withdrawal() -> Result<u64, String> {
-    let caller = ic_cdk::api::msg_caller();
+pub fn abandon_withdrawal() -> Result<u64, GameError> {
+    abandon_withdrawal_internal(ic_cdk::api::msg_caller())
+}
+
+pub(crate) fn abandon_withdrawal_internal(caller: Principal) -> Result<u64, GameError> {
+    require_authenticated(caller).map_err(|_| GameError::Unauthorized)?;
 
     let pending = PENDING_WITHDRAWALS.with(|p| p.borrow().get(&caller))
-        .ok_or("No pending withdrawal to abandon")?;
+        .ok_or(GameError::NotFound)?;
 
     let amount = pending.get_amount();
 
@@ -573,7 +891,7 @@ pub(crate) fn get_balance_internal(user: Principal) -> u64 {
 }
 
 pub(crate) fn get_max_allowed_payout_internal() -> u64 {
-    let house_balance = liquidity_pool::get_pool_reserve();
+    let house_balance = super::solvency::payout_backing();
     // Backend allows 15%, frontend shows 10% - creates 50% safety buffer for max bet race conditions
     (house_balance * 15) / 100
 }
@@ -603,10 +921,13 @@ pub fn update_balance(user: Principal, new_balance: u64) -> Result<(), String> {
 ///
 /// # Returns
 /// - Ok(remaining_balance) on success
-/// - Err if withdrawal pending, insufficient funds, or underflow
-pub fn try_deduct_balance(user: Principal, amount: u64) -> Result<u64, String> {
+/// - Err if betting is paused, withdrawal pending, insufficient funds, or underflow
+pub fn try_deduct_balance(user: Principal, amount: u64) -> Result<u64, GameError> {
+    if super::pool_drain::is_betting_paused() {
+        return Err(GameError::BettingPaused);
+    }
     if PENDING_WITHDRAWALS.with(|p| p.borrow().contains_key(&user)) {
-        return Err("Cannot deduct balance: withdrawal pending".to_string());
+        return Err(GameError::WithdrawalPending);
     }
 
     USER_BALANCES_STABLE.with(|balances| {
@@ -614,11 +935,11 @@ pub fn try_deduct_balance(user: Principal, amount: u64) -> Result<u64, String> {
         let current = balances.get(&user).unwrap_or(0);
 
         if current < amount {
-            return Err("INSUFFICIENT_BALANCE".to_string());
+            return Err(GameError::InsufficientBalance { have: current, need: amount });
         }
 
         let new_balance = current.checked_sub(amount)
-            .ok_or("Balance underflow")?;
+            .ok_or_else(|| GameError::Other("Balance underflow".to_string()))?;
 
         balances.insert(user, new_balance);
         Ok(new_balance)
@@ -668,6 +989,28 @@ pub(crate) fn force_credit_balance_system(user: Principal, amount: u64) -> Resul
     })
 }
 
+/// Credit a pool-funded amount: part of an excess distribution (an LP's slice or the
+/// parent fee) or a loyalty rebate.
+/// Returns false if skipped (pending withdrawal or overflow); the amount then stays in the pool.
+pub(crate) fn credit_distribution(user: Principal, amount: u64, now: u64) -> bool {
+    if PENDING_WITHDRAWALS.with(|p| p.borrow().contains_key(&user)) {
+        return false;
+    }
+
+    USER_BALANCES_STABLE.with(|balances| {
+        let mut balances = balances.borrow_mut();
+        let current = balances.get(&user).unwrap_or(0);
+        match current.checked_add(amount) {
+            Some(new_balance) => {
+                balances.insert(user, new_balance);
+                log_audit_at(AuditEvent::BalanceCredited { user, amount, new_balance, block_index: None }, now);
+                true
+            },
+            None => false,
+        }
+    })
+}
+
 /// Best-effort fee crediting.
 /// Returns true if credited, false if skipped (user has pending withdrawal).
 pub fn credit_parent_fee(user: Principal, amount: u64) -> bool {
@@ -700,6 +1043,72 @@ pub fn get_withdrawal_status() -> Option<PendingWithdrawal> {
     PENDING_WITHDRAWALS.with(|p| p.borrow().get(&caller))
 }
 
+pub fn get_withdrawal_status_detail() -> Option<WithdrawalStatusDetail> {
+    get_withdrawal_status_detail_internal(ic_cdk::api::msg_caller(), ic_cdk::api::time())
+}
+
+/// Explain the caller's withdrawal in user terms. Retries are never throttled, so
+/// `next_retry_at` is `now` whenever a retry can still reach the ledger.
+/// Returns None when there is no pending withdrawal and the last one was not abandoned.
+pub(crate) fn get_withdrawal_status_detail_internal(user: Principal, now: u64) -> Option<WithdrawalStatusDetail> {
+    let Some(pending) = PENDING_WITHDRAWALS.with(|p| p.borrow().get(&user)) else {
+        return last_abandoned_amount(user).map(|amount| WithdrawalStatusDetail {
+            state: WithdrawalState::Abandoned,
+            description: "Withdrawal was abandoned and your balance was not restored. \
+                          Funds you did not receive on-chain stay in the canister.".to_string(),
+            amount,
+            net_amount: 0,
+            created_at: None,
+            next_retry_at: None,
+            retry_deadline: None,
+        });
+    };
+
+    let amount = pending.get_amount();
+    let retry_deadline = pending.created_at.saturating_add(LEDGER_DEDUP_WINDOW_NANOS);
+    let (state, description, next_retry_at) = if WITHDRAWALS_IN_FLIGHT.with(|f| f.borrow().contains(&user)) {
+        (WithdrawalState::InProgress,
+         "Transfer is awaiting the ledger. Check again in a few seconds.", None)
+    } else if now >= retry_deadline {
+        (WithdrawalState::Failed,
+         "Retries can no longer reach the ledger. Check your on-chain balance: \
+          if the funds arrived, call abandon_withdrawal() to clear this record.", None)
+    } else {
+        (WithdrawalState::AwaitingRetry,
+         "Transfer outcome is unknown. Call retry_withdrawal(); the ledger deduplicates \
+          the retry, so you cannot be paid twice.", Some(now))
+    };
+
+    Some(WithdrawalStatusDetail {
+        state,
+        description: description.to_string(),
+        amount,
+        net_amount: amount.saturating_sub(transfer_fee()),
+        created_at: Some(pending.created_at),
+        next_retry_at,
+        retry_deadline: Some(retry_deadline),
+    })
+}
+
+/// Amount of the user's most recent withdrawal if it ended in an abandon
+fn last_abandoned_amount(user: Principal) -> Option<u64> {
+    AUDIT_LOG_MAP.with(|log| {
+        log.borrow().iter()
+            .filter_map(|entry| match entry.value().event {
+                AuditEvent::WithdrawalInitiated { user: u, .. }
+                | AuditEvent::WithdrawalCompleted { user: u, .. }
+                | AuditEvent::WithdrawalFailed { user: u, .. }
+                | AuditEvent::WithdrawalExpired { user: u, .. }
+                | AuditEvent::BalanceRestored { user: u, .. }
+                | AuditEvent::LPRestored { user: u, .. } if u == user => Some(None),
+                AuditEvent::WithdrawalAbandoned { user: u, amount } if u == user => Some(Some(amount)),
+                _ => None,
+            })
+            .last()
+            .flatten()
+    })
+}
+
 /// Get audit log entries in reverse chronological order (most recent first).
 /// Used by admin_query for the admin dashboard.
 ///
@@ -740,9 +1149,16 @@ pub(crate) fn get_audit_count() -> u64 {
     AUDIT_LOG_MAP.with(|log| log.borrow().len())
 }
 
+/// Next audit id to be assigned, and the highest id currently stored
+pub(crate) fn audit_log_bounds_internal() -> (u64, Option<u64>) {
+    let counter = AUDIT_LOG_COUNTER.with(|c| *c.borrow().get());
+    let max_id = AUDIT_LOG_MAP.with(|log| log.borrow().last_key_value().map(|(k, _)| k));
+    (counter, max_id)
+}
+
 #[allow(deprecated)]
 pub async fn refresh_canister_balance() -> u64 {
-    let ck_usdt_principal = Principal::from_text(CKUSDT_CANISTER_ID).expect("Invalid principal constant");
+    let ck_usdt_principal = get_ledger_canister_id();
 
     let account = Account {
         owner: ic_cdk::api::canister_self(),
@@ -780,6 +1196,13 @@ pub(crate) fn count_user_balances_internal() -> u64 {
     USER_BALANCES_STABLE.with(|b| b.borrow().len())
 }
 
+/// Count users holding a non-zero balance (zero entries are kept after withdrawals)
+pub(crate) fn count_active_users_internal() -> u64 {
+    USER_BALANCES_STABLE.with(|b| {
+        b.borrow().iter().filter(|entry| entry.value() > 0).count() as u64
+    })
+}
+
 /// Get pending withdrawal stats (count, total amount)
 pub(crate) fn get_pending_stats_internal() -> (u64, u64) {
     PENDING_WITHDRAWALS.with(|p| {
@@ -824,6 +1247,88 @@ pub(crate) fn iter_user_balances_internal(offset: usize, limit: usize) -> Vec<su
     })
 }
 
+/// Sum parent fees credited in `[start_ts, end_ts)` from the audit log
+pub(crate) fn sum_parent_fees_from_audit_internal(start_ts: u64, end_ts: u64) -> u64 {
+    AUDIT_LOG_MAP.with(|log| {
+        log.borrow().iter()
+            .filter_map(|entry| {
+                let entry = entry.value();
+                match entry.event {
+                    AuditEvent::ParentFeeCredited { amount }
+                        if (start_ts..end_ts).contains(&entry.timestamp) => Some(amount),
+                    _ => None,
+                }
+            })
+            .fold(0u64, |acc, amount| acc.saturating_add(amount))
+    })
+}
+
+/// Replay `user`'s balance and compare it with the stored one. Reports drift, never fixes it.
+///
+/// Starts at the user's latest balance-carrying event (deposits, distributions and refunds
+/// log `new_balance`), then applies later withdrawals, restores, parent fees and settled
+/// games. LP withdrawals log the same `WithdrawalInitiated` event as user withdrawals, so
+/// current LP shares or a restored LP withdrawal mark the report incomplete.
+pub(crate) fn reconcile_user_internal(user: Principal) -> ReconcileReport {
+    let is_parent = user == liquidity_pool::get_parent_principal();
+    let events: Vec<AuditEntry> = AUDIT_LOG_MAP.with(|log| {
+        log.borrow().iter()
+            .map(|entry| entry.value())
+            .filter(|entry| match &entry.event {
+                AuditEvent::WithdrawalInitiated { user: u, .. }
+                | AuditEvent::BalanceRestored { user: u, .. }
+                | AuditEvent::LPRestored { user: u, .. }
+                | AuditEvent::BalanceCredited { user: u, .. }
+                | AuditEvent::SystemRefundCredited { user: u, .. } => *u == user,
+                AuditEvent::ParentFeeCredited { .. } => is_parent,
+                _ => false,
+            })
+            .collect()
+    });
+
+    let anchor = events.iter().rposition(|entry| matches!(
+        entry.event,
+        AuditEvent::BalanceCredited { .. } | AuditEvent::SystemRefundCredited { .. }
+    ));
+    let anchor_timestamp = anchor.map(|i| events[i].timestamp);
+    let replay = &events[anchor.unwrap_or(0)..];
+
+    let mut expected: i128 = 0;
+    let mut lp_activity = liquidity_pool::LP_SHARES.with(|s| s.borrow().contains_key(&user));
+    for entry in replay {
+        match entry.event {
+            AuditEvent::BalanceCredited { new_balance, .. }
+            | AuditEvent::SystemRefundCredited { new_balance, .. } => expected = new_balance as i128,
+            AuditEvent::WithdrawalInitiated { amount, .. } => expected -= amount as i128,
+            AuditEvent::BalanceRestored { amount, .. }
+            | AuditEvent::ParentFeeCredited { amount } => expected += amount as i128,
+            AuditEvent::LPRestored { .. } => lp_activity = true,
+            _ => {}
+        }
+    }
+
+    let (games, games_truncated) = super::game_history::games_since(user, anchor_timestamp);
+    for game in &games {
+        expected += game.payout as i128 - game.bet as i128;
+    }
+
+    let audit_pruned = AUDIT_LOG_COUNTER.with(|c| *c.borrow().get()) > get_audit_count();
+    let stored_balance = get_balance_internal(user);
+    let expected_balance = expected.clamp(0, u64::MAX as i128) as u64;
+    let drift = (stored_balance as i128 - expected_balance as i128).clamp(i64::MIN as i128, i64::MAX as i128) as i64;
+
+    ReconcileReport {
+        user,
+        stored_balance,
+        expected_balance,
+        drift,
+        anchor_timestamp,
+        events_replayed: replay.len() as u64,
+        games_replayed: games.len() as u64,
+        complete: (anchor.is_some() || !audit_pruned) && !games_truncated && !lp_activity,
+    }
+}
+
 /// Sum all abandoned amounts from audit log
 pub(crate) fn sum_abandoned_from_audit_internal() -> u64 {
     AUDIT_LOG_MAP.with(|log| {
@@ -873,7 +1378,7 @@ pub(crate) fn build_orphaned_funds_report_internal(recent_limit: Option<usize>)
             .collect();
 
         // Sort by timestamp descending (most recent first)
-        all_abandonments.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        all_abandonments.sort_by_key(|e| std::cmp::Reverse(e.timestamp));
 
         // Apply limit if specified, otherwise return all
         let limited_abandonments = if let Some(limit) = recent_limit {
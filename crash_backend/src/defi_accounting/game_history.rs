@@ -0,0 +1,70 @@
+//! Per-principal recent game history.
+//!
+//! Each game records one entry right after `liquidity_pool::settle_bet` succeeds, so a
+//! refunded (unsettled) bet never shows up. Only the newest `MAX_HISTORY_PER_USER`
+//! entries are kept per principal.
+
+use candid::Principal;
+use ic_stable_structures::memory_manager::MemoryId;
+use ic_stable_structures::StableBTreeMap;
+use std::cell::RefCell;
+
+use crate::{MEMORY_MANAGER, Memory};
+use super::memory_ids::GAME_HISTORY_MEMORY_ID;
+use super::types::{GameHistoryEntry, GameHistoryList};
+
+pub const MAX_HISTORY_PER_USER: usize = 50;
+
+thread_local! {
+    static GAME_HISTORY: RefCell<StableBTreeMap<Principal, GameHistoryList, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(GAME_HISTORY_MEMORY_ID)))
+        )
+    );
+}
+
+/// Append a settled game to the player's history, dropping the oldest entry when full
+pub(crate) fn record_game(player: Principal, bet: u64, payout: u64, outcome_summary: String, now: u64) {
+    GAME_HISTORY.with(|history| {
+        let mut history = history.borrow_mut();
+        let mut list = history.get(&player).unwrap_or_default();
+
+        if list.entries.len() >= MAX_HISTORY_PER_USER {
+            list.entries.remove(0); // Entries are kept oldest first
+        }
+        list.entries.push(GameHistoryEntry {
+            game_id: list.next_game_id,
+            bet,
+            payout,
+            timestamp: now,
+            outcome_summary,
+        });
+        list.next_game_id = list.next_game_id.saturating_add(1);
+
+        history.insert(player, list);
+    });
+}
+
+/// Games settled after `after` (every retained game when None). The flag is true when
+/// older games in that window may have been dropped by the per-user cap.
+pub(crate) fn games_since(player: Principal, after: Option<u64>) -> (Vec<GameHistoryEntry>, bool) {
+    let list = GAME_HISTORY.with(|history| history.borrow().get(&player).unwrap_or_default());
+    let dropped = list.next_game_id > list.entries.len() as u64;
+    let window_predates_history = list.entries.first()
+        .is_none_or(|oldest| after.is_none_or(|after| oldest.timestamp > after));
+
+    let games = list.entries.into_iter()
+        .filter(|game| after.is_none_or(|after| game.timestamp > after))
+        .collect();
+    (games, dropped && window_predates_history)
+}
+
+/// Up to `limit` most recent games for a player, newest first
+pub fn get_recent_games(player: Principal, limit: u32) -> Vec<GameHistoryEntry> {
+    GAME_HISTORY.with(|history| {
+        history.borrow()
+            .get(&player)
+            .map(|list| list.entries.into_iter().rev().take(limit as usize).collect())
+            .unwrap_or_default()
+    })
+}
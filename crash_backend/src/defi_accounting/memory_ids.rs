@@ -4,24 +4,48 @@
 //! Run `cargo test` to verify no collisions exist.
 //!
 //! Allocation strategy:
-//! - 10-19: User accounting (balances, LP shares, pool state)
-//! - 20-29: Withdrawal & audit (pending, audit log)
+//! - 10-19: User accounting (balances, LP shares, pool state, token metadata, deposit keys, solvency margin, player stats, game history, reserve target)
+//! - 20-29: Withdrawal & audit (pending, audit log, ledger canister id, audit retention, pending pool drain, betting pause, parent withdrawal config)
 //! - 30-39: Statistics (snapshots, accumulator)
+//! - 40-49: Game state (seed commitments)
+//! - 50-59: Loyalty rebates (config, per-player accruals)
+//! - 60-69: Integrations (event subscriber)
 
 // User accounting (10-19)
 pub const USER_BALANCES_MEMORY_ID: u8 = 10;
 pub const LP_SHARES_MEMORY_ID: u8 = 11;
 pub const POOL_STATE_MEMORY_ID: u8 = 13;
+pub const TOKEN_INFO_MEMORY_ID: u8 = 14;
+pub const DEPOSIT_KEYS_MEMORY_ID: u8 = 15;
+pub const SOLVENCY_MARGIN_MEMORY_ID: u8 = 16;
+pub const PLAYER_STATS_MEMORY_ID: u8 = 17;
+pub const GAME_HISTORY_MEMORY_ID: u8 = 18;
+pub const RESERVE_TARGET_MEMORY_ID: u8 = 19;
 
 // Withdrawals & audit (20-29)
 pub const PENDING_WITHDRAWALS_MEMORY_ID: u8 = 20;
+pub const PENDING_DRAIN_MEMORY_ID: u8 = 21;
 pub const AUDIT_LOG_MAP_MEMORY_ID: u8 = 24;
 pub const AUDIT_LOG_COUNTER_MEMORY_ID: u8 = 25;
+pub const LEDGER_CANISTER_MEMORY_ID: u8 = 26;
+pub const AUDIT_RETENTION_MEMORY_ID: u8 = 27;
+pub const BETTING_PAUSED_MEMORY_ID: u8 = 28;
+pub const PARENT_CONFIG_MEMORY_ID: u8 = 29;
 
 // Statistics (30-39)
 pub const SNAPSHOTS_MEMORY_ID: u8 = 30;
 pub const ACCUMULATOR_MEMORY_ID: u8 = 31;
 
+// Loyalty rebates (50-59)
+pub const REBATE_CONFIG_MEMORY_ID: u8 = 50;
+pub const REBATE_ACCOUNTS_MEMORY_ID: u8 = 51;
+
+// Integrations (60-69)
+pub const EVENT_SUBSCRIBER_MEMORY_ID: u8 = 60;
+
+// Game state (40-49)
+pub const SEED_COMMITMENTS_MEMORY_ID: u8 = 40;
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -32,11 +56,26 @@ mod tests {
             USER_BALANCES_MEMORY_ID,
             LP_SHARES_MEMORY_ID,
             POOL_STATE_MEMORY_ID,
+            TOKEN_INFO_MEMORY_ID,
+            DEPOSIT_KEYS_MEMORY_ID,
+            SOLVENCY_MARGIN_MEMORY_ID,
+            PLAYER_STATS_MEMORY_ID,
+            GAME_HISTORY_MEMORY_ID,
+            RESERVE_TARGET_MEMORY_ID,
             PENDING_WITHDRAWALS_MEMORY_ID,
+            PENDING_DRAIN_MEMORY_ID,
             AUDIT_LOG_MAP_MEMORY_ID,
             AUDIT_LOG_COUNTER_MEMORY_ID,
+            LEDGER_CANISTER_MEMORY_ID,
+            AUDIT_RETENTION_MEMORY_ID,
+            BETTING_PAUSED_MEMORY_ID,
+            PARENT_CONFIG_MEMORY_ID,
             SNAPSHOTS_MEMORY_ID,
             ACCUMULATOR_MEMORY_ID,
+            REBATE_CONFIG_MEMORY_ID,
+            REBATE_ACCOUNTS_MEMORY_ID,
+            EVENT_SUBSCRIBER_MEMORY_ID,
+            SEED_COMMITMENTS_MEMORY_ID,
         ];
 
         let mut sorted = ids;
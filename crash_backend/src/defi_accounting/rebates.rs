@@ -0,0 +1,166 @@
+//! Loyalty rebates: a share of the house edge collected from each player, paid back to
+//! their betting balance out of the pool.
+//!
+//! While rebates are enabled, `record_settlement` (called from `liquidity_pool::settle_bet`)
+//! accrues every player's wagered volume and the house's net result against them since
+//! their last rebate. The daily `process_rebates` credits `rebate_bp` of the edge on that
+//! volume (`wagered * house_edge * rebate_bp`) to each player who wagered at least
+//! `min_volume`, then resets their accrual. The rebate is capped at the house's net result,
+//! and a net winner gets nothing and their winnings carry forward, so a rebate can never
+//! exceed what the house actually kept nor turn a strategy's expected value positive.
+//! Payouts leave the pool reserve, are skipped while the canister is insolvent, and stop
+//! before the reserve would drop below the operating minimum.
+
+use candid::Principal;
+use ic_stable_structures::memory_manager::MemoryId;
+use ic_stable_structures::{StableBTreeMap, StableCell};
+use std::cell::RefCell;
+use std::time::Duration;
+
+use crate::{MEMORY_MANAGER, Memory};
+use super::accounting;
+use super::liquidity_pool;
+use super::memory_ids::{REBATE_ACCOUNTS_MEMORY_ID, REBATE_CONFIG_MEMORY_ID};
+use super::solvency;
+use super::types::{AuditEvent, RebateAccount, RebateConfig};
+
+const BASIS_POINTS: u64 = 10_000;
+pub const MAX_REBATE_BP: u64 = 5_000; // At most half of the collected edge is returned
+
+thread_local! {
+    static REBATE_CONFIG: RefCell<StableCell<RebateConfig, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(REBATE_CONFIG_MEMORY_ID))),
+            RebateConfig::default()
+        )
+    );
+
+    static REBATE_ACCOUNTS: RefCell<StableBTreeMap<Principal, RebateAccount, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(REBATE_ACCOUNTS_MEMORY_ID)))
+        )
+    );
+}
+
+pub fn get_rebate_config() -> RebateConfig {
+    REBATE_CONFIG.with(|c| c.borrow().get().clone())
+}
+
+pub(crate) fn validate_rebate_config(config: &RebateConfig) -> Result<(), String> {
+    if config.rebate_bp > MAX_REBATE_BP {
+        return Err(format!("Rebate {} bp exceeds maximum {} bp", config.rebate_bp, MAX_REBATE_BP));
+    }
+    Ok(())
+}
+
+/// Replace the rebate settings (admin check is done by the caller in `admin_query`)
+pub(crate) fn set_rebate_config(config: RebateConfig) -> Result<(), String> {
+    validate_rebate_config(&config)?;
+    REBATE_CONFIG.with(|c| c.borrow_mut().set(config));
+    Ok(())
+}
+
+/// House edge of every round: crash = 0.99 / (1 - random) keeps 1% of each stake
+pub(crate) fn house_edge_bp() -> u64 {
+    100
+}
+
+/// Rebate owed on an accrual: `rebate_bp` of the edge on the wagered volume, capped at
+/// what the house kept from the player
+pub(crate) fn rebate_for(account: &RebateAccount, rebate_bp: u64) -> u64 {
+    if account.house_net <= 0 {
+        return 0;
+    }
+    let edge_share = (account.wagered as u128) * (house_edge_bp() as u128) * (rebate_bp as u128)
+        / (BASIS_POINTS as u128 * BASIS_POINTS as u128);
+    edge_share.min(account.house_net as u128) as u64
+}
+
+/// Accrual since the player's last rebate
+pub fn get_rebate_account(player: Principal) -> RebateAccount {
+    REBATE_ACCOUNTS.with(|a| a.borrow().get(&player).unwrap_or_default())
+}
+
+/// Fold one settled game into the player's accrual (saturating: rebates must never trap a bet).
+/// Nothing is tracked while rebates are disabled, so enabling them is never retroactive.
+pub(crate) fn record_settlement(player: Principal, bet_amount: u64, payout_amount: u64) {
+    if get_rebate_config().rebate_bp == 0 {
+        return;
+    }
+    REBATE_ACCOUNTS.with(|accounts| {
+        let mut accounts = accounts.borrow_mut();
+        let mut entry = accounts.get(&player).unwrap_or_default();
+        entry.wagered = entry.wagered.saturating_add(bet_amount);
+        let house_net = entry.house_net as i128 + bet_amount as i128 - payout_amount as i128;
+        entry.house_net = house_net.clamp(i64::MIN as i128, i64::MAX as i128) as i64;
+        accounts.insert(player, entry);
+    });
+}
+
+/// Daily rebate run
+pub fn start_rebate_timer() {
+    ic_cdk_timers::set_timer_interval(Duration::from_secs(86_400), || async {
+        if let Ok(paid) = process_rebates() {
+            if paid > 0 {
+                ic_cdk::println!("Loyalty rebates paid: {}", paid);
+            }
+        }
+    });
+}
+
+/// Credit every eligible player's rebate. Returns the amount removed from the reserve.
+pub(crate) fn process_rebates() -> Result<u64, String> {
+    process_rebates_internal(ic_cdk::api::time())
+}
+
+pub(crate) fn process_rebates_internal(now: u64) -> Result<u64, String> {
+    let config = get_rebate_config();
+    if config.rebate_bp == 0 {
+        return Err("Rebates disabled".to_string());
+    }
+    if !solvency::is_canister_solvent() {
+        return Err("Canister not solvent: rebates deferred".to_string());
+    }
+
+    let reserve = liquidity_pool::get_pool_reserve();
+    let mut budget = reserve.saturating_sub(liquidity_pool::min_operating_balance());
+
+    let eligible: Vec<(Principal, RebateAccount)> = REBATE_ACCOUNTS.with(|accounts| {
+        accounts.borrow()
+            .iter()
+            .map(|entry| (*entry.key(), entry.value()))
+            .filter(|(_, account)| account.wagered >= config.min_volume && account.house_net > 0)
+            .collect()
+    });
+
+    let mut paid = 0u64;
+    let mut user_count = 0u64;
+    for (user, account) in eligible {
+        let rebate = rebate_for(&account, config.rebate_bp);
+        if rebate == 0 {
+            continue;
+        }
+        // Unpaid players keep accruing and are paid once the pool has room again
+        if rebate > budget {
+            break;
+        }
+        if accounting::credit_distribution(user, rebate, now) {
+            REBATE_ACCOUNTS.with(|a| a.borrow_mut().remove(&user));
+            budget -= rebate;
+            paid += rebate;
+            user_count += 1;
+        }
+    }
+
+    if paid == 0 {
+        return Ok(0);
+    }
+    // Balances were credited above: the pool pays the rebates like a player win
+    liquidity_pool::update_pool_on_win(paid);
+    accounting::log_audit_at(AuditEvent::RebatesPaid {
+        amount: paid,
+        user_count,
+        reserve_after: liquidity_pool::get_pool_reserve(),
+    }, now);
+    Ok(paid)
+}
@@ -4,14 +4,18 @@ use super::types::{DailySnapshot, DailyAccumulator};
 use crate::defi_accounting::liquidity_pool;
 
 /// Nanoseconds per day (24 * 60 * 60 * 1e9)
-const NANOS_PER_DAY: u64 = 86_400_000_000_000;
+pub(super) const NANOS_PER_DAY: u64 = 86_400_000_000_000;
 
 /// Record bet volume - called by game logic after each bet
 ///
 /// This function is game-agnostic: any game just reports the bet amount.
 /// Snapshots are automatically taken when a new day starts.
 pub fn record_bet_volume(amount: u64) {
-    let now = ic_cdk::api::time();
+    record_bet_volume_at(amount, ic_cdk::api::time());
+}
+
+/// Record bet volume at an explicit timestamp (settlement helpers and tests)
+pub(crate) fn record_bet_volume_at(amount: u64, now: u64) {
     let current_day_start = get_day_start(now);
 
     DAILY_ACCUMULATOR.with(|acc| {
@@ -71,6 +75,7 @@ fn take_snapshot_internal(acc: &DailyAccumulator) -> Option<u64> {
 
     let current_reserve = liquidity_pool::get_pool_reserve();
     let share_price = liquidity_pool::get_share_price();
+    let total_shares = liquidity_pool::get_total_shares();
 
     // Calculate profit (can be negative if house lost)
     let daily_profit = (current_reserve as i64) - (acc.last_pool_reserve as i64);
@@ -81,6 +86,7 @@ fn take_snapshot_internal(acc: &DailyAccumulator) -> Option<u64> {
         daily_pool_profit: daily_profit,
         daily_volume: acc.volume_accumulated,
         share_price,
+        total_shares: Some(total_shares),
     };
 
     DAILY_SNAPSHOTS.with(|snapshots| {
@@ -134,17 +140,41 @@ mod tests {
     #[test]
     fn test_get_day_start() {
         // Test that timestamps within the same day return the same day_start
-        let ts1 = 1735689600_000_000_000u64; // Some timestamp
-        let ts2 = ts1 + 3600_000_000_000; // 1 hour later
+        let ts1 = 1_735_689_600_000_000_000u64; // Some timestamp
+        let ts2 = ts1 + 3_600_000_000_000; // 1 hour later
 
         assert_eq!(get_day_start(ts1), get_day_start(ts2));
     }
 
     #[test]
     fn test_get_day_start_different_days() {
-        let ts1 = 1735689600_000_000_000u64;
+        let ts1 = 1_735_689_600_000_000_000u64;
         let ts2 = ts1 + NANOS_PER_DAY; // Next day
 
         assert_ne!(get_day_start(ts1), get_day_start(ts2));
     }
+
+    fn current_volume() -> u64 {
+        DAILY_ACCUMULATOR.with(|acc| acc.borrow().get().volume_accumulated)
+    }
+
+    #[test]
+    fn test_record_bet_volume_accumulates_within_day() {
+        let day = 1_735_689_600_000_000_000u64;
+        record_bet_volume_at(1_000_000, day);
+        record_bet_volume_at(2_500_000, day + 3_600_000_000_000);
+
+        assert_eq!(current_volume(), 3_500_000);
+    }
+
+    #[test]
+    fn test_record_bet_volume_resets_on_new_day() {
+        let day = 1_735_689_600_000_000_000u64;
+        record_bet_volume_at(4_000_000, day);
+        record_bet_volume_at(1_000_000, day + NANOS_PER_DAY);
+
+        assert_eq!(current_volume(), 1_000_000);
+        let snapshot = DAILY_SNAPSHOTS.with(|s| s.borrow().get(s.borrow().len() - 1)).unwrap();
+        assert_eq!(snapshot.daily_volume, 4_000_000);
+    }
 }
@@ -29,7 +29,7 @@ fn attack_extract_more_than_deposited() {
     model.user_deposit(attacker, initial_deposit).expect_success();
 
     let mut attacker_total_withdrawn = 0u64;
-    let mut attacker_balance = *model.user_balances.get(&attacker).unwrap_or(&0);
+    let attacker_balance = *model.user_balances.get(&attacker).unwrap_or(&0);
 
     println!("  Attacker balance: {} USDT", attacker_balance / 1_000_000);
 
@@ -228,7 +228,7 @@ fn attack_withdrawal_during_game() {
 
     // Scenario 2: settle_bet then withdraw
     println!("\n  Scenario 2: settle_bet then withdraw");
-    let balance_before_game = *model.user_balances.get(&attacker).unwrap();
+    let _balance_before_game = *model.user_balances.get(&attacker).unwrap();
 
     // Settle a bet (win)
     let payout = bet_amount * 2; // 2x
@@ -31,7 +31,7 @@ fn attack_deposit_zero_reserve() {
     // Player wins repeatedly to drain pool
     let mut drain_attempts = 0;
     while model.pool_reserve > 0 && drain_attempts < 100 {
-        let bet = std::cmp::min(1_000_000, model.user_balances.get(&player).unwrap_or(&0).clone());
+        let bet = std::cmp::min(1_000_000, *model.user_balances.get(&player).unwrap_or(&0));
         if bet == 0 {
             // Player ran out of money, deposit more
             model.user_deposit(player, 10_000_000).expect_success();
@@ -235,16 +235,14 @@ fn attack_state_corruption_via_rapid_ops() {
     // Attack: Rapid sequence of different operations
     println!("\n🚨 ATTACK EXECUTION: Rapid operation sequence...");
 
-    let operations = vec![
-        "User deposit",
+    let operations = ["User deposit",
         "Settle bet (win)",
         "User deposit",
         "LP deposit",
         "Settle bet (lose)",
         "LP withdraw",
         "User withdraw",
-        "LP deposit",
-    ];
+        "LP deposit"];
 
     for (i, op_name) in operations.iter().enumerate() {
         println!("  Op {}: {}", i + 1, op_name);
@@ -50,7 +50,7 @@ fn test_deterministic_10k_operations() {
 
         // Check every 100 ops
         if i % 100 == 0 {
-            model.check_invariant().expect(&format!("Failed at op {}", i));
+            model.check_invariant().unwrap_or_else(|_| panic!("Failed at op {}", i));
         }
     }
 
@@ -0,0 +1,40 @@
+// Tests for the bounded per-principal game history
+
+use candid::Principal;
+use crate::defi_accounting::game_history::{get_recent_games, record_game, MAX_HISTORY_PER_USER};
+
+fn player(id: u8) -> Principal {
+    Principal::from_slice(&[id; 29])
+}
+
+#[test]
+fn test_recent_games_newest_first() {
+    let p = player(1);
+    record_game(p, 1_000_000, 0, "first".to_string(), 10);
+    record_game(p, 2_000_000, 4_000_000, "second".to_string(), 20);
+
+    let games = get_recent_games(p, 10);
+    assert_eq!(games.len(), 2);
+    assert_eq!(games[0].outcome_summary, "second");
+    assert_eq!((games[0].game_id, games[0].bet, games[0].payout, games[0].timestamp), (1, 2_000_000, 4_000_000, 20));
+    assert_eq!(games[1].game_id, 0);
+
+    assert_eq!(get_recent_games(p, 1).len(), 1);
+    assert!(get_recent_games(p, 0).is_empty());
+    assert!(get_recent_games(player(2), 10).is_empty());
+}
+
+#[test]
+fn test_history_is_bounded() {
+    let p = player(3);
+    let total = MAX_HISTORY_PER_USER as u64 + 5;
+    for i in 0..total {
+        record_game(p, 1_000_000, 0, format!("game {}", i), i);
+    }
+
+    let games = get_recent_games(p, u32::MAX);
+    assert_eq!(games.len(), MAX_HISTORY_PER_USER);
+    assert_eq!(games[0].game_id, total - 1);
+    // The oldest five were dropped, ids keep counting
+    assert_eq!(games.last().unwrap().game_id, 5);
+}
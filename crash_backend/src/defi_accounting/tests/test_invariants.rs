@@ -0,0 +1,72 @@
+// Tests for the cross-subsystem invariants in admin_health_check
+
+use candid::{Nat, Principal};
+use crate::defi_accounting::accounting::{PENDING_WITHDRAWALS, USER_BALANCES_STABLE};
+use crate::defi_accounting::invariants::*;
+use crate::defi_accounting::types::{PendingWithdrawal, WithdrawalType};
+
+fn user(id: u8) -> Principal {
+    Principal::from_slice(&[id; 29])
+}
+
+fn user_pending(amount: u64) -> PendingWithdrawal {
+    PendingWithdrawal { withdrawal_type: WithdrawalType::User { amount }, created_at: 0 }
+}
+
+#[test]
+fn test_fresh_state_passes_all_invariants() {
+    let checks = check_all(0);
+    assert_eq!(checks.len(), 4);
+    assert!(checks.iter().all(|c| c.passed), "{:?}", checks);
+}
+
+#[test]
+fn test_deposits_exceeding_balance_flagged() {
+    assert!(deposits_backed(600, 400, 1_000).passed);
+    let check = deposits_backed(601, 400, 1_000);
+    assert!(!check.passed);
+    assert_eq!(check.name, "deposits_backed");
+    // No u64 overflow on extreme values
+    assert!(!deposits_backed(u64::MAX, u64::MAX, u64::MAX).passed);
+}
+
+#[test]
+fn test_pending_without_balance_flagged() {
+    let u = user(1);
+    PENDING_WITHDRAWALS.with(|p| p.borrow_mut().insert(u, user_pending(1_000_000)));
+
+    let check = check_all(0).into_iter().find(|c| c.name == "pending_withdrawals_valid").unwrap();
+    assert!(!check.passed);
+    assert!(check.detail.contains(&u.to_text()));
+
+    // A (deducted) balance entry makes it valid again
+    USER_BALANCES_STABLE.with(|b| b.borrow_mut().insert(u, 0));
+    assert!(check_all(0).iter().all(|c| c.name != "pending_withdrawals_valid" || c.passed));
+}
+
+#[test]
+fn test_zero_amount_pending_flagged() {
+    let pending = vec![(user(2), user_pending(0))];
+    assert!(!pending_withdrawals_valid(&pending, |_| true).passed);
+}
+
+#[test]
+fn test_audit_counter_behind_flagged() {
+    assert!(audit_counter_ahead(0, None).passed);
+    assert!(audit_counter_ahead(10, Some(9)).passed);
+    assert!(!audit_counter_ahead(9, Some(9)).passed);
+    assert!(!audit_counter_ahead(3, Some(9)).passed);
+}
+
+#[test]
+fn test_lp_share_mismatch_flagged() {
+    let min = Nat::from(1_000u64);
+    assert!(lp_shares_consistent(false, &Nat::from(0u64), &Nat::from(0u64), &min).passed);
+    assert!(lp_shares_consistent(true, &Nat::from(5_000u64), &min, &min).passed);
+
+    // Shares exist in an uninitialized pool
+    assert!(!lp_shares_consistent(false, &Nat::from(5_000u64), &Nat::from(0u64), &min).passed);
+    // Burned entry missing or altered
+    assert!(!lp_shares_consistent(true, &Nat::from(5_000u64), &Nat::from(0u64), &min).passed);
+    assert!(!lp_shares_consistent(true, &Nat::from(5_000u64), &Nat::from(999u64), &min).passed);
+}
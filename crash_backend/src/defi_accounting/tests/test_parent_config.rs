@@ -0,0 +1,58 @@
+// Tests for the admin-set parent auto-withdrawal threshold and recipient
+
+use candid::Principal;
+use crate::defi_accounting::accounting::{build_withdrawal_transfer, PENDING_WITHDRAWALS};
+use crate::defi_accounting::liquidity_pool::get_parent_principal;
+use crate::defi_accounting::parent::{
+    get_parent_config, parent_withdrawal_due, payout_account, set_parent_config, DEFAULT_PARENT_WITHDRAW_THRESHOLD,
+};
+use crate::defi_accounting::types::{PendingWithdrawal, WithdrawalType};
+use crate::types::Account;
+
+const USDT: u64 = 1_000_000;
+const T: u64 = 1_700_000_000_000_000_000;
+
+fn treasury() -> Account {
+    Account { owner: Principal::from_slice(&[42; 29]), subaccount: Some([7; 32]) }
+}
+
+#[test]
+fn test_threshold_controls_auto_withdrawal() {
+    assert_eq!(get_parent_config().threshold, DEFAULT_PARENT_WITHDRAW_THRESHOLD);
+    assert!(parent_withdrawal_due(20 * USDT));
+
+    set_parent_config(50 * USDT, treasury(), T).unwrap();
+    assert!(!parent_withdrawal_due(20 * USDT), "Below the raised threshold: no withdrawal");
+    assert!(!parent_withdrawal_due(50 * USDT));
+    assert!(parent_withdrawal_due(50 * USDT + 1));
+}
+
+#[test]
+fn test_recipient_receives_parent_withdrawals_only() {
+    set_parent_config(DEFAULT_PARENT_WITHDRAW_THRESHOLD, treasury(), T).unwrap();
+
+    let (_, args) = build_withdrawal_transfer(get_parent_principal(), 20 * USDT, T).unwrap();
+    assert_eq!(args.to.owner, treasury().owner);
+    assert_eq!(args.to.subaccount, treasury().subaccount);
+
+    let player = Principal::from_slice(&[3; 29]);
+    let account = payout_account(player);
+    assert_eq!(account.owner, player);
+    assert_eq!(account.subaccount, None);
+}
+
+#[test]
+fn test_parent_config_validation() {
+    let before = get_parent_config();
+    assert!(set_parent_config(USDT, treasury(), T).is_err(), "Threshold must exceed min withdrawal + fee");
+    let anonymous = Account { owner: Principal::anonymous(), subaccount: None };
+    assert!(set_parent_config(50 * USDT, anonymous, T).is_err());
+
+    PENDING_WITHDRAWALS.with(|p| p.borrow_mut().insert(get_parent_principal(), PendingWithdrawal {
+        withdrawal_type: WithdrawalType::User { amount: 20 * USDT },
+        created_at: T,
+    }));
+    let err = set_parent_config(50 * USDT, treasury(), T).unwrap_err();
+    assert!(err.starts_with("Parent withdrawal pending"), "{}", err);
+    assert_eq!(get_parent_config().threshold, before.threshold, "Nothing changes on rejection");
+}
@@ -0,0 +1,131 @@
+// Tests for pausing betting and draining the pool for a migration
+
+use candid::{Nat, Principal};
+use crate::defi_accounting::accounting::{
+    get_audit_entries, try_deduct_balance, update_balance, PENDING_WITHDRAWALS,
+};
+use crate::defi_accounting::liquidity_pool::{
+    add_to_reserve, count_lp_positions_internal, deposit_liquidity_internal, get_pool_reserve, restore_lp_position,
+};
+use crate::defi_accounting::pool_drain::{
+    begin_drain, finish_drain, get_pending_drain, is_betting_paused, resolve_pool_drain, set_betting_paused,
+};
+use crate::defi_accounting::types::{AuditEvent, GameError, PendingWithdrawal, WithdrawalType};
+use crate::types::{Account, CKUSDT_TRANSFER_FEE};
+use std::future::Future;
+use std::pin::pin;
+use std::task::{Context, Poll, Waker};
+
+const USDT: u64 = 1_000_000;
+const T: u64 = 1_700_000_000_000_000_000;
+
+fn user(id: u8) -> Principal {
+    Principal::from_slice(&[id; 29])
+}
+
+fn recipient() -> Account {
+    Account { owner: user(9), subaccount: None }
+}
+
+fn poll_once<F: Future>(fut: F) -> Poll<F::Output> {
+    let mut cx = Context::from_waker(Waker::noop());
+    pin!(fut).poll(&mut cx)
+}
+
+#[test]
+fn test_pause_blocks_bets_and_lp_deposits() {
+    let player = user(1);
+    update_balance(player, 10 * USDT).unwrap();
+    assert!(!is_betting_paused());
+
+    set_betting_paused(true, T).unwrap();
+    assert_eq!(try_deduct_balance(player, USDT).unwrap_err(), GameError::BettingPaused);
+    match poll_once(deposit_liquidity_internal(player, 10 * USDT, None)) {
+        Poll::Ready(Err(e)) => assert_eq!(e, "Betting is paused"),
+        _ => panic!("LP deposit should be rejected synchronously"),
+    }
+
+    set_betting_paused(false, T).unwrap();
+    assert_eq!(try_deduct_balance(player, USDT), Ok(9 * USDT));
+}
+
+#[test]
+fn test_drain_requires_pause_and_no_pending_withdrawals() {
+    add_to_reserve(500 * USDT);
+    restore_lp_position(user(2), Nat::from(400 * USDT), Nat::from(0u64));
+    restore_lp_position(user(3), Nat::from(100 * USDT), Nat::from(0u64));
+
+    assert_eq!(begin_drain(recipient(), T).unwrap_err(), "Pause betting before draining the pool");
+
+    set_betting_paused(true, T).unwrap();
+    PENDING_WITHDRAWALS.with(|p| p.borrow_mut().insert(user(4), PendingWithdrawal {
+        withdrawal_type: WithdrawalType::User { amount: USDT },
+        created_at: 0,
+    }));
+    let err = begin_drain(recipient(), T).unwrap_err();
+    assert!(err.starts_with("1 pending withdrawals"), "{}", err);
+    assert_eq!(get_pool_reserve(), 500 * USDT, "Nothing moves on rejection");
+
+    PENDING_WITHDRAWALS.with(|p| p.borrow_mut().remove(&user(4)));
+    let drain = begin_drain(recipient(), T).unwrap();
+    assert_eq!((drain.amount, drain.created_at), (500 * USDT, T));
+    assert_eq!(get_pending_drain(), Some(drain), "Drain is recorded before the transfer");
+    assert_eq!(get_pool_reserve(), 0, "Reserve is taken before the transfer");
+    assert_eq!(begin_drain(recipient(), T).unwrap_err(), "A pool drain is already in flight");
+    assert!(set_betting_paused(false, T).is_err(), "Betting stays paused during the drain");
+
+    // Transfer succeeded: LP positions are cleared and the drain is logged
+    assert_eq!(finish_drain(T), 500 * USDT - CKUSDT_TRANSFER_FEE);
+    assert_eq!(count_lp_positions_internal(), 0);
+    assert_eq!(get_pending_drain(), None);
+    let last = get_audit_entries(1, 0);
+    assert!(matches!(
+        last[0].event,
+        AuditEvent::PoolDrained { amount, lp_positions: 2, .. } if amount == 500 * USDT
+    ));
+}
+
+/// Drain the pool and leave the transfer's outcome unknown, as after an uncertain ledger call
+fn uncertain_drain() -> u64 {
+    add_to_reserve(300 * USDT);
+    restore_lp_position(user(2), Nat::from(300 * USDT), Nat::from(0u64));
+    set_betting_paused(true, T).unwrap();
+    let drain = begin_drain(recipient(), T).unwrap();
+    // The in-flight call returned without an answer
+    crate::defi_accounting::pool_drain::clear_drain_in_flight();
+    drain.amount
+}
+
+#[test]
+fn test_unresolved_drain_blocks_resume_and_new_drains() {
+    uncertain_drain();
+    assert_eq!(
+        set_betting_paused(false, T).unwrap_err(),
+        "Cannot resume betting while a pool drain is unresolved"
+    );
+    assert!(begin_drain(recipient(), T).unwrap_err().starts_with("A pool drain is unresolved"));
+    assert!(is_betting_paused());
+}
+
+#[test]
+fn test_resolve_not_landed_restores_reserve() {
+    let amount = uncertain_drain();
+    assert_eq!(resolve_pool_drain(false, T), Ok(amount));
+    assert_eq!(get_pool_reserve(), amount);
+    assert_eq!(count_lp_positions_internal(), 1, "LP positions are untouched");
+    assert_eq!(get_pending_drain(), None);
+    set_betting_paused(false, T).unwrap();
+    assert_eq!(resolve_pool_drain(false, T).unwrap_err(), "No pool drain to resolve");
+}
+
+#[test]
+fn test_resolve_landed_finalizes_drain() {
+    let amount = uncertain_drain();
+    assert_eq!(resolve_pool_drain(true, T), Ok(amount - CKUSDT_TRANSFER_FEE));
+    assert_eq!(get_pool_reserve(), 0);
+    assert_eq!(count_lp_positions_internal(), 0);
+    assert_eq!(get_pending_drain(), None);
+    let last = get_audit_entries(1, 0);
+    assert!(matches!(last[0].event, AuditEvent::PoolDrained { lp_positions: 1, .. }));
+    set_betting_paused(false, T).unwrap();
+}
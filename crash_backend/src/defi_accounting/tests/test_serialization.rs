@@ -1,7 +1,7 @@
 // Tests serialization integrity of DeFi accounting types.
 // Verifies unbounded serialization works with large Nat values.
 
-use crate::defi_accounting::types::{PendingWithdrawal, WithdrawalType, sanitize_error};
+use crate::defi_accounting::types::{PendingWithdrawal, WithdrawalType, TokenInfo, sanitize_error};
 use ic_stable_structures::Storable;
 use ic_stable_structures::storable::Bound;
 use candid::Nat;
@@ -59,3 +59,35 @@ fn test_pending_withdrawal_unbounded_serialization() {
     }
     assert_eq!(decoded.created_at, u64::MAX, "created_at should survive round-trip");
 }
+
+#[test]
+fn test_token_info_roundtrip_and_default() {
+    let default = TokenInfo::default();
+    assert_eq!(default.decimals, 6, "Default must match ckUSDT decimals");
+
+    let info = TokenInfo { symbol: "TEST".to_string(), decimals: 8, fee: 12_345 };
+    let decoded = TokenInfo::from_bytes(info.to_bytes());
+    assert_eq!(decoded, info);
+}
+
+#[test]
+fn test_version_info_reports_toggles() {
+    use crate::defi_accounting::liquidity_pool::set_target_reserve;
+    let info = crate::defi_accounting::query::get_version_info("crash", 3, vec!["extra".to_string()]);
+    assert_eq!(info.game_name, "crash");
+    assert_eq!(info.schema_version, 3);
+    assert_eq!(info.enabled_features, vec!["extra", "deposit_and_play", "withdraw_keeping"]);
+
+    set_target_reserve(1_000_000_000).unwrap();
+    let info = crate::defi_accounting::query::get_version_info("crash", 3, vec![]);
+    assert!(info.enabled_features.contains(&"excess_distribution".to_string()));
+}
+
+#[test]
+fn test_stats_summary_fresh_state() {
+    let summary = crate::defi_accounting::query::get_stats_summary("crash");
+    assert_eq!(summary.game_name, "crash");
+    assert_eq!(summary.total_volume, 0);
+    assert_eq!(summary.house_pnl, 0);
+    assert_eq!(summary.active_users, 0);
+}
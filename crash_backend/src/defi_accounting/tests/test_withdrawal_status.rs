@@ -0,0 +1,79 @@
+// Tests for the user-facing withdrawal status derived from the pending record
+
+use candid::{Nat, Principal};
+use crate::defi_accounting::accounting::{
+    get_withdrawal_status_detail_internal, log_audit_at, LEDGER_DEDUP_WINDOW_NANOS,
+    PENDING_WITHDRAWALS, WITHDRAWALS_IN_FLIGHT,
+};
+use crate::defi_accounting::types::{AuditEvent, PendingWithdrawal, WithdrawalState, WithdrawalType};
+use crate::types::CKUSDT_TRANSFER_FEE;
+
+const CREATED_AT: u64 = 1_700_000_000_000_000_000;
+
+fn user(id: u8) -> Principal {
+    Principal::from_slice(&[id; 29])
+}
+
+fn insert_pending(u: Principal, withdrawal_type: WithdrawalType) {
+    PENDING_WITHDRAWALS.with(|p| p.borrow_mut().insert(u, PendingWithdrawal { withdrawal_type, created_at: CREATED_AT }));
+}
+
+#[test]
+fn test_no_withdrawal_has_no_detail() {
+    assert!(get_withdrawal_status_detail_internal(user(1), CREATED_AT).is_none());
+}
+
+#[test]
+fn test_pending_awaits_retry_until_dedup_window_ends() {
+    let u = user(2);
+    insert_pending(u, WithdrawalType::User { amount: 5_000_000 });
+
+    let now = CREATED_AT + 60_000_000_000;
+    let detail = get_withdrawal_status_detail_internal(u, now).unwrap();
+    assert_eq!(detail.state, WithdrawalState::AwaitingRetry);
+    assert_eq!(detail.amount, 5_000_000);
+    assert_eq!(detail.net_amount, 5_000_000 - CKUSDT_TRANSFER_FEE);
+    assert_eq!(detail.created_at, Some(CREATED_AT));
+    assert_eq!(detail.next_retry_at, Some(now));
+    assert_eq!(detail.retry_deadline, Some(CREATED_AT + LEDGER_DEDUP_WINDOW_NANOS));
+    assert!(detail.description.contains("retry_withdrawal"));
+
+    let detail = get_withdrawal_status_detail_internal(u, CREATED_AT + LEDGER_DEDUP_WINDOW_NANOS).unwrap();
+    assert_eq!(detail.state, WithdrawalState::Failed);
+    assert_eq!(detail.next_retry_at, None);
+    assert!(detail.description.contains("abandon_withdrawal"));
+}
+
+#[test]
+fn test_in_flight_transfer_reported_in_progress() {
+    let u = user(3);
+    let lp = WithdrawalType::LP { shares: Nat::from(10u64), reserve: Nat::from(2_000_000u64), amount: 1_980_000, fee: 20_000 };
+    insert_pending(u, lp);
+    WITHDRAWALS_IN_FLIGHT.with(|f| f.borrow_mut().insert(u));
+
+    let detail = get_withdrawal_status_detail_internal(u, CREATED_AT + 1).unwrap();
+    assert_eq!(detail.state, WithdrawalState::InProgress);
+    assert_eq!(detail.net_amount, 1_980_000 - CKUSDT_TRANSFER_FEE);
+    assert_eq!(detail.next_retry_at, None);
+}
+
+#[test]
+fn test_abandoned_reported_until_next_withdrawal() {
+    let u = user(4);
+    let other = user(5);
+    log_audit_at(AuditEvent::WithdrawalInitiated { user: u, amount: 3_000_000 }, CREATED_AT);
+    log_audit_at(AuditEvent::WithdrawalAbandoned { user: u, amount: 3_000_000 }, CREATED_AT + 1);
+    log_audit_at(AuditEvent::WithdrawalCompleted { user: other, amount: 1_000_000 }, CREATED_AT + 2);
+
+    let detail = get_withdrawal_status_detail_internal(u, CREATED_AT + 3).unwrap();
+    assert_eq!(detail.state, WithdrawalState::Abandoned);
+    assert_eq!(detail.amount, 3_000_000);
+    assert_eq!(detail.net_amount, 0);
+    assert_eq!(detail.created_at, None);
+    assert!(get_withdrawal_status_detail_internal(other, CREATED_AT + 3).is_none());
+
+    // A later completed withdrawal supersedes the abandon
+    log_audit_at(AuditEvent::WithdrawalInitiated { user: u, amount: 2_000_000 }, CREATED_AT + 4);
+    log_audit_at(AuditEvent::WithdrawalCompleted { user: u, amount: 2_000_000 }, CREATED_AT + 5);
+    assert!(get_withdrawal_status_detail_internal(u, CREATED_AT + 6).is_none());
+}
@@ -3,10 +3,78 @@ use ic_stable_structures::Storable;
 use std::borrow::Cow;
 use ic_stable_structures::storable::Bound;
 
+use super::accounting;
+
 pub fn sanitize_error(msg: &str) -> String {
     msg.chars().take(256).collect()
 }
 
+/// Structured error for accounting endpoints.
+///
+/// Deposit and withdrawal endpoints return it over candid so frontends can branch on
+/// the variant. Endpoints that still return `text` convert with `String::from`.
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum GameError {
+    Unauthorized,
+    BelowMinimum { amount: u64, minimum: u64 },
+    AboveMaximum { amount: u64, maximum: u64 },
+    WithdrawalPending,
+    /// The house cannot cover payouts; play is suspended until it is topped up
+    Insolvent,
+    InsufficientBalance { have: u64, need: u64 },
+    BettingPaused,
+    /// No pending withdrawal to retry or abandon
+    NotFound,
+    /// Failures without a dedicated variant yet (ledger errors, rollbacks, ...)
+    Other(String),
+}
+
+impl std::fmt::Display for GameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameError::Unauthorized => write!(f, "Authentication required"),
+            GameError::BelowMinimum { amount, minimum } => write!(
+                f,
+                "Amount {} is below the minimum of {}",
+                accounting::format_token_amount(*amount),
+                accounting::format_token_amount(*minimum)
+            ),
+            GameError::AboveMaximum { amount, maximum } => write!(
+                f,
+                "Amount {} exceeds the maximum of {}",
+                accounting::format_token_amount(*amount),
+                accounting::format_token_amount(*maximum)
+            ),
+            GameError::WithdrawalPending => write!(
+                f,
+                "Withdrawal already pending. Call retry_withdrawal() to retry or abandon_withdrawal() to cancel."
+            ),
+            GameError::Insolvent => write!(f, "Game temporarily paused - insufficient funds. Contact admin."),
+            GameError::InsufficientBalance { have, need } => write!(
+                f,
+                "Insufficient balance: {} available, {} needed",
+                accounting::format_token_amount(*have),
+                accounting::format_token_amount(*need)
+            ),
+            GameError::BettingPaused => write!(f, "Betting is paused"),
+            GameError::NotFound => write!(f, "No pending withdrawal"),
+            GameError::Other(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl From<GameError> for String {
+    fn from(err: GameError) -> String {
+        err.to_string()
+    }
+}
+
+impl From<String> for GameError {
+    fn from(msg: String) -> GameError {
+        GameError::Other(msg)
+    }
+}
+
 /// Pending withdrawal awaiting confirmation or user action.
 ///
 /// # Design Note
@@ -87,6 +155,7 @@ pub enum AuditEvent {
         user: Principal,
         amount: u64,
         new_balance: u64,
+        block_index: Option<u64>, // Ledger block of a deposit's transfer_from (None for pool credits)
     },
     SlippageProtectionTriggered {
         user: Principal,
@@ -101,6 +170,23 @@ pub enum AuditEvent {
         amount: u64,
         new_balance: u64,
     },
+    /// Pool reserve above the target paid out to LPs as balance credits
+    ExcessDistributed {
+        amount: u64,
+        lp_count: u64,
+        reserve_after: u64,
+    },
+    RebatesPaid {
+        amount: u64,
+        user_count: u64,
+        reserve_after: u64,
+    },
+    /// Whole pool reserve sent to `recipient` for a migration; LP positions were cleared
+    PoolDrained {
+        recipient: Principal,
+        amount: u64,
+        lp_positions: u64,
+    },
 }
 
 /// Health check result for admin monitoring.
@@ -126,6 +212,44 @@ pub struct HealthCheck {
     pub unique_users: u64,
     pub unique_lps: u64,
     pub is_solvent: bool,
+    pub invariants: Vec<InvariantCheck>,
+}
+
+/// Result of one cross-subsystem invariant (see `invariants.rs`)
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct InvariantCheck {
+    pub name: String,
+    pub passed: bool,
+    pub detail: String,
+}
+
+/// Where a caller's withdrawal stands, derived from the pending record
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum WithdrawalState {
+    /// Outcome unknown and no transfer in flight: `retry_withdrawal` may be called now
+    AwaitingRetry,
+    /// A ledger transfer for this withdrawal is awaiting its response
+    InProgress,
+    /// Past the ledger deduplication window: retries are rejected as TooOld
+    Failed,
+    /// Cleared with `abandon_withdrawal` (balance was not restored)
+    Abandoned,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct WithdrawalStatusDetail {
+    pub state: WithdrawalState,
+    /// What the user should do next
+    pub description: String,
+    pub amount: u64,
+    /// What arrives on-chain: `amount` minus the ledger transfer fee (0 once abandoned)
+    pub net_amount: u64,
+    /// Ledger `created_at_time` of the pending transfer (None once abandoned)
+    pub created_at: Option<u64>,
+    /// Earliest time `retry_withdrawal` is accepted (None when retrying is pointless)
+    pub next_retry_at: Option<u64>,
+    /// After this time the ledger rejects retries as TooOld
+    pub retry_deadline: Option<u64>,
 }
 
 #[derive(CandidType, Deserialize, Clone, Debug)]
@@ -150,6 +274,22 @@ pub struct AbandonedEntry {
     pub timestamp: u64,
 }
 
+/// One user's balance replayed from the audit log and game history (diagnostic only)
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct ReconcileReport {
+    pub user: Principal,
+    pub stored_balance: u64,
+    pub expected_balance: u64,
+    /// stored_balance - expected_balance
+    pub drift: i64,
+    /// Balance-carrying audit event the replay starts from (None = replayed from zero)
+    pub anchor_timestamp: Option<u64>,
+    pub events_replayed: u64,
+    pub games_replayed: u64,
+    /// False when history may be missing: pruned audit log, truncated game history or LP activity
+    pub complete: bool,
+}
+
 #[derive(CandidType, Deserialize, Clone, Debug)]
 pub struct UserBalance {
     pub user: Principal,
@@ -187,3 +327,293 @@ impl Storable for AuditEntry {
 
     const BOUND: Bound = Bound::Unbounded;
 }
+
+/// Ledger token metadata, fetched once at init and cached in stable memory.
+/// Defaults to ckUSDT (6 decimals) until the ledger has been queried.
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct TokenInfo {
+    pub symbol: String,
+    pub decimals: u8,
+    pub fee: u64,
+}
+
+impl Default for TokenInfo {
+    fn default() -> Self {
+        Self {
+            symbol: "ckUSDT".to_string(),
+            decimals: 6,
+            fee: crate::types::CKUSDT_TRANSFER_FEE,
+        }
+    }
+}
+
+impl Storable for TokenInfo {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(candid::encode_one(self).expect("CRITICAL: Failed to encode TokenInfo"))
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        self.to_bytes().into_owned()
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        candid::decode_one(&bytes).expect("CRITICAL: Failed to decode TokenInfo from stable storage")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// Returned by a successful `deposit`: the ledger block is the user's on-ledger reference
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct DepositReceipt {
+    pub new_balance: u64,
+    pub block_index: u64,
+}
+
+/// A deposit attempt registered under a client-supplied idempotency key.
+/// `new_balance` and `block_index` are set once the transfer has been credited.
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct DepositKeyRecord {
+    pub key: [u8; 16],
+    pub amount: u64,
+    pub created_at: u64,        // Ledger created_at_time reused on retries
+    pub new_balance: Option<u64>,
+    pub block_index: Option<u64>, // None for keys completed before receipts were recorded
+}
+
+/// Recent deposit keys for one principal, oldest first
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct DepositKeyList {
+    pub records: Vec<DepositKeyRecord>,
+}
+
+impl Storable for DepositKeyList {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(candid::encode_one(self).expect("CRITICAL: Failed to encode DepositKeyList"))
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        self.to_bytes().into_owned()
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        candid::decode_one(&bytes).expect("CRITICAL: Failed to decode DepositKeyList from stable storage")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// Compact per-game summary for dashboards.
+/// The shape is shared by every game so a frontend can aggregate them uniformly.
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct StatsSummary {
+    pub game_name: String,
+    pub total_volume: u64,
+    pub pool_reserve: u64,
+    pub house_pnl: i64,
+    pub active_users: u64,
+}
+
+/// Deployed version and active configuration, the same shape in every game
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct VersionInfo {
+    pub game_name: String,
+    /// Bumped whenever the Candid interface or stable memory layout changes
+    pub schema_version: u32,
+    /// Optional features and admin toggles currently active, as snake_case names
+    pub enabled_features: Vec<String>,
+    /// Git commit baked in at build time through the GIT_COMMIT env var, if it was set
+    pub build_commit: Option<String>,
+}
+
+/// Result of the shared solvency check (see `solvency.rs`)
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct SolvencyStatus {
+    pub canister_balance: u64,
+    pub obligations: u64,       // pool reserve + user deposits
+    pub margin_bp: u64,
+    pub required_balance: u64,  // obligations * (1 + margin)
+    pub is_solvent: bool,
+}
+
+/// Per-principal lifetime totals, updated by `liquidity_pool::settle_bet`.
+/// `total_won` / `total_lost` are net amounts per settlement (payout - bet, bet - payout),
+/// so `total_won - total_lost` is the player's lifetime result.
+#[derive(CandidType, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct PlayerLifetimeStats {
+    pub total_wagered: u64,
+    pub total_won: u64,
+    pub total_lost: u64,
+    pub games_played: u64,
+    pub biggest_win: u64,
+}
+
+impl Storable for PlayerLifetimeStats {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(candid::encode_one(self).expect("CRITICAL: Failed to encode PlayerLifetimeStats"))
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        self.to_bytes().into_owned()
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        candid::decode_one(&bytes).expect("CRITICAL: Failed to decode PlayerLifetimeStats from stable storage")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// Parent fee auto-withdrawal settings (see `parent`)
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct ParentWithdrawalConfig {
+    /// The weekly timer withdraws the parent's balance once it exceeds this
+    pub threshold: u64,
+    /// Ledger account the parent's balance is paid to
+    pub recipient: crate::types::Account,
+}
+
+impl Storable for ParentWithdrawalConfig {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(candid::encode_one(self).expect("CRITICAL: Failed to encode ParentWithdrawalConfig"))
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        self.to_bytes().into_owned()
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        candid::decode_one(&bytes).expect("CRITICAL: Failed to decode ParentWithdrawalConfig from stable storage")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// Pool drain taken out of the reserve but not yet confirmed on the ledger (see `pool_drain`)
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct PendingDrain {
+    /// Reserve taken from the pool, ledger fee included
+    pub amount: u64,
+    /// Ledger fee when the drain started, reused on retries like `created_at`
+    pub fee: u64,
+    pub recipient: crate::types::Account,
+    /// `created_at_time` of the transfer, reused on retries so the ledger deduplicates them
+    pub created_at: u64,
+}
+
+impl Storable for PendingDrain {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(candid::encode_one(self).expect("CRITICAL: Failed to encode PendingDrain"))
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        self.to_bytes().into_owned()
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        candid::decode_one(&bytes).expect("CRITICAL: Failed to decode PendingDrain from stable storage")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// Loyalty rebate settings. `rebate_bp` = 0 (the default) disables rebates.
+#[derive(CandidType, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct RebateConfig {
+    /// Share of the edge collected from a player that is paid back, in basis points
+    pub rebate_bp: u64,
+    /// Volume a player must wager between rebates to qualify
+    pub min_volume: u64,
+}
+
+impl Storable for RebateConfig {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(candid::encode_one(self).expect("CRITICAL: Failed to encode RebateConfig"))
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        self.to_bytes().into_owned()
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        candid::decode_one(&bytes).expect("CRITICAL: Failed to decode RebateConfig from stable storage")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// Every admin-settable accounting parameter (see `config`)
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct AccountingConfig {
+    pub audit_retention: u64,
+    pub solvency_margin_bp: u64,
+    pub target_reserve: u64,
+    pub rebate: RebateConfig,
+    pub parent: ParentWithdrawalConfig,
+    pub event_subscriber: Option<Principal>,
+}
+
+/// Notification sent to the event subscriber (see `events`)
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum GameEvent {
+    Deposit { user: Principal, amount: u64, new_balance: u64 },
+    Withdrawal { user: Principal, amount: u64 },
+    LargeWin { user: Principal, bet_amount: u64, payout_amount: u64 },
+}
+
+/// A player's rebate accrual since their last rebate
+#[derive(CandidType, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct RebateAccount {
+    pub wagered: u64,
+    /// Bets minus payouts: what the house kept from this player (negative while they are ahead)
+    pub house_net: i64,
+}
+
+impl Storable for RebateAccount {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(candid::encode_one(self).expect("CRITICAL: Failed to encode RebateAccount"))
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        self.to_bytes().into_owned()
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        candid::decode_one(&bytes).expect("CRITICAL: Failed to decode RebateAccount from stable storage")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// One settled game in a player's recent history
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct GameHistoryEntry {
+    pub game_id: u64, // Per-player sequence number
+    pub bet: u64,
+    pub payout: u64,
+    pub timestamp: u64,
+    pub outcome_summary: String,
+}
+
+/// A player's recent games, oldest first
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct GameHistoryList {
+    pub next_game_id: u64,
+    pub entries: Vec<GameHistoryEntry>,
+}
+
+impl Storable for GameHistoryList {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(candid::encode_one(self).expect("CRITICAL: Failed to encode GameHistoryList"))
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        self.to_bytes().into_owned()
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        candid::decode_one(&bytes).expect("CRITICAL: Failed to decode GameHistoryList from stable storage")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
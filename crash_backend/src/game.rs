@@ -1,5 +1,5 @@
 use candid::{CandidType, Deserialize, Principal};
-use ic_cdk::management_canister::raw_rand;
+use crate::vrf::{raw_rand_with_retries, VRF_RETRIES};
 use crate::types::MIN_BET;
 use crate::defi_accounting::{self as accounting, liquidity_pool};
 use serde::Serialize;
@@ -8,6 +8,7 @@ use sha2::{Sha256, Digest};
 // Constants
 const MAX_CRASH: f64 = 100.0;
 const MAX_ROCKETS: u8 = 10;
+const MAX_LADDER_TARGETS: usize = 10;
 
 // Max multiplier for bet validation (100x max crash)
 // This must match MAX_CRASH
@@ -27,6 +28,11 @@ pub struct PlayCrashResult {
     pub payout: u64,
     pub profit: i64,
     pub randomness_hash: String,
+    // Commit-reveal rounds only (None for VRF rounds)
+    pub server_seed: Option<[u8; 32]>,
+    pub server_seed_hash: Option<String>,
+    pub client_seed: Option<String>,
+    pub nonce: Option<u64>,
 }
 
 #[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
@@ -41,6 +47,8 @@ pub struct SingleRocketResult {
 pub struct MultiCrashResult {
     pub rockets: Vec<SingleRocketResult>,
     pub target_multiplier: f64,
+    /// Chance each rocket reaches the target, P(crash >= target)
+    pub win_probability: f64,
     pub rocket_count: u8,
     pub rockets_succeeded: u8,
     pub bet_per_rocket: u64,
@@ -50,6 +58,25 @@ pub struct MultiCrashResult {
     pub master_randomness_hash: String,
 }
 
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
+pub struct LadderTrancheResult {
+    pub target_multiplier: f64,
+    pub bet_amount: u64,
+    pub reached_target: bool,
+    pub payout: u64,
+}
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
+pub struct LadderResult {
+    pub crash_point: f64,
+    pub tranches: Vec<LadderTrancheResult>,
+    pub tranches_hit: u8,
+    pub total_bet: u64,
+    pub total_payout: u64,
+    pub net_profit: i64,
+    pub randomness_hash: String,
+}
+
 // =============================================================================
 // HELPER FUNCTIONS
 // =============================================================================
@@ -68,33 +95,61 @@ pub fn calculate_max_bet() -> u64 {
     max_bet as u64
 }
 
-/// Calculate payout from bet and multiplier using integer math to avoid f64 precision loss.
+/// Payout for `bet` at `multiplier_bp` (multiplier scaled by MULTIPLIER_SCALE), in pure integer math.
 ///
-/// Uses scaled integer arithmetic: multiplier is converted to basis points (1.5x = 1_500_000),
-/// then we compute (bet * multiplier_scaled) / MULTIPLIER_SCALE using u128 intermediates.
-/// This ensures exact results for all representable multipliers.
-fn calculate_payout(bet_amount: u64, multiplier: f64) -> Result<u64, String> {
+/// Rounding policy: round half up to the nearest unit, so the paid amount is the exact
+/// product to within half a unit. Saturates at u64::MAX, which no house limit can accept.
+pub fn apply_multiplier(bet: u64, multiplier_bp: u64) -> u64 {
+    let scaled = bet as u128 * multiplier_bp as u128 + (MULTIPLIER_SCALE / 2) as u128;
+    u64::try_from(scaled / MULTIPLIER_SCALE as u128).unwrap_or(u64::MAX)
+}
+
+/// Convert a float multiplier (player target) to MULTIPLIER_SCALE units.
+///
+/// Rounds to the nearest step: truncating would turn 2.3x into 2.299999x because
+/// 2.3 * 1_000_000 is 2299999.9999999995 in f64.
+fn multiplier_to_scaled(multiplier: f64) -> Result<u64, String> {
     if !multiplier.is_finite() || multiplier < 0.0 {
         return Err("Invalid multiplier".to_string());
     }
+    let scaled = (multiplier * MULTIPLIER_SCALE as f64).round();
+    if scaled > u64::MAX as f64 {
+        return Err("Multiplier too large".to_string());
+    }
+    Ok(scaled as u64)
+}
 
-    // Convert multiplier to scaled integer (e.g., 2.5x = 2_500_000)
-    // This preserves 6 decimal places of precision
-    let multiplier_scaled = (multiplier * MULTIPLIER_SCALE as f64) as u128;
-
-    // Use u128 for intermediate calculation to prevent overflow
-    let numerator = (bet_amount as u128)
-        .checked_mul(multiplier_scaled)
-        .ok_or("Payout calculation overflow")?;
+/// Calculate payout from bet and a float multiplier: the multiplier is converted to
+/// MULTIPLIER_SCALE units once, then `apply_multiplier` does the rest in integers.
+fn calculate_payout(bet_amount: u64, multiplier: f64) -> Result<u64, String> {
+    Ok(apply_multiplier(bet_amount, multiplier_to_scaled(multiplier)?))
+}
 
-    let payout = numerator / (MULTIPLIER_SCALE as u128);
+/// Validate a single-rocket bet: minimum amount, target range and house limit
+fn validate_single_bet(bet_amount: u64, target_multiplier: f64) -> Result<(), String> {
+    // Validate minimum bet (0.01 USDT)
+    if bet_amount < MIN_BET {
+        return Err("Invalid bet: minimum is 0.01 USDT".to_string());
+    }
 
-    // Check if result fits in u64
-    if payout > u64::MAX as u128 {
-        return Err("Payout exceeds u64 limit".to_string());
+    // Validate target multiplier
+    if target_multiplier < 1.01 {
+        return Err("Target must be at least 1.01x".to_string());
+    }
+    if target_multiplier > MAX_CRASH {
+        return Err(format!("Target cannot exceed {}x", MAX_CRASH));
+    }
+    if !target_multiplier.is_finite() {
+        return Err("Target must be a finite number".to_string());
     }
 
-    Ok(payout as u64)
+    // Check max payout against house limit
+    let max_potential_payout = calculate_payout(bet_amount, target_multiplier)?;
+    let max_allowed = accounting::get_max_allowed_payout();
+    if max_potential_payout > max_allowed {
+        return Err("Invalid bet: exceeds house limit".to_string());
+    }
+    Ok(())
 }
 
 /// Validate randomness bytes are not degenerate (all zeros or all ones).
@@ -124,10 +179,13 @@ fn bytes_to_float(bytes: &[u8]) -> Result<f64, String> {
 
     let mut byte_array = [0u8; 8];
     byte_array.copy_from_slice(&bytes[0..8]);
-    let random_u64 = u64::from_be_bytes(byte_array);
-    // >> 11 extracts most significant 53 bits for f64 mantissa precision
-    let random = (random_u64 >> 11) as f64 / (1u64 << 53) as f64;
-    Ok(random)
+    Ok(u64_to_unit_float(u64::from_be_bytes(byte_array)))
+}
+
+/// Map a u64 onto [0.0, 1.0). >> 11 keeps the 53 most significant bits (f64 mantissa
+/// precision), so even u64::MAX maps to 1 - 2^-53, strictly below 1.0.
+pub fn u64_to_unit_float(random_u64: u64) -> f64 {
+    (random_u64 >> 11) as f64 / (1u64 << 53) as f64
 }
 
 /// Derive an independent float for a specific rocket index.
@@ -143,16 +201,20 @@ fn derive_rocket_random(vrf_bytes: &[u8], rocket_index: u8) -> Result<f64, Strin
 
     let mut byte_array = [0u8; 8];
     byte_array.copy_from_slice(&hash[0..8]);
-    let random_u64 = u64::from_be_bytes(byte_array);
-    // >> 11 extracts most significant 53 bits for f64 mantissa precision
-    let random = (random_u64 >> 11) as f64 / (1u64 << 53) as f64;
-    Ok(random)
+    Ok(u64_to_unit_float(u64::from_be_bytes(byte_array)))
 }
 
 /// Calculate crash point using the formula: crash = 0.99 / (1.0 - random)
+/// Any non-finite input or result falls back to MAX_CRASH (NaN would pass through `clamp`).
 pub fn calculate_crash_point(random: f64) -> f64 {
-    let random = random.max(0.0).min(0.99999);
+    if !random.is_finite() {
+        return MAX_CRASH;
+    }
+    let random = random.clamp(0.0, 0.99999);
     let crash = 0.99 / (1.0 - random);
+    if !crash.is_finite() {
+        return MAX_CRASH;
+    }
     crash.min(MAX_CRASH)
 }
 
@@ -168,36 +230,114 @@ fn create_randomness_hash(bytes: &[u8]) -> String {
     format!("{:x}", hasher.finalize())
 }
 
-// =============================================================================
-// MAIN GAME LOGIC
-// =============================================================================
+/// Validate ladder targets and split the bet evenly across them.
+/// Any remainder (at most targets.len() - 1 decimals) goes one decimal each to the lowest targets.
+fn split_ladder_bet(bet_amount: u64, targets: &[f64]) -> Result<Vec<u64>, String> {
+    if targets.is_empty() {
+        return Err("Ladder needs at least 1 target".to_string());
+    }
+    if targets.len() > MAX_LADDER_TARGETS {
+        return Err(format!("Maximum {} ladder targets allowed", MAX_LADDER_TARGETS));
+    }
+    for &target in targets {
+        if !target.is_finite() {
+            return Err("Target must be a finite number".to_string());
+        }
+        if target < 1.01 {
+            return Err("Target must be at least 1.01x".to_string());
+        }
+        if target > MAX_CRASH {
+            return Err(format!("Target cannot exceed {}x", MAX_CRASH));
+        }
+    }
+    if targets.windows(2).any(|w| w[0] >= w[1]) {
+        return Err("Targets must be sorted in ascending order without duplicates".to_string());
+    }
 
-pub async fn play_crash(bet_amount: u64, target_multiplier: f64, caller: Principal) -> Result<PlayCrashResult, String> {
-    // 1. Validate minimum bet (0.01 USDT)
-    if bet_amount < MIN_BET {
-        return Err("Invalid bet: minimum is 0.01 USDT".to_string());
+    let count = targets.len() as u64;
+    let per_target = bet_amount / count;
+    if per_target < MIN_BET {
+        return Err("Invalid bet: minimum is 0.01 USDT per ladder target".to_string());
     }
+    let remainder = (bet_amount % count) as usize;
+    Ok((0..targets.len())
+        .map(|i| if i < remainder { per_target + 1 } else { per_target })
+        .collect())
+}
 
-    // 2. Validate target multiplier
+/// Reject a multi-rocket launch up front, before any randomness is fetched or balance moved.
+/// A target above MAX_CRASH can never be reached, so it is an error rather than a certain loss.
+fn validate_multi_launch(bet_per_rocket: u64, target_multiplier: f64, rocket_count: u8) -> Result<(), String> {
+    if rocket_count < 1 {
+        return Err("Must launch at least 1 rocket".to_string());
+    }
+    if rocket_count > MAX_ROCKETS {
+        return Err(format!("Maximum {} rockets allowed", MAX_ROCKETS));
+    }
+    if bet_per_rocket < MIN_BET {
+        return Err("Invalid bet: minimum is 0.01 USDT per rocket".to_string());
+    }
+    if !target_multiplier.is_finite() {
+        return Err("Target must be a finite number".to_string());
+    }
     if target_multiplier < 1.01 {
         return Err("Target must be at least 1.01x".to_string());
     }
     if target_multiplier > MAX_CRASH {
         return Err(format!("Target cannot exceed {}x", MAX_CRASH));
     }
-    if !target_multiplier.is_finite() {
-        return Err("Target must be a finite number".to_string());
+    Ok(())
+}
+
+/// P(crash >= target) for a finite target: 1 below 1x, 0 above MAX_CRASH
+pub fn win_probability(target: f64) -> f64 {
+    if target < 1.0 {
+        1.0
+    } else if target > MAX_CRASH {
+        0.0
+    } else {
+        (0.99 / target).min(1.0)
     }
+}
 
-    // 3. Check max payout against house limit
-    let max_potential_payout = calculate_payout(bet_amount, target_multiplier)?;
-    let max_allowed = accounting::get_max_allowed_payout();
-    if max_potential_payout > max_allowed {
-        return Err("Invalid bet: exceeds house limit".to_string());
+/// Resolve every tranche against a single crash point
+fn evaluate_ladder(targets: &[f64], amounts: &[u64], crash_point: f64) -> Result<(Vec<LadderTrancheResult>, u64), String> {
+    let mut tranches = Vec::with_capacity(targets.len());
+    let mut total_payout: u64 = 0;
+
+    for (&target_multiplier, &bet_amount) in targets.iter().zip(amounts) {
+        let reached_target = crash_point >= target_multiplier;
+        let payout = if reached_target {
+            calculate_payout(bet_amount, target_multiplier)?
+        } else {
+            0
+        };
+        total_payout = total_payout.checked_add(payout)
+            .ok_or("Total payout overflow")?;
+
+        tranches.push(LadderTrancheResult {
+            target_multiplier,
+            bet_amount,
+            reached_target,
+            payout,
+        });
     }
 
+    Ok((tranches, total_payout))
+}
+
+// =============================================================================
+// MAIN GAME LOGIC
+// =============================================================================
+
+pub async fn play_crash(bet_amount: u64, target_multiplier: f64, caller: Principal) -> Result<PlayCrashResult, String> {
+    accounting::require_authenticated(caller)?;
+
+    // 1-3. Validate bet, target and house limit
+    validate_single_bet(bet_amount, target_multiplier)?;
+
     // 4. Get VRF randomness (async call - execution may suspend here)
-    let random_bytes = raw_rand().await
+    let random_bytes = raw_rand_with_retries(VRF_RETRIES).await
         .map_err(|e| format!("Randomness unavailable: {:?}", e))?;
 
     if random_bytes.len() < 8 {
@@ -231,7 +371,7 @@ pub async fn play_crash(bet_amount: u64, target_multiplier: f64, caller: Princip
     accounting::update_balance(caller, new_balance)?;
 
     // 11. Settle with pool
-    if let Err(e) = liquidity_pool::settle_bet(bet_amount, payout) {
+    if let Err(e) = liquidity_pool::settle_bet(caller, bet_amount, payout) {
         // CRITICAL: Rollback if pool settlement fails
         let refund_balance = current_balance.checked_add(bet_amount)
             .ok_or("Refund calculation overflow")?;
@@ -241,6 +381,12 @@ pub async fn play_crash(bet_amount: u64, target_multiplier: f64, caller: Princip
         return Err(format!("House settlement failed. Bet refunded. Error: {}", e));
     }
 
+    crate::defi_accounting::game_history::record_game(
+        caller, bet_amount, payout,
+        format!("Crashed at {:.2}x, target {:.2}x", crash_point, target_multiplier),
+        ic_cdk::api::time(),
+    );
+
     // 12. Create randomness hash
     let randomness_hash = create_randomness_hash(&random_bytes);
 
@@ -252,34 +398,89 @@ pub async fn play_crash(bet_amount: u64, target_multiplier: f64, caller: Princip
         payout,
         profit,
         randomness_hash,
+        server_seed: None,
+        server_seed_hash: None,
+        client_seed: None,
+        nonce: None,
     })
 }
 
-pub async fn play_crash_multi(bet_per_rocket: u64, target_multiplier: f64, rocket_count: u8, caller: Principal) -> Result<MultiCrashResult, String> {
+/// Play a single round against a previously committed server seed (commit-reveal mode).
+/// No async calls: the seed was drawn in `commit_crash_seed`, so deduction and payout are atomic.
+pub fn play_crash_committed(bet_amount: u64, target_multiplier: f64, client_seed: String, caller: Principal) -> Result<PlayCrashResult, String> {
+    accounting::require_authenticated(caller)?;
+
     // 1. Validate inputs
-    if rocket_count < 1 {
-        return Err("Must launch at least 1 rocket".to_string());
-    }
-    if rocket_count > MAX_ROCKETS {
-        return Err(format!("Maximum {} rockets allowed", MAX_ROCKETS));
-    }
-    if bet_per_rocket < MIN_BET {
-        return Err("Invalid bet: minimum is 0.01 USDT per rocket".to_string());
+    validate_single_bet(bet_amount, target_multiplier)?;
+    if client_seed.len() > 256 {
+        return Err("Invalid seed: max 256 characters".to_string());
     }
+    let commitment = crate::seed::get_commitment(caller)
+        .ok_or("No committed server seed. Call commit_crash_seed() first.")?;
 
-    // Validate target multiplier
-    if target_multiplier < 1.01 {
-        return Err("Target must be at least 1.01x".to_string());
-    }
-    if target_multiplier > MAX_CRASH {
-        return Err(format!("Target cannot exceed {}x", MAX_CRASH));
-    }
-    if !target_multiplier.is_finite() {
-        return Err("Target must be a finite number".to_string());
+    // 2. Deduct bet, then consume the commitment so the seed can't be replayed
+    let _balance_after_bet = accounting::try_deduct_balance(caller, bet_amount)?;
+    crate::seed::consume_commitment(caller);
+
+    // 3. Record volume for statistics
+    crate::defi_accounting::record_bet_volume(bet_amount);
+
+    // 4. Derive crash point from HMAC(server_seed, client_seed || nonce)
+    let crash_point = crate::seed::verify_crash(commitment.server_seed, &client_seed, commitment.nonce);
+
+    // 5. Determine outcome
+    let won = crash_point >= target_multiplier;
+    let payout = if won {
+        calculate_payout(bet_amount, target_multiplier)?
+    } else {
+        0
+    };
+    let profit = (payout as i64) - (bet_amount as i64);
+
+    // 6. Credit payout to user
+    let current_balance = accounting::get_balance(caller);
+    let new_balance = current_balance.checked_add(payout)
+        .ok_or("Balance overflow when adding winnings")?;
+    accounting::update_balance(caller, new_balance)?;
+
+    // 7. Settle with pool
+    if let Err(e) = liquidity_pool::settle_bet(caller, bet_amount, payout) {
+        let refund_balance = current_balance.checked_add(bet_amount)
+            .ok_or("Refund calculation overflow")?;
+        accounting::update_balance(caller, refund_balance)?;
+
+        ic_cdk::println!("CRITICAL: Crash payout failure. Refunded {} to {}", bet_amount, caller);
+        return Err(format!("House settlement failed. Bet refunded. Error: {}", e));
     }
 
-    let total_bet = bet_per_rocket.checked_mul(rocket_count as u64)
-        .ok_or("Total bet calculation overflow")?;
+    crate::defi_accounting::game_history::record_game(
+        caller, bet_amount, payout,
+        format!("Crashed at {:.2}x, target {:.2}x", crash_point, target_multiplier),
+        ic_cdk::api::time(),
+    );
+
+    let server_seed_hash = crate::seed::hash_server_seed(&commitment.server_seed);
+
+    Ok(PlayCrashResult {
+        crash_point,
+        won,
+        target_multiplier,
+        bet_amount,
+        payout,
+        profit,
+        randomness_hash: server_seed_hash.clone(),
+        server_seed: Some(commitment.server_seed),
+        server_seed_hash: Some(server_seed_hash),
+        client_seed: Some(client_seed),
+        nonce: Some(commitment.nonce),
+    })
+}
+
+pub async fn play_crash_multi(bet_per_rocket: u64, target_multiplier: f64, rocket_count: u8, caller: Principal) -> Result<MultiCrashResult, String> {
+    accounting::require_authenticated(caller)?;
+
+    // 1. Validate inputs
+    validate_multi_launch(bet_per_rocket, target_multiplier, rocket_count)?;
 
     // 2. Check max payout against house limit
     // Worst case: all rockets win at target multiplier
@@ -293,18 +494,34 @@ pub async fn play_crash_multi(bet_per_rocket: u64, target_multiplier: f64, rocke
     }
 
     // 3. Get VRF randomness (async call - execution may suspend here)
-    let random_bytes = raw_rand().await
+    let random_bytes = raw_rand_with_retries(VRF_RETRIES).await
         .map_err(|e| format!("Randomness unavailable: {:?}", e))?;
 
     if random_bytes.len() < 32 {
         return Err("Insufficient randomness".to_string());
     }
 
+    settle_multi_rocket(caller, bet_per_rocket, target_multiplier, rocket_count, &random_bytes, ic_cdk::api::time())
+}
+
+/// Apply a multi-rocket launch as a single settlement: one deduction, one volume
+/// record and one pool settlement for the combined stake of every rocket.
+pub(crate) fn settle_multi_rocket(
+    caller: Principal,
+    bet_per_rocket: u64,
+    target_multiplier: f64,
+    rocket_count: u8,
+    random_bytes: &[u8],
+    now: u64,
+) -> Result<MultiCrashResult, String> {
+    let total_bet = bet_per_rocket.checked_mul(rocket_count as u64)
+        .ok_or("Total bet calculation overflow")?;
+
     // 4. Atomically deduct total bet AFTER await to prevent TOCTOU race condition
     let _balance_after_bet = accounting::try_deduct_balance(caller, total_bet)?;
 
-    // 5. Record volume
-    crate::defi_accounting::record_bet_volume(total_bet);
+    // 5. Record volume once for the whole launch
+    crate::defi_accounting::statistics::record_bet_volume_at(total_bet, now);
 
     // 7. Process each rocket
     let mut rockets = Vec::with_capacity(rocket_count as usize);
@@ -312,7 +529,7 @@ pub async fn play_crash_multi(bet_per_rocket: u64, target_multiplier: f64, rocke
     let mut total_payout: u64 = 0;
 
     for i in 0..rocket_count {
-        let random = derive_rocket_random(&random_bytes, i)?;
+        let random = derive_rocket_random(random_bytes, i)?;
         let crash_point = calculate_crash_point(random);
         let reached_target = crash_point >= target_multiplier;
 
@@ -343,7 +560,7 @@ pub async fn play_crash_multi(bet_per_rocket: u64, target_multiplier: f64, rocke
     accounting::update_balance(caller, new_balance)?;
 
     // 9. Settle with pool
-    if let Err(e) = liquidity_pool::settle_bet(total_bet, total_payout) {
+    if let Err(e) = liquidity_pool::settle_bet(caller, total_bet, total_payout) {
         // Rollback on failure
         let refund_balance = current_balance.checked_add(total_bet)
             .ok_or("Refund calculation overflow")?;
@@ -353,13 +570,20 @@ pub async fn play_crash_multi(bet_per_rocket: u64, target_multiplier: f64, rocke
         return Err(format!("House settlement failed. Bet refunded. Error: {}", e));
     }
 
+    crate::defi_accounting::game_history::record_game(
+        caller, total_bet, total_payout,
+        format!("{}/{} rockets reached {:.2}x", rockets_succeeded, rocket_count, target_multiplier),
+        now,
+    );
+
     // 10. Aggregate results
     let net_profit = (total_payout as i64) - (total_bet as i64);
-    let master_randomness_hash = create_randomness_hash(&random_bytes);
+    let master_randomness_hash = create_randomness_hash(random_bytes);
 
     Ok(MultiCrashResult {
         rockets,
         target_multiplier,
+        win_probability: win_probability(target_multiplier),
         rocket_count,
         rockets_succeeded,
         bet_per_rocket,
@@ -370,6 +594,84 @@ pub async fn play_crash_multi(bet_per_rocket: u64, target_multiplier: f64, rocke
     })
 }
 
+/// Play a "ladder": the bet is split evenly across ascending targets and every tranche
+/// is resolved against ONE crash point, e.g. [1.5, 2.0, 5.0] at a 2.3x crash wins the
+/// 1.5x and 2.0x tranches and loses the 5.0x tranche.
+pub async fn play_crash_ladder(bet_amount: u64, targets: Vec<f64>, caller: Principal) -> Result<LadderResult, String> {
+    accounting::require_authenticated(caller)?;
+
+    // 1. Validate targets and split the bet
+    let amounts = split_ladder_bet(bet_amount, &targets)?;
+
+    // 2. Check max payout against house limit
+    // Worst case: crash point reaches the highest target, so every tranche wins
+    let mut max_potential_payout: u64 = 0;
+    for (&target, &amount) in targets.iter().zip(&amounts) {
+        max_potential_payout = max_potential_payout.checked_add(calculate_payout(amount, target)?)
+            .ok_or("Max payout calculation overflow")?;
+    }
+    let max_allowed = accounting::get_max_allowed_payout();
+    if max_potential_payout > max_allowed {
+        return Err("Invalid bet: exceeds house limit for total payout".to_string());
+    }
+
+    // 3. Get VRF randomness (async call - execution may suspend here)
+    let random_bytes = raw_rand_with_retries(VRF_RETRIES).await
+        .map_err(|e| format!("Randomness unavailable: {:?}", e))?;
+
+    if random_bytes.len() < 8 {
+        return Err("Insufficient randomness".to_string());
+    }
+
+    // 4. Atomically deduct total bet AFTER await to prevent TOCTOU race condition
+    let _balance_after_bet = accounting::try_deduct_balance(caller, bet_amount)?;
+
+    // 5. Record volume
+    crate::defi_accounting::record_bet_volume(bet_amount);
+
+    // 6. Single crash point for all tranches
+    let random = bytes_to_float(&random_bytes)?;
+    let crash_point = calculate_crash_point(random);
+    let (tranches, total_payout) = evaluate_ladder(&targets, &amounts, crash_point)?;
+    let tranches_hit = tranches.iter().filter(|t| t.reached_target).count() as u8;
+
+    // 7. Credit total payout
+    let current_balance = accounting::get_balance(caller);
+    let new_balance = current_balance.checked_add(total_payout)
+        .ok_or("Balance overflow when adding winnings")?;
+    accounting::update_balance(caller, new_balance)?;
+
+    // 8. Settle with pool
+    if let Err(e) = liquidity_pool::settle_bet(caller, bet_amount, total_payout) {
+        // Rollback on failure
+        let refund_balance = current_balance.checked_add(bet_amount)
+            .ok_or("Refund calculation overflow")?;
+        accounting::update_balance(caller, refund_balance)?;
+
+        ic_cdk::println!("CRITICAL: Ladder payout failure. Refunded {} to {}", bet_amount, caller);
+        return Err(format!("House settlement failed. Bet refunded. Error: {}", e));
+    }
+
+    crate::defi_accounting::game_history::record_game(
+        caller, bet_amount, total_payout,
+        format!("Crashed at {:.2}x, {}/{} tranches hit", crash_point, tranches_hit, targets.len()),
+        ic_cdk::api::time(),
+    );
+
+    let net_profit = (total_payout as i64) - (bet_amount as i64);
+    let randomness_hash = create_randomness_hash(&random_bytes);
+
+    Ok(LadderResult {
+        crash_point,
+        tranches,
+        tranches_hit,
+        total_bet: bet_amount,
+        total_payout,
+        net_profit,
+        randomness_hash,
+    })
+}
+
 /// Get the maximum bet allowed for a single rocket crash game
 pub fn get_max_bet() -> u64 {
     calculate_max_bet()
@@ -395,7 +697,7 @@ pub fn get_max_bet_per_rocket(rocket_count: u8, target_multiplier: f64) -> Resul
     if max_allowed == 0 { return Ok(0); }
 
     // Convert target to scaled integer (e.g., 2.5x = 2_500_000)
-    let target_scaled = (target * MULTIPLIER_SCALE as f64) as u128;
+    let target_scaled = multiplier_to_scaled(target)? as u128;
 
     // Max bet = Max Allowed Payout / (Rockets * Target Multiplier)
     // Use u128 for calculation to prevent overflow
@@ -421,3 +723,146 @@ pub fn get_max_bet_per_rocket(rocket_count: u8, target_multiplier: f64) -> Resul
 
     Ok(max_bet as u64)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Average return per unit bet over a uniform grid of random values
+    fn ladder_return(targets: &[f64], bet_amount: u64) -> f64 {
+        const SAMPLES: u64 = 200_000;
+        let amounts = split_ladder_bet(bet_amount, targets).unwrap();
+        let mut total_payout: u128 = 0;
+        for i in 0..SAMPLES {
+            let random = (i as f64 + 0.5) / SAMPLES as f64;
+            let (_, payout) = evaluate_ladder(targets, &amounts, calculate_crash_point(random)).unwrap();
+            total_payout += payout as u128;
+        }
+        total_payout as f64 / (SAMPLES as f64 * bet_amount as f64)
+    }
+
+    #[test]
+    fn test_payout_rounding_matches_float() {
+        for target in [1.01, 1.1, 2.3, 3.33, 9.99, 33.333333, 99.99, MAX_CRASH] {
+            for bet in [MIN_BET, 1_234_567, 3_000_001, 99_999_999] {
+                let exact = (bet as f64 * target).round() as u64;
+                assert_eq!(calculate_payout(bet, target).unwrap(), exact, "bet {} at {}x", bet, target);
+            }
+        }
+        // Truncating 2.3 * 1e6 in f64 would pay 2.299999x
+        assert_eq!(multiplier_to_scaled(2.3).unwrap(), 2_300_000);
+        // Half a unit rounds up, below half rounds down
+        assert_eq!(apply_multiplier(1, 1_500_000), 2);
+        assert_eq!(apply_multiplier(1, 1_499_999), 1);
+        assert_eq!(apply_multiplier(u64::MAX, 2 * MULTIPLIER_SCALE), u64::MAX);
+        assert!(calculate_payout(MIN_BET, f64::NAN).is_err());
+    }
+
+    #[test]
+    fn test_all_ones_random_gives_max_crash() {
+        let random = u64_to_unit_float(u64::MAX);
+        assert!(random < 1.0);
+        let crash = calculate_crash_point(random);
+        assert!(crash.is_finite());
+        assert_eq!(crash, MAX_CRASH);
+
+        // All-ones VRF output is still rejected as degenerate before it reaches the formula
+        assert!(bytes_to_float(&[0xFF; 32]).is_err());
+        for bad in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert_eq!(calculate_crash_point(bad), MAX_CRASH);
+        }
+    }
+
+    #[test]
+    fn test_ladder_single_crash_point() {
+        let targets = [1.5, 2.0, 5.0];
+        let amounts = split_ladder_bet(3_000_000, &targets).unwrap();
+        assert_eq!(amounts, vec![1_000_000; 3]);
+
+        let (tranches, total_payout) = evaluate_ladder(&targets, &amounts, 2.3).unwrap();
+        let hits: Vec<bool> = tranches.iter().map(|t| t.reached_target).collect();
+        assert_eq!(hits, vec![true, true, false]);
+        assert_eq!(total_payout, 1_500_000 + 2_000_000);
+    }
+
+    #[test]
+    fn test_ladder_split_remainder() {
+        let amounts = split_ladder_bet(100_001, &[2.0, 3.0, 4.0]).unwrap();
+        assert_eq!(amounts, vec![33_334, 33_334, 33_333]);
+        assert_eq!(amounts.iter().sum::<u64>(), 100_001);
+    }
+
+    #[test]
+    fn test_ladder_validation() {
+        assert!(split_ladder_bet(MIN_BET * 3, &[]).is_err());
+        assert!(split_ladder_bet(MIN_BET * 3, &[2.0, 1.5]).is_err());
+        assert!(split_ladder_bet(MIN_BET * 3, &[2.0, 2.0]).is_err());
+        assert!(split_ladder_bet(MIN_BET * 3, &[1.5, f64::NAN]).is_err());
+        assert!(split_ladder_bet(MIN_BET * 3, &[1.5, f64::INFINITY]).is_err());
+        assert!(split_ladder_bet(MIN_BET * 3, &[1.0, 2.0]).is_err());
+        assert!(split_ladder_bet(MIN_BET * 3, &[2.0, 101.0]).is_err());
+        assert!(split_ladder_bet(MIN_BET * 11, &[1.5; 11]).is_err());
+        // Each tranche must meet the minimum bet
+        assert!(split_ladder_bet(MIN_BET * 2, &[1.5, 2.0, 3.0]).is_err());
+        assert!(split_ladder_bet(MIN_BET * 3, &[1.5, 2.0, 3.0]).is_ok());
+    }
+
+    #[test]
+    fn test_ladder_expected_return_is_099() {
+        let ladders: [&[f64]; 4] = [
+            &[2.0],
+            &[1.5, 2.0, 5.0],
+            &[1.01, 1.1, 10.0, 100.0],
+            &[1.2, 1.4, 1.6, 1.8, 2.0, 3.0, 5.0, 8.0, 13.0, 21.0],
+        ];
+        for targets in ladders {
+            let rtp = ladder_return(targets, 10_000_000);
+            assert!(
+                (rtp - 0.99).abs() < 0.002,
+                "Ladder {:?}: expected return = {}, should be 0.99",
+                targets, rtp
+            );
+        }
+    }
+
+    #[test]
+    fn test_multi_rocket_records_total_volume_once() {
+        let player = Principal::from_slice(&[5; 29]);
+        accounting::update_balance(player, 50_000_000).unwrap();
+        liquidity_pool::add_to_reserve(1_000_000_000);
+        let day = 1_735_689_600_000_000_000u64;
+        let volume = || crate::defi_accounting::statistics::storage::DAILY_ACCUMULATOR
+            .with(|acc| acc.borrow().get().volume_accumulated);
+
+        let result = settle_multi_rocket(player, 1_000_000, 2.0, 5, &[0x5a; 32], day).unwrap();
+        assert_eq!(result.total_bet, 5_000_000);
+        assert_eq!(volume(), 5_000_000, "Volume is the launch's total bet, recorded once");
+
+        settle_multi_rocket(player, 2_000_000, 1.5, 3, &[0xa5; 32], day + 60_000_000_000).unwrap();
+        assert_eq!(volume(), 11_000_000);
+    }
+
+    #[test]
+    fn test_multi_rocket_rejects_unreachable_targets() {
+        for target in [1.01, 2.0, 50.0, MAX_CRASH] {
+            assert!(validate_multi_launch(MIN_BET, target, 3).is_ok(), "{}x is achievable", target);
+            assert!(win_probability(target) > 0.0);
+        }
+        for target in [100.01, 150.0, 1_000.0] {
+            assert_eq!(
+                validate_multi_launch(MIN_BET, target, 3).unwrap_err(),
+                "Target cannot exceed 100x",
+                "{}x can never be reached",
+                target
+            );
+            assert_eq!(win_probability(target), 0.0);
+        }
+        assert!(validate_multi_launch(MIN_BET, f64::NAN, 3).is_err());
+
+        let player = Principal::from_slice(&[6; 29]);
+        accounting::update_balance(player, 50_000_000).unwrap();
+        liquidity_pool::add_to_reserve(1_000_000_000);
+        let result = settle_multi_rocket(player, 1_000_000, 2.0, 3, &[0x3c; 32], 1_735_689_600_000_000_000).unwrap();
+        assert!((result.win_probability - 0.495).abs() < 1e-9);
+    }
+}
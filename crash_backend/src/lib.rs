@@ -14,6 +14,7 @@
 //!
 //! **Transparency & Fairness:**
 //! - Randomness: IC VRF (raw_rand) - no fallback
+//! - Optional commit-reveal mode: HMAC-SHA256(server_seed, client_seed || nonce)
 //! - Expected value: Exactly 0.99 (1% house edge)
 //! - All crash points independently verifiable
 //! - Real ckUSDT betting with liquidity pool backing
@@ -31,8 +32,10 @@ use std::cell::RefCell;
 mod defi_accounting;
 pub mod types;
 pub mod game;
+pub mod seed;
+pub mod vrf;
 
-pub use game::{PlayCrashResult, MultiCrashResult, SingleRocketResult};
+pub use game::{PlayCrashResult, MultiCrashResult, SingleRocketResult, LadderResult, LadderTrancheResult};
 
 // ============================================================================
 // MEMORY MANAGEMENT
@@ -62,14 +65,20 @@ pub struct CrashResult {
 #[init]
 fn init() {
     ic_cdk::println!("Crash Backend Initialized with DeFi Accounting");
+
+    // Fresh canisters start with the default solvency margin; upgrades keep theirs
+    defi_accounting::solvency::init_solvency_margin();
+
     defi_accounting::accounting::start_parent_withdrawal_timer();
     defi_accounting::accounting::start_balance_reconciliation_timer();
     defi_accounting::start_stats_timer();
+    defi_accounting::rebates::start_rebate_timer();
 
     // Initialize cached balance on fresh install using a one-shot timer
     // (spawn not allowed in init mode)
     ic_cdk_timers::set_timer(std::time::Duration::ZERO, async {
         defi_accounting::accounting::refresh_canister_balance().await;
+        defi_accounting::accounting::refresh_token_info().await;
         ic_cdk::println!("Init: balance cache initialized");
     });
 }
@@ -85,6 +94,7 @@ fn post_upgrade() {
     defi_accounting::accounting::start_parent_withdrawal_timer();
     defi_accounting::accounting::start_balance_reconciliation_timer();
     defi_accounting::start_stats_timer();
+    defi_accounting::rebates::start_rebate_timer();
 
     // Initialize cached balance immediately after upgrade using a one-shot timer
     // This prevents games being blocked until hourly reconciliation
@@ -102,19 +112,7 @@ fn post_upgrade() {
 // ============================================================================
 
 fn is_canister_solvent() -> bool {
-    let pool_reserve = defi_accounting::liquidity_pool::get_pool_reserve();
-    let total_deposits = defi_accounting::accounting::calculate_total_deposits_internal();
-    let canister_balance = defi_accounting::accounting::get_cached_canister_balance_internal();
-
-    let obligations = match pool_reserve.checked_add(total_deposits) {
-        Some(o) => o,
-        None => {
-            ic_cdk::println!("CRITICAL: Obligations overflow u64::MAX");
-            return false;
-        }
-    };
-
-    canister_balance >= obligations
+    defi_accounting::solvency::is_canister_solvent()
 }
 
 // ============================================================================
@@ -126,21 +124,70 @@ fn is_canister_solvent() -> bool {
 #[update]
 async fn play_crash(bet_amount: u64, target_multiplier: f64) -> Result<PlayCrashResult, String> {
     if !is_canister_solvent() {
-        return Err("Game temporarily paused - insufficient funds.".to_string());
+        return Err(defi_accounting::types::GameError::Insolvent.into());
     }
     game::play_crash(bet_amount, target_multiplier, ic_cdk::api::msg_caller()).await
 }
 
+/// Deposit via ICRC-2 and play crash in one call. If the bet fails, the deposit stays in the balance.
+#[update]
+async fn deposit_and_play(deposit_amount: u64, bet_amount: u64, target_multiplier: f64) -> Result<PlayCrashResult, String> {
+    if !is_canister_solvent() {
+        return Err(defi_accounting::types::GameError::Insolvent.into());
+    }
+    let caller = ic_cdk::api::msg_caller();
+    defi_accounting::accounting::deposit_and_play(
+        defi_accounting::accounting::deposit_internal(caller, deposit_amount, None),
+        game::play_crash(bet_amount, target_multiplier, caller),
+    ).await
+}
+
 /// Play crash game with multiple rockets
 /// BREAKING CHANGE: Now requires bet_per_rocket parameter
 #[update]
 async fn play_crash_multi(bet_per_rocket: u64, target_multiplier: f64, rocket_count: u8) -> Result<MultiCrashResult, String> {
     if !is_canister_solvent() {
-        return Err("Game temporarily paused - insufficient funds.".to_string());
+        return Err(defi_accounting::types::GameError::Insolvent.into());
     }
     game::play_crash_multi(bet_per_rocket, target_multiplier, rocket_count, ic_cdk::api::msg_caller()).await
 }
 
+/// Play a ladder: bet split evenly across ascending targets, all resolved by one crash point
+#[update]
+async fn play_crash_ladder(bet_amount: u64, targets: Vec<f64>) -> Result<LadderResult, String> {
+    if !is_canister_solvent() {
+        return Err(defi_accounting::types::GameError::Insolvent.into());
+    }
+    game::play_crash_ladder(bet_amount, targets, ic_cdk::api::msg_caller()).await
+}
+
+/// Commit a server seed for the next commit-reveal round; returns SHA256(server_seed)
+#[update]
+async fn commit_crash_seed() -> Result<String, String> {
+    seed::commit_server_seed(ic_cdk::api::msg_caller()).await
+}
+
+/// Hash of the caller's currently committed (unrevealed) server seed, if any
+#[query]
+fn get_crash_commitment() -> Option<String> {
+    seed::get_commitment(ic_cdk::api::msg_caller()).map(|c| seed::hash_server_seed(&c.server_seed))
+}
+
+/// Play crash against the caller's committed seed (provably fair commit-reveal mode)
+#[update]
+fn play_crash_committed(bet_amount: u64, target_multiplier: f64, client_seed: String) -> Result<PlayCrashResult, String> {
+    if !is_canister_solvent() {
+        return Err(defi_accounting::types::GameError::Insolvent.into());
+    }
+    game::play_crash_committed(bet_amount, target_multiplier, client_seed, ic_cdk::api::msg_caller())
+}
+
+/// Recompute the crash point of a commit-reveal round
+#[query]
+fn verify_crash(server_seed: [u8; 32], client_seed: String, nonce: u64) -> f64 {
+    seed::verify_crash(server_seed, &client_seed, nonce)
+}
+
 #[query]
 fn get_max_bet() -> u64 {
     game::get_max_bet()
@@ -156,23 +203,28 @@ fn get_max_bet_per_rocket(rocket_count: u8, target_multiplier: f64) -> Result<u6
 // =============================================================================
 
 #[update]
-async fn deposit(amount: u64) -> Result<u64, String> {
-    defi_accounting::accounting::deposit(amount).await
+async fn deposit(amount: u64, idempotency_key: Option<[u8; 16]>) -> Result<defi_accounting::types::DepositReceipt, String> {
+    defi_accounting::accounting::deposit(amount, idempotency_key).await.map_err(|e| e.to_string())
 }
 
 #[update]
 async fn withdraw_all() -> Result<u64, String> {
-    defi_accounting::accounting::withdraw_all().await
+    defi_accounting::accounting::withdraw_all().await.map_err(|e| e.to_string())
+}
+
+#[update]
+async fn withdraw_keeping(keep_amount: u64) -> Result<u64, defi_accounting::types::GameError> {
+    defi_accounting::accounting::withdraw_keeping(keep_amount).await
 }
 
 #[update]
 async fn retry_withdrawal() -> Result<u64, String> {
-    defi_accounting::accounting::retry_withdrawal().await
+    defi_accounting::accounting::retry_withdrawal().await.map_err(|e| e.to_string())
 }
 
 #[update]
 fn abandon_withdrawal() -> Result<u64, String> {
-    defi_accounting::accounting::abandon_withdrawal()
+    defi_accounting::accounting::abandon_withdrawal().map_err(|e| e.to_string())
 }
 
 #[query]
@@ -180,6 +232,11 @@ fn get_my_withdrawal_status() -> Option<defi_accounting::types::PendingWithdrawa
     defi_accounting::accounting::get_withdrawal_status()
 }
 
+#[query]
+fn get_my_withdrawal_detail() -> Option<defi_accounting::types::WithdrawalStatusDetail> {
+    defi_accounting::accounting::get_withdrawal_status_detail()
+}
+
 #[query]
 fn get_balance(principal: Principal) -> u64 {
     defi_accounting::query::get_balance(principal)
@@ -200,6 +257,27 @@ fn get_max_allowed_payout() -> u64 {
     defi_accounting::query::get_max_allowed_payout()
 }
 
+#[query]
+fn get_token_info() -> defi_accounting::types::TokenInfo {
+    defi_accounting::query::get_token_info()
+}
+
+#[query]
+fn get_solvency_status() -> defi_accounting::types::SolvencyStatus {
+    defi_accounting::solvency::get_solvency_status()
+}
+
+#[query]
+fn get_my_lifetime_stats() -> defi_accounting::types::PlayerLifetimeStats {
+    defi_accounting::player_stats::get_player_stats(ic_cdk::api::msg_caller())
+}
+
+/// The caller's most recent settled games, newest first (at most 50 are kept)
+#[query]
+fn get_my_recent_games(limit: u32) -> Vec<defi_accounting::types::GameHistoryEntry> {
+    defi_accounting::game_history::get_recent_games(ic_cdk::api::msg_caller(), limit)
+}
+
 // =============================================================================
 // LIQUIDITY POOL ENDPOINTS
 // =============================================================================
@@ -219,6 +297,28 @@ fn get_pool_stats() -> defi_accounting::liquidity_pool::PoolStats {
     defi_accounting::query::get_pool_stats()
 }
 
+#[query]
+fn get_target_reserve() -> u64 {
+    defi_accounting::liquidity_pool::get_target_reserve()
+}
+
+#[query]
+fn get_rebate_config() -> defi_accounting::types::RebateConfig {
+    defi_accounting::rebates::get_rebate_config()
+}
+
+/// Caller's loyalty rebate accrual since their last rebate
+#[query]
+fn get_my_rebate_account() -> defi_accounting::types::RebateAccount {
+    defi_accounting::rebates::get_rebate_account(ic_cdk::api::msg_caller())
+}
+
+/// True while an admin has paused betting (e.g. during a migration)
+#[query]
+fn is_betting_paused() -> bool {
+    defi_accounting::pool_drain::is_betting_paused()
+}
+
 #[query]
 fn get_lp_position(principal: Principal) -> defi_accounting::liquidity_pool::LPPosition {
     defi_accounting::query::get_lp_position(principal)
@@ -268,6 +368,11 @@ fn admin_get_orphaned_funds_report_full() -> Result<defi_accounting::types::Orph
     defi_accounting::admin_query::get_orphaned_funds_report_full()
 }
 
+#[query]
+fn admin_reconcile_user(user: candid::Principal) -> Result<defi_accounting::types::ReconcileReport, String> {
+    defi_accounting::admin_query::reconcile_user(user)
+}
+
 #[query]
 fn admin_get_all_balances(offset: u64, limit: u64) -> Result<Vec<defi_accounting::types::UserBalance>, String> {
     defi_accounting::admin_query::get_all_balances(offset, limit)
@@ -298,6 +403,133 @@ fn admin_get_audit_log_count() -> Result<u64, String> {
     defi_accounting::admin_query::get_audit_log_count()
 }
 
+#[query]
+fn admin_get_audit_retention() -> Result<u64, String> {
+    defi_accounting::admin_query::get_audit_retention()
+}
+
+#[update]
+fn admin_set_audit_retention(limit: u64) -> Result<(), String> {
+    defi_accounting::admin_query::set_audit_retention(limit)
+}
+
+#[query]
+fn admin_get_parent_config() -> Result<defi_accounting::types::ParentWithdrawalConfig, String> {
+    defi_accounting::admin_query::get_parent_config()
+}
+
+#[update]
+fn admin_set_parent_config(threshold: u64, recipient: types::Account) -> Result<(), String> {
+    defi_accounting::admin_query::set_parent_config(threshold, recipient)
+}
+
+#[update]
+fn admin_set_betting_paused(paused: bool) -> Result<(), String> {
+    defi_accounting::admin_query::set_betting_paused(paused)
+}
+
+#[update]
+async fn admin_initiate_pool_drain(recipient: types::Account) -> Result<u64, String> {
+    defi_accounting::admin_query::initiate_pool_drain(recipient).await
+}
+
+#[query]
+fn admin_get_pending_drain() -> Result<Option<defi_accounting::types::PendingDrain>, String> {
+    defi_accounting::admin_query::get_pending_drain()
+}
+
+#[update]
+async fn admin_retry_pool_drain() -> Result<u64, String> {
+    defi_accounting::admin_query::retry_pool_drain().await
+}
+
+#[update]
+fn admin_resolve_pool_drain(landed: bool) -> Result<u64, String> {
+    defi_accounting::admin_query::resolve_pool_drain(landed)
+}
+
+#[update]
+fn admin_set_solvency_margin_bp(margin_bp: u64) -> Result<(), String> {
+    defi_accounting::admin_query::set_solvency_margin_bp(margin_bp)
+}
+
+#[update]
+fn admin_set_target_reserve(target: u64) -> Result<(), String> {
+    defi_accounting::admin_query::set_target_reserve(target)
+}
+
+#[update]
+fn admin_distribute_excess() -> Result<u64, String> {
+    defi_accounting::admin_query::distribute_excess()
+}
+
+#[update]
+fn admin_set_rebate_config(config: defi_accounting::types::RebateConfig) -> Result<(), String> {
+    defi_accounting::admin_query::set_rebate_config(config)
+}
+
+#[update]
+fn admin_process_rebates() -> Result<u64, String> {
+    defi_accounting::admin_query::process_rebates()
+}
+
+#[update]
+async fn admin_set_ledger_canister_id(ledger: candid::Principal) -> Result<defi_accounting::types::TokenInfo, String> {
+    defi_accounting::admin_query::set_ledger_canister_id(ledger).await
+}
+
+#[query]
+fn admin_get_event_subscriber() -> Result<Option<candid::Principal>, String> {
+    defi_accounting::admin_query::get_event_subscriber()
+}
+
+#[update]
+fn admin_set_event_subscriber(canister: candid::Principal) -> Result<(), String> {
+    defi_accounting::admin_query::set_event_subscriber(canister)
+}
+
+#[update]
+fn admin_clear_event_subscriber() -> Result<(), String> {
+    defi_accounting::admin_query::clear_event_subscriber()
+}
+
+/// Every admin-settable parameter as one candid blob, to clone onto another canister
+/// or restore later with `admin_import_config`
+#[query]
+fn admin_export_config() -> Result<types::GameConfigBlob, String> {
+    defi_accounting::admin_query::require_admin()?;
+    Ok(export_game_config())
+}
+
+/// Restore a blob from `admin_export_config`. Nothing changes unless every setting is valid.
+#[update]
+fn admin_import_config(blob: types::GameConfigBlob) -> Result<(), String> {
+    defi_accounting::admin_query::require_admin()?;
+    import_game_config(&blob, ic_cdk::api::time())
+}
+
+fn export_game_config() -> types::GameConfigBlob {
+    let config = types::GameConfig {
+        game: env!("CARGO_PKG_NAME").to_string(),
+        accounting: defi_accounting::config::export_config(),
+    };
+    candid::encode_one(config).expect("CRITICAL: Failed to encode GameConfig")
+}
+
+fn import_game_config(blob: &[u8], now: u64) -> Result<(), String> {
+    let config: types::GameConfig = candid::decode_one(blob)
+        .map_err(|e| format!("Invalid config blob: {}", e))?;
+    if config.game != env!("CARGO_PKG_NAME") {
+        return Err(format!("Config blob is for {}, not {}", config.game, env!("CARGO_PKG_NAME")));
+    }
+    defi_accounting::config::apply_config(config.accounting, now)
+}
+
+#[query]
+fn get_ledger_canister_id() -> candid::Principal {
+    defi_accounting::accounting::get_ledger_canister_id()
+}
+
 // =============================================================================
 // STATISTICS ENDPOINTS
 // =============================================================================
@@ -312,6 +544,17 @@ fn get_pool_apy(days: Option<u32>) -> defi_accounting::ApyInfo {
     defi_accounting::get_apy_info(days)
 }
 
+#[query]
+fn get_pool_apy_breakdown(days: Option<u32>) -> defi_accounting::ApyBreakdown {
+    defi_accounting::get_apy_breakdown(days)
+}
+
+/// (day_timestamp, reserve per share) for the last `days` days, to judge LP entry and exit timing
+#[query]
+fn get_share_price_history(days: u32) -> Vec<(u64, f64)> {
+    defi_accounting::get_share_price_history(days)
+}
+
 #[query]
 fn get_stats_range(start_ts: u64, end_ts: u64) -> Vec<defi_accounting::DailySnapshot> {
     defi_accounting::get_snapshots_range(start_ts, end_ts)
@@ -322,6 +565,20 @@ fn get_stats_count() -> u64 {
     defi_accounting::get_snapshot_count()
 }
 
+#[query]
+fn get_stats_summary() -> defi_accounting::types::StatsSummary {
+    defi_accounting::query::get_stats_summary("crash")
+}
+
+/// Candid interface / stable layout version reported by `get_version_info`
+const SCHEMA_VERSION: u32 = 1;
+
+/// Game name, schema version, enabled features and build commit, so clients can adapt
+#[query]
+fn get_version_info() -> defi_accounting::types::VersionInfo {
+    defi_accounting::query::get_version_info("crash", SCHEMA_VERSION, vec!["commit_reveal".to_string(), "multi_rocket".to_string(), "ladder".to_string()])
+}
+
 // ============================================================================
 // EXISTING PURE GAME LOGIC (PRESERVED FOR BACKWARDS COMPATIBILITY)
 // ============================================================================
@@ -338,6 +595,18 @@ fn get_expected_value() -> f64 {
     0.99
 }
 
+/// Formula, edge, randomness source and verifiability in the shape shared by all games
+#[query]
+fn get_fairness_info() -> types::FairnessInfo {
+    types::FairnessInfo {
+        formula: get_crash_formula(),
+        house_edge_bp: types::house_edge_bp(get_expected_value()),
+        randomness_source: "IC VRF (raw_rand); commit-reveal rounds use HMAC-SHA256(server_seed, client_seed:nonce)".to_string(),
+        // commit_crash_seed + play_crash_committed rounds can be re-derived with verify_crash
+        verifiable: true,
+    }
+}
+
 /// Calculate probability of reaching a specific multiplier
 /// Returns P(crash ≥ target)
 #[query]
@@ -345,13 +614,7 @@ fn get_win_probability(target: f64) -> Result<f64, String> {
     if !target.is_finite() {
         return Err("Target must be a finite number".to_string());
     }
-    if target < 1.0 {
-        return Ok(1.0);
-    }
-    if target > MAX_CRASH {
-        return Ok(0.0);
-    }
-    Ok((0.99 / target).min(1.0))
+    Ok(game::win_probability(target))
 }
 
 /// Get example crash probabilities for common targets
@@ -375,7 +638,7 @@ fn greet(name: String) -> String {
 /// Calculate crash point using the formula
 /// Exposed for testing only - actual game uses game::calculate_crash_point
 pub fn calculate_crash_point(random: f64) -> f64 {
-    let random = random.max(0.0).min(0.99999);
+    let random = random.clamp(0.0, 0.99999);
     let crash = 0.99 / (1.0 - random);
     crash.min(MAX_CRASH)
 }
@@ -441,4 +704,12 @@ mod tests {
         assert!((game::calculate_crash_point(0.0) - 0.99).abs() < 0.01);
         assert!((game::calculate_crash_point(0.5) - 1.98).abs() < 0.01);
     }
+
+    #[test]
+    fn test_fairness_info() {
+        let info = get_fairness_info();
+        assert_eq!(info.formula, get_crash_formula());
+        assert_eq!(info.house_edge_bp, 100);
+        assert!(info.verifiable);
+    }
 }
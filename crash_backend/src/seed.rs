@@ -0,0 +1,158 @@
+//! Commit-reveal seeds for provably fair crash rounds.
+//!
+//! Flow:
+//! 1. Player calls `commit_crash_seed()` - canister draws a server seed from VRF,
+//!    stores it per principal and returns only SHA256(server_seed).
+//! 2. Player calls `play_crash_committed(..., client_seed)` - the stored seed is consumed
+//!    and the crash point is derived from HMAC-SHA256(server_seed, client_seed || nonce).
+//! 3. The seed is revealed in the result; anyone can recompute with `verify_crash`.
+
+use candid::{CandidType, Deserialize, Principal};
+use hmac::{Hmac, Mac};
+use crate::vrf::{raw_rand_with_retries, VRF_RETRIES};
+use ic_stable_structures::memory_manager::MemoryId;
+use ic_stable_structures::storable::Bound;
+use ic_stable_structures::{StableBTreeMap, Storable};
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::cell::RefCell;
+
+use crate::defi_accounting::memory_ids::SEED_COMMITMENTS_MEMORY_ID;
+use crate::game::{calculate_crash_point, u64_to_unit_float};
+use crate::{Memory, MEMORY_MANAGER};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// A server seed committed to a player but not yet revealed
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct SeedCommitment {
+    pub server_seed: [u8; 32],
+    pub nonce: u64,
+}
+
+impl Storable for SeedCommitment {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(candid::encode_one(self).expect("CRITICAL: Failed to encode SeedCommitment"))
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        self.to_bytes().into_owned()
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        candid::decode_one(&bytes).expect("CRITICAL: Failed to decode SeedCommitment from stable storage")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+thread_local! {
+    static SEED_COMMITMENTS: RefCell<StableBTreeMap<Principal, SeedCommitment, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(SEED_COMMITMENTS_MEMORY_ID)))
+        )
+    );
+}
+
+// =============================================================================
+// COMMITMENTS
+// =============================================================================
+
+/// Commit a fresh server seed for the caller and return its hash.
+/// If an unrevealed commitment already exists, its hash is returned unchanged.
+pub async fn commit_server_seed(caller: Principal) -> Result<String, String> {
+    crate::defi_accounting::require_authenticated(caller)?;
+
+    if let Some(existing) = get_commitment(caller) {
+        return Ok(hash_server_seed(&existing.server_seed));
+    }
+
+    let random_bytes = raw_rand_with_retries(VRF_RETRIES).await
+        .map_err(|e| format!("VRF unavailable: {:?}. Please retry.", e))?;
+    let server_seed: [u8; 32] = random_bytes.get(0..32)
+        .and_then(|b| b.try_into().ok())
+        .ok_or("Insufficient randomness")?;
+
+    // Re-check after await: a concurrent call may have committed first
+    if let Some(existing) = get_commitment(caller) {
+        return Ok(hash_server_seed(&existing.server_seed));
+    }
+
+    let commitment = SeedCommitment { server_seed, nonce: ic_cdk::api::time() };
+    SEED_COMMITMENTS.with(|c| c.borrow_mut().insert(caller, commitment));
+    Ok(hash_server_seed(&server_seed))
+}
+
+pub fn get_commitment(caller: Principal) -> Option<SeedCommitment> {
+    SEED_COMMITMENTS.with(|c| c.borrow().get(&caller))
+}
+
+/// Remove the caller's commitment once it has been used (seed is then public)
+pub fn consume_commitment(caller: Principal) -> Option<SeedCommitment> {
+    SEED_COMMITMENTS.with(|c| c.borrow_mut().remove(&caller))
+}
+
+/// Get hash of server seed for pre-game commitment (provable fairness)
+pub fn hash_server_seed(server_seed: &[u8; 32]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(server_seed);
+    format!("{:x}", hasher.finalize())
+}
+
+// =============================================================================
+// DERIVATION & VERIFICATION
+// =============================================================================
+
+/// Derive a float in [0.0, 1.0) from HMAC-SHA256(server_seed, client_seed || nonce).
+/// Uses the top 53 bits of the MAC, matching `bytes_to_float` for VRF rounds.
+pub fn derive_committed_random(server_seed: &[u8; 32], client_seed: &str, nonce: u64) -> f64 {
+    let mut mac = HmacSha256::new_from_slice(server_seed)
+        .expect("HMAC accepts keys of any length");
+    mac.update(client_seed.as_bytes());
+    mac.update(&nonce.to_be_bytes());
+    let tag = mac.finalize().into_bytes();
+
+    let random_u64 = u64::from_be_bytes(
+        tag[0..8].try_into().expect("HMAC-SHA256 always produces 32 bytes, slice [0..8] is always valid")
+    );
+    u64_to_unit_float(random_u64)
+}
+
+/// Recompute the crash point of a committed round from its revealed inputs
+pub fn verify_crash(server_seed: [u8; 32], client_seed: &str, nonce: u64) -> f64 {
+    calculate_crash_point(derive_committed_random(&server_seed, client_seed, nonce))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derivation_is_deterministic() {
+        let seed = [7u8; 32];
+        let a = verify_crash(seed, "player-seed", 42);
+        let b = verify_crash(seed, "player-seed", 42);
+        assert_eq!(a, b);
+        assert!((0.99..=100.0).contains(&a));
+    }
+
+    #[test]
+    fn test_inputs_change_result() {
+        let seed = [7u8; 32];
+        let base = derive_committed_random(&seed, "a", 1);
+        assert_ne!(base, derive_committed_random(&seed, "b", 1));
+        assert_ne!(base, derive_committed_random(&seed, "a", 2));
+        assert_ne!(base, derive_committed_random(&[8u8; 32], "a", 1));
+    }
+
+    #[test]
+    fn test_commitment_consumed_once() {
+        let user = Principal::from_slice(&[1; 29]);
+        let commitment = SeedCommitment { server_seed: [3u8; 32], nonce: 9 };
+        SEED_COMMITMENTS.with(|c| c.borrow_mut().insert(user, commitment));
+
+        assert!(get_commitment(user).is_some());
+        assert_eq!(consume_commitment(user).unwrap().nonce, 9);
+        assert!(consume_commitment(user).is_none());
+    }
+}
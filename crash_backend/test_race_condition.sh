@@ -89,7 +89,7 @@ for i in $(seq 1 $CONCURRENT_BETS); do
     elif echo "$RESULT" | grep -q "won = false"; then
         echo "  Game $i: LOST"
         ((LOSSES++)) || true
-    elif echo "$RESULT" | grep -q "INSUFFICIENT_BALANCE"; then
+    elif echo "$RESULT" | grep -q "Insufficient balance"; then
         echo "  Game $i: REJECTED (insufficient balance)"
         ((ERRORS++)) || true
     else
@@ -0,0 +1,96 @@
+//! Cross-subsystem accounting invariants reported by `admin_health_check`.
+//!
+//! Each check is a pure function over the relevant numbers so it can be unit tested with
+//! deliberately corrupted inputs; `check_all` gathers the live state and runs every check.
+
+use candid::{Nat, Principal};
+
+use super::accounting::{self, PENDING_WITHDRAWALS, USER_BALANCES_STABLE};
+use super::liquidity_pool;
+use super::types::{InvariantCheck, PendingWithdrawal, WithdrawalType};
+
+fn check(name: &str, passed: bool, detail: String) -> InvariantCheck {
+    InvariantCheck { name: name.to_string(), passed, detail }
+}
+
+/// User deposits plus the pool reserve must be backed by the canister's ckUSDT balance
+pub fn deposits_backed(total_deposits: u64, pool_reserve: u64, canister_balance: u64) -> InvariantCheck {
+    let obligations = total_deposits as u128 + pool_reserve as u128;
+    check(
+        "deposits_backed",
+        obligations <= canister_balance as u128,
+        format!("deposits {} + reserve {} = {} vs canister balance {}",
+            total_deposits, pool_reserve, obligations, canister_balance),
+    )
+}
+
+/// Every pending withdrawal has a non-zero amount, and user withdrawals point at an
+/// existing balance entry (the entry is deducted, never removed, when a withdrawal starts)
+pub fn pending_withdrawals_valid(
+    pending: &[(Principal, PendingWithdrawal)],
+    has_balance: impl Fn(&Principal) -> bool,
+) -> InvariantCheck {
+    let invalid: Vec<String> = pending.iter()
+        .filter(|(user, p)| {
+            p.get_amount() == 0
+                || matches!(p.withdrawal_type, WithdrawalType::User { .. }) && !has_balance(user)
+        })
+        .map(|(user, _)| user.to_text())
+        .collect();
+    let detail = if invalid.is_empty() {
+        format!("{} pending withdrawals", pending.len())
+    } else {
+        format!("invalid pending withdrawals for: {}", invalid.join(", "))
+    };
+    check("pending_withdrawals_valid", invalid.is_empty(), detail)
+}
+
+/// The audit counter is the next id to assign, so it must exceed every stored id
+pub fn audit_counter_ahead(counter: u64, max_stored_id: Option<u64>) -> InvariantCheck {
+    let passed = max_stored_id.is_none_or(|max| counter > max);
+    check(
+        "audit_counter_ahead",
+        passed,
+        format!("counter {} vs max stored id {:?}", counter, max_stored_id),
+    )
+}
+
+/// LP share totals agree with pool state: an uninitialized pool has no shares, an
+/// initialized one has exactly the minimum liquidity burned and no more shares than that
+/// burn plus real positions
+pub fn lp_shares_consistent(initialized: bool, total_shares: &Nat, burned: &Nat, minimum_liquidity: &Nat) -> InvariantCheck {
+    let positions = if total_shares >= burned { total_shares.clone() - burned.clone() } else { Nat::from(0u64) };
+    let passed = if initialized {
+        burned == minimum_liquidity && total_shares >= burned
+    } else {
+        *total_shares == 0u64
+    };
+    check(
+        "lp_shares_consistent",
+        passed,
+        format!("initialized {}, total shares {} = burned {} + positions {}",
+            initialized, total_shares, burned, positions),
+    )
+}
+
+/// Run every invariant against live state
+pub fn check_all(canister_balance: u64) -> Vec<InvariantCheck> {
+    let pending: Vec<(Principal, PendingWithdrawal)> = PENDING_WITHDRAWALS.with(|p| {
+        p.borrow().iter().map(|entry| (*entry.key(), entry.value())).collect()
+    });
+    let (counter, max_id) = accounting::audit_log_bounds_internal();
+    let (total_shares, burned, initialized) = liquidity_pool::lp_share_totals_internal();
+
+    vec![
+        deposits_backed(
+            accounting::calculate_total_deposits_internal(),
+            liquidity_pool::get_pool_reserve(),
+            canister_balance,
+        ),
+        pending_withdrawals_valid(&pending, |user| {
+            USER_BALANCES_STABLE.with(|b| b.borrow().contains_key(user))
+        }),
+        audit_counter_ahead(counter, max_id),
+        lp_shares_consistent(initialized, &total_shares, &burned, &liquidity_pool::minimum_liquidity()),
+    ]
+}
@@ -5,23 +5,40 @@ use std::cell::RefCell;
 use std::borrow::Cow;
 use num_traits::ToPrimitive;
 
-use crate::types::{Account, TransferFromArgs, TransferFromError, CKUSDT_CANISTER_ID, CKUSDT_TRANSFER_FEE};
+use crate::types::{Account, TransferFromArgs, TransferFromError};
 use super::accounting;
-use super::memory_ids::{LP_SHARES_MEMORY_ID, POOL_STATE_MEMORY_ID};
+use super::types::GameError;
+use super::memory_ids::{LP_SHARES_MEMORY_ID, POOL_STATE_MEMORY_ID, RESERVE_TARGET_MEMORY_ID};
 
 // Constants
 
 const MINIMUM_LIQUIDITY: u64 = 1000;
-const MIN_DEPOSIT: u64 = 10_000_000; // 10 USDT minimum for LP (higher barrier than user deposits)
-const MIN_WITHDRAWAL: u64 = 100_000; // 0.1 USDT
-const MIN_OPERATING_BALANCE: u64 = 100_000_000; // 100 USDT to operate games
+// Sizes in whole tokens, scaled by the ledger's decimals (see `accounting::token_unit`)
+const MIN_DEPOSIT_TOKENS: u64 = 10; // 10 USDT minimum for LP (higher barrier than user deposits)
+const MIN_WITHDRAWAL_PER_TOKEN: u64 = 10; // 1/10 token = 0.1 USDT
+const MIN_OPERATING_BALANCE_TOKENS: u64 = 100; // 100 USDT to operate games
 const PARENT_STAKER_CANISTER: &str = "e454q-riaaa-aaaap-qqcyq-cai";
 const LP_WITHDRAWAL_FEE_BPS: u64 = 100; // 1%
+/// Reserve must exceed the target by this much before excess is distributed
+const DISTRIBUTION_MARGIN_BP: u64 = 1_000; // 10%
 
 pub fn get_parent_principal() -> Principal {
     Principal::from_text(PARENT_STAKER_CANISTER).expect("Invalid parent canister ID")
 }
 
+fn min_lp_deposit() -> u64 {
+    MIN_DEPOSIT_TOKENS.saturating_mul(accounting::token_unit())
+}
+
+fn min_lp_withdrawal() -> u64 {
+    accounting::token_unit() / MIN_WITHDRAWAL_PER_TOKEN
+}
+
+/// Reserve the pool needs before games accept bets
+pub(crate) fn min_operating_balance() -> u64 {
+    MIN_OPERATING_BALANCE_TOKENS.saturating_mul(accounting::token_unit())
+}
+
 // Storable wrapper for Nat
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct StorableNat(pub Nat);
@@ -86,7 +103,7 @@ impl Storable for PoolState {
 // Storage
 thread_local! {
     // LP shares by user
-    static LP_SHARES: RefCell<StableBTreeMap<Principal, StorableNat, VirtualMemory<DefaultMemoryImpl>>> = {
+    pub(crate) static LP_SHARES: RefCell<StableBTreeMap<Principal, StorableNat, VirtualMemory<DefaultMemoryImpl>>> = {
         RefCell::new(StableBTreeMap::init(
             crate::MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(LP_SHARES_MEMORY_ID)))
         ))
@@ -102,6 +119,14 @@ thread_local! {
             }
         ))
     };
+
+    // Target reserve for excess distribution (0 = disabled)
+    static TARGET_RESERVE: RefCell<StableCell<u64, VirtualMemory<DefaultMemoryImpl>>> = {
+        RefCell::new(StableCell::init(
+            crate::MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(RESERVE_TARGET_MEMORY_ID))),
+            0
+        ))
+    };
 }
 
 // Types
@@ -155,16 +180,18 @@ fn calculate_shares_for_deposit(amount_nat: &Nat) -> Result<Nat, String> {
 // in `accounting.rs` which use the legacy `transfer` (ICRC-1) where the user sends
 // funds directly to the canister's subaccount.
 pub async fn deposit_liquidity(amount: u64, min_shares_expected: Option<Nat>) -> Result<Nat, String> {
-    // Validate
-    if amount < MIN_DEPOSIT {
-        return Err(format!("Minimum LP deposit is {} USDT", MIN_DEPOSIT / 1_000_000));
-    }
+    deposit_liquidity_internal(ic_cdk::api::msg_caller(), amount, min_shares_expected).await
+}
 
-    let caller = ic_cdk::api::msg_caller();
+pub(crate) async fn deposit_liquidity_internal(caller: Principal, amount: u64, min_shares_expected: Option<Nat>) -> Result<Nat, String> {
+    accounting::require_authenticated(caller)?;
+    if super::pool_drain::is_betting_paused() {
+        return Err(GameError::BettingPaused.into());
+    }
 
-    // Prevent anonymous principal from depositing (they couldn't withdraw anyway)
-    if caller == Principal::anonymous() {
-        return Err("Anonymous principal cannot deposit".to_string());
+    // Validate
+    if amount < min_lp_deposit() {
+        return Err(GameError::BelowMinimum { amount, minimum: min_lp_deposit() }.into());
     }
 
     // CRITICAL SAFETY CHECK: Ensure no pending withdrawals
@@ -285,13 +312,9 @@ pub async fn deposit_liquidity(amount: u64, min_shares_expected: Option<Nat>) ->
 // 3. Transfer the Fee (Best Effort). If this fails, we DO NOT rollback.
 //    The fee remains in the canister as a protocol buffer.
 //    This ensures the Reserve is always solvent (Reserve <= Balance).
-async fn withdraw_liquidity(shares_to_burn: Nat) -> Result<u64, String> {
-    let caller = ic_cdk::api::msg_caller();
-
-    // Prevent anonymous principal from withdrawing burned shares
-    if caller == Principal::anonymous() {
-        return Err("Anonymous principal cannot withdraw".to_string());
-    }
+async fn withdraw_liquidity(caller: Principal, shares_to_burn: Nat) -> Result<u64, String> {
+    // Also keeps the anonymous principal from withdrawing burned shares
+    accounting::require_authenticated(caller)?;
 
     // Validate shares
     if shares_to_burn == 0u64 {
@@ -304,33 +327,12 @@ async fn withdraw_liquidity(shares_to_burn: Nat) -> Result<u64, String> {
     }
 
     // Calculate payout
-    let payout_nat = POOL_STATE.with(|state| {
-        let pool_state = state.borrow().get().clone();
-        let current_reserve = pool_state.reserve;
-        let total_shares = calculate_total_supply();
-
-        if total_shares == 0u64 {
-            return Err("No shares in circulation".to_string());
-        }
-
-        // payout = (shares_to_burn * current_reserve) / total_shares
-        // payout = (shares_to_burn * current_reserve) / total_shares
-        let numerator = shares_to_burn.clone() * current_reserve.clone();
-        // SAFETY: total_shares checked for zero above (line 207)
-        let payout = numerator / total_shares;
-
-        // Check reserve sufficiency (read-only check)
-        if current_reserve < payout {
-             return Err("Insufficient pool reserve".to_string());
-        }
-
-        Ok(payout)
-    })?;
+    let payout_nat = calculate_lp_payout(&shares_to_burn)?;
 
     // Check minimum withdrawal
     let payout_u64 = payout_nat.0.to_u64().ok_or("Payout too large")?;
-    if payout_u64 < MIN_WITHDRAWAL {
-        return Err(format!("Minimum withdrawal is {} e8s", MIN_WITHDRAWAL));
+    if payout_u64 < min_lp_withdrawal() {
+        return Err(GameError::BelowMinimum { amount: payout_u64, minimum: min_lp_withdrawal() }.into());
     }
 
     // Calculate fee (1% using basis points for precision)
@@ -416,15 +418,49 @@ async fn withdraw_liquidity(shares_to_burn: Nat) -> Result<u64, String> {
     }
 }
 
+/// Pro-rata value of `shares_to_burn` against the current reserve.
+///
+/// Withdrawal fairness policy: every withdrawal takes the same haircut. LP claims are
+/// never fixed amounts, only fractions of whatever the reserve holds right now, so after
+/// a pool loss the first LP out receives `shares * reserve / total_shares` like everyone
+/// else and a bank run cannot leave the last LP with less than their share. Rounding is
+/// down, leaving dust for the LPs who stay. No queue is needed because the reserve can
+/// always cover every outstanding claim.
+pub(crate) fn calculate_lp_payout(shares_to_burn: &Nat) -> Result<Nat, String> {
+    let current_reserve = get_pool_reserve_nat();
+    let total_shares = calculate_total_supply();
+
+    if total_shares == 0u64 {
+        return Err("No shares in circulation".to_string());
+    }
+
+    // payout = (shares_to_burn * current_reserve) / total_shares
+    let numerator = shares_to_burn.clone() * current_reserve.clone();
+    // SAFETY: total_shares checked for zero above
+    let payout = numerator / total_shares;
+
+    // Check reserve sufficiency (read-only check)
+    if current_reserve < payout {
+         return Err("Insufficient pool reserve".to_string());
+    }
+
+    Ok(payout)
+}
+
 pub async fn withdraw_all_liquidity() -> Result<u64, String> {
-    let caller = ic_cdk::api::msg_caller();
+    withdraw_all_liquidity_internal(ic_cdk::api::msg_caller()).await
+}
+
+pub(crate) async fn withdraw_all_liquidity_internal(caller: Principal) -> Result<u64, String> {
+    accounting::require_authenticated(caller)?;
+
     let shares = LP_SHARES.with(|s| s.borrow().get(&caller).map_or(Nat::from(0u64), |sn| sn.0));
 
     if shares == 0u64 {
         return Err("No liquidity to withdraw".to_string());
     }
 
-    withdraw_liquidity(shares).await
+    withdraw_liquidity(caller, shares).await
 }
 
 // Query functions
@@ -514,6 +550,27 @@ pub fn get_pool_reserve_nat() -> Nat {
     POOL_STATE.with(|s| s.borrow().get().reserve.clone())
 }
 
+/// Zero the reserve for a pool drain (`pool_drain` restores it if the transfer fails)
+pub(crate) fn take_reserve_for_drain() {
+    POOL_STATE.with(|state| {
+        let mut pool_state = state.borrow().get().clone();
+        pool_state.reserve = Nat::from(0u64);
+        state.borrow_mut().set(pool_state);
+    });
+}
+
+/// Remove every LP position after a completed pool drain. Returns how many were cleared.
+pub(crate) fn clear_lp_positions() -> u64 {
+    LP_SHARES.with(|shares| {
+        let mut shares = shares.borrow_mut();
+        let keys: Vec<Principal> = shares.iter().map(|entry| *entry.key()).collect();
+        for key in &keys {
+            shares.remove(key);
+        }
+        keys.len() as u64
+    })
+}
+
 /// Add amount to pool reserve (used for fee fallback when parent credit fails)
 pub fn add_to_reserve(amount: u64) {
     POOL_STATE.with(|state| {
@@ -523,6 +580,114 @@ pub fn add_to_reserve(amount: u64) {
     });
 }
 
+// =============================================================================
+// EXCESS DISTRIBUTION
+// =============================================================================
+
+/// Target pool reserve. 0 disables excess distribution.
+pub fn get_target_reserve() -> u64 {
+    TARGET_RESERVE.with(|t| *t.borrow().get())
+}
+
+/// A non-zero target must cover `min_operating_balance` so a distribution can never
+/// leave the pool unable to accept bets.
+pub(crate) fn validate_target_reserve(target: u64) -> Result<(), String> {
+    if target != 0 && target < min_operating_balance() {
+        return Err(format!(
+            "Target reserve must be 0 (disabled) or at least {}",
+            accounting::format_token_amount(min_operating_balance())
+        ));
+    }
+    Ok(())
+}
+
+/// Set the target reserve (admin check is done by the caller in `admin_query`)
+pub(crate) fn set_target_reserve(target: u64) -> Result<(), String> {
+    validate_target_reserve(target)?;
+    TARGET_RESERVE.with(|t| t.borrow_mut().set(target));
+    Ok(())
+}
+
+/// Pay the reserve above the target out to current LPs.
+///
+/// LPs already own the whole reserve, so the excess cannot raise share value any
+/// further; it is realized instead. Each LP is credited `excess * shares / total_shares`
+/// to their betting balance, less the usual LP withdrawal fee (credited to the parent),
+/// and share counts are left untouched. Nothing happens until the reserve exceeds the
+/// target by DISTRIBUTION_MARGIN_BP, and the reserve never drops below the target, so
+/// the max allowed payout stays at least 15% of the target.
+///
+/// Slices belonging to the burned minimum-liquidity shares or to LPs with a pending
+/// withdrawal, a failed parent fee credit and rounding dust all stay in the reserve.
+///
+/// Returns the amount removed from the reserve.
+pub(crate) fn distribute_excess_to_lps() -> Result<u64, String> {
+    distribute_excess_to_lps_internal(ic_cdk::api::time())
+}
+
+pub(crate) fn distribute_excess_to_lps_internal(now: u64) -> Result<u64, String> {
+    let target = get_target_reserve();
+    if target == 0 {
+        return Err("Target reserve not set".to_string());
+    }
+
+    let reserve = get_pool_reserve();
+    let threshold = (target as u128) * ((10_000 + DISTRIBUTION_MARGIN_BP) as u128) / 10_000;
+    if (reserve as u128) <= threshold {
+        return Ok(0);
+    }
+
+    let total_shares = calculate_total_supply();
+    if total_shares == 0u64 {
+        return Ok(0);
+    }
+    let excess = Nat::from(reserve - target);
+
+    let holders: Vec<(Principal, Nat)> = LP_SHARES.with(|shares| {
+        shares.borrow()
+            .iter()
+            .map(|entry| (*entry.key(), entry.value().0))
+            .filter(|(user, _)| *user != Principal::anonymous())
+            .collect()
+    });
+
+    let mut credited = 0u64;
+    let mut fees = 0u64;
+    let mut lp_count = 0u64;
+    for (user, shares) in holders {
+        let slice = (excess.clone() * shares / total_shares.clone()).0.to_u64().unwrap_or(0);
+        if slice == 0 {
+            continue;
+        }
+        let fee = ((slice as u128) * (LP_WITHDRAWAL_FEE_BPS as u128) / 10_000) as u64;
+        let net = slice - fee;
+        if accounting::credit_distribution(user, net, now) {
+            credited += net;
+            fees += fee;
+            lp_count += 1;
+        }
+    }
+
+    if fees > 0 && !accounting::credit_distribution(get_parent_principal(), fees, now) {
+        fees = 0;
+    }
+
+    let distributed = credited + fees;
+    let reserve_after = reserve - distributed;
+    POOL_STATE.with(|state| {
+        let mut pool_state = state.borrow().get().clone();
+        pool_state.reserve = Nat::from(reserve_after);
+        state.borrow_mut().set(pool_state);
+    });
+
+    accounting::log_audit_at(crate::defi_accounting::types::AuditEvent::ExcessDistributed {
+        amount: distributed,
+        lp_count,
+        reserve_after,
+    }, now);
+    Ok(distributed)
+}
+
 /// Get current share price in decimals (for statistics tracking)
 /// Returns pool_reserve / total_shares, or 100_000_000 (1 USDT) if no shares exist
 pub fn get_share_price() -> u64 {
@@ -530,9 +695,13 @@ pub fn get_share_price() -> u64 {
     stats.share_price.0.to_u64().unwrap_or(100_000_000)
 }
 
+pub fn get_total_shares() -> u64 {
+    calculate_total_supply().0.to_u64().unwrap_or(u64::MAX)
+}
+
 pub fn can_accept_bets() -> bool {
     let pool_reserve = get_pool_reserve();
-    pool_reserve >= MIN_OPERATING_BALANCE
+    pool_reserve >= min_operating_balance()
 }
 
 // Game integration (internal use only - called by game logic)
@@ -573,6 +742,7 @@ pub(crate) fn update_pool_on_loss(bet: u64) {
 /// update_pool_on_win/loss.
 ///
 /// # Arguments
+/// * `player` - Principal who placed the bet (for lifetime statistics)
 /// * `bet_amount` - Original wager amount in e8s
 /// * `payout_amount` - Total payout to player in e8s (0 for total loss, bet for push, >bet for win)
 ///
@@ -588,18 +758,18 @@ pub(crate) fn update_pool_on_loss(bet: u64) {
 /// # Examples
 /// ```ignore
 /// // Total loss: bet 100, get 0 back. Pool gains 100.
-/// settle_bet(100, 0)?;
+/// settle_bet(player, 100, 0)?;
 ///
 /// // Partial loss (Plinko 0.2x): bet 100, get 20 back. Pool gains 80.
-/// settle_bet(100, 20)?;
+/// settle_bet(player, 100, 20)?;
 ///
 /// // Push: bet 100, get 100 back. No pool change.
-/// settle_bet(100, 100)?;
+/// settle_bet(player, 100, 100)?;
 ///
 /// // Win (2x): bet 100, get 200 back. Pool pays 100.
-/// settle_bet(100, 200)?;
+/// settle_bet(player, 100, 200)?;
 /// ```
-pub fn settle_bet(bet_amount: u64, payout_amount: u64) -> Result<(), String> {
+pub fn settle_bet(player: Principal, bet_amount: u64, payout_amount: u64) -> Result<(), String> {
     if payout_amount > bet_amount {
         // Player won: pool pays profit
         let profit = payout_amount - bet_amount;
@@ -623,6 +793,9 @@ pub fn settle_bet(bet_amount: u64, payout_amount: u64) -> Result<(), String> {
     }
     // payout == bet: push, no pool change
 
+    super::player_stats::record_settlement(player, bet_amount, payout_amount);
+    super::rebates::record_settlement(player, bet_amount, payout_amount);
+    super::events::notify_if_large_win(player, bet_amount, payout_amount);
     Ok(())
 }
 
@@ -649,7 +822,7 @@ pub fn restore_lp_position(user: Principal, shares: Nat, reserve_amount: Nat) {
 async fn transfer_from_user(user: Principal, amount: u64) -> Result<(), String> {
     // Frontend must call icrc2_approve first
     // Then we use transfer_from
-    let ledger = Principal::from_text(CKUSDT_CANISTER_ID).expect("Invalid ckUSDT canister ID");
+    let ledger = accounting::get_ledger_canister_id();
     let canister_id = ic_cdk::api::canister_self();
 
     let args = TransferFromArgs {
@@ -663,7 +836,7 @@ async fn transfer_from_user(user: Principal, amount: u64) -> Result<(), String>
         },
         amount: Nat::from(amount),
         // Explicitly charge fee to sender to prevent protocol loss
-        fee: Some(Nat::from(CKUSDT_TRANSFER_FEE)),
+        fee: Some(Nat::from(accounting::transfer_fee())),
         memo: None,
         created_at_time: None,
         spender_subaccount: None,
@@ -691,6 +864,19 @@ pub fn calculate_shares_preview(amount: u64) -> Result<Nat, String> {
 // ADMIN QUERY HELPERS
 // =============================================================================
 
+/// Sum of all LP share entries, the burned minimum-liquidity entry and whether the pool is initialized
+pub(crate) fn lp_share_totals_internal() -> (Nat, Nat, bool) {
+    let total = calculate_total_supply();
+    let burned = LP_SHARES.with(|s| s.borrow().get(&Principal::anonymous()).map_or(Nat::from(0u64), |sn| sn.0));
+    let initialized = POOL_STATE.with(|s| s.borrow().get().initialized);
+    (total, burned, initialized)
+}
+
+/// Shares that must be burned to the anonymous principal on the first deposit
+pub(crate) fn minimum_liquidity() -> Nat {
+    Nat::from(MINIMUM_LIQUIDITY)
+}
+
 /// Count LP positions (excluding burned shares)
 pub(crate) fn count_lp_positions_internal() -> u64 {
     LP_SHARES.with(|shares| {
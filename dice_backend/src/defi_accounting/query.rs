@@ -18,10 +18,42 @@ pub fn get_max_allowed_payout() -> u64 {
     accounting::get_max_allowed_payout_internal()
 }
 
+pub fn get_token_info() -> super::types::TokenInfo {
+    accounting::get_token_info()
+}
+
 pub fn get_house_balance() -> u64 {
     liquidity_pool::get_pool_reserve()
 }
 
+/// Summary derived from daily snapshots and pool state; `game_name` is supplied by the caller
+pub fn get_stats_summary(game_name: &str) -> super::types::StatsSummary {
+    let (total_volume, house_pnl) = super::statistics::get_lifetime_totals();
+    super::types::StatsSummary {
+        game_name: game_name.to_string(),
+        total_volume,
+        pool_reserve: liquidity_pool::get_pool_reserve(),
+        house_pnl,
+        active_users: accounting::count_active_users_internal(),
+    }
+}
+
+/// Version descriptor; the caller supplies its name, schema version and game-specific
+/// features, and the accounting features shared by every game are appended
+pub fn get_version_info(game_name: &str, schema_version: u32, mut features: Vec<String>) -> super::types::VersionInfo {
+    features.push("deposit_and_play".to_string());
+    features.push("withdraw_keeping".to_string());
+    if liquidity_pool::get_target_reserve() > 0 {
+        features.push("excess_distribution".to_string());
+    }
+    super::types::VersionInfo {
+        game_name: game_name.to_string(),
+        schema_version,
+        enabled_features: features,
+        build_commit: option_env!("GIT_COMMIT").map(str::to_string),
+    }
+}
+
 // =============================================================================
 // LIQUIDITY POOL QUERIES (internal helpers - endpoints in lib.rs)
 // =============================================================================
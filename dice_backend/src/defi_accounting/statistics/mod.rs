@@ -18,12 +18,13 @@
 //!
 //! - **Actual APY**: Based on real profit/loss (can be negative)
 //! - **Expected APY**: Based on theoretical 1% house edge
+//! - **APY breakdown**: Net APY split into edge yield and parent fee drag
 
 mod types;
-mod storage;
+pub(crate) mod storage;
 mod collector;
 mod queries;
 
-pub use types::{DailySnapshot, ApyInfo};
+pub use types::{DailySnapshot, ApyInfo, ApyBreakdown};
 pub use collector::{record_bet_volume, start_stats_timer};
-pub use queries::{get_daily_snapshots, get_snapshots_range, get_snapshot_count, get_apy_info};
+pub use queries::{get_daily_snapshots, get_snapshots_range, get_snapshot_count, get_apy_info, get_apy_breakdown, get_lifetime_totals, get_share_price_history};
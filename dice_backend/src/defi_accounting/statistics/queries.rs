@@ -1,5 +1,7 @@
-use super::storage::DAILY_SNAPSHOTS;
-use super::types::{DailySnapshot, ApyInfo};
+use super::storage::{DAILY_SNAPSHOTS, DAILY_ACCUMULATOR};
+use super::collector::NANOS_PER_DAY;
+use super::types::{DailySnapshot, ApyInfo, ApyBreakdown};
+use crate::defi_accounting::{accounting, liquidity_pool};
 
 /// Get recent snapshots (for graphing)
 /// Returns the most recent `limit` snapshots in chronological order
@@ -43,6 +45,48 @@ pub fn get_snapshot_count() -> u64 {
     DAILY_SNAPSHOTS.with(|s| s.borrow().len())
 }
 
+/// Lifetime (volume, pool profit) across all snapshots plus the day in progress.
+///
+/// Profit is the change in pool reserve, so like `daily_pool_profit` it includes
+/// LP deposits and withdrawals, not just game outcomes.
+pub fn get_lifetime_totals() -> (u64, i64) {
+    let (mut volume, mut profit) = DAILY_SNAPSHOTS.with(|snapshots| {
+        snapshots.borrow().iter().fold((0u64, 0i64), |(v, p), snap| {
+            (v.saturating_add(snap.daily_volume), p.saturating_add(snap.daily_pool_profit))
+        })
+    });
+
+    let acc = DAILY_ACCUMULATOR.with(|a| a.borrow().get().clone());
+    if acc.day_start > 0 {
+        let current_reserve = liquidity_pool::get_pool_reserve();
+        volume = volume.saturating_add(acc.volume_accumulated);
+        profit = profit.saturating_add((current_reserve as i64) - (acc.last_pool_reserve as i64));
+    }
+
+    (volume, profit)
+}
+
+/// Reserve per LP share at the end of each of the last `days` snapshot days, oldest first.
+///
+/// This is the rate `deposit_liquidity` charges: a deposit of `amount` mints
+/// `amount * total_shares / reserve` shares, i.e. pays `reserve / total_shares` per share.
+/// Snapshots from before `total_shares` was recorded fall back to `share_price / 100`
+/// (the same ratio, rounded to 1%). Days with an empty pool have no price and are skipped.
+pub fn get_share_price_history(days: u32) -> Vec<(u64, f64)> {
+    get_daily_snapshots(days)
+        .into_iter()
+        .filter(|snap| snap.pool_reserve_end > 0)
+        .filter_map(|snap| {
+            let price = match snap.total_shares {
+                Some(0) => return None,
+                Some(shares) => snap.pool_reserve_end as f64 / shares as f64,
+                None => snap.share_price as f64 / 100.0,
+            };
+            Some((snap.day_timestamp, price))
+        })
+        .collect()
+}
+
 /// Maximum days allowed for APY calculation (prevents excessive computation)
 const MAX_APY_DAYS: u32 = 365;
 
@@ -69,6 +113,87 @@ const MAX_APY_DAYS: u32 = 365;
 /// - `days`: Number of days to calculate APY over (1-365, default 7)
 ///   Values above 365 are capped to prevent excessive computation.
 pub fn get_apy_info(days: Option<u32>) -> ApyInfo {
+    let Some(period) = get_apy_period(days) else {
+        return ApyInfo::default();
+    };
+
+    if period.start_reserve == 0 {
+        return ApyInfo {
+            actual_apy_percent: 0.0,
+            expected_apy_percent: 0.0,
+            days_calculated: period.days as u32,
+            total_volume: period.total_volume,
+            total_profit: period.total_profit,
+        };
+    }
+
+    // Expected APY from theoretical 1% edge
+    let expected_profit = period.total_volume as f64 * 0.01;
+
+    ApyInfo {
+        actual_apy_percent: period.annualize(period.total_profit as f64),
+        expected_apy_percent: period.annualize(expected_profit),
+        days_calculated: period.days as u32,
+        total_volume: period.total_volume,
+        total_profit: period.total_profit,
+    }
+}
+
+/// Split the actual APY over the last N days into edge yield and parent fee drag
+///
+/// ```text
+/// net_apy    = actual_apy_percent (from snapshots)
+/// fee_drag   = (parent_fees / starting_reserve) * (365 / days) * 100
+/// edge_yield = net_apy + fee_drag
+/// ```
+///
+/// Parent fees are the `ParentFeeCredited` audit events inside the snapshot days.
+pub fn get_apy_breakdown(days: Option<u32>) -> ApyBreakdown {
+    let Some(period) = get_apy_period(days) else {
+        return ApyBreakdown::default();
+    };
+
+    let parent_fees = accounting::sum_parent_fees_from_audit_internal(period.start_ts, period.end_ts);
+    if period.start_reserve == 0 {
+        return ApyBreakdown {
+            days_calculated: period.days as u32,
+            total_parent_fees: parent_fees,
+            ..ApyBreakdown::default()
+        };
+    }
+
+    let net_apy = period.annualize(period.total_profit as f64);
+    let fee_drag = period.annualize(parent_fees as f64);
+
+    ApyBreakdown {
+        edge_yield: net_apy + fee_drag,
+        fee_drag,
+        net_apy,
+        days_calculated: period.days as u32,
+        total_parent_fees: parent_fees,
+    }
+}
+
+/// Snapshot totals over the APY window (last `days` snapshots, capped at MAX_APY_DAYS)
+struct ApyPeriod {
+    days: u64,
+    start_reserve: u64,
+    /// First snapshot day (inclusive) and the day after the last one (exclusive)
+    start_ts: u64,
+    end_ts: u64,
+    total_profit: i64,
+    total_volume: u64,
+}
+
+impl ApyPeriod {
+    /// `(amount / start_reserve) * (365 / days) * 100`
+    fn annualize(&self, amount: f64) -> f64 {
+        (amount / self.start_reserve as f64) * (365.0 / self.days as f64) * 100.0
+    }
+}
+
+/// None when no snapshot has been taken yet
+fn get_apy_period(days: Option<u32>) -> Option<ApyPeriod> {
     // Cap at MAX_APY_DAYS to prevent excessive computation
     let days = days.unwrap_or(7).clamp(1, MAX_APY_DAYS) as u64;
 
@@ -77,7 +202,7 @@ pub fn get_apy_info(days: Option<u32>) -> ApyInfo {
         let len = snapshots.len();
 
         if len == 0 {
-            return ApyInfo::default();
+            return None;
         }
 
         // Use min(days, available) snapshots
@@ -111,33 +236,93 @@ pub fn get_apy_info(days: Option<u32>) -> ApyInfo {
             })
         };
 
-        if start_reserve == 0 {
-            return ApyInfo {
-                actual_apy_percent: 0.0,
-                expected_apy_percent: 0.0,
-                days_calculated: use_days as u32,
-                total_volume,
-                total_profit,
-            };
-        }
+        let start_ts = snapshots.get(start_idx).map_or(0, |s| s.day_timestamp);
+        let end_ts = snapshots.get(len - 1).map_or(0, |s| s.day_timestamp.saturating_add(NANOS_PER_DAY));
 
-        // Calculate APYs (annualized)
-        let days_f = use_days as f64;
-        let reserve_f = start_reserve as f64;
+        Some(ApyPeriod { days: use_days, start_reserve, start_ts, end_ts, total_profit, total_volume })
+    })
+}
 
-        // Actual APY from real profit (can be negative)
-        let actual_apy = (total_profit as f64 / reserve_f) * (365.0 / days_f) * 100.0;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::defi_accounting::types::AuditEvent;
 
-        // Expected APY from theoretical 1% edge
-        let expected_profit = total_volume as f64 * 0.01;
-        let expected_apy = (expected_profit / reserve_f) * (365.0 / days_f) * 100.0;
+    const DAY_0: u64 = 1_735_689_600_000_000_000; // Midnight
 
-        ApyInfo {
-            actual_apy_percent: actual_apy,
-            expected_apy_percent: expected_apy,
-            days_calculated: use_days as u32,
-            total_volume,
-            total_profit,
-        }
-    })
+    fn push_snapshot(day: u64, pool_reserve_end: u64, daily_pool_profit: i64) {
+        DAILY_SNAPSHOTS.with(|s| {
+            s.borrow_mut().push(&DailySnapshot {
+                day_timestamp: DAY_0 + day * NANOS_PER_DAY,
+                pool_reserve_end,
+                daily_pool_profit,
+                daily_volume: 100_000_000,
+                share_price: 100_000_000,
+                total_shares: Some(1_000_000_000),
+            })
+        });
+    }
+
+    #[test]
+    fn test_apy_breakdown_components_sum_to_net() {
+        push_snapshot(0, 1_000_000_000, 0);
+        push_snapshot(1, 1_004_000_000, 4_000_000);
+        push_snapshot(2, 1_001_000_000, -3_000_000);
+
+        // Only fees inside days 1-2 count toward a 2-day window
+        accounting::log_audit_at(AuditEvent::ParentFeeCredited { amount: 9_000_000 }, DAY_0 + 1);
+        accounting::log_audit_at(AuditEvent::ParentFeeCredited { amount: 1_500_000 }, DAY_0 + NANOS_PER_DAY + 5);
+        accounting::log_audit_at(AuditEvent::ParentFeeCredited { amount: 500_000 }, DAY_0 + 3 * NANOS_PER_DAY - 1);
+        accounting::log_audit_at(AuditEvent::ParentFeeCredited { amount: 7_000_000 }, DAY_0 + 3 * NANOS_PER_DAY);
+
+        let breakdown = get_apy_breakdown(Some(2));
+        assert_eq!(breakdown.days_calculated, 2);
+        assert_eq!(breakdown.total_parent_fees, 2_000_000);
+
+        // Net profit 1 USDT and fees 2 USDT on a 1,000 USDT reserve over 2 days
+        let annualize = |amount: f64| amount / 1_000.0 * (365.0 / 2.0) * 100.0;
+        assert!((breakdown.net_apy - annualize(1.0)).abs() < 1e-9);
+        assert!((breakdown.fee_drag - annualize(2.0)).abs() < 1e-9);
+        assert!((breakdown.edge_yield - annualize(3.0)).abs() < 1e-9);
+        assert!((breakdown.edge_yield - breakdown.fee_drag - breakdown.net_apy).abs() < 1e-9);
+        assert_eq!(breakdown.net_apy, get_apy_info(Some(2)).actual_apy_percent);
+    }
+
+    #[test]
+    fn test_apy_breakdown_without_snapshots() {
+        let breakdown = get_apy_breakdown(None);
+        assert_eq!(breakdown.days_calculated, 0);
+        assert_eq!(breakdown.net_apy, 0.0);
+        assert_eq!(breakdown.edge_yield, 0.0);
+    }
+
+    #[test]
+    fn test_share_price_history_rises_with_reserve() {
+        // Constant 1,000 shares outstanding while the reserve grows
+        push_snapshot(0, 1_000_000_000, 0);
+        push_snapshot(1, 1_010_000_000, 10_000_000);
+        push_snapshot(2, 1_025_000_000, 15_000_000);
+
+        let history = get_share_price_history(2);
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0], (DAY_0 + NANOS_PER_DAY, 1.01));
+        assert_eq!(history[1], (DAY_0 + 2 * NANOS_PER_DAY, 1.025));
+
+        let history = get_share_price_history(30);
+        assert_eq!(history.len(), 3);
+        assert!(history.windows(2).all(|w| w[1].1 > w[0].1));
+    }
+
+    #[test]
+    fn test_share_price_history_legacy_and_empty_days() {
+        DAILY_SNAPSHOTS.with(|s| {
+            let s = s.borrow_mut();
+            // Empty pool: no shares, placeholder price
+            s.push(&DailySnapshot { day_timestamp: DAY_0, share_price: 100_000_000, total_shares: Some(0), ..Default::default() });
+            // Recorded before total_shares existed
+            s.push(&DailySnapshot { day_timestamp: DAY_0 + NANOS_PER_DAY, pool_reserve_end: 5_000_000, share_price: 102, total_shares: None, ..Default::default() });
+        });
+
+        assert_eq!(get_share_price_history(7), vec![(DAY_0 + NANOS_PER_DAY, 1.02)]);
+    }
 }
@@ -16,6 +16,8 @@ pub struct DailySnapshot {
     /// Share price at end of day (in decimals, divide by 100_000_000 for USDT per share)
     /// Calculated as pool_reserve / total_shares
     pub share_price: u64,
+    /// LP shares outstanding at end of day (None for snapshots taken before it was recorded)
+    pub total_shares: Option<u64>,
 }
 
 impl Storable for DailySnapshot {
@@ -92,6 +94,21 @@ pub struct ApyInfo {
     pub total_profit: i64,
 }
 
+/// APY split by source. `edge_yield - fee_drag == net_apy`.
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct ApyBreakdown {
+    /// Yield from game outcomes before protocol fees (percent, can be negative)
+    pub edge_yield: f64,
+    /// Yield lost to protocol fees credited to the parent canister (percent)
+    pub fee_drag: f64,
+    /// Net APY seen by LPs, same as `ApyInfo::actual_apy_percent`
+    pub net_apy: f64,
+    /// Number of days used in calculation
+    pub days_calculated: u32,
+    /// Parent fees credited over the period (decimals)
+    pub total_parent_fees: u64,
+}
+
 impl Default for ApyInfo {
     fn default() -> Self {
         Self {
@@ -151,7 +151,7 @@ impl AccountingModel {
         let payout = (amount as u128 * multiplier_bps as u128 / 10000) as u64;
         
         if win {
-            let profit = if payout > amount { payout - amount } else { 0 };
+            let profit = payout.saturating_sub(amount);
             
             // Check if pool can afford profit
             if self.pool_reserve < profit {
@@ -0,0 +1,86 @@
+// Tests that anonymous callers are rejected before any state is touched
+
+use candid::{Nat, Principal};
+use std::future::Future;
+use std::pin::pin;
+use std::task::{Context, Poll, Waker};
+
+use crate::defi_accounting::accounting;
+use crate::defi_accounting::liquidity_pool;
+use crate::defi_accounting::types::GameError;
+
+/// Poll a future once. Every rejection below must happen before the first await.
+fn poll_once<F: Future>(future: F) -> F::Output {
+    match pin!(future).poll(&mut Context::from_waker(Waker::noop())) {
+        Poll::Ready(output) => output,
+        Poll::Pending => panic!("Call reached an await instead of rejecting the caller"),
+    }
+}
+
+fn assert_rejected<T: std::fmt::Debug>(result: Result<T, String>) {
+    assert_eq!(result.unwrap_err(), "Authentication required");
+
+    let anon = Principal::anonymous();
+    assert_eq!(accounting::get_balance_internal(anon), 0);
+    assert_eq!(accounting::get_pending_stats_internal(), (0, 0));
+    assert_eq!(accounting::get_audit_count(), 0);
+    assert_eq!(liquidity_pool::get_pool_reserve(), 0);
+}
+
+#[test]
+fn test_deposit_rejects_anonymous() {
+    let result = poll_once(accounting::deposit_internal(Principal::anonymous(), 10_000_000, Some([1; 16])));
+    assert_eq!(result, Err(GameError::Unauthorized));
+    assert_rejected(result.map_err(String::from));
+}
+
+#[test]
+fn test_withdraw_all_rejects_anonymous() {
+    let result = poll_once(accounting::withdraw_internal(Principal::anonymous()));
+    assert_eq!(result, Err(GameError::Unauthorized));
+    assert_rejected(result.map_err(String::from));
+}
+
+#[test]
+fn test_withdraw_keeping_rejects_anonymous() {
+    let result = poll_once(accounting::withdraw_keeping_internal(Principal::anonymous(), 0));
+    assert_eq!(result, Err(GameError::Unauthorized));
+    assert_rejected(result.map_err(String::from));
+}
+
+#[test]
+fn test_retry_withdrawal_rejects_anonymous() {
+    let result = poll_once(accounting::retry_withdrawal_internal(Principal::anonymous()));
+    assert_eq!(result, Err(GameError::Unauthorized));
+    assert_rejected(result.map_err(String::from));
+}
+
+#[test]
+fn test_abandon_withdrawal_rejects_anonymous() {
+    let result = accounting::abandon_withdrawal_internal(Principal::anonymous());
+    assert_eq!(result, Err(GameError::Unauthorized));
+    assert_rejected(result.map_err(String::from));
+}
+
+#[test]
+fn test_deposit_liquidity_rejects_anonymous() {
+    let min_shares = Some(Nat::from(1u64));
+    assert_rejected(poll_once(liquidity_pool::deposit_liquidity_internal(Principal::anonymous(), 10_000_000, min_shares)));
+}
+
+#[test]
+fn test_withdraw_all_liquidity_rejects_anonymous() {
+    assert_rejected(poll_once(liquidity_pool::withdraw_all_liquidity_internal(Principal::anonymous())));
+}
+
+#[test]
+fn test_play_dice_rejects_anonymous() {
+    let direction = crate::types::RollDirection::Over;
+    assert_rejected(poll_once(crate::game::play_dice(1_000_000, 50, direction, "seed".to_string(), Principal::anonymous())));
+}
+
+#[test]
+fn test_play_multi_dice_rejects_anonymous() {
+    let direction = crate::types::RollDirection::Under;
+    assert_rejected(poll_once(crate::game::play_multi_dice(3, 1_000_000, 50, direction, "seed".to_string(), Principal::anonymous())));
+}
@@ -0,0 +1,68 @@
+// Tests for LP withdrawal fairness when many LPs exit after a pool loss
+
+use candid::{Nat, Principal};
+use num_traits::ToPrimitive;
+use crate::defi_accounting::liquidity_pool::{
+    add_to_reserve, calculate_lp_payout, get_pool_reserve, update_pool_on_win, StorableNat, LP_SHARES,
+};
+
+fn user(id: u8) -> Principal {
+    Principal::from_slice(&[id; 29])
+}
+
+fn give_shares(owner: Principal, shares: u64) {
+    LP_SHARES.with(|s| s.borrow_mut().insert(owner, StorableNat(Nat::from(shares))));
+}
+
+/// Burn all of `owner`'s shares and take the payout from the reserve, as a withdrawal does
+fn withdraw_all(owner: Principal) -> u64 {
+    let shares = LP_SHARES.with(|s| s.borrow().get(&owner).unwrap().0);
+    let payout = calculate_lp_payout(&shares).unwrap().0.to_u64().unwrap();
+    LP_SHARES.with(|s| s.borrow_mut().remove(&owner));
+    update_pool_on_win(payout);
+    payout
+}
+
+#[test]
+fn test_bank_run_pays_every_lp_pro_rata() {
+    let lps = [(user(1), 400_000_000u64), (user(2), 300_000_000), (user(3), 200_000_000), (user(4), 99_999_000)];
+    // Shares burned at pool creation stay in the pool forever
+    give_shares(Principal::anonymous(), 1_000);
+    for (lp, shares) in lps {
+        give_shares(lp, shares);
+    }
+    add_to_reserve(1_000_000_000);
+
+    // Players win 37% of the pool, and then every LP runs for the exit
+    update_pool_on_win(370_000_000);
+    let reserve_before = get_pool_reserve();
+    let total_shares = 1_000_000_000u128;
+
+    let mut total_paid = 0u64;
+    for (lp, shares) in lps {
+        let fair_share = (shares as u128 * reserve_before as u128 / total_shares) as u64;
+        let paid = withdraw_all(lp);
+        // Order doesn't matter: each LP gets their fraction of the post-loss reserve,
+        // up to a few units of rounding dust left behind by earlier withdrawals
+        assert!(paid >= fair_share && paid - fair_share <= 4, "LP paid {} vs fair share {}", paid, fair_share);
+        total_paid += paid;
+    }
+
+    assert!(total_paid <= reserve_before, "Payouts {} exceed reserve {}", total_paid, reserve_before);
+    // Only the burned shares' slice (plus dust) remains
+    assert_eq!(get_pool_reserve(), reserve_before - total_paid);
+    assert!(get_pool_reserve() >= reserve_before / 1_000_000);
+}
+
+#[test]
+fn test_first_withdrawer_cannot_take_more_than_their_share() {
+    give_shares(user(1), 500_000);
+    give_shares(user(2), 500_000);
+    add_to_reserve(1_000_000);
+    update_pool_on_win(600_000);
+
+    // Half the shares buy half of what is left, not the LP's original deposit
+    assert_eq!(withdraw_all(user(1)), 200_000);
+    assert_eq!(withdraw_all(user(2)), 200_000);
+    assert_eq!(get_pool_reserve(), 0);
+}
@@ -0,0 +1,62 @@
+// Tests for the deposit-then-bet combo: a failed bet must never lose the deposit
+
+use candid::Principal;
+use std::cell::Cell;
+use std::future::Future;
+use std::pin::pin;
+use std::task::{Context, Poll, Waker};
+
+use crate::defi_accounting::accounting::{credit_deposit, deposit_and_play, get_balance_internal, try_deduct_balance};
+use crate::defi_accounting::types::GameError;
+
+const T: u64 = 1_700_000_000_000_000_000;
+
+/// Poll a future once. The futures below never suspend.
+fn poll_once<F: Future>(future: F) -> F::Output {
+    match pin!(future).poll(&mut Context::from_waker(Waker::noop())) {
+        Poll::Ready(output) => output,
+        Poll::Pending => panic!("Future suspended"),
+    }
+}
+
+fn user(id: u8) -> Principal {
+    Principal::from_slice(&[id; 29])
+}
+
+#[test]
+fn test_failed_bet_keeps_full_deposit() {
+    let u = user(1);
+    let result = poll_once(deposit_and_play(
+        async { Ok(credit_deposit(u, 10_000_000, 77, None, T)) },
+        async { Err::<u64, String>("Bet too large".to_string()) },
+    ));
+
+    let err = result.unwrap_err();
+    assert!(err.contains("Bet too large"), "{}", err);
+    assert!(err.contains("balance 10000000 decimals (ledger block 77)"), "{}", err);
+    assert_eq!(get_balance_internal(u), 10_000_000);
+}
+
+#[test]
+fn test_bet_spends_deposited_funds() {
+    let u = user(2);
+    let result = poll_once(deposit_and_play(
+        async { Ok(credit_deposit(u, 10_000_000, 77, None, T)) },
+        async { try_deduct_balance(u, 4_000_000) },
+    ));
+
+    assert_eq!(result, Ok(6_000_000));
+    assert_eq!(get_balance_internal(u), 6_000_000);
+}
+
+#[test]
+fn test_failed_deposit_never_plays() {
+    let played = Cell::new(false);
+    let result = poll_once(deposit_and_play(
+        async { Err(GameError::BelowMinimum { amount: 1, minimum: 10 }) },
+        async { played.set(true); Ok::<u64, String>(0) },
+    ));
+
+    assert_eq!(result, Err(String::from(GameError::BelowMinimum { amount: 1, minimum: 10 })));
+    assert!(!played.get());
+}
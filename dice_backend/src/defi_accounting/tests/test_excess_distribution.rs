@@ -0,0 +1,86 @@
+// Tests for distributing pool reserve above the target to LPs
+
+use candid::{Nat, Principal};
+use crate::defi_accounting::accounting::get_balance_internal;
+use crate::defi_accounting::liquidity_pool::{
+    add_to_reserve, distribute_excess_to_lps_internal, get_parent_principal, get_pool_reserve,
+    get_target_reserve, set_target_reserve, StorableNat, LP_SHARES,
+};
+
+const TARGET: u64 = 1_000_000_000; // 1,000 USDT
+const NOW: u64 = 1_700_000_000_000_000_000;
+
+fn user(id: u8) -> Principal {
+    Principal::from_slice(&[id; 29])
+}
+
+fn give_shares(owner: Principal, shares: u64) {
+    LP_SHARES.with(|s| s.borrow_mut().insert(owner, StorableNat(Nat::from(shares))));
+}
+
+fn shares_of(owner: Principal) -> Nat {
+    LP_SHARES.with(|s| s.borrow().get(&owner).map(|n| n.0).unwrap_or_else(|| Nat::from(0u64)))
+}
+
+#[test]
+fn test_target_reserve_bounds() {
+    assert_eq!(get_target_reserve(), 0);
+    assert!(distribute_excess_to_lps_internal(NOW).is_err(), "Distribution requires a target");
+    assert!(set_target_reserve(1).is_err(), "Target below the operating minimum is rejected");
+
+    set_target_reserve(TARGET).unwrap();
+    assert_eq!(get_target_reserve(), TARGET);
+    set_target_reserve(0).unwrap();
+    assert_eq!(get_target_reserve(), 0);
+}
+
+#[test]
+fn test_no_distribution_within_margin() {
+    give_shares(user(1), 1_000_000);
+    add_to_reserve(TARGET + TARGET / 10); // Exactly at the margin
+    set_target_reserve(TARGET).unwrap();
+
+    assert_eq!(distribute_excess_to_lps_internal(NOW), Ok(0));
+    assert_eq!(get_pool_reserve(), TARGET + TARGET / 10);
+    assert_eq!(get_balance_internal(user(1)), 0);
+}
+
+#[test]
+fn test_distribution_pays_equal_value_per_share_without_changing_shares() {
+    let (a, b) = (user(1), user(2));
+    give_shares(Principal::anonymous(), 1_000); // Burned minimum liquidity
+    give_shares(a, 3_000_000);
+    give_shares(b, 1_000_000);
+
+    let excess = 400_000_000;
+    add_to_reserve(TARGET + excess);
+    set_target_reserve(TARGET).unwrap();
+
+    let distributed = distribute_excess_to_lps_internal(NOW).unwrap();
+    assert!(distributed > 0);
+
+    // Share counts are untouched
+    assert_eq!(shares_of(a), Nat::from(3_000_000u64));
+    assert_eq!(shares_of(b), Nat::from(1_000_000u64));
+    assert_eq!(shares_of(Principal::anonymous()), Nat::from(1_000u64));
+
+    // Every LP realizes the same value per share
+    let (credit_a, credit_b) = (get_balance_internal(a), get_balance_internal(b));
+    assert!(credit_b > 0);
+    assert_eq!(credit_a, 3 * credit_b);
+
+    // Credits are net of the 1% LP fee, which goes to the parent
+    let total_shares = 4_001_000u128;
+    let slice_b = (excess as u128 * 1_000_000 / total_shares) as u64;
+    assert_eq!(credit_b, slice_b - slice_b / 100);
+    let parent_fee = get_balance_internal(get_parent_principal());
+    assert_eq!(distributed, credit_a + credit_b + parent_fee);
+
+    // The reserve never drops below the target; the burned shares' slice stays in it
+    let reserve = get_pool_reserve();
+    assert_eq!(reserve, TARGET + excess - distributed);
+    assert!(reserve >= TARGET);
+
+    // A second run is a no-op
+    assert_eq!(distribute_excess_to_lps_internal(NOW), Ok(0));
+}
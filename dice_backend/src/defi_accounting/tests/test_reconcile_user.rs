@@ -0,0 +1,68 @@
+// Tests for the per-user balance reconciliation diagnostic
+
+use candid::Principal;
+use crate::defi_accounting::accounting::{self, log_audit_at, reconcile_user_internal};
+use crate::defi_accounting::game_history::{record_game, MAX_HISTORY_PER_USER};
+use crate::defi_accounting::types::AuditEvent;
+
+const T: u64 = 1_700_000_000_000_000_000;
+
+fn user(id: u8) -> Principal {
+    Principal::from_slice(&[id; 29])
+}
+
+/// Deposit of 100 USDT, a won game, a failed (restored) withdrawal and a lost game,
+/// plus a game before the deposit and another user's events that must be ignored
+fn synthetic_history(u: Principal) {
+    record_game(u, 5_000_000, 0, "before the anchor".to_string(), T - 1);
+    log_audit_at(AuditEvent::BalanceCredited { user: u, amount: 100_000_000, new_balance: 100_000_000, block_index: Some(1) }, T);
+    log_audit_at(AuditEvent::BalanceCredited { user: user(99), amount: 7_000_000, new_balance: 7_000_000, block_index: Some(2) }, T);
+    record_game(u, 10_000_000, 25_000_000, "won".to_string(), T + 1);
+    log_audit_at(AuditEvent::WithdrawalInitiated { user: u, amount: 50_000_000 }, T + 2);
+    log_audit_at(AuditEvent::BalanceRestored { user: u, amount: 50_000_000 }, T + 3);
+    log_audit_at(AuditEvent::WithdrawalInitiated { user: user(99), amount: 7_000_000 }, T + 3);
+    record_game(u, 20_000_000, 0, "lost".to_string(), T + 4);
+}
+
+#[test]
+fn test_reconcile_reports_drift_without_fixing() {
+    let u = user(1);
+    synthetic_history(u);
+    accounting::update_balance(u, 90_000_000).unwrap(); // Should be 95 USDT
+
+    let report = reconcile_user_internal(u);
+    assert_eq!(report.stored_balance, 90_000_000);
+    assert_eq!(report.expected_balance, 95_000_000);
+    assert_eq!(report.drift, -5_000_000);
+    assert_eq!(report.anchor_timestamp, Some(T));
+    assert_eq!(report.events_replayed, 3);
+    assert_eq!(report.games_replayed, 2);
+    assert!(report.complete);
+    assert_eq!(accounting::get_balance_internal(u), 90_000_000, "Diagnostic must not change the balance");
+}
+
+#[test]
+fn test_reconcile_matching_balance_has_no_drift() {
+    let u = user(2);
+    synthetic_history(u);
+    accounting::update_balance(u, 95_000_000).unwrap();
+
+    let report = reconcile_user_internal(u);
+    assert_eq!(report.drift, 0);
+    assert!(report.complete);
+}
+
+#[test]
+fn test_reconcile_truncated_history_is_incomplete() {
+    let u = user(3);
+    for i in 0..=MAX_HISTORY_PER_USER as u64 {
+        record_game(u, 1_000_000, 2_000_000, "won".to_string(), T + i);
+    }
+    accounting::update_balance(u, MAX_HISTORY_PER_USER as u64 * 1_000_000).unwrap();
+
+    let report = reconcile_user_internal(u);
+    assert_eq!(report.anchor_timestamp, None);
+    assert_eq!(report.games_replayed, MAX_HISTORY_PER_USER as u64);
+    assert_eq!(report.drift, 0);
+    assert!(!report.complete, "The oldest game was dropped by the per-user cap");
+}
@@ -16,7 +16,6 @@
 // METHODOLOGY:
 // We verify the state transitions step-by-step.
 
-use std::cell::RefCell;
 use candid::Nat;
 
 // Mock State to simulate the canister's memory
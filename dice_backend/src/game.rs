@@ -40,11 +40,30 @@ pub fn calculate_multiplier_direct(target: u8, direction: &RollDirection) -> f64
     100.0 / winning_numbers  // Clean round numbers: 2x, 4x, 5x, 10x, 20x, 50x, 100x
 }
 
-/// Calculate payout for a bet with given multiplier (P0 fix: ensures consistent rounding)
-/// This helper guarantees identical payout calculation across max_payout checks and actual payouts
-#[inline]
-pub fn calculate_payout(bet_amount: u64, multiplier: f64) -> u64 {
-    (bet_amount as f64 * multiplier).round() as u64
+/// Multiplier precision: 1_000_000 = 1.0x
+pub const MULTIPLIER_SCALE: u64 = 1_000_000;
+
+/// `calculate_multiplier_direct` in MULTIPLIER_SCALE units, computed in integers.
+/// Non-terminating multipliers are rounded to the nearest step (100/3 = 33.333333x).
+pub fn calculate_multiplier_bp(target: u8, direction: &RollDirection) -> u64 {
+    let winning_numbers = match direction {
+        RollDirection::Over => (100 - target) as u64,
+        RollDirection::Under => target as u64,
+    };
+    if winning_numbers == 0 {
+        return 0;
+    }
+    (100 * MULTIPLIER_SCALE + winning_numbers / 2) / winning_numbers
+}
+
+/// Payout for `bet` at `multiplier_bp` (scaled by MULTIPLIER_SCALE), in pure integer math.
+/// Used for both the max payout check and the actual payout so they can never disagree.
+///
+/// Rounding policy: round half up to the nearest unit. Saturates at u64::MAX, which no
+/// house limit can accept.
+pub fn apply_multiplier(bet: u64, multiplier_bp: u64) -> u64 {
+    let scaled = bet as u128 * multiplier_bp as u128 + (MULTIPLIER_SCALE / 2) as u128;
+    u64::try_from(scaled / MULTIPLIER_SCALE as u128).unwrap_or(u64::MAX)
 }
 
 /// Validate target number based on direction (P3 fix: shared validation logic)
@@ -83,6 +102,8 @@ pub async fn play_dice(
     client_seed: String,
     caller: Principal
 ) -> Result<MinimalGameResult, String> {
+    accounting::require_authenticated(caller)?;
+
     // 1. Validate bet amount
     if bet_amount < MIN_BET {
         return Err(format!("Invalid bet: minimum is {:.2} USDT", MIN_BET as f64 / DECIMALS_PER_CKUSDT as f64));
@@ -92,10 +113,10 @@ pub async fn play_dice(
     validate_target_number(target_number, &direction)?;
 
     // 3. Calculate multiplier for this specific bet
-    let multiplier = calculate_multiplier_direct(target_number, &direction);
+    let multiplier_bp = calculate_multiplier_bp(target_number, &direction);
 
     // 4. Check house limit (P0: uses shared payout calculator)
-    let max_payout = calculate_payout(bet_amount, multiplier);
+    let max_payout = apply_multiplier(bet_amount, multiplier_bp);
     let max_allowed = accounting::get_max_allowed_payout();
     if max_allowed == 0 {
         return Err("Error: house balance not initialized, please try again".to_string());
@@ -113,8 +134,9 @@ pub async fn play_dice(
         return Err("Invalid seed: max 256 characters".to_string());
     }
 
-    // 6. Generate roll using per-game VRF (async call - execution may suspend here)
-    let (rolled_number, server_seed, nonce) = crate::seed::generate_dice_roll_vrf(&client_seed).await?;
+    // 6. Roll with the committed seed; VRF draws the next one (async call - execution may suspend here)
+    let (rolled_number, seed_draw) = crate::seed::generate_dice_roll_vrf(&client_seed).await?;
+    let (server_seed, nonce) = (seed_draw.server_seed, seed_draw.nonce);
     let server_seed_hash = crate::seed::hash_server_seed(&server_seed);
 
     // 7. Atomically deduct bet AFTER await to prevent TOCTOU race condition
@@ -138,7 +160,7 @@ pub async fn play_dice(
 
     // Calculate payout (P0: uses shared calculator for consistency)
     let payout = if is_win {
-        calculate_payout(bet_amount, multiplier)
+        apply_multiplier(bet_amount, multiplier_bp)
     } else {
         0
     };
@@ -154,7 +176,7 @@ pub async fn play_dice(
     // Race note: If pool drains during VRF (~2-4s), settle_bet can fail and user gets
     // only bet refund. Requires 6-7 concurrent max-wins or large LP withdrawal—attacker
     // scenario only. Refusing payout under attack is correct; accounting stays consistent.
-    if let Err(e) = liquidity_pool::settle_bet(bet_amount, payout) {
+    if let Err(e) = liquidity_pool::settle_bet(caller, bet_amount, payout) {
         // Pool couldn't afford payout - rollback user balance and refund bet
         let refund_balance = current_balance.checked_add(bet_amount)
             .ok_or("Error: balance overflow on refund")?;
@@ -169,6 +191,15 @@ pub async fn play_dice(
         ));
     }
 
+    crate::defi_accounting::game_history::record_game(
+        caller, bet_amount, payout,
+        format!("Rolled {}, {:?} {}", rolled_number, direction, target_number),
+        ic_cdk::api::time(),
+    );
+
+    // Bet settled: reveal the seed in the chain and commit the next one
+    seed_draw.settle();
+
     Ok(MinimalGameResult {
         rolled_number,
         is_win,
@@ -196,6 +227,8 @@ pub async fn play_multi_dice(
     client_seed: String,
     caller: Principal,
 ) -> Result<MultiDiceGameResult, String> {
+    accounting::require_authenticated(caller)?;
+
     // 1. Validate dice count
     if dice_count == 0 || dice_count > MAX_DICE_COUNT {
         return Err(format!("Invalid dice count: must be 1-{}", MAX_DICE_COUNT));
@@ -214,10 +247,10 @@ pub async fn play_multi_dice(
     validate_target_number(target_number, &direction)?;
 
     // 4. Calculate multiplier (same for all dice)
-    let multiplier = calculate_multiplier_direct(target_number, &direction);
+    let multiplier_bp = calculate_multiplier_bp(target_number, &direction);
 
     // 5. Aggregate max payout check - worst case: all dice win (P0: uses shared calculator)
-    let max_payout_per_dice = calculate_payout(bet_per_dice, multiplier);
+    let max_payout_per_dice = apply_multiplier(bet_per_dice, multiplier_bp);
     let max_aggregate_payout = max_payout_per_dice
         .checked_mul(dice_count as u64)
         .ok_or("Error: max payout calculation overflow")?;
@@ -240,9 +273,10 @@ pub async fn play_multi_dice(
         return Err("Invalid seed: max 256 characters".to_string());
     }
 
-    // 7. VRF generation (async call - execution may suspend here)
-    let (rolled_numbers, server_seed, nonce) =
+    // 7. Roll with the committed seed; VRF draws the next one (async call - execution may suspend here)
+    let (rolled_numbers, seed_draw) =
         crate::seed::generate_multi_dice_roll_vrf(dice_count, &client_seed).await?;
+    let (server_seed, nonce) = (seed_draw.server_seed, seed_draw.nonce);
     let server_seed_hash = crate::seed::hash_server_seed(&server_seed);
 
     // 8. Atomically deduct total bet AFTER await to prevent TOCTOU race condition
@@ -269,7 +303,7 @@ pub async fn play_multi_dice(
 
         // P0: uses shared calculator for consistency
         let payout = if is_win {
-            calculate_payout(bet_per_dice, multiplier)
+            apply_multiplier(bet_per_dice, multiplier_bp)
         } else {
             0
         };
@@ -292,7 +326,7 @@ pub async fn play_multi_dice(
     accounting::update_balance(caller, new_balance)?;
 
     // Settle with pool (see race condition note in play_dice)
-    if let Err(e) = liquidity_pool::settle_bet(total_bet, total_payout) {
+    if let Err(e) = liquidity_pool::settle_bet(caller, total_bet, total_payout) {
         // Rollback on pool failure
         let refund_balance = current_balance.checked_add(total_bet).ok_or("Error: refund overflow")?;
         accounting::update_balance(caller, refund_balance)?;
@@ -306,6 +340,15 @@ pub async fn play_multi_dice(
         ));
     }
 
+    crate::defi_accounting::game_history::record_game(
+        caller, total_bet, total_payout,
+        format!("{}/{} dice won, {:?} {}", total_wins, dice_count, direction, target_number),
+        ic_cdk::api::time(),
+    );
+
+    // Bet settled: reveal the seed in the chain and commit the next one
+    seed_draw.settle();
+
     let net_result = (total_payout as i64) - (total_bet as i64);
 
     Ok(MultiDiceGameResult {
@@ -332,8 +375,8 @@ pub fn calculate_max_bet_per_dice(
         return Err(format!("Invalid dice count: must be 1-{}", MAX_DICE_COUNT));
     }
 
-    let multiplier = calculate_multiplier_direct(target_number, direction);
-    if multiplier <= 0.0 {
+    let multiplier_bp = calculate_multiplier_bp(target_number, direction);
+    if multiplier_bp == 0 {
         return Err("Invalid target: multiplier is zero".to_string());
     }
 
@@ -342,9 +385,10 @@ pub fn calculate_max_bet_per_dice(
         return Err("Error: house not initialized".to_string());
     }
 
-    // max_allowed / (dice_count * multiplier)
-    let max_bet_per_dice = (max_allowed as f64) / (dice_count as f64 * multiplier);
-    Ok(max_bet_per_dice as u64)
+    // max_allowed / (dice_count * multiplier), rounded down
+    let max_bet_per_dice = (max_allowed as u128 * MULTIPLIER_SCALE as u128)
+        / (dice_count as u128 * multiplier_bp as u128);
+    Ok(max_bet_per_dice.min(u64::MAX as u128) as u64)
 }
 
 // =============================================================================
@@ -357,7 +401,8 @@ pub fn calculate_payout_info(target_number: u8, direction: RollDirection) -> Res
     validate_target_number(target_number, &direction)?;
 
     let win_chance = calculate_win_chance(target_number, &direction);
-    let multiplier = calculate_multiplier_direct(target_number, &direction);
+    // Report the multiplier actually applied to payouts
+    let multiplier = calculate_multiplier_bp(target_number, &direction) as f64 / MULTIPLIER_SCALE as f64;
     Ok((win_chance, multiplier))
 }
 
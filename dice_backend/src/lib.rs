@@ -11,12 +11,13 @@ mod defi_accounting;
 pub mod types;
 pub mod seed;
 pub mod game;
+pub mod vrf;
 
 // =============================================================================
 // RE-EXPORTS
 // =============================================================================
 
-pub use types::{RollDirection, MinimalGameResult, MultiDiceGameResult, SingleDiceResult};
+pub use types::{RollDirection, MinimalGameResult, MultiDiceGameResult, SingleDiceResult, RollProof};
 
 // =============================================================================
 // MEMORY MANAGEMENT
@@ -38,6 +39,9 @@ fn init() {
     // Initialize game state
     ic_cdk::println!("Dice Game Backend Initialized");
 
+    // Fresh canisters start with the default solvency margin; upgrades keep theirs
+    defi_accounting::solvency::init_solvency_margin();
+
     // Start parent auto-withdrawal timer (weekly fee collection)
     defi_accounting::accounting::start_parent_withdrawal_timer();
 
@@ -46,11 +50,16 @@ fn init() {
 
     // Start daily statistics timer
     defi_accounting::start_stats_timer();
+    defi_accounting::rebates::start_rebate_timer();
+
+    // Commit the first server seed before any game uses it
+    seed::start_seed_commitment();
 
     // Initialize cached balance on fresh install using a one-shot timer
     // (spawn not allowed in init mode)
     ic_cdk_timers::set_timer(std::time::Duration::ZERO, async {
         defi_accounting::accounting::refresh_canister_balance().await;
+        defi_accounting::accounting::refresh_token_info().await;
         ic_cdk::println!("Init: balance cache initialized");
     });
 }
@@ -72,6 +81,10 @@ fn post_upgrade() {
 
     // Start daily statistics timer
     defi_accounting::start_stats_timer();
+    defi_accounting::rebates::start_rebate_timer();
+
+    // Commit a server seed if this canister has none yet
+    seed::start_seed_commitment();
 
     // Initialize cached balance immediately after upgrade using a one-shot timer
     // This prevents games being blocked until hourly reconciliation
@@ -92,26 +105,32 @@ fn post_upgrade() {
 async fn play_dice(bet_amount: u64, target_number: u8, direction: RollDirection, client_seed: String) -> Result<MinimalGameResult, String> {
     // NEW: Check solvency before accepting bet (O(1) operation)
     if !is_canister_solvent() {
-        return Err("Game temporarily paused - insufficient funds. Contact admin.".to_string());
+        return Err(defi_accounting::types::GameError::Insolvent.into());
     }
     game::play_dice(bet_amount, target_number, direction, client_seed, ic_cdk::api::msg_caller()).await
 }
 
+/// Deposit via ICRC-2 and roll in one call. If the roll fails, the deposit stays in the balance.
+#[update]
+async fn deposit_and_play(
+    deposit_amount: u64,
+    bet_amount: u64,
+    target_number: u8,
+    direction: RollDirection,
+    client_seed: String,
+) -> Result<MinimalGameResult, String> {
+    if !is_canister_solvent() {
+        return Err(defi_accounting::types::GameError::Insolvent.into());
+    }
+    let caller = ic_cdk::api::msg_caller();
+    defi_accounting::accounting::deposit_and_play(
+        defi_accounting::accounting::deposit_internal(caller, deposit_amount, None),
+        game::play_dice(bet_amount, target_number, direction, client_seed, caller),
+    ).await
+}
+
 fn is_canister_solvent() -> bool {
-    let pool_reserve = defi_accounting::liquidity_pool::get_pool_reserve();
-    let total_deposits = defi_accounting::accounting::calculate_total_deposits_internal();
-    let canister_balance = defi_accounting::accounting::get_cached_canister_balance_internal();
-
-    // Use checked_add to detect impossible overflow scenarios
-    let obligations = match pool_reserve.checked_add(total_deposits) {
-        Some(o) => o,
-        None => {
-            ic_cdk::println!("CRITICAL: Obligations overflow u64::MAX");
-            return false;  // Treat as insolvent if obligations overflow
-        }
-    };
-    
-    canister_balance >= obligations
+    defi_accounting::solvency::is_canister_solvent()
 }
 
 #[query]
@@ -119,6 +138,33 @@ fn verify_game_result(server_seed: [u8; 32], client_seed: String, nonce: u64, ex
     seed::verify_game_result(server_seed, client_seed, nonce, expected_roll)
 }
 
+/// Verify many past rolls in one call: one pass/fail per roll
+#[query]
+fn verify_game_results(rolls: Vec<RollProof>) -> Result<Vec<bool>, String> {
+    seed::verify_game_results(rolls)
+}
+
+/// Recent server-seed chain links, newest first: (nonce, commitment to the next seed, revealed seed)
+#[query]
+fn get_seed_chain(limit: u32) -> Vec<(u64, String, Option<[u8; 32]>)> {
+    seed::get_seed_chain_entries(limit)
+        .into_iter()
+        .map(|e| (e.nonce, e.next_commitment, e.server_seed))
+        .collect()
+}
+
+/// Hash of the server seed the next game will use, published before any bet uses it
+#[query]
+fn get_seed_commitment() -> Option<String> {
+    seed::get_seed_commitment()
+}
+
+/// Verify that the most recent `limit` links form an unbroken chain
+#[query]
+fn verify_seed_chain(limit: u32) -> Result<u64, String> {
+    seed::verify_stored_chain(limit)
+}
+
 #[query]
 fn calculate_payout_info(target_number: u8, direction: RollDirection) -> Result<(f64, f64), String> {
     game::calculate_payout_info(target_number, direction)
@@ -175,23 +221,28 @@ fn get_max_bet_per_dice(
 // =============================================================================
 
 #[update]
-async fn deposit(amount: u64) -> Result<u64, String> {
-    defi_accounting::accounting::deposit(amount).await
+async fn deposit(amount: u64, idempotency_key: Option<[u8; 16]>) -> Result<defi_accounting::types::DepositReceipt, String> {
+    defi_accounting::accounting::deposit(amount, idempotency_key).await.map_err(|e| e.to_string())
 }
 
 #[update]
 async fn withdraw_all() -> Result<u64, String> {
-    defi_accounting::accounting::withdraw_all().await
+    defi_accounting::accounting::withdraw_all().await.map_err(|e| e.to_string())
+}
+
+#[update]
+async fn withdraw_keeping(keep_amount: u64) -> Result<u64, defi_accounting::types::GameError> {
+    defi_accounting::accounting::withdraw_keeping(keep_amount).await
 }
 
 #[update]
 async fn retry_withdrawal() -> Result<u64, String> {
-    defi_accounting::accounting::retry_withdrawal().await
+    defi_accounting::accounting::retry_withdrawal().await.map_err(|e| e.to_string())
 }
 
 #[update]
 fn abandon_withdrawal() -> Result<u64, String> {
-    defi_accounting::accounting::abandon_withdrawal()
+    defi_accounting::accounting::abandon_withdrawal().map_err(|e| e.to_string())
 }
 
 #[query]
@@ -199,6 +250,11 @@ fn get_my_withdrawal_status() -> Option<defi_accounting::types::PendingWithdrawa
     defi_accounting::accounting::get_withdrawal_status()
 }
 
+#[query]
+fn get_my_withdrawal_detail() -> Option<defi_accounting::types::WithdrawalStatusDetail> {
+    defi_accounting::accounting::get_withdrawal_status_detail()
+}
+
 #[query]
 fn get_balance(principal: candid::Principal) -> u64 {
     defi_accounting::query::get_balance(principal)
@@ -219,6 +275,27 @@ fn get_max_allowed_payout() -> u64 {
     defi_accounting::query::get_max_allowed_payout()
 }
 
+#[query]
+fn get_token_info() -> defi_accounting::types::TokenInfo {
+    defi_accounting::query::get_token_info()
+}
+
+#[query]
+fn get_solvency_status() -> defi_accounting::types::SolvencyStatus {
+    defi_accounting::solvency::get_solvency_status()
+}
+
+#[query]
+fn get_my_lifetime_stats() -> defi_accounting::types::PlayerLifetimeStats {
+    defi_accounting::player_stats::get_player_stats(ic_cdk::api::msg_caller())
+}
+
+/// The caller's most recent settled games, newest first (at most 50 are kept)
+#[query]
+fn get_my_recent_games(limit: u32) -> Vec<defi_accounting::types::GameHistoryEntry> {
+    defi_accounting::game_history::get_recent_games(ic_cdk::api::msg_caller(), limit)
+}
+
 // =============================================================================
 // ADMIN DIAGNOSTIC ENDPOINTS
 // =============================================================================
@@ -243,6 +320,11 @@ fn admin_get_orphaned_funds_report_full() -> Result<defi_accounting::types::Orph
     defi_accounting::admin_query::get_orphaned_funds_report_full()
 }
 
+#[query]
+fn admin_reconcile_user(user: candid::Principal) -> Result<defi_accounting::types::ReconcileReport, String> {
+    defi_accounting::admin_query::reconcile_user(user)
+}
+
 #[query]
 fn admin_get_all_balances(offset: u64, limit: u64) -> Result<Vec<defi_accounting::types::UserBalance>, String> {
     defi_accounting::admin_query::get_all_balances(offset, limit)
@@ -273,6 +355,133 @@ fn admin_get_audit_log_count() -> Result<u64, String> {
     defi_accounting::admin_query::get_audit_log_count()
 }
 
+#[query]
+fn admin_get_audit_retention() -> Result<u64, String> {
+    defi_accounting::admin_query::get_audit_retention()
+}
+
+#[update]
+fn admin_set_audit_retention(limit: u64) -> Result<(), String> {
+    defi_accounting::admin_query::set_audit_retention(limit)
+}
+
+#[query]
+fn admin_get_parent_config() -> Result<defi_accounting::types::ParentWithdrawalConfig, String> {
+    defi_accounting::admin_query::get_parent_config()
+}
+
+#[update]
+fn admin_set_parent_config(threshold: u64, recipient: types::Account) -> Result<(), String> {
+    defi_accounting::admin_query::set_parent_config(threshold, recipient)
+}
+
+#[update]
+fn admin_set_betting_paused(paused: bool) -> Result<(), String> {
+    defi_accounting::admin_query::set_betting_paused(paused)
+}
+
+#[update]
+async fn admin_initiate_pool_drain(recipient: types::Account) -> Result<u64, String> {
+    defi_accounting::admin_query::initiate_pool_drain(recipient).await
+}
+
+#[query]
+fn admin_get_pending_drain() -> Result<Option<defi_accounting::types::PendingDrain>, String> {
+    defi_accounting::admin_query::get_pending_drain()
+}
+
+#[update]
+async fn admin_retry_pool_drain() -> Result<u64, String> {
+    defi_accounting::admin_query::retry_pool_drain().await
+}
+
+#[update]
+fn admin_resolve_pool_drain(landed: bool) -> Result<u64, String> {
+    defi_accounting::admin_query::resolve_pool_drain(landed)
+}
+
+#[update]
+fn admin_set_solvency_margin_bp(margin_bp: u64) -> Result<(), String> {
+    defi_accounting::admin_query::set_solvency_margin_bp(margin_bp)
+}
+
+#[update]
+fn admin_set_target_reserve(target: u64) -> Result<(), String> {
+    defi_accounting::admin_query::set_target_reserve(target)
+}
+
+#[update]
+fn admin_distribute_excess() -> Result<u64, String> {
+    defi_accounting::admin_query::distribute_excess()
+}
+
+#[update]
+fn admin_set_rebate_config(config: defi_accounting::types::RebateConfig) -> Result<(), String> {
+    defi_accounting::admin_query::set_rebate_config(config)
+}
+
+#[update]
+fn admin_process_rebates() -> Result<u64, String> {
+    defi_accounting::admin_query::process_rebates()
+}
+
+#[update]
+async fn admin_set_ledger_canister_id(ledger: candid::Principal) -> Result<defi_accounting::types::TokenInfo, String> {
+    defi_accounting::admin_query::set_ledger_canister_id(ledger).await
+}
+
+#[query]
+fn admin_get_event_subscriber() -> Result<Option<candid::Principal>, String> {
+    defi_accounting::admin_query::get_event_subscriber()
+}
+
+#[update]
+fn admin_set_event_subscriber(canister: candid::Principal) -> Result<(), String> {
+    defi_accounting::admin_query::set_event_subscriber(canister)
+}
+
+#[update]
+fn admin_clear_event_subscriber() -> Result<(), String> {
+    defi_accounting::admin_query::clear_event_subscriber()
+}
+
+/// Every admin-settable parameter as one candid blob, to clone onto another canister
+/// or restore later with `admin_import_config`
+#[query]
+fn admin_export_config() -> Result<types::GameConfigBlob, String> {
+    defi_accounting::admin_query::require_admin()?;
+    Ok(export_game_config())
+}
+
+/// Restore a blob from `admin_export_config`. Nothing changes unless every setting is valid.
+#[update]
+fn admin_import_config(blob: types::GameConfigBlob) -> Result<(), String> {
+    defi_accounting::admin_query::require_admin()?;
+    import_game_config(&blob, ic_cdk::api::time())
+}
+
+fn export_game_config() -> types::GameConfigBlob {
+    let config = types::GameConfig {
+        game: env!("CARGO_PKG_NAME").to_string(),
+        accounting: defi_accounting::config::export_config(),
+    };
+    candid::encode_one(config).expect("CRITICAL: Failed to encode GameConfig")
+}
+
+fn import_game_config(blob: &[u8], now: u64) -> Result<(), String> {
+    let config: types::GameConfig = candid::decode_one(blob)
+        .map_err(|e| format!("Invalid config blob: {}", e))?;
+    if config.game != env!("CARGO_PKG_NAME") {
+        return Err(format!("Config blob is for {}, not {}", config.game, env!("CARGO_PKG_NAME")));
+    }
+    defi_accounting::config::apply_config(config.accounting, now)
+}
+
+#[query]
+fn get_ledger_canister_id() -> candid::Principal {
+    defi_accounting::accounting::get_ledger_canister_id()
+}
+
 // =============================================================================
 // LIQUIDITY POOL ENDPOINTS
 // =============================================================================
@@ -307,6 +516,28 @@ fn get_pool_stats() -> defi_accounting::liquidity_pool::PoolStats {
     defi_accounting::query::get_pool_stats()
 }
 
+#[query]
+fn get_target_reserve() -> u64 {
+    defi_accounting::liquidity_pool::get_target_reserve()
+}
+
+#[query]
+fn get_rebate_config() -> defi_accounting::types::RebateConfig {
+    defi_accounting::rebates::get_rebate_config()
+}
+
+/// Caller's loyalty rebate accrual since their last rebate
+#[query]
+fn get_my_rebate_account() -> defi_accounting::types::RebateAccount {
+    defi_accounting::rebates::get_rebate_account(ic_cdk::api::msg_caller())
+}
+
+/// True while an admin has paused betting (e.g. during a migration)
+#[query]
+fn is_betting_paused() -> bool {
+    defi_accounting::pool_drain::is_betting_paused()
+}
+
 #[query]
 fn get_house_mode() -> String {
     defi_accounting::query::get_house_mode()
@@ -336,8 +567,33 @@ fn get_stats_count() -> u64 {
     defi_accounting::get_snapshot_count()
 }
 
+#[query]
+fn get_stats_summary() -> defi_accounting::types::StatsSummary {
+    defi_accounting::query::get_stats_summary("dice")
+}
+
+/// Candid interface / stable layout version reported by `get_version_info`
+const SCHEMA_VERSION: u32 = 1;
+
+/// Game name, schema version, enabled features and build commit, so clients can adapt
+#[query]
+fn get_version_info() -> defi_accounting::types::VersionInfo {
+    defi_accounting::query::get_version_info("dice", SCHEMA_VERSION, vec!["multi_dice".to_string(), "seed_chain".to_string()])
+}
+
 #[query]
 fn get_pool_apy(days: Option<u32>) -> defi_accounting::ApyInfo {
     defi_accounting::get_apy_info(days)
 }
 
+#[query]
+fn get_pool_apy_breakdown(days: Option<u32>) -> defi_accounting::ApyBreakdown {
+    defi_accounting::get_apy_breakdown(days)
+}
+
+/// (day_timestamp, reserve per share) for the last `days` days, to judge LP entry and exit timing
+#[query]
+fn get_share_price_history(days: u32) -> Vec<(u64, f64)> {
+    defi_accounting::get_share_price_history(days)
+}
+
@@ -1,31 +1,238 @@
-use ic_cdk::management_canister::raw_rand;
+use candid::{CandidType, Deserialize};
+use crate::vrf::{raw_rand_with_retries, VRF_RETRIES};
+use ic_stable_structures::memory_manager::MemoryId;
+use ic_stable_structures::storable::Bound;
+use ic_stable_structures::{StableBTreeMap, StableCell, Storable};
 use sha2::{Digest, Sha256};
-use crate::types::MAX_NUMBER;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use crate::defi_accounting::memory_ids::{NEXT_SEED_MEMORY_ID, SEED_CHAIN_MEMORY_ID};
+use crate::types::{RollProof, MAX_NUMBER};
+use crate::{Memory, MEMORY_MANAGER};
+
+/// Most recent chain links kept in stable memory (older links are pruned)
+const MAX_SEED_CHAIN_ENTRIES: u64 = 10_000;
+/// Maximum entries returned by `get_seed_chain`
+pub const MAX_SEED_CHAIN_PAGE: u32 = 100;
+/// Maximum rolls checked by one `verify_game_results` call
+pub const MAX_VERIFY_BATCH: usize = 1_000;
+/// `prev_hash` of the very first link
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
 
 // =============================================================================
-// PUBLIC FUNCTIONS
+// SEED CHAIN
 // =============================================================================
+//
+// Server seeds are committed before they are used. The canister always holds one
+// unrevealed seed whose hash (`get_seed_commitment`) is already published in the chain
+// tip. A game rolls with that committed seed and, once the bet is settled, appends a link
+// revealing it and committing a freshly drawn VRF seed for the next game:
+//
+//   hash_i = SHA256(hash_{i-1} || server_seed_i || nonce_i || next_commitment_i)
+//
+// with SHA256(server_seed_i) == next_commitment_{i-1}. Every seed was therefore fixed
+// before the bet that used it was placed, so it cannot have been chosen adaptively, and
+// no seed can be dropped or rewritten without breaking every later link. The first link
+// only commits the first seed and reveals nothing. Bets that are rejected or refunded
+// leave the commitment in place for the next game and add no link.
 
-/// Generate dice roll using per-game VRF
-/// Returns: (rolled_number, server_seed, nonce) for verification
-pub async fn generate_dice_roll_vrf(client_seed: &str) -> Result<(u8, [u8; 32], u64), String> {
-    // Get fresh VRF randomness (async call to IC consensus)
-    let random_bytes = raw_rand().await
-        .map_err(|e| format!("VRF unavailable: {:?}. Please retry.", e))?;
+/// One link of the server-seed chain
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct SeedChainEntry {
+    pub nonce: u64,
+    pub prev_hash: String,
+    pub hash: String,
+    /// The seed this link reveals (None only for the first link)
+    pub server_seed: Option<[u8; 32]>,
+    /// SHA256 of the seed the next game will use
+    pub next_commitment: String,
+}
+
+impl Storable for SeedChainEntry {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(candid::encode_one(self).expect("CRITICAL: Failed to encode SeedChainEntry"))
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        self.to_bytes().into_owned()
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        candid::decode_one(&bytes).expect("CRITICAL: Failed to decode SeedChainEntry from stable storage")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+thread_local! {
+    static SEED_CHAIN: RefCell<StableBTreeMap<u64, SeedChainEntry, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(SEED_CHAIN_MEMORY_ID)))
+        )
+    );
+
+    // The committed, not yet revealed seed for the next game
+    static NEXT_SEED: RefCell<StableCell<Option<[u8; 32]>, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(NEXT_SEED_MEMORY_ID))),
+            None
+        )
+    );
+}
+
+/// Hash of a chain link
+pub fn chain_link_hash(prev_hash: &str, server_seed: Option<&[u8; 32]>, nonce: u64, next_commitment: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(prev_hash.as_bytes());
+    if let Some(seed) = server_seed {
+        hasher.update(seed);
+    }
+    hasher.update(nonce.to_be_bytes());
+    hasher.update(next_commitment.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Append a link revealing `server_seed` (if any) and committing `next_seed`
+fn append_to_chain(server_seed: Option<[u8; 32]>, nonce: u64, next_seed: [u8; 32]) {
+    let next_commitment = hash_server_seed(&next_seed);
+    SEED_CHAIN.with(|chain| {
+        let mut chain = chain.borrow_mut();
+        let (next_index, prev_hash) = match chain.last_key_value() {
+            Some((index, last)) => (index + 1, last.hash),
+            None => (0, GENESIS_HASH.to_string()),
+        };
+        let hash = chain_link_hash(&prev_hash, server_seed.as_ref(), nonce, &next_commitment);
+        chain.insert(next_index, SeedChainEntry { nonce, prev_hash, hash, server_seed, next_commitment });
+
+        if chain.len() > MAX_SEED_CHAIN_ENTRIES {
+            if let Some((oldest, _)) = chain.first_key_value() {
+                chain.remove(&oldest);
+            }
+        }
+    });
+    NEXT_SEED.with(|s| s.borrow_mut().set(Some(next_seed)));
+}
+
+/// Hash of the seed the next game will use (None until the first seed is committed)
+pub fn get_seed_commitment() -> Option<String> {
+    NEXT_SEED.with(|s| s.borrow().get().map(|seed| hash_server_seed(&seed)))
+}
+
+/// Commit the first server seed. No-op once a seed is committed.
+fn commit_first_seed(seed: [u8; 32], now: u64) {
+    if get_seed_commitment().is_none() {
+        append_to_chain(None, now, seed);
+    }
+}
+
+/// Commit the first server seed on a fresh install (one-shot timer; games commit it
+/// themselves if this has not run yet)
+pub fn start_seed_commitment() {
+    ic_cdk_timers::set_timer(std::time::Duration::ZERO, async {
+        if get_seed_commitment().is_some() {
+            return;
+        }
+        match raw_rand_with_retries(VRF_RETRIES).await {
+            Ok(bytes) => match bytes[0..32].try_into() {
+                Ok(seed) => commit_first_seed(seed, ic_cdk::api::time()),
+                Err(_) => ic_cdk::println!("Seed commitment: insufficient randomness"),
+            },
+            Err(e) => ic_cdk::println!("Seed commitment: VRF unavailable: {:?}", e),
+        }
+    });
+}
+
+/// The committed seed taken for one game. Call `settle` once the bet is settled to
+/// reveal it and commit the next seed; dropping it leaves the commitment in place.
+pub struct SeedDraw {
+    pub server_seed: [u8; 32],
+    pub nonce: u64,
+    next_seed: [u8; 32],
+}
+
+impl SeedDraw {
+    pub fn settle(self) {
+        append_to_chain(Some(self.server_seed), self.nonce, self.next_seed);
+    }
+}
+
+/// Take the committed seed, with `fresh` (new VRF output) as the next one. Without a
+/// commitment yet, `fresh` is committed instead and the game must be retried.
+fn draw_committed_seed(fresh: [u8; 32], nonce: u64) -> Result<SeedDraw, String> {
+    match NEXT_SEED.with(|s| *s.borrow().get()) {
+        Some(server_seed) => Ok(SeedDraw { server_seed, nonce, next_seed: fresh }),
+        None => {
+            commit_first_seed(fresh, nonce);
+            Err("Server seed committed for the first time. Please retry.".to_string())
+        }
+    }
+}
+
+/// Most recent links, newest first (at most `MAX_SEED_CHAIN_PAGE`)
+pub fn get_seed_chain_entries(limit: u32) -> Vec<SeedChainEntry> {
+    let limit = limit.min(MAX_SEED_CHAIN_PAGE) as usize;
+    SEED_CHAIN.with(|chain| {
+        chain.borrow().iter().rev().take(limit).map(|entry| entry.value()).collect()
+    })
+}
+
+/// Check that links (oldest first) are internally consistent, each one points at the
+/// previous link's hash, and each revealed seed matches the commitment published by the
+/// previous link. Returns the number of links verified.
+pub fn verify_chain(entries: &[SeedChainEntry]) -> Result<u64, String> {
+    for (i, entry) in entries.iter().enumerate() {
+        if chain_link_hash(&entry.prev_hash, entry.server_seed.as_ref(), entry.nonce, &entry.next_commitment) != entry.hash {
+            return Err(format!("Link {} hash does not match its contents", entry.nonce));
+        }
+        if i == 0 {
+            continue;
+        }
+        let prev = &entries[i - 1];
+        if entry.prev_hash != prev.hash {
+            return Err(format!("Link {} does not extend the previous link", entry.nonce));
+        }
+        let seed = entry.server_seed.ok_or_else(|| format!("Link {} has no revealed seed", entry.nonce))?;
+        if hash_server_seed(&seed) != prev.next_commitment {
+            return Err(format!("Link {} reveals a seed that was not committed", entry.nonce));
+        }
+    }
+    Ok(entries.len() as u64)
+}
 
-    // Use first 32 bytes as server seed
-    let server_seed: [u8; 32] = random_bytes[0..32]
+/// Verify the most recent `limit` links stored in the canister
+pub fn verify_stored_chain(limit: u32) -> Result<u64, String> {
+    let mut entries = get_seed_chain_entries(limit);
+    entries.reverse();
+    verify_chain(&entries)
+}
+
+/// Fresh VRF output for the next commitment
+async fn draw_fresh_seed() -> Result<[u8; 32], String> {
+    let random_bytes = raw_rand_with_retries(VRF_RETRIES).await
+        .map_err(|e| format!("VRF unavailable: {:?}. Please retry.", e))?;
+    random_bytes[0..32]
         .try_into()
-        .map_err(|_| "Insufficient randomness")?;
+        .map_err(|_| "Insufficient randomness".to_string())
+}
+
+// =============================================================================
+// PUBLIC FUNCTIONS
+// =============================================================================
+
+/// Generate dice roll from the committed server seed
+/// Returns: (rolled_number, seed draw to settle once the bet is settled)
+pub async fn generate_dice_roll_vrf(client_seed: &str) -> Result<(u8, SeedDraw), String> {
+    // Get fresh VRF randomness (async call to IC consensus) for the next commitment
+    let fresh = draw_fresh_seed().await?;
 
     // Generate unique nonce from timestamp
-    let nonce = ic_cdk::api::time();
+    let draw = draw_committed_seed(fresh, ic_cdk::api::time())?;
 
     // Combine server_seed + client_seed + nonce
     let mut hasher = Sha256::new();
-    hasher.update(server_seed);
+    hasher.update(draw.server_seed);
     hasher.update(client_seed.as_bytes());
-    hasher.update(nonce.to_be_bytes());
+    hasher.update(draw.nonce.to_be_bytes());
     let hash = hasher.finalize();
 
     // Convert to 0-100 range
@@ -34,7 +241,7 @@ pub async fn generate_dice_roll_vrf(client_seed: &str) -> Result<(u8, [u8; 32],
     );
     let roll = (rand_u64 % (MAX_NUMBER as u64 + 1)) as u8;
 
-    Ok((roll, server_seed, nonce))
+    Ok((roll, draw))
 }
 
 /// Verify game result for provable fairness
@@ -59,6 +266,19 @@ pub fn verify_game_result(
     Ok(calculated_roll == expected_roll)
 }
 
+/// Verify many past rolls in one call, e.g. a whole session taken from the player's game
+/// history. Each roll carries its own server seed, client seed and nonce, since nonces are
+/// timestamps rather than a counter. Each goes through `verify_game_result`, so single and
+/// batch verification always agree.
+pub fn verify_game_results(rolls: Vec<RollProof>) -> Result<Vec<bool>, String> {
+    if rolls.len() > MAX_VERIFY_BATCH {
+        return Err(format!("Maximum {} rolls per verification", MAX_VERIFY_BATCH));
+    }
+    rolls.into_iter()
+        .map(|r| verify_game_result(r.server_seed, r.client_seed, r.nonce, r.rolled_number))
+        .collect()
+}
+
 /// Get hash of server seed for pre-game commitment (provable fairness)
 pub fn hash_server_seed(server_seed: &[u8; 32]) -> String {
     let mut hasher = Sha256::new();
@@ -91,36 +311,30 @@ fn derive_single_roll(
     (rand_u64 % (MAX_NUMBER as u64 + 1)) as u8
 }
 
-/// Generate multiple dice rolls using per-game VRF with deterministic derivation
-/// Single raw_rand() call, derive N independent rolls
+/// Generate multiple dice rolls from the committed server seed with deterministic derivation
+/// Single raw_rand() call (for the next commitment), derive N independent rolls
 pub async fn generate_multi_dice_roll_vrf(
     dice_count: u8,
     client_seed: &str,
-) -> Result<(Vec<u8>, [u8; 32], u64), String> {
+) -> Result<(Vec<u8>, SeedDraw), String> {
     use crate::types::MAX_DICE_COUNT;
 
     if dice_count == 0 || dice_count > MAX_DICE_COUNT {
         return Err(format!("Dice count must be 1-{}", MAX_DICE_COUNT));
     }
 
-    // Get fresh VRF randomness (single async call to IC consensus)
-    let random_bytes = raw_rand().await
-        .map_err(|e| format!("VRF unavailable: {:?}. Please retry.", e))?;
-
-    let server_seed: [u8; 32] = random_bytes[0..32]
-        .try_into()
-        .map_err(|_| "Insufficient randomness")?;
-
-    let nonce = ic_cdk::api::time();
+    // Get fresh VRF randomness (single async call to IC consensus) for the next commitment
+    let fresh = draw_fresh_seed().await?;
+    let draw = draw_committed_seed(fresh, ic_cdk::api::time())?;
 
     // Derive each dice roll independently using index-based hashing
     let mut rolls = Vec::with_capacity(dice_count as usize);
     for i in 0..dice_count {
-        let roll = derive_single_roll(&server_seed, client_seed, nonce, i);
+        let roll = derive_single_roll(&draw.server_seed, client_seed, draw.nonce, i);
         rolls.push(roll);
     }
 
-    Ok((rolls, server_seed, nonce))
+    Ok((rolls, draw))
 }
 
 /// Verify multi-dice game result for provable fairness
@@ -144,4 +358,139 @@ pub fn verify_multi_dice_result(
         }
     }
     Ok(true)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// First commitment plus `n` settled games
+    fn build_chain(n: u8) {
+        commit_first_seed([0; 32], 999);
+        for i in 0..n {
+            draw_committed_seed([i + 1; 32], 1_000 + i as u64).unwrap().settle();
+        }
+    }
+
+    fn oldest_first(limit: u32) -> Vec<SeedChainEntry> {
+        let mut entries = get_seed_chain_entries(limit);
+        entries.reverse();
+        entries
+    }
+
+    #[test]
+    fn test_chain_links_to_previous_reveal() {
+        build_chain(5);
+        let entries = oldest_first(10);
+        assert_eq!(entries.len(), 6);
+        assert_eq!(entries[0].prev_hash, GENESIS_HASH);
+        assert_eq!(entries[0].server_seed, None, "The first link only commits");
+        assert_eq!(entries[1].prev_hash, entries[0].hash);
+        assert_eq!(verify_stored_chain(10), Ok(6));
+
+        // Each game reveals the seed committed by the link before it
+        for pair in entries.windows(2) {
+            assert_eq!(hash_server_seed(&pair[1].server_seed.unwrap()), pair[0].next_commitment);
+        }
+        assert_eq!(entries[1].server_seed, Some([0; 32]));
+        assert_eq!(get_seed_commitment(), Some(entries[5].next_commitment.clone()));
+        assert_eq!(get_seed_commitment(), Some(hash_server_seed(&[5; 32])));
+
+        // Newest first, capped
+        let recent = get_seed_chain_entries(2);
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].nonce, 1_004);
+    }
+
+    #[test]
+    fn test_unsettled_games_add_no_link() {
+        // Without a commitment the first game only commits and must be retried
+        assert!(draw_committed_seed([1; 32], 10).is_err());
+        assert_eq!(get_seed_commitment(), Some(hash_server_seed(&[1; 32])));
+        assert_eq!(oldest_first(10).len(), 1);
+
+        // A rejected bet drops its draw: the same committed seed serves the next game
+        let rejected = draw_committed_seed([2; 32], 11).unwrap();
+        assert_eq!(rejected.server_seed, [1; 32]);
+        assert_eq!(oldest_first(10).len(), 1);
+        assert_eq!(get_seed_commitment(), Some(hash_server_seed(&[1; 32])));
+
+        let settled = draw_committed_seed([3; 32], 12).unwrap();
+        assert_eq!(settled.server_seed, [1; 32]);
+        settled.settle();
+        assert_eq!(oldest_first(10).len(), 2);
+        assert_eq!(get_seed_commitment(), Some(hash_server_seed(&[3; 32])));
+        assert_eq!(verify_stored_chain(10), Ok(2));
+    }
+
+    /// The roll `verify_game_result` accepts for this nonce
+    fn actual_roll(seed: [u8; 32], client: &str, nonce: u64) -> u8 {
+        (0..=MAX_NUMBER)
+            .find(|&r| verify_game_result(seed, client.to_string(), nonce, r) == Ok(true))
+            .expect("Exactly one roll verifies")
+    }
+
+    #[test]
+    fn test_batch_verification_matches_single_rolls() {
+        // A session as played: fresh seed per roll, timestamp nonces with gaps
+        let rolls: Vec<RollProof> = (0..10u8)
+            .map(|i| {
+                let server_seed = [i; 32];
+                let nonce = 1_700_000_000_000_000_000 + i as u64 * 3_250_000_017;
+                RollProof {
+                    server_seed,
+                    client_seed: "session".to_string(),
+                    nonce,
+                    rolled_number: actual_roll(server_seed, "session", nonce),
+                }
+            })
+            .collect();
+        assert_eq!(verify_game_results(rolls.clone()), Ok(vec![true; 10]));
+
+        // One tampered roll in the middle fails on its own
+        let mut tampered = rolls.clone();
+        tampered[5].rolled_number = (tampered[5].rolled_number + 1) % (MAX_NUMBER + 1);
+        let results = verify_game_results(tampered).unwrap();
+        assert_eq!(results.iter().filter(|&&ok| !ok).count(), 1);
+        assert!(!results[5]);
+
+        // A wrong nonce or seed is caught just like a wrong roll
+        let mut shifted = rolls.clone();
+        shifted[2].nonce += 1;
+        shifted[7].server_seed = [99; 32];
+        let results = verify_game_results(shifted).unwrap();
+        assert_eq!(results.iter().filter(|&&ok| !ok).count(), 2);
+
+        let too_many = vec![rolls[0].clone(); MAX_VERIFY_BATCH + 1];
+        assert!(verify_game_results(too_many).is_err());
+    }
+
+    #[test]
+    fn test_tampered_link_breaks_chain() {
+        build_chain(4);
+
+        // Swap in a different revealed seed
+        let mut entries = oldest_first(10);
+        entries[2].server_seed = Some([99; 32]);
+        assert!(verify_chain(&entries).is_err());
+
+        // Recompute the tampered link's own hash: the seed was never committed
+        let mut entries = oldest_first(10);
+        entries[2].server_seed = Some([99; 32]);
+        entries[2].hash = chain_link_hash(&entries[2].prev_hash, Some(&[99; 32]), entries[2].nonce, &entries[2].next_commitment);
+        let err = verify_chain(&entries).unwrap_err();
+        assert!(err.contains("not committed"), "{}", err);
+
+        // Swapping the commitment instead breaks the next link's extension
+        let mut entries = oldest_first(10);
+        entries[2].next_commitment = hash_server_seed(&[99; 32]);
+        entries[2].hash = chain_link_hash(&entries[2].prev_hash, entries[2].server_seed.as_ref(), entries[2].nonce, &entries[2].next_commitment);
+        let err = verify_chain(&entries).unwrap_err();
+        assert!(err.contains("does not extend"), "{}", err);
+
+        // Dropping a link is detected too
+        let mut entries = oldest_first(10);
+        entries.remove(1);
+        assert!(verify_chain(&entries).is_err());
+    }
+}
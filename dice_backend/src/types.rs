@@ -42,6 +42,16 @@ pub struct DiceGameResult {
 // Keep MinimalGameResult as alias for backward compatibility in other modules if needed
 pub type MinimalGameResult = DiceGameResult;
 
+/// One past roll to re-check with `verify_game_results`: the verification fields of its
+/// `DiceGameResult`. Every roll has its own server seed and a timestamp nonce.
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
+pub struct RollProof {
+    pub server_seed: [u8; 32],
+    pub client_seed: String,
+    pub nonce: u64,
+    pub rolled_number: u8,
+}
+
 // =============================================================================
 // MULTI-DICE GAME TYPES
 // =============================================================================
@@ -80,7 +90,7 @@ pub struct MultiDiceGameResult {
 // ICRC-2 TYPES
 // =============================================================================
 
-#[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
 pub struct Account {
     pub owner: Principal,
     pub subaccount: Option<[u8; 32]>,
@@ -140,3 +150,14 @@ pub enum TransferError {
     TemporarilyUnavailable,
     GenericError { error_code: candid::Nat, message: String },
 }
+
+/// Candid-encoded `GameConfig`, as exported by `admin_export_config`
+pub type GameConfigBlob = Vec<u8>;
+
+/// Every admin-settable parameter of this game
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct GameConfig {
+    /// Crate name of the exporting game: a blob only imports into the same game
+    pub game: String,
+    pub accounting: crate::defi_accounting::types::AccountingConfig,
+}
@@ -1,4 +1,4 @@
-use dice_backend::game::calculate_multiplier_direct;
+use dice_backend::game::{apply_multiplier, calculate_multiplier_bp, calculate_multiplier_direct, MULTIPLIER_SCALE};
 use dice_backend::types::RollDirection;
 
 // Note: Tests for calculate_max_bet() and MAX_WIN removed as we simplified
@@ -55,3 +55,31 @@ fn test_house_hit_detection() {
     assert!(rolled_win_over > target, "Player should win Over on 51 vs target 50");
     assert!(rolled_win_under < target, "Player should win Under on 49 vs target 50");
 }
+
+#[test]
+fn test_integer_payout_matches_float() {
+    for target in 1..100u8 {
+        for direction in [RollDirection::Over, RollDirection::Under] {
+            let multiplier = calculate_multiplier_direct(target, &direction);
+            let multiplier_bp = calculate_multiplier_bp(target, &direction);
+            for bet in [10_000u64, 1_000_001, 123_456_789] {
+                let float_payout = (bet as f64 * multiplier).round() as u64;
+                let int_payout = apply_multiplier(bet, multiplier_bp);
+                // Multipliers are quantized to 1e-6, so the two agree to within bet / 2e6 (+1 rounding)
+                let tolerance = bet / (2 * MULTIPLIER_SCALE) + 1;
+                assert!(
+                    int_payout.abs_diff(float_payout) <= tolerance,
+                    "target {} {:?}: int {} vs float {}", target, direction, int_payout, float_payout
+                );
+                if (100 * MULTIPLIER_SCALE).is_multiple_of(multiplier_bp) {
+                    assert_eq!(int_payout, float_payout, "Terminating multipliers must match exactly");
+                }
+            }
+        }
+    }
+
+    // Half a unit rounds up, below half rounds down
+    assert_eq!(apply_multiplier(1, 2_500_000), 3);
+    assert_eq!(apply_multiplier(1, 2_499_999), 2);
+    assert_eq!(calculate_multiplier_bp(97, &RollDirection::Over), 33_333_333);
+}
@@ -54,11 +54,7 @@ impl OperationStats {
     }
 
     pub fn average(&self) -> u64 {
-        if self.call_count == 0 {
-            0
-        } else {
-            self.total_cycles / self.call_count
-        }
+        self.total_cycles.checked_div(self.call_count).unwrap_or(0)
     }
 
     pub fn recent_average(&self) -> u64 {
@@ -197,6 +193,7 @@ pub struct BenchmarkGuard {
 }
 
 #[derive(Clone, Copy)]
+#[allow(dead_code)]
 pub enum BenchmarkOperation {
     Tick,
     StepGeneration,
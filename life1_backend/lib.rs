@@ -3,7 +3,11 @@
 //! A 512x512 toroidal grid running Conway's Game of Life at 10 generations/second
 //! with base-centric territory control.
 
+// The generation hot path passes neighbour words as scalars to stay in registers.
+#![allow(clippy::too_many_arguments)]
+
 mod benchmarks;
+mod rng;
 
 // Re-export benchmark types for candid export
 pub use benchmarks::{BenchmarkData, BenchmarkReport, CycleBreakdown, IdleBurnInfo, OperationStats};
@@ -27,6 +31,7 @@ const WORDS_PER_ROW: usize = 8; // 512 / 64
 const TOTAL_WORDS: usize = 4_096; // 512 * 8
 
 /// Chunks for territory (64x64 cells each)
+#[allow(dead_code)]
 const CHUNK_SIZE: u16 = 64;
 const CHUNKS_PER_ROW: usize = 8; // 512 / 64
 const TOTAL_CHUNKS: usize = 64; // 8 * 8
@@ -35,27 +40,65 @@ const TOTAL_CHUNKS: usize = 64; // 8 * 8
 const QUADRANT_SIZE: u16 = 128;
 const QUADRANTS_PER_ROW: usize = 4; // 512 / 128
 const TOTAL_QUADRANTS: u8 = 16; // 4 * 4
+const DEFAULT_QUADRANT_CONTROL_PERCENT: u8 = 80; // Share of a quadrant's claimed territory needed to control it
+const MIN_QUADRANT_CONTROL_PERCENT: u8 = 50; // Below a majority two players could both qualify
+const CONTESTED_MARGIN_PERCENT: u32 = 10; // Leader ahead of the runner-up by at most this much = contested
 
-/// Player limits
-const MAX_PLAYERS: usize = 8;
+/// Player limits: per-slot arrays are sized to MAX_PLAYERS; joins are capped by the admin-set player cap
+const MAX_PLAYERS: usize = 15; // One base per quadrant, and the owner cache reserves 255 for unowned
+const MIN_PLAYER_CAP: u8 = 2;
+const DEFAULT_PLAYER_CAP: u8 = 8;
+const DEFAULT_MIN_PLAYERS_TO_START: u8 = 0; // Players needed before the world starts (0: start immediately)
 
 /// Economy
 const FAUCET_AMOUNT: u64 = 1000;
-const BASE_COST: u64 = 100;
-const PLACEMENT_COST: u64 = 1;
-const SIEGE_DAMAGE: u64 = 10;  // Coins stolen per blocked birth (10x placement cost = high ROI for reaching walls)
-const MAX_PLACE_CELLS: usize = 1000;
+const DEFAULT_JOIN_COST: u64 = 100; // Moved from wallet into the new base's treasury (admin-set)
+const MAX_JOIN_COST: u64 = 10_000;
+const MAX_STARTING_GRANT: u32 = 16; // Free cells for new joiners fill at most the 4x4 core of the base
+const DEFAULT_PLACEMENT_COST: u64 = 1; // Per live cell (admin-set)
+const MAX_PLACEMENT_COST: u64 = 100;
+const DEFAULT_SIEGE_DAMAGE: u64 = 10;  // Coins stolen per blocked birth (10x placement cost = high ROI for reaching walls)
+const MAX_SIEGE_DAMAGE: u64 = 1_000;
+const DEFAULT_PLACE_CELLS_LIMIT: u32 = 1000; // Max cells per place_cells call (admin-set)
+const MAX_PLACE_CELLS_LIMIT: u32 = 4_096;
+const DEFAULT_SURGE_TIER_CELLS: u32 = 0; // Cells per price step within a surge window (0: flat pricing)
+const DEFAULT_SURGE_WINDOW_SECS: u64 = 60; // A player's surge count resets this long after their first placement
+const MAX_SURGE_WINDOW_SECS: u64 = 3_600;
+const STALE_VIEW_TOLERANCE: u64 = 2 * GENERATIONS_PER_TICK as u64; // expected_generation may lag by two ticks
+const WALL_COST: u64 = 10; // Per wall cell - permanent, so priced well above a live cell
+const MAX_PLACE_WALLS: usize = 100;
+const MAX_SOUP_DENSITY_PERCENT: u8 = 100;
+
+/// Paged state: cap on cells per `get_state_paged` response (~8 bytes each in candid)
+const MAX_STATE_PAGE_CELLS: u32 = 50_000;
 
 /// Timing
 const GENERATIONS_PER_TICK: u32 = 8;   // 8 gen/sec - matches frontend LOCAL_TICK_MS=125
 const TICK_INTERVAL_MS: u64 = 1000;
+const SLOW_TICK_INTERVAL_MS: u64 = 4000; // 2 gen/sec while the board is quiet
+const QUIET_POTENTIAL_THRESHOLD: u32 = 256; // Potential cells below this make a tick "quiet"
+const QUIET_TICKS_BEFORE_DOWNSHIFT: u32 = 10;
 const WIPE_INTERVAL_NS: u64 = 120_000_000_000; // 2 minutes
 const GRACE_PERIOD_NS: u64 = 600_000_000_000; // 10 minutes
+const TERRITORY_DECAY_AFTER_NS: u64 = 900_000_000_000; // 15 minutes without joining or placing
+const DEFAULT_TERRITORY_DECAY_RATE: u32 = 64; // Dead territory cells reclaimed per tick (admin-set)
+const MAX_TERRITORY_DECAY_RATE: u32 = 4_096;
 const IDLE_FREEZE_NS: u64 = 1_800_000_000_000; // 30 minutes - freeze if no player activity
+const DEFAULT_PLACEMENT_LOCKOUT_NS: u64 = 5_000_000_000; // Wiped quadrant rejects placement for 5s
+const MAX_PLACEMENT_LOCKOUT_NS: u64 = 60_000_000_000;
 
 /// Base dimensions
 const BASE_SIZE: u16 = 8;
 
+/// Admin
+const ADMIN_PRINCIPAL: &str = "p7336-jmpo5-pkjsf-7dqkd-ea3zu-g2ror-ctcn2-sxtuo-tjve3-ulrx7-wae";
+
+/// Grid export format: 1 version byte + 1 byte per cell (bit 7 = alive, low 7 bits = owner slot, 0x7F = unowned)
+const GRID_EXPORT_VERSION: u8 = 1;
+const GRID_EXPORT_LEN: usize = 1 + TOTAL_CELLS;
+const GRID_ALIVE_FLAG: u8 = 0x80;
+const GRID_UNOWNED: u8 = 0x7F;
+
 // =============================================================================
 // DATA STRUCTURES
 // =============================================================================
@@ -87,7 +130,7 @@ struct Base {
 #[derive(Clone, Copy)]
 enum CellFate {
     Survives,
-    Birth(usize), // new_owner
+    Birth(Option<usize>), // new_owner, None when every parent is unowned
     Death,
     StaysDead,
 }
@@ -145,7 +188,7 @@ impl BFSWorkspace {
 /// Territory changes collected during a generation for batch disconnection check
 struct TerritoryChanges {
     /// Bitmask of which players lost territory this generation
-    affected_players: u8,
+    affected_players: u16,
     /// Per-player: cells lost
     lost_cells: [ArrayVec<(u16, u16), 64>; MAX_PLAYERS],
 }
@@ -176,6 +219,38 @@ struct PersistedState {
     owner: Vec<u8>,
     #[serde(default)]
     last_activity_ns: Option<u64>,
+    #[serde(default)]
+    walls: Option<Vec<u64>>,
+    #[serde(default)]
+    placement_lockout_ns: Option<u64>,
+    #[serde(default)]
+    alive_checksum: Option<u32>,
+    #[serde(default)]
+    join_cost: Option<u64>,
+    #[serde(default)]
+    placement_cost: Option<u64>,
+    #[serde(default)]
+    siege_damage: Option<u64>,
+    #[serde(default)]
+    max_players: Option<u8>,
+    #[serde(default)]
+    territory_decay_rate: Option<u32>,
+    #[serde(default)]
+    last_active_ns: Option<Vec<u64>>,
+    #[serde(default)]
+    quadrant_control_percent: Option<u8>,
+    #[serde(default)]
+    generation_limit: Option<u64>,
+    #[serde(default)]
+    starting_grant: Option<u32>,
+    #[serde(default)]
+    place_cells_limit: Option<u32>,
+    #[serde(default)]
+    placement_surge: Option<(u32, u64)>,
+    #[serde(default)]
+    min_players_to_start: Option<u8>,
+    #[serde(default)]
+    game_started: Option<bool>,
 }
 
 // =============================================================================
@@ -219,6 +294,47 @@ pub struct GameState {
     pub slots: Vec<Option<SlotInfo>>,
     pub next_wipe_quadrant: u8,
     pub seconds_until_wipe: u64,
+    pub walls: Vec<(u16, u16)>,
+}
+
+/// An alive and/or owned cell in `get_state_paged`
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PagedCell {
+    pub x: u16,
+    pub y: u16,
+    pub alive: bool,
+    pub owner: Option<u8>,
+}
+
+/// One page of cells. `slots` and `walls` are only filled on the first page (offset 0).
+#[derive(CandidType, Deserialize, Serialize)]
+pub struct PagedState {
+    pub generation: u64,
+    pub cells: Vec<PagedCell>,
+    pub total: u32,
+    pub has_more: bool,
+    pub slots: Option<Vec<Option<SlotInfo>>>,
+    pub walls: Option<Vec<(u16, u16)>>,
+}
+
+/// Public leaderboard entry (no balances)
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct PlayerStanding {
+    pub slot: u8,
+    pub principal: Principal,
+    pub alive_cells: u32,
+    pub territory_cells: u32,
+}
+
+/// Lightweight public view for spectators and landing pages
+#[derive(CandidType, Deserialize, Serialize)]
+pub struct SpectatorView {
+    pub generation: u64,
+    pub is_running: bool,
+    /// Alive cells per 64x64 chunk, row-major (TOTAL_CHUNKS entries)
+    pub heatmap: Vec<u32>,
+    /// Players by territory, then alive cells
+    pub standings: Vec<PlayerStanding>,
 }
 
 #[derive(CandidType, Deserialize, Serialize, Clone)]
@@ -227,10 +343,54 @@ pub struct TerritoryExport {
     pub chunks: Vec<Vec<u64>>,
 }
 
+/// Territory control of one 128x128 quadrant
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct QuadrantInfo {
+    pub quadrant: u8,
+    /// Slot holding at least the control threshold (default 80%) of the claimed territory
+    pub controller: Option<u8>,
+    /// Claimed cells per player slot (MAX_PLAYERS entries)
+    pub territory: Vec<u32>,
+    /// No controller and the top two players are within CONTESTED_MARGIN_PERCENT
+    pub contested: bool,
+    /// Leader's lead over the runner-up, as a percent of claimed territory
+    pub leading_margin_percent: u8,
+}
+
+/// Lobby phase of the world
+#[derive(CandidType, Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameStatus {
+    /// Fewer than the minimum players have joined: simulation paused, placement closed
+    Waiting,
+    Active,
+    /// The generation limit was reached
+    Finished,
+}
+
 #[derive(CandidType, Deserialize, Serialize)]
 pub struct WipeInfo {
     pub next_quadrant: u8,
     pub seconds_until: u64,
+    /// Generation the board will be at when the wipe runs
+    pub generation: u64,
+}
+
+#[derive(CandidType, Deserialize, Serialize)]
+pub struct TickRateInfo {
+    pub generations_per_second: u32,
+    pub tick_interval_ms: u64,
+    pub quiet_ticks: u32,
+}
+
+/// Current coin prices (join cost, placement cost and siege damage are admin-set)
+#[derive(CandidType, Deserialize, Serialize)]
+pub struct EconomyInfo {
+    pub join_cost: u64,
+    pub placement_cost: u64,
+    pub siege_damage: u64,
+    pub wall_cost: u64,
+    pub surge_tier_cells: u32,
+    pub surge_window_secs: u64,
 }
 
 // =============================================================================
@@ -239,35 +399,72 @@ pub struct WipeInfo {
 
 thread_local! {
     // Hot path - accessed every generation
-    static ALIVE: RefCell<[u64; TOTAL_WORDS]> = RefCell::new([0u64; TOTAL_WORDS]);
-    static POTENTIAL: RefCell<[u64; TOTAL_WORDS]> = RefCell::new([0u64; TOTAL_WORDS]);
-    static NEXT_POTENTIAL: RefCell<[u64; TOTAL_WORDS]> = RefCell::new([0u64; TOTAL_WORDS]);
+    static ALIVE: RefCell<[u64; TOTAL_WORDS]> = const { RefCell::new([0u64; TOTAL_WORDS]) };
+    static POTENTIAL: RefCell<[u64; TOTAL_WORDS]> = const { RefCell::new([0u64; TOTAL_WORDS]) };
+    static NEXT_POTENTIAL: RefCell<[u64; TOTAL_WORDS]> = const { RefCell::new([0u64; TOTAL_WORDS]) };
+
+    // Wall cells - never alive, never born, untouched by wipes
+    static WALLS: RefCell<[u64; TOTAL_WORDS]> = const { RefCell::new([0u64; TOTAL_WORDS]) };
 
     // Warm path - accessed on births, place_cells
     static TERRITORY: RefCell<[PlayerTerritory; MAX_PLAYERS]> = RefCell::new(Default::default());
 
     // O(1) owner lookup cache - 255 means unowned
-    static OWNER: RefCell<[u8; TOTAL_CELLS]> = RefCell::new([255u8; TOTAL_CELLS]);
+    static OWNER: RefCell<[u8; TOTAL_CELLS]> = const { RefCell::new([255u8; TOTAL_CELLS]) };
+
+    // Claimed cells per slot in each quadrant, kept in step with OWNER
+    static QUADRANT_TERRITORY: RefCell<[[u32; MAX_PLAYERS]; TOTAL_QUADRANTS as usize]> =
+        const { RefCell::new([[0u32; MAX_PLAYERS]; TOTAL_QUADRANTS as usize]) };
 
     // Cold path - rarely accessed
-    static PLAYERS: RefCell<[Option<Principal>; MAX_PLAYERS]> = RefCell::new([None; MAX_PLAYERS]);
+    static PLAYERS: RefCell<[Option<Principal>; MAX_PLAYERS]> = const { RefCell::new([None; MAX_PLAYERS]) };
     static BASES: RefCell<[Option<Base>; MAX_PLAYERS]> = RefCell::new(Default::default());
     static WALLETS: RefCell<HashMap<Principal, u64>> = RefCell::new(HashMap::new());
-    static CELL_COUNTS: RefCell<[u32; MAX_PLAYERS]> = RefCell::new([0u32; MAX_PLAYERS]);
-    static ZERO_CELLS_SINCE: RefCell<[Option<u64>; MAX_PLAYERS]> = RefCell::new([None; MAX_PLAYERS]);
+    static CELL_COUNTS: RefCell<[u32; MAX_PLAYERS]> = const { RefCell::new([0u32; MAX_PLAYERS]) };
+    static ZERO_CELLS_SINCE: RefCell<[Option<u64>; MAX_PLAYERS]> = const { RefCell::new([None; MAX_PLAYERS]) };
+    // Last join or placement per slot, for territory decay
+    static LAST_ACTIVE_NS: RefCell<[u64; MAX_PLAYERS]> = const { RefCell::new([0u64; MAX_PLAYERS]) };
 
     // Game state
-    static GENERATION: RefCell<u64> = RefCell::new(0);
-    static IS_RUNNING: RefCell<bool> = RefCell::new(true);
-    static NEXT_WIPE_QUADRANT: RefCell<u8> = RefCell::new(0);
-    static LAST_WIPE_NS: RefCell<u64> = RefCell::new(0);
-    static LAST_ACTIVITY_NS: RefCell<u64> = RefCell::new(0);
+    static GENERATION: RefCell<u64> = const { RefCell::new(0) };
+    static IS_RUNNING: RefCell<bool> = const { RefCell::new(true) };
+    static NEXT_WIPE_QUADRANT: RefCell<u8> = const { RefCell::new(0) };
+    static LAST_WIPE_NS: RefCell<u64> = const { RefCell::new(0) };
+    static LAST_ACTIVITY_NS: RefCell<u64> = const { RefCell::new(0) };
+    static PLACEMENT_LOCKOUT_NS: RefCell<u64> = const { RefCell::new(DEFAULT_PLACEMENT_LOCKOUT_NS) };
+    static JOIN_COST: RefCell<u64> = const { RefCell::new(DEFAULT_JOIN_COST) };
+    static STARTING_GRANT: RefCell<u32> = const { RefCell::new(0) };
+    static PLACEMENT_COST: RefCell<u64> = const { RefCell::new(DEFAULT_PLACEMENT_COST) };
+    static PLACE_CELLS_LIMIT: RefCell<u32> = const { RefCell::new(DEFAULT_PLACE_CELLS_LIMIT) };
+    static SURGE_TIER_CELLS: RefCell<u32> = const { RefCell::new(DEFAULT_SURGE_TIER_CELLS) };
+    static SURGE_WINDOW_SECS: RefCell<u64> = const { RefCell::new(DEFAULT_SURGE_WINDOW_SECS) };
+    // (window start, cells placed) per principal (not persisted: windows are short)
+    static SURGE_WINDOWS: RefCell<HashMap<Principal, (u64, u32)>> = RefCell::new(HashMap::new());
+    static SIEGE_DAMAGE: RefCell<u64> = const { RefCell::new(DEFAULT_SIEGE_DAMAGE) };
+    static PLAYER_CAP: RefCell<u8> = const { RefCell::new(DEFAULT_PLAYER_CAP) };
+    static MIN_PLAYERS_TO_START: RefCell<u8> = const { RefCell::new(DEFAULT_MIN_PLAYERS_TO_START) };
+    // Latched once the lobby fills; players leaving later does not pause the world again
+    static GAME_STARTED: RefCell<bool> = const { RefCell::new(false) };
+    static TERRITORY_DECAY_RATE: RefCell<u32> = const { RefCell::new(DEFAULT_TERRITORY_DECAY_RATE) };
+    static QUADRANT_CONTROL_PERCENT: RefCell<u8> = const { RefCell::new(DEFAULT_QUADRANT_CONTROL_PERCENT) };
+    // The world finishes at this generation (None: runs forever)
+    static GENERATION_LIMIT: RefCell<Option<u64>> = const { RefCell::new(None) };
 
     // BFS workspace (pre-allocated)
     static BFS_WORKSPACE: RefCell<BFSWorkspace> = RefCell::new(BFSWorkspace::new());
 
     // Timer ID
-    static TIMER_ID: RefCell<Option<TimerId>> = RefCell::new(None);
+    static TIMER_ID: RefCell<Option<TimerId>> = const { RefCell::new(None) };
+
+    // One-shot timer that keeps the wipe rotation going while the board is frozen
+    static FROZEN_WIPE_TIMER_ID: RefCell<Option<TimerId>> = const { RefCell::new(None) };
+
+    // When `generation` was last materialized (not persisted: start_timer resets it)
+    static LAST_TICK_NS: RefCell<u64> = const { RefCell::new(0) };
+
+    // Adaptive tick rate (not persisted: restarts at full speed after upgrade)
+    static SLOW_MODE: RefCell<bool> = const { RefCell::new(false) };
+    static QUIET_TICKS: RefCell<u32> = const { RefCell::new(0) };
 }
 
 // =============================================================================
@@ -325,6 +522,20 @@ fn clear_alive_idx(idx: usize) {
     })
 }
 
+fn is_wall(x: u16, y: u16) -> bool {
+    WALLS.with(|walls| {
+        let idx = coords_to_idx(x, y);
+        (walls.borrow()[idx >> 6] >> (idx & 63)) & 1 == 1
+    })
+}
+
+fn set_wall(x: u16, y: u16) {
+    WALLS.with(|walls| {
+        let idx = coords_to_idx(x, y);
+        walls.borrow_mut()[idx >> 6] |= 1u64 << (idx & 63);
+    })
+}
+
 fn set_potential_bit(idx: usize) {
     NEXT_POTENTIAL.with(|np| {
         let mut np = np.borrow_mut();
@@ -423,14 +634,70 @@ fn quadrant_bounds(q: u8) -> (u16, u16, u16, u16) {
     (qx * QUADRANT_SIZE, qy * QUADRANT_SIZE, QUADRANT_SIZE, QUADRANT_SIZE)
 }
 
+/// Claimed cells per player slot within a quadrant (cached, no scan)
+fn quadrant_territory(q: u8) -> [u32; MAX_PLAYERS] {
+    QUADRANT_TERRITORY.with(|qt| qt.borrow()[q as usize])
+}
+
+/// Recompute the quadrant territory cache from OWNER (after bulk OWNER writes)
+fn rebuild_quadrant_territory() {
+    let counts: Vec<[u32; MAX_PLAYERS]> = (0..TOTAL_QUADRANTS).map(scan_quadrant_territory).collect();
+    QUADRANT_TERRITORY.with(|qt| {
+        let mut qt = qt.borrow_mut();
+        for (q, c) in counts.into_iter().enumerate() {
+            qt[q] = c;
+        }
+    });
+}
+
+/// Claimed cells per player slot within a quadrant (scans the owner cache)
+fn scan_quadrant_territory(q: u8) -> [u32; MAX_PLAYERS] {
+    let (x_start, y_start, width, height) = quadrant_bounds(q);
+    let mut counts = [0u32; MAX_PLAYERS];
+    OWNER.with(|o| {
+        let o = o.borrow();
+        for y in y_start..y_start + height {
+            let row = coords_to_idx(x_start, y);
+            for &owner in &o[row..row + width as usize] {
+                if (owner as usize) < MAX_PLAYERS {
+                    counts[owner as usize] += 1;
+                }
+            }
+        }
+    });
+    counts
+}
+
+/// Derive (controller, contested, leading margin %) from per-slot quadrant territory
+fn quadrant_control(territory: &[u32]) -> (Option<u8>, bool, u8) {
+    let total: u64 = territory.iter().map(|&c| c as u64).sum();
+    if total == 0 {
+        return (None, false, 0);
+    }
+    let (leader, &top) = territory.iter().enumerate()
+        .max_by_key(|&(slot, &cells)| (cells, std::cmp::Reverse(slot)))
+        .expect("territory has MAX_PLAYERS entries");
+    let second = territory.iter().enumerate()
+        .filter(|&(slot, _)| slot != leader)
+        .map(|(_, &cells)| cells)
+        .max()
+        .unwrap_or(0);
+
+    let margin_percent = ((top - second) as u64 * 100 / total) as u8;
+    let threshold = QUADRANT_CONTROL_PERCENT.with(|t| *t.borrow());
+    if top as u64 * 100 >= total * threshold as u64 {
+        return (Some(leader as u8), false, margin_percent);
+    }
+    let contested = second > 0 && margin_percent as u32 <= CONTESTED_MARGIN_PERCENT;
+    (None, contested, margin_percent)
+}
+
 fn quadrant_has_base(q: u8) -> bool {
     BASES.with(|bases| {
         let bases = bases.borrow();
-        for base_opt in bases.iter() {
-            if let Some(base) = base_opt {
-                if get_quadrant(base.x, base.y) == q {
-                    return true;
-                }
+        for base in bases.iter().flatten() {
+            if get_quadrant(base.x, base.y) == q {
+                return true;
             }
         }
         false
@@ -510,6 +777,25 @@ fn player_owns(player: usize, x: u16, y: u16) -> bool {
     })
 }
 
+/// Write an OWNER entry and keep the quadrant territory cache in step
+fn set_owner(idx: usize, owner: u8) {
+    let previous = OWNER.with(|o| std::mem::replace(&mut o.borrow_mut()[idx], owner));
+    if previous == owner {
+        return;
+    }
+    let (x, y) = idx_to_coords(idx);
+    let q = get_quadrant(x, y) as usize;
+    QUADRANT_TERRITORY.with(|qt| {
+        let counts = &mut qt.borrow_mut()[q];
+        if (previous as usize) < MAX_PLAYERS {
+            counts[previous as usize] = counts[previous as usize].saturating_sub(1);
+        }
+        if (owner as usize) < MAX_PLAYERS {
+            counts[owner as usize] += 1;
+        }
+    });
+}
+
 fn find_owner(x: u16, y: u16) -> Option<usize> {
     benchmark!(FindOwner);
     let idx = coords_to_idx(x, y);
@@ -544,10 +830,7 @@ fn set_territory(player: usize, x: u16, y: u16) {
     });
 
     // Update OWNER cache
-    OWNER.with(|o| {
-        let idx = coords_to_idx(x, y);
-        o.borrow_mut()[idx] = player as u8;
-    });
+    set_owner(coords_to_idx(x, y), player as u8);
 }
 
 fn clear_territory(player: usize, x: u16, y: u16) {
@@ -581,12 +864,16 @@ fn clear_territory(player: usize, x: u16, y: u16) {
     });
 
     // Update OWNER cache
-    OWNER.with(|o| {
-        let idx = coords_to_idx(x, y);
-        o.borrow_mut()[idx] = 255;
-    });
+    set_owner(coords_to_idx(x, y), 255);
+}
+
+/// Claimed cells for a player slot, summed from the quadrant cache (no scan)
+fn territory_cells(player: usize) -> u32 {
+    QUADRANT_TERRITORY.with(|qt| qt.borrow().iter().map(|q| q[player]).sum())
 }
 
+/// Claimed cells for a player slot, counted from the territory bitmaps
+#[cfg(test)]
 fn count_territory_cells(player: usize) -> u32 {
     TERRITORY.with(|territory| {
         let territory = territory.borrow();
@@ -612,7 +899,7 @@ fn step_generation() {
     let (mut births, mut deaths, mut survivors) = {
         benchmark!(VecAllocation);
         (
-            Vec::<(usize, usize)>::with_capacity(500),
+            Vec::<(usize, Option<usize>)>::with_capacity(500),
             Vec::<usize>::with_capacity(500),
             Vec::<usize>::with_capacity(15000),
         )
@@ -645,7 +932,7 @@ fn step_generation() {
 }
 
 fn compute_fates_into(
-    births: &mut Vec<(usize, usize)>,
+    births: &mut Vec<(usize, Option<usize>)>,
     deaths: &mut Vec<usize>,
     survivors: &mut Vec<usize>,
 ) {
@@ -665,6 +952,12 @@ fn compute_fates_into(
                     continue;
                 }
 
+                // Walls are permanent obstacles: never evaluated, never born
+                potential_word &= !WALLS.with(|w| w.borrow()[word_idx]);
+                if potential_word == 0 {
+                    continue;
+                }
+
                 let row = word_idx / WORDS_PER_ROW;
                 let row_above = if row > 0 { word_idx - WORDS_PER_ROW } else { word_idx + TOTAL_WORDS - WORDS_PER_ROW };
                 let row_below = if row < GRID_SIZE as usize - 1 { word_idx + WORDS_PER_ROW } else { word_idx - TOTAL_WORDS + WORDS_PER_ROW };
@@ -749,24 +1042,24 @@ fn extract_neighbor_bits(
     if bit_pos == 0 {
         (
             ((left_above >> 63) & 1) as u8,
-            ((above >> 0) & 1) as u8,
+            (above & 1) as u8,
             ((above >> 1) & 1) as u8,
             ((left_same >> 63) & 1) as u8,
             ((same >> 1) & 1) as u8,
             ((left_below >> 63) & 1) as u8,
-            ((below >> 0) & 1) as u8,
+            (below & 1) as u8,
             ((below >> 1) & 1) as u8,
         )
     } else if bit_pos == 63 {
         (
             ((above >> 62) & 1) as u8,
             ((above >> 63) & 1) as u8,
-            ((right_above >> 0) & 1) as u8,
+            (right_above & 1) as u8,
             ((same >> 62) & 1) as u8,
-            ((right_same >> 0) & 1) as u8,
+            (right_same & 1) as u8,
             ((below >> 62) & 1) as u8,
             ((below >> 63) & 1) as u8,
-            ((right_below >> 0) & 1) as u8,
+            (right_below & 1) as u8,
         )
     } else {
         (
@@ -821,7 +1114,7 @@ fn find_birth_owner(
     x: u16, y: u16,
     nw: u8, n: u8, ne: u8, w: u8, e: u8, sw: u8, s: u8, se: u8,
     cell_idx: usize,
-) -> usize {
+) -> Option<usize> {
     let mut owner_counts = [0u8; MAX_PLAYERS];
     let mut neutral_count = 0u8;
 
@@ -856,11 +1149,11 @@ fn find_birth_owner(
         for (alive, (nx, ny)) in neighbors {
             if alive == 1 {
                 if let Some(owner) = find_owner(nx, ny) {
-                    return owner;
+                    return Some(owner);
                 }
             }
         }
-        return 0; // Fallback
+        return None; // Only unowned parents (e.g. seeded soup): the child stays unowned
     }
 
     // Find candidates with max count
@@ -872,16 +1165,16 @@ fn find_birth_owner(
     }
 
     if candidates.len() == 1 {
-        candidates[0]
+        Some(candidates[0])
     } else if candidates.is_empty() {
-        0 // Neutral birth, shouldn't happen with alive parents
+        None // Neutral birth, shouldn't happen with alive parents
     } else {
         // Tie-break using cell index
-        candidates[cell_idx % candidates.len()]
+        Some(candidates[cell_idx % candidates.len()])
     }
 }
 
-fn apply_changes(births: &[(usize, usize)], deaths: &[usize], survivors: &[usize]) {
+fn apply_changes(births: &[(usize, Option<usize>)], deaths: &[usize], survivors: &[usize]) {
     // Clear NEXT_POTENTIAL
     NEXT_POTENTIAL.with(|np| {
         np.borrow_mut().fill(0);
@@ -930,6 +1223,10 @@ fn apply_changes(births: &[(usize, usize)], deaths: &[usize], survivors: &[usize
             in_protection_zone(x, y)
         };
         if let Some(base_owner) = base_owner_opt {
+            // Unowned births never enter a base, and have no wallet to siege for
+            let Some(new_owner) = new_owner else {
+                continue;
+            };
             if base_owner != new_owner {
                 // SIEGE! Birth prevented, transfer coins (capped at what defender has)
                 let mut eliminated = false;
@@ -938,8 +1235,8 @@ fn apply_changes(births: &[(usize, usize)], deaths: &[usize], survivors: &[usize
                     let mut bases = bases.borrow_mut();
                     if let Some(base) = &mut bases[base_owner] {
                         if base.coins > 0 {
-                            // Take up to SIEGE_DAMAGE, but not more than defender has
-                            let damage = base.coins.min(SIEGE_DAMAGE);
+                            // Take up to the siege damage, but not more than defender has
+                            let damage = base.coins.min(SIEGE_DAMAGE.with(|d| *d.borrow()));
                             base.coins -= damage;
 
                             // Transfer coins to attacker's wallet
@@ -969,7 +1266,7 @@ fn apply_changes(births: &[(usize, usize)], deaths: &[usize], survivors: &[usize
 
         // Check if territory changes (for disconnection check)
         if let Some(old_owner) = find_owner(x, y) {
-            if old_owner != new_owner {
+            if Some(old_owner) != new_owner {
                 territory_changes.affected_players |= 1 << old_owner;
                 if territory_changes.lost_cells[old_owner].len() < 64 {
                     territory_changes.lost_cells[old_owner].push((x, y));
@@ -980,17 +1277,19 @@ fn apply_changes(births: &[(usize, usize)], deaths: &[usize], survivors: &[usize
 
         // Normal birth
         set_alive_idx(cell_idx);
-        set_territory(new_owner, x, y);
+        if let Some(new_owner) = new_owner {
+            set_territory(new_owner, x, y);
 
-        // Update cell count
-        CELL_COUNTS.with(|cc| {
-            cc.borrow_mut()[new_owner] += 1;
-        });
+            // Update cell count
+            CELL_COUNTS.with(|cc| {
+                cc.borrow_mut()[new_owner] += 1;
+            });
 
-        // Clear grace period if we had 0 cells
-        ZERO_CELLS_SINCE.with(|zcs| {
-            zcs.borrow_mut()[new_owner] = None;
-        });
+            // Clear grace period if we had 0 cells
+            ZERO_CELLS_SINCE.with(|zcs| {
+                zcs.borrow_mut()[new_owner] = None;
+            });
+        }
 
         mark_with_neighbors_potential(cell_idx);
     }
@@ -1212,6 +1511,8 @@ fn apply_disconnection(player: usize, disconnected: &[(u16, u16)]) {
 // GAME MECHANICS
 // =============================================================================
 
+/// Free a slot. Whatever is left in the base treasury (the join cost plus placement
+/// fees) is forfeited, never refunded: holding slots with alt principals costs coins.
 fn eliminate_player(player: usize) {
     // 1. Kill ALL player's alive cells AND clear OWNER entries
     //    (iterate via territory bitmap, do both in single pass)
@@ -1230,8 +1531,8 @@ fn eliminate_player(player: usize) {
             let chunk_base_x = (chunk_idx % CHUNKS_PER_ROW) * 64;
             let chunk_base_y = (chunk_idx / CHUNKS_PER_ROW) * 64;
 
-            for local_y in 0..64 {
-                let mut word = chunk[local_y];
+            for (local_y, &row) in chunk.iter().enumerate() {
+                let mut word = row;
                 while word != 0 {
                     let local_x = word.trailing_zeros() as usize;
                     word &= word - 1;
@@ -1247,9 +1548,7 @@ fn eliminate_player(player: usize) {
                     }
 
                     // Clear OWNER entry (MUST happen before territory reset)
-                    OWNER.with(|o| {
-                        o.borrow_mut()[idx] = 255;
-                    });
+                    set_owner(idx, 255);
                 }
             }
 
@@ -1333,8 +1632,7 @@ fn wipe_quadrant(quadrant: u8) {
     });
 }
 
-fn run_wipe_if_needed() {
-    let now = ic_cdk::api::time();
+fn run_wipe_if_needed(now: u64) {
     let last_wipe = LAST_WIPE_NS.with(|lw| *lw.borrow());
 
     if now - last_wipe >= WIPE_INTERVAL_NS {
@@ -1345,14 +1643,60 @@ fn run_wipe_if_needed() {
             *q.borrow_mut() = (quadrant + 1) % TOTAL_QUADRANTS;
         });
         LAST_WIPE_NS.with(|lw| {
-            *lw.borrow_mut() = now;
+            *lw.borrow_mut() = next_wipe_anchor(last_wipe, now);
         });
     }
 }
 
-fn check_grace_periods() {
-    let now = ic_cdk::api::time();
+/// The quadrant wiped most recently (the rotation has already advanced past it)
+fn last_wiped_quadrant() -> u8 {
+    NEXT_WIPE_QUADRANT.with(|q| (*q.borrow() + TOTAL_QUADRANTS - 1) % TOTAL_QUADRANTS)
+}
 
+/// Reject placement into the quadrant that was just wiped until the lockout window has
+/// passed, so the board gets a short settling period instead of immediate re-spam.
+/// The window is measured from `LAST_WIPE_NS` (the scheduled wipe time).
+fn check_placement_lockout(cells: &[(i32, i32)], now: u64) -> Result<(), String> {
+    let lockout = PLACEMENT_LOCKOUT_NS.with(|l| *l.borrow());
+    let elapsed = now.saturating_sub(LAST_WIPE_NS.with(|lw| *lw.borrow()));
+    let remaining = lockout.saturating_sub(elapsed);
+    if remaining == 0 {
+        return Ok(());
+    }
+
+    let quadrant = last_wiped_quadrant();
+    if cells.iter().any(|&(x, y)| get_quadrant(x as u16, y as u16) == quadrant) {
+        return Err(format!(
+            "Quadrant {} was just wiped: placement reopens in {}s",
+            quadrant,
+            remaining.div_ceil(1_000_000_000)
+        ));
+    }
+    Ok(())
+}
+
+/// When the next wipe will actually run, and the generation the board will be at then.
+/// The schedule is wall-clock (due `WIPE_INTERVAL_NS` after the last anchor), but a ticking
+/// board only checks at ticks, so the wipe lands on the first tick at or after it is due,
+/// assuming the current rate holds. A frozen board wipes exactly when due, at its current
+/// generation.
+fn predict_next_wipe(last_wipe: u64, last_tick_ns: u64, generation: u64, tick_interval_ms: u64, ticking: bool) -> (u64, u64) {
+    let due = last_wipe + WIPE_INTERVAL_NS;
+    if !ticking {
+        return (due, generation);
+    }
+    let interval_ns = tick_interval_ms * 1_000_000;
+    let ticks = due.saturating_sub(last_tick_ns).div_ceil(interval_ns).max(1);
+    (last_tick_ns + ticks * interval_ns, generation + ticks * GENERATIONS_PER_TICK as u64)
+}
+
+/// Keep wipes on a fixed wall-clock schedule: ticks may land late (especially in slow
+/// mode), so anchor to the interval boundary rather than to `now`.
+fn next_wipe_anchor(last_wipe: u64, now: u64) -> u64 {
+    now - (now - last_wipe) % WIPE_INTERVAL_NS
+}
+
+fn check_grace_periods(now: u64) {
     for player in 0..MAX_PLAYERS {
         let zero_since = ZERO_CELLS_SINCE.with(|zcs| zcs.borrow()[player]);
 
@@ -1367,60 +1711,373 @@ fn check_grace_periods() {
     }
 }
 
+/// Clear up to the decay rate of dead territory cells from each player who has not joined
+/// or placed for TERRITORY_DECAY_AFTER_NS. The base footprint and cells still alive keep
+/// their owner, and dead cells that connect live cells to the base are kept too, so decay
+/// never kills anything, never eliminates a player by itself and never disconnects territory.
+fn decay_inactive_territory(now: u64) {
+    let rate = TERRITORY_DECAY_RATE.with(|r| *r.borrow()) as usize;
+    if rate == 0 {
+        return;
+    }
+
+    for player in 0..MAX_PLAYERS {
+        let Some(base) = BASES.with(|bases| bases.borrow()[player].clone()) else {
+            continue;
+        };
+        let last_active = LAST_ACTIVE_NS.with(|la| la.borrow()[player]);
+        if now.saturating_sub(last_active) < TERRITORY_DECAY_AFTER_NS {
+            continue;
+        }
+
+        // Collect first: clear_territory mutates the bitmap being walked
+        for (x, y) in reclaimable_territory(player, &base, rate) {
+            clear_territory(player, x, y);
+        }
+    }
+}
+
+/// Up to `limit` dead, non-base territory cells that can be cleared without cutting any
+/// live cell off from the base. Territory already unreachable from the base goes first,
+/// then cells farthest from the base along a BFS tree, skipping every dead cell on the
+/// tree path of a live cell. Clearing deepest-first only ever removes leaves of the
+/// remaining tree, so what is left stays orthogonally connected.
+fn reclaimable_territory(player: usize, base: &Base, limit: usize) -> Vec<(u16, u16)> {
+    let mut reclaim: Vec<(u16, u16)> = Vec::with_capacity(limit);
+    let mut tree: Vec<(u16, u16)> = Vec::new();
+    let mut parent: Vec<usize> = Vec::new();
+
+    BFS_WORKSPACE.with(|ws| {
+        let mut ws = ws.borrow_mut();
+        ws.clear();
+
+        // BFS from the base over the player's territory, remembering each cell's parent
+        for dy in 0..BASE_SIZE {
+            for dx in 0..BASE_SIZE {
+                let x = base.x.wrapping_add(dx) & 511;
+                let y = base.y.wrapping_add(dy) & 511;
+                if player_owns(player, x, y) && !ws.mark_visited(x, y) {
+                    tree.push((x, y));
+                    parent.push(usize::MAX);
+                }
+            }
+        }
+        let mut i = 0;
+        while i < tree.len() {
+            let (x, y) = tree[i];
+            for (nx, ny) in orthogonal_neighbors(x, y) {
+                if !ws.is_visited(nx, ny) && player_owns(player, nx, ny) {
+                    ws.mark_visited(nx, ny);
+                    tree.push((nx, ny));
+                    parent.push(i);
+                }
+            }
+            i += 1;
+        }
+
+        // Dead territory the BFS never reached is already cut off from the base
+        TERRITORY.with(|territory| {
+            let territory = territory.borrow();
+            let pt = &territory[player];
+            let mut chunk_idx_iter = pt.chunk_mask;
+            let mut vec_idx = 0;
+
+            'chunks: while chunk_idx_iter != 0 {
+                let chunk_idx = chunk_idx_iter.trailing_zeros() as usize;
+                chunk_idx_iter &= chunk_idx_iter - 1;
+
+                let chunk_base_x = (chunk_idx % CHUNKS_PER_ROW) * 64;
+                let chunk_base_y = (chunk_idx / CHUNKS_PER_ROW) * 64;
+                for (local_y, &row) in pt.chunks[vec_idx].iter().enumerate() {
+                    let mut word = row;
+                    while word != 0 {
+                        let local_x = word.trailing_zeros() as usize;
+                        word &= word - 1;
+
+                        let x = (chunk_base_x + local_x) as u16;
+                        let y = (chunk_base_y + local_y) as u16;
+                        if !ws.is_visited(x, y) && !is_in_base(base, x, y) && !is_alive(x, y) {
+                            reclaim.push((x, y));
+                            if reclaim.len() == limit {
+                                break 'chunks;
+                            }
+                        }
+                    }
+                }
+                vec_idx += 1;
+            }
+        });
+    });
+
+    // Every dead cell on a live cell's path to the base must stay
+    let mut needed = vec![false; tree.len()];
+    for (i, &(x, y)) in tree.iter().enumerate() {
+        if is_alive(x, y) {
+            let mut p = parent[i];
+            while p != usize::MAX && !needed[p] {
+                needed[p] = true;
+                p = parent[p];
+            }
+        }
+    }
+
+    let remaining = limit - reclaim.len();
+    reclaim.extend(
+        tree.iter()
+            .enumerate()
+            .rev()
+            .filter(|&(i, &(x, y))| !needed[i] && !is_alive(x, y) && !is_in_base(base, x, y))
+            .map(|(_, &cell)| cell)
+            .take(remaining),
+    );
+    reclaim
+}
+
 // =============================================================================
 // TICK ORCHESTRATION
 // =============================================================================
 
+/// What the interval timer should do after a tick
+#[derive(Debug, PartialEq)]
+enum TickOutcome {
+    Keep,
+    RateChanged,
+    /// Board is empty: nothing to simulate or wipe
+    Stop,
+    /// No player activity: stop simulating but keep the wipe rotation going
+    Freeze,
+}
+
 fn tick() {
     benchmark!(Tick);
 
+    let now = ic_cdk::api::time();
+    match tick_at(now) {
+        TickOutcome::Keep => {}
+        TickOutcome::RateChanged => restart_timer(),
+        TickOutcome::Stop => stop_timer(),
+        TickOutcome::Freeze => {
+            stop_timer();
+            schedule_frozen_wipe(now);
+        }
+    }
+}
+
+fn tick_at(now: u64) -> TickOutcome {
     let running = IS_RUNNING.with(|r| *r.borrow());
-    if !running {
-        return;
+    if !running || is_waiting() {
+        return TickOutcome::Keep;
+    }
+
+    let generations = tick_generations();
+    if generations == 0 {
+        return TickOutcome::Stop;
     }
 
     // OPTIMIZATION: Check if there are any alive cells or potential cells
     // If not, skip expensive generation computation entirely
-    let has_activity = POTENTIAL.with(|p| {
-        p.borrow().iter().any(|&w| w != 0)
+    let potential_population: u32 = POTENTIAL.with(|p| {
+        p.borrow().iter().map(|w| w.count_ones()).sum()
     });
+    let has_activity = potential_population > 0;
 
     if has_activity {
-        // Run 10 generations
-        for _ in 0..GENERATIONS_PER_TICK {
+        // Run a tick's worth of generations
+        for _ in 0..generations {
             step_generation();
         }
     } else {
         // Just increment generation counter (no computation needed)
         GENERATION.with(|gen| {
-            *gen.borrow_mut() += GENERATIONS_PER_TICK as u64;
+            *gen.borrow_mut() += generations as u64;
         });
     }
+    LAST_TICK_NS.with(|t| *t.borrow_mut() = now);
+
+    // The final generation is kept as-is: no wipes, decay or eliminations after it
+    if is_finished() {
+        return TickOutcome::Stop;
+    }
 
     // Check quadrant wipe timer (still needed even when idle)
-    run_wipe_if_needed();
+    run_wipe_if_needed(now);
 
     // Check grace periods
-    check_grace_periods();
+    check_grace_periods(now);
+
+    // Reclaim dead territory of players who stopped playing
+    decay_inactive_territory(now);
 
     // Stop timer if board is completely empty (saves cycles)
-    let board_empty = ALIVE.with(|a| a.borrow().iter().all(|&w| w == 0));
-    if board_empty {
-        stop_timer();
-        return;
+    if is_board_empty() {
+        return TickOutcome::Stop;
     }
 
     // Freeze if no player activity for 30 minutes (saves cycles on straggler gliders)
     let last_activity = LAST_ACTIVITY_NS.with(|la| *la.borrow());
-    let idle_time = ic_cdk::api::time().saturating_sub(last_activity);
+    let idle_time = now.saturating_sub(last_activity);
     if idle_time >= IDLE_FREEZE_NS {
-        stop_timer();
+        return TickOutcome::Freeze;
+    }
+
+    if update_tick_rate(potential_population) {
+        TickOutcome::RateChanged
+    } else {
+        TickOutcome::Keep
+    }
+}
+
+/// Generations this tick may run: GENERATIONS_PER_TICK, fewer (or none) at the generation limit
+fn tick_generations() -> u32 {
+    let generation = GENERATION.with(|g| *g.borrow());
+    match GENERATION_LIMIT.with(|l| *l.borrow()) {
+        Some(limit) => limit.saturating_sub(generation).min(GENERATIONS_PER_TICK as u64) as u32,
+        None => GENERATIONS_PER_TICK,
+    }
+}
+
+/// The world reached its generation limit
+fn is_finished() -> bool {
+    GENERATION_LIMIT.with(|l| *l.borrow())
+        .is_some_and(|limit| GENERATION.with(|g| *g.borrow()) >= limit)
+}
+
+fn player_count() -> usize {
+    PLAYERS.with(|players| players.borrow().iter().flatten().count())
+}
+
+/// The world is in its lobby: not enough players have joined yet
+fn is_waiting() -> bool {
+    !GAME_STARTED.with(|s| *s.borrow())
+        && player_count() < MIN_PLAYERS_TO_START.with(|m| *m.borrow()) as usize
+}
+
+/// Leave the lobby once enough players have joined. The clocks restart at `now`, so the
+/// simulation and wipe schedule begin from this moment instead of catching up on the wait.
+/// Returns true if the world started.
+fn start_game_if_ready(now: u64) -> bool {
+    if GAME_STARTED.with(|s| *s.borrow()) || is_waiting() {
+        return false;
+    }
+    GAME_STARTED.with(|s| *s.borrow_mut() = true);
+    if MIN_PLAYERS_TO_START.with(|m| *m.borrow()) > 0 {
+        LAST_TICK_NS.with(|t| *t.borrow_mut() = now);
+        LAST_WIPE_NS.with(|lw| *lw.borrow_mut() = now);
+    }
+    true
+}
+
+fn game_status() -> GameStatus {
+    if is_finished() {
+        GameStatus::Finished
+    } else if is_waiting() {
+        GameStatus::Waiting
+    } else {
+        GameStatus::Active
+    }
+}
+
+/// Reject player actions once the world has finished (queries keep working)
+fn check_not_finished() -> Result<(), String> {
+    if is_finished() {
+        return Err(format!(
+            "Game over: the world finished at generation {}",
+            GENERATION.with(|g| *g.borrow())
+        ));
+    }
+    Ok(())
+}
+
+/// Reject placement while the lobby is still filling up
+fn check_not_waiting() -> Result<(), String> {
+    if is_waiting() {
+        return Err(format!(
+            "Waiting for players: {}/{} joined",
+            player_count(),
+            MIN_PLAYERS_TO_START.with(|m| *m.borrow())
+        ));
+    }
+    Ok(())
+}
+
+fn is_board_empty() -> bool {
+    ALIVE.with(|a| a.borrow().iter().all(|&w| w == 0))
+}
+
+/// Sleep until the next scheduled wipe. Re-armed after each frozen wipe until the wipes
+/// have emptied the board; activity restarting the tick timer cancels it.
+fn schedule_frozen_wipe(now: u64) {
+    let last_wipe = LAST_WIPE_NS.with(|lw| *lw.borrow());
+    let delay_ns = (last_wipe + WIPE_INTERVAL_NS).saturating_sub(now);
+    let timer_id = ic_cdk_timers::set_timer(Duration::from_nanos(delay_ns), async {
+        FROZEN_WIPE_TIMER_ID.with(|t| *t.borrow_mut() = None);
+        let now = ic_cdk::api::time();
+        if run_frozen_wipe(now) {
+            schedule_frozen_wipe(now);
+        }
+    });
+    FROZEN_WIPE_TIMER_ID.with(|t| *t.borrow_mut() = Some(timer_id));
+}
+
+/// Run a wipe that fell due while the board was frozen, so quadrant rotation does not stall.
+/// Returns true while live cells remain for a later wipe to clear.
+fn run_frozen_wipe(now: u64) -> bool {
+    if is_timer_running() || !IS_RUNNING.with(|r| *r.borrow()) || is_finished() {
+        return false;
+    }
+    run_wipe_if_needed(now);
+    !is_board_empty()
+}
+
+/// Track quiet ticks and switch between fast and slow mode.
+/// Returns true if the rate changed (caller must restart the timer).
+fn update_tick_rate(potential_population: u32) -> bool {
+    let slow = SLOW_MODE.with(|m| *m.borrow());
+
+    if potential_population >= QUIET_POTENTIAL_THRESHOLD {
+        QUIET_TICKS.with(|q| *q.borrow_mut() = 0);
+        if slow {
+            SLOW_MODE.with(|m| *m.borrow_mut() = false);
+            return true;
+        }
+        return false;
+    }
+
+    let quiet = QUIET_TICKS.with(|q| {
+        let mut q = q.borrow_mut();
+        *q = q.saturating_add(1);
+        *q
+    });
+    if !slow && quiet >= QUIET_TICKS_BEFORE_DOWNSHIFT {
+        SLOW_MODE.with(|m| *m.borrow_mut() = true);
+        return true;
+    }
+    false
+}
+
+/// Player placed cells: return to full speed immediately.
+/// Returns true if the rate changed.
+fn upshift_on_activity() -> bool {
+    QUIET_TICKS.with(|q| *q.borrow_mut() = 0);
+    SLOW_MODE.with(|m| std::mem::replace(&mut *m.borrow_mut(), false))
+}
+
+fn current_tick_interval_ms() -> u64 {
+    if SLOW_MODE.with(|m| *m.borrow()) {
+        SLOW_TICK_INTERVAL_MS
+    } else {
+        TICK_INTERVAL_MS
     }
 }
 
 fn start_timer() {
+    // The tick runs wipes itself
+    if let Some(id) = FROZEN_WIPE_TIMER_ID.with(|t| t.borrow_mut().take()) {
+        ic_cdk_timers::clear_timer(id);
+    }
+    LAST_TICK_NS.with(|t| *t.borrow_mut() = ic_cdk::api::time());
     let timer_id = ic_cdk_timers::set_timer_interval(
-        Duration::from_millis(TICK_INTERVAL_MS),
+        Duration::from_millis(current_tick_interval_ms()),
         || async { tick() },
     );
     TIMER_ID.with(|t| {
@@ -1440,6 +2097,14 @@ fn is_timer_running() -> bool {
     TIMER_ID.with(|t| t.borrow().is_some())
 }
 
+/// Re-arm the interval timer at the current rate (no-op if stopped)
+fn restart_timer() {
+    if is_timer_running() {
+        stop_timer();
+        start_timer();
+    }
+}
+
 // =============================================================================
 // UPDATE FUNCTIONS (PLAYER ACTIONS)
 // =============================================================================
@@ -1460,6 +2125,41 @@ fn faucet() -> Result<u64, String> {
     })
 }
 
+/// Send wallet coins to another principal (alliances). Returns the caller's new balance.
+#[ic_cdk::update]
+fn transfer_coins(to: Principal, amount: u64) -> Result<u64, String> {
+    transfer_coins_internal(ic_cdk::api::msg_caller(), to, amount)
+}
+
+fn transfer_coins_internal(caller: Principal, to: Principal, amount: u64) -> Result<u64, String> {
+    if caller == Principal::anonymous() {
+        return Err("Must be authenticated".to_string());
+    }
+    if to == Principal::anonymous() {
+        return Err("Cannot transfer to the anonymous principal".to_string());
+    }
+    if to == caller {
+        return Err("Cannot transfer to yourself".to_string());
+    }
+    if amount == 0 {
+        return Err("Amount must be greater than zero".to_string());
+    }
+
+    WALLETS.with(|wallets| {
+        let mut wallets = wallets.borrow_mut();
+        let balance = *wallets.get(&caller).unwrap_or(&0);
+        if balance < amount {
+            return Err(format!("Insufficient balance: have {}, need {}", balance, amount));
+        }
+        let recipient = *wallets.get(&to).unwrap_or(&0);
+        let credited = recipient.checked_add(amount).ok_or("Recipient balance overflow")?;
+
+        wallets.insert(caller, balance - amount);
+        wallets.insert(to, credited);
+        Ok(balance - amount)
+    })
+}
+
 #[ic_cdk::update]
 fn join_game(base_x: i32, base_y: i32, desired_slot: u8) -> Result<u8, String> {
     let caller = ic_cdk::api::msg_caller();
@@ -1472,20 +2172,30 @@ fn join_game(base_x: i32, base_y: i32, desired_slot: u8) -> Result<u8, String> {
         start_timer();
     }
 
-    // Validation 1: Auth
+    let slot = join_game_internal(caller, base_x, base_y, desired_slot)?;
+    LAST_ACTIVE_NS.with(|la| la.borrow_mut()[slot as usize] = ic_cdk::api::time());
+    start_game_if_ready(ic_cdk::api::time());
+    Ok(slot)
+}
+
+fn join_game_internal(caller: Principal, base_x: i32, base_y: i32, desired_slot: u8) -> Result<u8, String> {
+    // Validation 1: Auth
     if caller == Principal::anonymous() {
         return Err("Must be authenticated".to_string());
     }
 
-    // Validation 2: Not already playing
+    check_not_finished()?;
+
+    // Validation 2: One slot per principal (scans every slot)
     if find_player_slot(caller).is_some() {
         return Err("Already in game".to_string());
     }
 
-    // Validation 3: Has coins
+    // Validation 3: Has coins for the join cost
+    let join_cost = JOIN_COST.with(|c| *c.borrow());
     let wallet_balance = WALLETS.with(|w| *w.borrow().get(&caller).unwrap_or(&0));
-    if wallet_balance < BASE_COST {
-        return Err(format!("Need {} coins to join", BASE_COST));
+    if wallet_balance < join_cost {
+        return Err(format!("Need {} coins to join", join_cost));
     }
 
     // Validation 4: Coords valid
@@ -1512,9 +2222,14 @@ fn join_game(base_x: i32, base_y: i32, desired_slot: u8) -> Result<u8, String> {
         Ok(())
     })?;
 
-    // Validation 7: Desired slot is valid and available
-    if desired_slot as usize >= MAX_PLAYERS {
-        return Err(format!("Invalid slot {} (max {})", desired_slot, MAX_PLAYERS - 1));
+    // Validation 7: Desired slot is valid and available, and the player cap is not reached.
+    // Lowering the cap never evicts anyone: players already above it keep their slots.
+    let player_cap = PLAYER_CAP.with(|c| *c.borrow());
+    if desired_slot >= player_cap {
+        return Err(format!("Invalid slot {} (max {})", desired_slot, player_cap - 1));
+    }
+    if player_count() >= player_cap as usize {
+        return Err(format!("Game is full ({} players)", player_cap));
     }
     let slot = desired_slot as usize;
     let slot_available = PLAYERS.with(|players| {
@@ -1528,7 +2243,7 @@ fn join_game(base_x: i32, base_y: i32, desired_slot: u8) -> Result<u8, String> {
     WALLETS.with(|wallets| {
         let mut wallets = wallets.borrow_mut();
         if let Some(balance) = wallets.get_mut(&caller) {
-            *balance -= BASE_COST;
+            *balance -= join_cost;
         }
     });
 
@@ -1537,7 +2252,7 @@ fn join_game(base_x: i32, base_y: i32, desired_slot: u8) -> Result<u8, String> {
         bases.borrow_mut()[slot] = Some(Base {
             x: base_x,
             y: base_y,
-            coins: BASE_COST,
+            coins: join_cost,
         });
     });
 
@@ -1587,26 +2302,116 @@ fn join_game(base_x: i32, base_y: i32, desired_slot: u8) -> Result<u8, String> {
         }
     }
 
+    grant_starting_cells(slot, base_x, base_y);
+
     Ok(slot as u8)
 }
 
+/// Welcome bonus: place STARTING_GRANT free cells in the 4x4 core of a new base, row by row.
+/// No coins move. Returns the number of cells placed.
+fn grant_starting_cells(slot: usize, base_x: u16, base_y: u16) -> u32 {
+    let grant = STARTING_GRANT.with(|g| *g.borrow());
+    let mut placed = 0;
+    for i in 0..grant as u16 {
+        let x = base_x.wrapping_add(2 + i % 4) & 511;
+        let y = base_y.wrapping_add(2 + i / 4) & 511;
+        // Walls left behind by an eliminated player are not overwritten
+        if is_alive(x, y) || is_wall(x, y) {
+            continue;
+        }
+        set_alive(x, y);
+        mark_with_neighbors_potential(coords_to_idx(x, y));
+        placed += 1;
+    }
+    if placed == 0 {
+        return 0;
+    }
+
+    // Same as place_cells: make the new cells visible to the next tick
+    NEXT_POTENTIAL.with(|np| {
+        POTENTIAL.with(|p| {
+            let np_ref = np.borrow();
+            let mut p_ref = p.borrow_mut();
+            for i in 0..TOTAL_WORDS {
+                p_ref[i] |= np_ref[i];
+            }
+        });
+    });
+    CELL_COUNTS.with(|cc| cc.borrow_mut()[slot] += placed);
+    placed
+}
+
 #[ic_cdk::update]
-fn place_cells(cells: Vec<(i32, i32)>) -> Result<u32, String> {
+fn place_cells(cells: Vec<(i32, i32)>, expected_generation: Option<u64>) -> Result<u32, String> {
     let caller = ic_cdk::api::msg_caller();
+    let now = ic_cdk::api::time();
 
     // Record activity for freeze detection
-    LAST_ACTIVITY_NS.with(|la| *la.borrow_mut() = ic_cdk::api::time());
+    LAST_ACTIVITY_NS.with(|la| *la.borrow_mut() = now);
 
     // Restart timer if it was stopped (board was empty or frozen)
     if !is_timer_running() {
         start_timer();
     }
 
+    let placed = place_cells_internal(caller, cells, expected_generation, now)?;
+
+    if placed > 0 && upshift_on_activity() {
+        restart_timer();
+    }
+
+    Ok(placed)
+}
+
+/// Toroidal variant of `place_cells`: any coordinate is wrapped onto the grid instead of
+/// being rejected. For clients that deliberately place across the edges.
+#[ic_cdk::update]
+fn place_cells_wrapped(cells: Vec<(i32, i32)>, expected_generation: Option<u64>) -> Result<u32, String> {
+    place_cells(wrap_cells(cells), expected_generation)
+}
+
+/// Map every coordinate onto the 0..GRID_SIZE torus
+fn wrap_cells(cells: Vec<(i32, i32)>) -> Vec<(i32, i32)> {
+    cells.into_iter()
+        .map(|(x, y)| (x.rem_euclid(GRID_SIZE as i32), y.rem_euclid(GRID_SIZE as i32)))
+        .collect()
+}
+
+/// Drop repeated coordinates (keeping first-seen order) so a cell is charged and counted once
+fn dedup_cells(cells: Vec<(i32, i32)>) -> Vec<(i32, i32)> {
+    let mut seen = std::collections::HashSet::with_capacity(cells.len());
+    cells.into_iter().filter(|&cell| seen.insert(cell)).collect()
+}
+
+/// Reject placement planned on a view more than STALE_VIEW_TOLERANCE generations away from
+/// the board. `None` or 0 skips the check (lenient clients).
+fn check_expected_generation(expected_generation: Option<u64>) -> Result<(), String> {
+    let expected = match expected_generation {
+        Some(g) if g > 0 => g,
+        _ => return Ok(()),
+    };
+    let generation = GENERATION.with(|g| *g.borrow());
+    if generation.abs_diff(expected) > STALE_VIEW_TOLERANCE {
+        return Err(format!(
+            "Stale view: board is at generation {}, placement was planned at {}. Refetch state and retry.",
+            generation, expected
+        ));
+    }
+    Ok(())
+}
+
+/// Validate, charge and place cells for `caller`. Returns the number of distinct cells placed.
+fn place_cells_internal(caller: Principal, cells: Vec<(i32, i32)>, expected_generation: Option<u64>, now: u64) -> Result<u32, String> {
     // Size limit validation
-    if cells.len() > MAX_PLACE_CELLS {
-        return Err(format!("Max {} cells per call", MAX_PLACE_CELLS));
+    let limit = PLACE_CELLS_LIMIT.with(|l| *l.borrow());
+    if cells.len() > limit as usize {
+        return Err(format!("Max {} cells per call", limit));
     }
+    check_not_finished()?;
+    check_not_waiting()?;
+    check_expected_generation(expected_generation)?;
 
+    let cells = dedup_cells(cells);
     if cells.is_empty() {
         return Ok(0);
     }
@@ -1617,40 +2422,31 @@ fn place_cells(cells: Vec<(i32, i32)>) -> Result<u32, String> {
         bases.borrow()[slot].clone()
     }).ok_or("No base")?;
 
+    let cost = surge_placement_cost(
+        PLACEMENT_COST.with(|c| *c.borrow()),
+        SURGE_TIER_CELLS.with(|t| *t.borrow()),
+        cells_in_surge_window(caller, now),
+        cells.len() as u32,
+    );
     let wallet_balance = WALLETS.with(|w| *w.borrow().get(&caller).unwrap_or(&0));
-    if wallet_balance < cells.len() as u64 * PLACEMENT_COST {
+    if wallet_balance < cost {
         return Err("Insufficient coins".to_string());
     }
 
     // Phase 1: Validate ALL cells first (atomic)
-    for &(x, y) in &cells {
-        if x < 0 || x >= GRID_SIZE as i32 || y < 0 || y >= GRID_SIZE as i32 {
-            return Err("Coordinates out of range".to_string());
-        }
-        let x = x as u16;
-        let y = y as u16;
-
-        // Base (including walls) is ALWAYS the owner's territory - no bitmap check needed
-        // For positions outside base, must own the territory
-        if !is_in_base(&base, x, y) && !player_owns(slot, x, y) {
-            return Err("Not your territory".to_string());
-        }
-
-        if is_alive(x, y) {
-            return Err("Cell already alive".to_string());
-        }
-    }
+    validate_placement(slot, &base, &cells)?;
+    check_placement_lockout(&cells, now)?;
+    record_surge_placement(caller, now, cells.len() as u32);
 
     // Phase 2: Deduct coins (wallet -> base treasury)
-    let count = cells.len() as u64;
     WALLETS.with(|wallets| {
         if let Some(balance) = wallets.borrow_mut().get_mut(&caller) {
-            *balance -= count * PLACEMENT_COST;
+            *balance -= cost;
         }
     });
     BASES.with(|bases| {
         if let Some(base) = &mut bases.borrow_mut()[slot] {
-            base.coins += count * PLACEMENT_COST;
+            base.coins += cost;
         }
     });
 
@@ -1679,10 +2475,158 @@ fn place_cells(cells: Vec<(i32, i32)>) -> Result<u32, String> {
         cc.borrow_mut()[slot] += cells.len() as u32;
     });
 
-    // Clear grace period
+    // Clear grace period and hold off territory decay
     ZERO_CELLS_SINCE.with(|zcs| {
         zcs.borrow_mut()[slot] = None;
     });
+    LAST_ACTIVE_NS.with(|la| la.borrow_mut()[slot] = now);
+
+    Ok(cells.len() as u32)
+}
+
+/// Total price of `count` cells when `already_placed` went down earlier in the caller's
+/// surge window: the Nth cell of the window costs `unit * (1 + N / tier_cells)`.
+/// `tier_cells` 0 keeps the flat per-cell price.
+fn surge_placement_cost(unit: u64, tier_cells: u32, already_placed: u32, count: u32) -> u64 {
+    if tier_cells == 0 {
+        return unit.saturating_mul(count as u64);
+    }
+    (already_placed..already_placed.saturating_add(count))
+        .map(|n| unit.saturating_mul(1 + (n / tier_cells) as u64))
+        .fold(0u64, u64::saturating_add)
+}
+
+/// Cells `caller` has placed in their current surge window (0 once it has lapsed)
+fn cells_in_surge_window(caller: Principal, now: u64) -> u32 {
+    let window_ns = SURGE_WINDOW_SECS.with(|w| *w.borrow()) * 1_000_000_000;
+    SURGE_WINDOWS.with(|sw| match sw.borrow().get(&caller) {
+        Some(&(start, placed)) if now.saturating_sub(start) < window_ns => placed,
+        _ => 0,
+    })
+}
+
+fn record_surge_placement(caller: Principal, now: u64, count: u32) {
+    if SURGE_TIER_CELLS.with(|t| *t.borrow()) == 0 {
+        return;
+    }
+    let window_ns = SURGE_WINDOW_SECS.with(|w| *w.borrow()) * 1_000_000_000;
+    SURGE_WINDOWS.with(|sw| {
+        let mut sw = sw.borrow_mut();
+        let window = sw.entry(caller).or_insert((now, 0));
+        if now.saturating_sub(window.0) >= window_ns {
+            *window = (now, 0);
+        }
+        window.1 = window.1.saturating_add(count);
+    });
+}
+
+/// Check every cell before anything is mutated. Cells must be inside the player's base
+/// or territory they already own, so placing can never flip another player's (or
+/// neutral) territory to the caller.
+fn validate_placement(slot: usize, base: &Base, cells: &[(i32, i32)]) -> Result<(), String> {
+    for &(x, y) in cells {
+        if x < 0 || x >= GRID_SIZE as i32 || y < 0 || y >= GRID_SIZE as i32 {
+            return Err(format!(
+                "Coordinates ({}, {}) out of range 0..{} (use place_cells_wrapped for toroidal placement)",
+                x, y, GRID_SIZE
+            ));
+        }
+        let x = x as u16;
+        let y = y as u16;
+
+        // Base (including walls) is ALWAYS the owner's territory - no bitmap check needed
+        // For positions outside base, must own the territory
+        if !is_in_base(base, x, y) && !player_owns(slot, x, y) {
+            return Err(match find_owner(x, y) {
+                Some(owner) if owner != slot => "Cannot place on enemy territory".to_string(),
+                _ => "Not your territory".to_string(),
+            });
+        }
+
+        if is_alive(x, y) {
+            return Err("Cell already alive".to_string());
+        }
+
+        if is_wall(x, y) {
+            return Err("Cell is a wall".to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Place permanent wall cells in the caller's territory. Walls block births and survive wipes.
+#[ic_cdk::update]
+fn place_walls(cells: Vec<(i32, i32)>) -> Result<u32, String> {
+    let now = ic_cdk::api::time();
+    LAST_ACTIVITY_NS.with(|la| *la.borrow_mut() = now);
+    place_walls_internal(ic_cdk::api::msg_caller(), cells, now)
+}
+
+/// Validate, charge and place walls for `caller`, under the same game-state guards as
+/// `place_cells_internal`
+fn place_walls_internal(caller: Principal, cells: Vec<(i32, i32)>, now: u64) -> Result<u32, String> {
+    if cells.len() > MAX_PLACE_WALLS {
+        return Err(format!("Max {} walls per call", MAX_PLACE_WALLS));
+    }
+    check_not_finished()?;
+    check_not_waiting()?;
+
+    if cells.is_empty() {
+        return Ok(0);
+    }
+
+    let slot = find_player_slot(caller).ok_or("Not in game")?;
+
+    let base = BASES.with(|bases| {
+        bases.borrow()[slot].clone()
+    }).ok_or("No base")?;
+
+    let cost = cells.len() as u64 * WALL_COST;
+    let wallet_balance = WALLETS.with(|w| *w.borrow().get(&caller).unwrap_or(&0));
+    if wallet_balance < cost {
+        return Err("Insufficient coins".to_string());
+    }
+
+    // Phase 1: Validate ALL cells first (atomic)
+    let mut seen = std::collections::HashSet::with_capacity(cells.len());
+    for &(x, y) in &cells {
+        if x < 0 || x >= GRID_SIZE as i32 || y < 0 || y >= GRID_SIZE as i32 {
+            return Err("Coordinates out of range".to_string());
+        }
+        let x = x as u16;
+        let y = y as u16;
+
+        if !seen.insert((x, y)) {
+            return Err("Duplicate wall cell".to_string());
+        }
+        if !is_in_base(&base, x, y) && !player_owns(slot, x, y) {
+            return Err("Not your territory".to_string());
+        }
+        if is_alive(x, y) {
+            return Err("Cell already alive".to_string());
+        }
+        if is_wall(x, y) {
+            return Err("Cell is already a wall".to_string());
+        }
+    }
+    check_placement_lockout(&cells, now)?;
+
+    // Phase 2: Deduct coins (wallet -> base treasury)
+    WALLETS.with(|wallets| {
+        if let Some(balance) = wallets.borrow_mut().get_mut(&caller) {
+            *balance -= cost;
+        }
+    });
+    BASES.with(|bases| {
+        if let Some(base) = &mut bases.borrow_mut()[slot] {
+            base.coins += cost;
+        }
+    });
+
+    // Phase 3: Place walls
+    for &(x, y) in &cells {
+        set_wall(x as u16, y as u16);
+    }
 
     Ok(cells.len() as u32)
 }
@@ -1712,6 +2656,355 @@ fn resume_game() -> Result<(), String> {
     Ok(())
 }
 
+// =============================================================================
+// ADMIN FUNCTIONS
+// =============================================================================
+
+fn require_admin(caller: Principal) -> Result<(), String> {
+    let admin = Principal::from_text(ADMIN_PRINCIPAL)
+        .map_err(|_| "Invalid admin principal".to_string())?;
+    if caller != admin {
+        return Err("Unauthorized: admin only".to_string());
+    }
+    Ok(())
+}
+
+/// Snapshot the full grid (alive bits + owner cache) for backup/migration
+#[ic_cdk::query]
+fn export_grid() -> Result<Vec<u8>, String> {
+    require_admin(ic_cdk::api::msg_caller())?;
+    Ok(encode_grid())
+}
+
+/// Restore a grid produced by `export_grid`. Players, bases and wallets are untouched.
+#[ic_cdk::update]
+fn import_grid(data: Vec<u8>) -> Result<(), String> {
+    require_admin(ic_cdk::api::msg_caller())?;
+    decode_grid(&data)
+}
+
+/// Set how long a freshly wiped quadrant rejects placement (0 disables the lockout)
+#[ic_cdk::update]
+fn set_placement_lockout(seconds: u64) -> Result<(), String> {
+    set_placement_lockout_internal(ic_cdk::api::msg_caller(), seconds)
+}
+
+fn set_placement_lockout_internal(caller: Principal, seconds: u64) -> Result<(), String> {
+    require_admin(caller)?;
+    let lockout_ns = seconds.saturating_mul(1_000_000_000);
+    if lockout_ns > MAX_PLACEMENT_LOCKOUT_NS {
+        return Err(format!("Lockout cannot exceed {}s", MAX_PLACEMENT_LOCKOUT_NS / 1_000_000_000));
+    }
+    PLACEMENT_LOCKOUT_NS.with(|l| *l.borrow_mut() = lockout_ns);
+    Ok(())
+}
+
+/// Set the coins charged to join. Must be at least 1 so a base always has a treasury to siege.
+#[ic_cdk::update]
+fn set_join_cost(coins: u64) -> Result<(), String> {
+    set_join_cost_internal(ic_cdk::api::msg_caller(), coins)
+}
+
+fn set_join_cost_internal(caller: Principal, coins: u64) -> Result<(), String> {
+    require_admin(caller)?;
+    if coins == 0 || coins > MAX_JOIN_COST {
+        return Err(format!("Join cost must be 1-{} coins", MAX_JOIN_COST));
+    }
+    JOIN_COST.with(|c| *c.borrow_mut() = coins);
+    Ok(())
+}
+
+/// Set the free cells granted to each new joiner (0 disables the grant)
+#[ic_cdk::update]
+fn set_starting_grant(cells: u32) -> Result<(), String> {
+    set_starting_grant_internal(ic_cdk::api::msg_caller(), cells)
+}
+
+fn set_starting_grant_internal(caller: Principal, cells: u32) -> Result<(), String> {
+    require_admin(caller)?;
+    if cells > MAX_STARTING_GRANT {
+        return Err(format!("Starting grant must be 0-{} cells", MAX_STARTING_GRANT));
+    }
+    STARTING_GRANT.with(|g| *g.borrow_mut() = cells);
+    Ok(())
+}
+
+/// Set the coins charged per placed cell
+#[ic_cdk::update]
+fn set_placement_cost(coins: u64) -> Result<(), String> {
+    set_placement_cost_internal(ic_cdk::api::msg_caller(), coins)
+}
+
+fn set_placement_cost_internal(caller: Principal, coins: u64) -> Result<(), String> {
+    require_admin(caller)?;
+    if coins == 0 || coins > MAX_PLACEMENT_COST {
+        return Err(format!("Placement cost must be 1-{} coins", MAX_PLACEMENT_COST));
+    }
+    PLACEMENT_COST.with(|c| *c.borrow_mut() = coins);
+    Ok(())
+}
+
+/// Set the most cells one `place_cells` call may submit
+#[ic_cdk::update]
+fn set_place_cells_limit(cells: u32) -> Result<(), String> {
+    set_place_cells_limit_internal(ic_cdk::api::msg_caller(), cells)
+}
+
+fn set_place_cells_limit_internal(caller: Principal, cells: u32) -> Result<(), String> {
+    require_admin(caller)?;
+    if cells == 0 || cells > MAX_PLACE_CELLS_LIMIT {
+        return Err(format!("Place cells limit must be 1-{} cells", MAX_PLACE_CELLS_LIMIT));
+    }
+    PLACE_CELLS_LIMIT.with(|l| *l.borrow_mut() = cells);
+    Ok(())
+}
+
+/// Escalate placement prices for rapid placement: within `window_secs` of a player's first
+/// placement, every `tier_cells` cells raise the per-cell price by one placement cost.
+/// `tier_cells` 0 restores flat pricing.
+#[ic_cdk::update]
+fn set_placement_surge(tier_cells: u32, window_secs: u64) -> Result<(), String> {
+    set_placement_surge_internal(ic_cdk::api::msg_caller(), tier_cells, window_secs)
+}
+
+fn set_placement_surge_internal(caller: Principal, tier_cells: u32, window_secs: u64) -> Result<(), String> {
+    require_admin(caller)?;
+    if tier_cells > MAX_PLACE_CELLS_LIMIT {
+        return Err(format!("Surge tier must be 0-{} cells", MAX_PLACE_CELLS_LIMIT));
+    }
+    if window_secs == 0 || window_secs > MAX_SURGE_WINDOW_SECS {
+        return Err(format!("Surge window must be 1-{} seconds", MAX_SURGE_WINDOW_SECS));
+    }
+    SURGE_TIER_CELLS.with(|t| *t.borrow_mut() = tier_cells);
+    SURGE_WINDOW_SECS.with(|w| *w.borrow_mut() = window_secs);
+    SURGE_WINDOWS.with(|sw| sw.borrow_mut().clear());
+    Ok(())
+}
+
+/// Set the coins moved from a base to the attacker per blocked birth
+#[ic_cdk::update]
+fn set_siege_damage(coins: u64) -> Result<(), String> {
+    set_siege_damage_internal(ic_cdk::api::msg_caller(), coins)
+}
+
+fn set_siege_damage_internal(caller: Principal, coins: u64) -> Result<(), String> {
+    require_admin(caller)?;
+    if coins == 0 || coins > MAX_SIEGE_DAMAGE {
+        return Err(format!("Siege damage must be 1-{} coins", MAX_SIEGE_DAMAGE));
+    }
+    SIEGE_DAMAGE.with(|d| *d.borrow_mut() = coins);
+    Ok(())
+}
+
+/// Set how many players may hold a slot. Lowering it only blocks new joins.
+#[ic_cdk::update]
+fn set_max_players(max_players: u8) -> Result<(), String> {
+    set_max_players_internal(ic_cdk::api::msg_caller(), max_players)
+}
+
+fn set_max_players_internal(caller: Principal, max_players: u8) -> Result<(), String> {
+    require_admin(caller)?;
+    if max_players < MIN_PLAYER_CAP || max_players as usize > MAX_PLAYERS {
+        return Err(format!("Max players must be {}-{}", MIN_PLAYER_CAP, MAX_PLAYERS));
+    }
+    PLAYER_CAP.with(|c| *c.borrow_mut() = max_players);
+    Ok(())
+}
+
+/// Hold a fresh world in its lobby until this many players have joined (0 starts immediately).
+/// Only affects a world that has not started yet; `reset_world` opens a new lobby.
+#[ic_cdk::update]
+fn set_min_players_to_start(players: u8) -> Result<(), String> {
+    set_min_players_to_start_internal(ic_cdk::api::msg_caller(), players)
+}
+
+fn set_min_players_to_start_internal(caller: Principal, players: u8) -> Result<(), String> {
+    require_admin(caller)?;
+    let player_cap = PLAYER_CAP.with(|c| *c.borrow());
+    if players > player_cap {
+        return Err(format!("Min players to start must be 0-{}", player_cap));
+    }
+    MIN_PLAYERS_TO_START.with(|m| *m.borrow_mut() = players);
+    Ok(())
+}
+
+/// Set how many dead territory cells per tick are reclaimed from inactive players (0 disables decay)
+#[ic_cdk::update]
+fn set_territory_decay_rate(cells_per_tick: u32) -> Result<(), String> {
+    set_territory_decay_rate_internal(ic_cdk::api::msg_caller(), cells_per_tick)
+}
+
+fn set_territory_decay_rate_internal(caller: Principal, cells_per_tick: u32) -> Result<(), String> {
+    require_admin(caller)?;
+    if cells_per_tick > MAX_TERRITORY_DECAY_RATE {
+        return Err(format!("Decay rate cannot exceed {} cells per tick", MAX_TERRITORY_DECAY_RATE));
+    }
+    TERRITORY_DECAY_RATE.with(|r| *r.borrow_mut() = cells_per_tick);
+    Ok(())
+}
+
+/// Set the share of a quadrant's claimed territory (50-100%) needed to control it.
+/// Controllers are derived on every `get_quadrant_info`, so the change applies at once.
+#[ic_cdk::update]
+fn set_quadrant_control_threshold(percent: u8) -> Result<(), String> {
+    set_quadrant_control_threshold_internal(ic_cdk::api::msg_caller(), percent)
+}
+
+fn set_quadrant_control_threshold_internal(caller: Principal, percent: u8) -> Result<(), String> {
+    require_admin(caller)?;
+    if !(MIN_QUADRANT_CONTROL_PERCENT..=100).contains(&percent) {
+        return Err(format!("Control threshold must be {}-100%", MIN_QUADRANT_CONTROL_PERCENT));
+    }
+    QUADRANT_CONTROL_PERCENT.with(|t| *t.borrow_mut() = percent);
+    Ok(())
+}
+
+/// Finish the world at `limit` generations (None: run forever). The limit must be ahead of
+/// the current generation; raising or clearing it resumes a finished world.
+#[ic_cdk::update]
+fn set_generation_limit(limit: Option<u64>) -> Result<(), String> {
+    set_generation_limit_internal(ic_cdk::api::msg_caller(), limit)?;
+    if !is_timer_running() {
+        start_timer();
+    }
+    Ok(())
+}
+
+fn set_generation_limit_internal(caller: Principal, limit: Option<u64>) -> Result<(), String> {
+    require_admin(caller)?;
+    let generation = GENERATION.with(|g| *g.borrow());
+    if let Some(limit) = limit {
+        if limit <= generation {
+            return Err(format!("Generation limit must be above the current generation {}", generation));
+        }
+    }
+    GENERATION_LIMIT.with(|l| *l.borrow_mut() = limit);
+    Ok(())
+}
+
+/// Restart the global world without redeploying. Clears the grid, walls, players, bases
+/// and generation counters. Wallets are kept by principal when `keep_wallets` is true.
+#[ic_cdk::update]
+fn reset_world(keep_wallets: bool) -> Result<(), String> {
+    reset_world_internal(ic_cdk::api::msg_caller(), keep_wallets, ic_cdk::api::time())
+}
+
+fn reset_world_internal(caller: Principal, keep_wallets: bool, now: u64) -> Result<(), String> {
+    require_admin(caller)?;
+
+    // Grid buffers
+    ALIVE.with(|a| a.borrow_mut().fill(0));
+    POTENTIAL.with(|p| p.borrow_mut().fill(0));
+    NEXT_POTENTIAL.with(|np| np.borrow_mut().fill(0));
+    WALLS.with(|w| w.borrow_mut().fill(0));
+    OWNER.with(|o| o.borrow_mut().fill(255));
+    QUADRANT_TERRITORY.with(|qt| *qt.borrow_mut() = [[0u32; MAX_PLAYERS]; TOTAL_QUADRANTS as usize]);
+
+    // Players and balances
+    TERRITORY.with(|t| *t.borrow_mut() = Default::default());
+    PLAYERS.with(|p| p.borrow_mut().fill(None));
+    BASES.with(|b| *b.borrow_mut() = Default::default());
+    CELL_COUNTS.with(|cc| cc.borrow_mut().fill(0));
+    ZERO_CELLS_SINCE.with(|zcs| zcs.borrow_mut().fill(None));
+    LAST_ACTIVE_NS.with(|la| la.borrow_mut().fill(0));
+    SURGE_WINDOWS.with(|sw| sw.borrow_mut().clear());
+    GAME_STARTED.with(|s| *s.borrow_mut() = false);
+    if !keep_wallets {
+        WALLETS.with(|w| w.borrow_mut().clear());
+    }
+
+    // Generation and checkpoint timestamps (running/paused state is left as-is)
+    GENERATION.with(|g| *g.borrow_mut() = 0);
+    NEXT_WIPE_QUADRANT.with(|q| *q.borrow_mut() = 0);
+    LAST_WIPE_NS.with(|lw| *lw.borrow_mut() = now);
+    LAST_ACTIVITY_NS.with(|la| *la.borrow_mut() = now);
+
+    Ok(())
+}
+
+/// Fill a region with random unowned live cells so an empty world has something to watch.
+/// `region` is (x, y, width, height) and wraps around the torus; the whole grid by default.
+/// Returns the number of cells seeded.
+#[ic_cdk::update]
+async fn seed_random_soup(density_percent: u8, region: Option<(u16, u16, u16, u16)>) -> Result<u32, String> {
+    let caller = ic_cdk::api::msg_caller();
+    // Reject before spending a raw_rand call
+    validate_soup_request(caller, density_percent, region)?;
+
+    let random_bytes = ic_cdk::management_canister::raw_rand().await
+        .map_err(|e| format!("VRF unavailable: {:?}. Please retry.", e))?;
+    let seed: [u8; 32] = random_bytes.get(0..32)
+        .and_then(|b| b.try_into().ok())
+        .ok_or("Insufficient randomness")?;
+
+    let seeded = seed_soup_internal(caller, density_percent, region, seed)?;
+
+    LAST_ACTIVITY_NS.with(|la| *la.borrow_mut() = ic_cdk::api::time());
+    if !is_timer_running() {
+        start_timer();
+    }
+    if seeded > 0 && upshift_on_activity() {
+        restart_timer();
+    }
+
+    Ok(seeded)
+}
+
+fn validate_soup_request(caller: Principal, density_percent: u8, region: Option<(u16, u16, u16, u16)>) -> Result<(u16, u16, u16, u16), String> {
+    require_admin(caller)?;
+    if density_percent == 0 || density_percent > MAX_SOUP_DENSITY_PERCENT {
+        return Err(format!("Density must be 1-{}%", MAX_SOUP_DENSITY_PERCENT));
+    }
+    let (x, y, width, height) = region.unwrap_or((0, 0, GRID_SIZE, GRID_SIZE));
+    if x >= GRID_SIZE || y >= GRID_SIZE {
+        return Err("Coordinates out of range".to_string());
+    }
+    if width == 0 || height == 0 || width > GRID_SIZE || height > GRID_SIZE {
+        return Err(format!("Region width and height must be 1-{}", GRID_SIZE));
+    }
+    Ok((x, y, width, height))
+}
+
+/// Seed unowned cells from `seed`. Cells that are alive, walls, inside a base or owned
+/// territory are skipped, so player standings and cell counts are unaffected. Their
+/// offspring stay unowned too (see `find_birth_owner`) and never siege a base.
+fn seed_soup_internal(caller: Principal, density_percent: u8, region: Option<(u16, u16, u16, u16)>, seed: [u8; 32]) -> Result<u32, String> {
+    let (x0, y0, width, height) = validate_soup_request(caller, density_percent, region)?;
+
+    // One draw per cell, deterministic for a given seed
+    let mut rng = rng::Rng::from_bytes(&seed);
+
+    let mut seeded = 0u32;
+    for dy in 0..height {
+        for dx in 0..width {
+            if rng.below(100) >= density_percent as u64 {
+                continue;
+            }
+            let x = x0.wrapping_add(dx) & 511;
+            let y = y0.wrapping_add(dy) & 511;
+            if is_alive(x, y) || is_wall(x, y) || find_owner(x, y).is_some() || in_protection_zone(x, y).is_some() {
+                continue;
+            }
+            set_alive(x, y);
+            mark_with_neighbors_potential(coords_to_idx(x, y));
+            seeded += 1;
+        }
+    }
+
+    // Same as place_cells: make the new cells visible to the next tick
+    NEXT_POTENTIAL.with(|np| {
+        POTENTIAL.with(|p| {
+            let np_ref = np.borrow();
+            let mut p_ref = p.borrow_mut();
+            for i in 0..TOTAL_WORDS {
+                p_ref[i] |= np_ref[i];
+            }
+        });
+    });
+
+    Ok(seeded)
+}
+
 // =============================================================================
 // QUERY FUNCTIONS
 // =============================================================================
@@ -1734,7 +3027,7 @@ fn get_state() -> GameState {
         let principal = PLAYERS.with(|p| p.borrow()[slot]);
         let base = BASES.with(|b| b.borrow()[slot].clone());
         let alive_cells = CELL_COUNTS.with(|cc| cc.borrow()[slot]);
-        let territory_cells = count_territory_cells(slot);
+        let territory_cells = territory_cells(slot);
         let zero_since = ZERO_CELLS_SINCE.with(|zcs| zcs.borrow()[slot]);
 
         let (in_grace_period, grace_seconds_remaining) = if let Some(since) = zero_since {
@@ -1761,11 +3054,7 @@ fn get_state() -> GameState {
         })
     }).collect();
 
-    let next_wipe_quadrant = NEXT_WIPE_QUADRANT.with(|q| *q.borrow());
-    let last_wipe = LAST_WIPE_NS.with(|lw| *lw.borrow());
-    let now = ic_cdk::api::time();
-    let elapsed = now.saturating_sub(last_wipe);
-    let seconds_until_wipe = WIPE_INTERVAL_NS.saturating_sub(elapsed) / 1_000_000_000;
+    let next_wipe = next_wipe_info(ic_cdk::api::time());
 
     GameState {
         generation,
@@ -1773,18 +3062,113 @@ fn get_state() -> GameState {
         alive_bitmap,
         territories,
         slots,
-        next_wipe_quadrant,
-        seconds_until_wipe,
+        next_wipe_quadrant: next_wipe.next_quadrant,
+        seconds_until_wipe: next_wipe.seconds_until,
+        walls: get_wall_cells(),
+    }
+}
+
+/// `get_state` for boards too dense for one response: alive and owned cells in row-major
+/// order, `limit` at a time (capped at MAX_STATE_PAGE_CELLS). Pages are stable within a
+/// generation, so restart if `generation` changes between pages. Wipe and run state come
+/// from `get_next_wipe` / `is_frozen`.
+#[ic_cdk::query]
+fn get_state_paged(offset: u32, limit: u32) -> PagedState {
+    let (cells, total) = collect_state_page(offset, limit.min(MAX_STATE_PAGE_CELLS));
+    let first_page = offset == 0;
+
+    PagedState {
+        generation: GENERATION.with(|g| *g.borrow()),
+        has_more: (offset as u64 + cells.len() as u64) < total as u64,
+        cells,
+        total,
+        slots: first_page.then(get_slots_info),
+        walls: first_page.then(get_wall_cells),
     }
 }
 
+/// Cells `offset..offset + limit` of the alive-or-owned set, plus the size of the whole set
+fn collect_state_page(offset: u32, limit: u32) -> (Vec<PagedCell>, u32) {
+    let (offset, limit) = (offset as usize, limit as usize);
+    let mut cells = Vec::with_capacity(limit.min(1024));
+    let mut total = 0usize;
+
+    ALIVE.with(|a| {
+        let alive = a.borrow();
+        OWNER.with(|o| {
+            let owner = o.borrow();
+            for idx in 0..TOTAL_CELLS {
+                let is_alive = (alive[idx >> 6] >> (idx & 63)) & 1 == 1;
+                if !is_alive && owner[idx] == 255 {
+                    continue;
+                }
+                if total >= offset && cells.len() < limit {
+                    let (x, y) = idx_to_coords(idx);
+                    cells.push(PagedCell {
+                        x,
+                        y,
+                        alive: is_alive,
+                        owner: (owner[idx] != 255).then_some(owner[idx]),
+                    });
+                }
+                total += 1;
+            }
+        });
+    });
+
+    (cells, total as u32)
+}
+
+/// Heatmap and standings only - a few hundred bytes instead of the full board.
+/// Needs no authentication and contains no cells, wallets or treasuries.
+#[ic_cdk::query]
+fn get_spectator_view() -> SpectatorView {
+    SpectatorView {
+        generation: GENERATION.with(|g| *g.borrow()),
+        is_running: IS_RUNNING.with(|r| *r.borrow()),
+        heatmap: alive_heatmap(),
+        standings: player_standings(),
+    }
+}
+
+/// Alive cell count per territory chunk. A grid word is 64 cells of one row, i.e. exactly
+/// one chunk row, so whole words can be counted.
+fn alive_heatmap() -> Vec<u32> {
+    let mut heatmap = vec![0u32; TOTAL_CHUNKS];
+    ALIVE.with(|alive| {
+        for (word_idx, word) in alive.borrow().iter().enumerate() {
+            let y = word_idx / WORDS_PER_ROW;
+            let chunk_x = word_idx % WORDS_PER_ROW;
+            heatmap[(y >> 6) * CHUNKS_PER_ROW + chunk_x] += word.count_ones();
+        }
+    });
+    heatmap
+}
+
+fn player_standings() -> Vec<PlayerStanding> {
+    let mut standings: Vec<PlayerStanding> = (0..MAX_PLAYERS).filter_map(|slot| {
+        PLAYERS.with(|p| p.borrow()[slot]).map(|principal| PlayerStanding {
+            slot: slot as u8,
+            principal,
+            alive_cells: CELL_COUNTS.with(|cc| cc.borrow()[slot]),
+            territory_cells: territory_cells(slot),
+        })
+    }).collect();
+    standings.sort_by(|a, b| {
+        b.territory_cells.cmp(&a.territory_cells)
+            .then(b.alive_cells.cmp(&a.alive_cells))
+            .then(a.slot.cmp(&b.slot))
+    });
+    standings
+}
+
 #[ic_cdk::query]
 fn get_slots_info() -> Vec<Option<SlotInfo>> {
     (0..MAX_PLAYERS).map(|slot| {
         let principal = PLAYERS.with(|p| p.borrow()[slot]);
         let base = BASES.with(|b| b.borrow()[slot].clone());
         let alive_cells = CELL_COUNTS.with(|cc| cc.borrow()[slot]);
-        let territory_cells = count_territory_cells(slot);
+        let territory_cells = territory_cells(slot);
         let zero_since = ZERO_CELLS_SINCE.with(|zcs| zcs.borrow()[slot]);
 
         let (in_grace_period, grace_seconds_remaining) = if let Some(since) = zero_since {
@@ -1845,36 +3229,199 @@ fn get_territory_info(slot: u8) -> Option<TerritoryExport> {
     })
 }
 
+#[ic_cdk::query]
+fn get_quadrant_info(quadrant: u8) -> Option<QuadrantInfo> {
+    if quadrant >= TOTAL_QUADRANTS {
+        return None;
+    }
+    let territory = quadrant_territory(quadrant);
+    let (controller, contested, leading_margin_percent) = quadrant_control(&territory);
+    Some(QuadrantInfo {
+        quadrant,
+        controller,
+        territory: territory.to_vec(),
+        contested,
+        leading_margin_percent,
+    })
+}
+
 #[ic_cdk::query]
 fn get_next_wipe() -> WipeInfo {
-    let next_quadrant = NEXT_WIPE_QUADRANT.with(|q| *q.borrow());
-    let last_wipe = LAST_WIPE_NS.with(|lw| *lw.borrow());
-    let now = ic_cdk::api::time();
-    let elapsed = now.saturating_sub(last_wipe);
-    let seconds_until = WIPE_INTERVAL_NS.saturating_sub(elapsed) / 1_000_000_000;
+    next_wipe_info(ic_cdk::api::time())
+}
+
+/// Shared by `get_next_wipe` and `get_state` so both countdowns agree
+fn next_wipe_info(now: u64) -> WipeInfo {
+    let (wipe_at, generation) = predict_next_wipe(
+        LAST_WIPE_NS.with(|lw| *lw.borrow()),
+        LAST_TICK_NS.with(|t| *t.borrow()),
+        GENERATION.with(|g| *g.borrow()),
+        current_tick_interval_ms(),
+        is_timer_running(),
+    );
 
     WipeInfo {
-        next_quadrant,
-        seconds_until,
+        next_quadrant: NEXT_WIPE_QUADRANT.with(|q| *q.borrow()),
+        seconds_until: wipe_at.saturating_sub(now).div_ceil(1_000_000_000),
+        generation,
+    }
+}
+
+/// Seconds a freshly wiped quadrant rejects placement
+#[ic_cdk::query]
+fn get_placement_lockout() -> u64 {
+    PLACEMENT_LOCKOUT_NS.with(|l| *l.borrow()) / 1_000_000_000
+}
+
+/// Coins currently charged by `join_game`
+#[ic_cdk::query]
+fn get_join_cost() -> u64 {
+    JOIN_COST.with(|c| *c.borrow())
+}
+
+/// Free cells placed in each new joiner's base
+#[ic_cdk::query]
+fn get_starting_grant() -> u32 {
+    STARTING_GRANT.with(|g| *g.borrow())
+}
+
+/// Most cells accepted by one `place_cells` call
+#[ic_cdk::query]
+fn get_place_cells_limit() -> u32 {
+    PLACE_CELLS_LIMIT.with(|l| *l.borrow())
+}
+
+/// Dead territory cells reclaimed per tick from each inactive player
+#[ic_cdk::query]
+fn get_territory_decay_rate() -> u32 {
+    TERRITORY_DECAY_RATE.with(|r| *r.borrow())
+}
+
+/// Percent of a quadrant's claimed territory needed to control it
+#[ic_cdk::query]
+fn get_quadrant_control_threshold() -> u8 {
+    QUADRANT_CONTROL_PERCENT.with(|t| *t.borrow())
+}
+
+/// Players who joined or placed cells within the last `window_secs` (capacity planning).
+/// Built on the per-slot activity timestamps, so it is bounded by MAX_PLAYERS.
+#[ic_cdk::query]
+fn get_active_player_count(window_secs: u64) -> u32 {
+    active_player_count(window_secs, ic_cdk::api::time())
+}
+
+fn active_player_count(window_secs: u64, now: u64) -> u32 {
+    let window_ns = window_secs.saturating_mul(1_000_000_000);
+    PLAYERS.with(|players| {
+        LAST_ACTIVE_NS.with(|la| {
+            let la = la.borrow();
+            players.borrow().iter().enumerate()
+                .filter(|(slot, p)| p.is_some() && now.saturating_sub(la[*slot]) <= window_ns)
+                .count() as u32
+        })
+    })
+}
+
+/// Number of slots open to `join_game`
+#[ic_cdk::query]
+fn get_max_players() -> u8 {
+    PLAYER_CAP.with(|c| *c.borrow())
+}
+
+#[ic_cdk::query]
+fn get_economy() -> EconomyInfo {
+    EconomyInfo {
+        join_cost: JOIN_COST.with(|c| *c.borrow()),
+        placement_cost: PLACEMENT_COST.with(|c| *c.borrow()),
+        siege_damage: SIEGE_DAMAGE.with(|d| *d.borrow()),
+        wall_cost: WALL_COST,
+        surge_tier_cells: SURGE_TIER_CELLS.with(|t| *t.borrow()),
+        surge_window_secs: SURGE_WINDOW_SECS.with(|w| *w.borrow()),
+    }
+}
+
+/// Current simulation rate. Clients must match `generations_per_second` locally.
+#[ic_cdk::query]
+fn get_tick_rate() -> TickRateInfo {
+    let tick_interval_ms = current_tick_interval_ms();
+    TickRateInfo {
+        generations_per_second: (GENERATIONS_PER_TICK as u64 * 1000 / tick_interval_ms) as u32,
+        tick_interval_ms,
+        quiet_ticks: QUIET_TICKS.with(|q| *q.borrow()),
     }
 }
 
 #[ic_cdk::query]
 fn get_balance() -> u64 {
-    let caller = ic_cdk::api::msg_caller();
-    WALLETS.with(|w| *w.borrow().get(&caller).unwrap_or(&0))
+    get_balance_of(ic_cdk::api::msg_caller())
+}
+
+/// Wallet balance of any principal (0 if it never held coins), for leaderboards and support
+#[ic_cdk::query]
+fn get_balance_of(principal: Principal) -> u64 {
+    WALLETS.with(|w| *w.borrow().get(&principal).unwrap_or(&0))
 }
 
+/// Last materialized generation. It only advances in whole ticks, so between ticks it
+/// lags the wall clock; clients syncing to a target should use `get_true_generation`.
 #[ic_cdk::query]
 fn get_generation() -> u64 {
     GENERATION.with(|g| *g.borrow())
 }
 
+/// Generation implied by the wall clock: the materialized generation plus the generations
+/// elapsed since the last tick at the current rate. Never mutates state.
+#[ic_cdk::query]
+fn get_true_generation() -> u64 {
+    true_generation(
+        GENERATION.with(|g| *g.borrow()),
+        LAST_TICK_NS.with(|t| *t.borrow()),
+        ic_cdk::api::time(),
+        current_tick_interval_ms(),
+        IS_RUNNING.with(|r| *r.borrow()) && is_timer_running(),
+    )
+}
+
+/// Extrapolation is capped at one tick so the value never runs ahead of what the next
+/// (possibly late) tick materializes. Paused or frozen boards do not advance.
+fn true_generation(stored: u64, last_tick_ns: u64, now: u64, tick_interval_ms: u64, running: bool) -> u64 {
+    if !running {
+        return stored;
+    }
+    let elapsed_ns = now.saturating_sub(last_tick_ns) as u128;
+    let pending = elapsed_ns * GENERATIONS_PER_TICK as u128 / (tick_interval_ms as u128 * 1_000_000);
+    stored + pending.min(GENERATIONS_PER_TICK as u128) as u64
+}
+
 #[ic_cdk::query]
 fn is_frozen() -> bool {
     !is_timer_running()
 }
 
+/// Generation at which the world finishes, if a limit is set
+#[ic_cdk::query]
+fn get_generation_limit() -> Option<u64> {
+    GENERATION_LIMIT.with(|l| *l.borrow())
+}
+
+/// Waiting (lobby), Active or Finished
+#[ic_cdk::query]
+fn get_game_status() -> GameStatus {
+    game_status()
+}
+
+/// Players needed before a fresh world starts (0: starts immediately)
+#[ic_cdk::query]
+fn get_min_players_to_start() -> u8 {
+    MIN_PLAYERS_TO_START.with(|m| *m.borrow())
+}
+
+/// The world reached its generation limit: placement and joining are closed
+#[ic_cdk::query(name = "is_finished")]
+fn is_finished_query() -> bool {
+    is_finished()
+}
+
 #[ic_cdk::query]
 fn get_alive_cells() -> Vec<(u16, u16)> {
     let mut cells = Vec::new();
@@ -1906,6 +3453,22 @@ fn greet(name: String) -> String {
 
 // Benchmark query functions are in benchmarks.rs
 
+fn get_wall_cells() -> Vec<(u16, u16)> {
+    WALLS.with(|walls| {
+        let walls = walls.borrow();
+        let mut cells = Vec::new();
+        for (word_idx, &word) in walls.iter().enumerate() {
+            let mut word = word;
+            while word != 0 {
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                cells.push(idx_to_coords(word_idx * 64 + bit));
+            }
+        }
+        cells
+    })
+}
+
 /// Helper for benchmarks module to count alive cells
 pub(crate) fn get_alive_cell_count() -> u32 {
     ALIVE.with(|alive| {
@@ -1946,10 +3509,101 @@ fn rebuild_potential_from_alive() {
     });
 }
 
+/// Rebuild per-player territory bitmaps and alive cell counts from the OWNER cache
+fn rebuild_territory_from_owner() {
+    TERRITORY.with(|t| {
+        *t.borrow_mut() = Default::default();
+    });
+    CELL_COUNTS.with(|cc| cc.borrow_mut().fill(0));
+
+    let owner_snapshot: Vec<u8> = OWNER.with(|o| o.borrow().to_vec());
+    for (idx, &owner) in owner_snapshot.iter().enumerate() {
+        if owner == 255 {
+            continue;
+        }
+        let (x, y) = idx_to_coords(idx);
+        set_territory(owner as usize, x, y);
+        if is_alive_idx(idx) {
+            CELL_COUNTS.with(|cc| cc.borrow_mut()[owner as usize] += 1);
+        }
+    }
+    rebuild_quadrant_territory();
+}
+
+fn encode_grid() -> Vec<u8> {
+    let mut data = Vec::with_capacity(GRID_EXPORT_LEN);
+    data.push(GRID_EXPORT_VERSION);
+    OWNER.with(|o| {
+        let owner = o.borrow();
+        ALIVE.with(|a| {
+            let alive = a.borrow();
+            for idx in 0..TOTAL_CELLS {
+                let is_alive = (alive[idx >> 6] >> (idx & 63)) & 1 == 1;
+                let mut byte = if owner[idx] == 255 { GRID_UNOWNED } else { owner[idx] };
+                if is_alive {
+                    byte |= GRID_ALIVE_FLAG;
+                }
+                data.push(byte);
+            }
+        });
+    });
+    data
+}
+
+fn decode_grid(data: &[u8]) -> Result<(), String> {
+    if data.len() != GRID_EXPORT_LEN {
+        return Err(format!("Invalid grid length: expected {}, got {}", GRID_EXPORT_LEN, data.len()));
+    }
+    if data[0] != GRID_EXPORT_VERSION {
+        return Err(format!("Unsupported grid version: {}", data[0]));
+    }
+
+    let cells = &data[1..];
+    if let Some(bad) = cells.iter().map(|b| b & !GRID_ALIVE_FLAG).find(|&o| o != GRID_UNOWNED && o as usize >= MAX_PLAYERS) {
+        return Err(format!("Invalid owner in grid data: {}", bad));
+    }
+
+    ALIVE.with(|a| {
+        let mut alive = a.borrow_mut();
+        alive.fill(0);
+        for (idx, &byte) in cells.iter().enumerate() {
+            if byte & GRID_ALIVE_FLAG != 0 {
+                alive[idx >> 6] |= 1u64 << (idx & 63);
+            }
+        }
+    });
+    OWNER.with(|o| {
+        let mut owner = o.borrow_mut();
+        for (idx, &byte) in cells.iter().enumerate() {
+            let v = byte & !GRID_ALIVE_FLAG;
+            owner[idx] = if v == GRID_UNOWNED { 255 } else { v };
+        }
+    });
+
+    rebuild_territory_from_owner();
+    rebuild_potential_from_alive();
+    Ok(())
+}
+
+/// Number of alive cells in a bitmap (stored with the snapshot to detect a bad restore)
+fn alive_checksum(alive: &[u64]) -> u32 {
+    alive.iter().map(|w| w.count_ones()).sum()
+}
+
 #[ic_cdk::pre_upgrade]
 fn pre_upgrade() {
-    let state = PersistedState {
-        alive: ALIVE.with(|a| a.borrow().to_vec()),
+    let state = snapshot_state();
+    ic_cdk::println!("pre_upgrade: saving {} alive cells", state.alive_checksum.unwrap_or(0));
+    ic_cdk::storage::stable_save((state,)).expect("Failed to save state");
+}
+
+fn snapshot_state() -> PersistedState {
+    let alive = ALIVE.with(|a| a.borrow().to_vec());
+    assert_eq!(alive.len(), TOTAL_WORDS, "Alive bitmap has wrong length");
+    let checksum = alive_checksum(&alive);
+
+    PersistedState {
+        alive,
         territory: TERRITORY.with(|t| t.borrow().to_vec()),
         bases: BASES.with(|b| b.borrow().to_vec()),
         players: PLAYERS.with(|p| p.borrow().to_vec()),
@@ -1962,16 +3616,44 @@ fn pre_upgrade() {
         last_wipe_ns: LAST_WIPE_NS.with(|lw| *lw.borrow()),
         owner: OWNER.with(|o| o.borrow().to_vec()),
         last_activity_ns: Some(LAST_ACTIVITY_NS.with(|la| *la.borrow())),
-    };
-
-    ic_cdk::storage::stable_save((state,)).expect("Failed to save state");
+        walls: Some(WALLS.with(|w| w.borrow().to_vec())),
+        placement_lockout_ns: Some(PLACEMENT_LOCKOUT_NS.with(|l| *l.borrow())),
+        alive_checksum: Some(checksum),
+        join_cost: Some(JOIN_COST.with(|c| *c.borrow())),
+        placement_cost: Some(PLACEMENT_COST.with(|c| *c.borrow())),
+        siege_damage: Some(SIEGE_DAMAGE.with(|d| *d.borrow())),
+        max_players: Some(PLAYER_CAP.with(|c| *c.borrow())),
+        territory_decay_rate: Some(TERRITORY_DECAY_RATE.with(|r| *r.borrow())),
+        last_active_ns: Some(LAST_ACTIVE_NS.with(|la| la.borrow().to_vec())),
+        quadrant_control_percent: Some(QUADRANT_CONTROL_PERCENT.with(|t| *t.borrow())),
+        generation_limit: GENERATION_LIMIT.with(|l| *l.borrow()),
+        starting_grant: Some(STARTING_GRANT.with(|g| *g.borrow())),
+        place_cells_limit: Some(PLACE_CELLS_LIMIT.with(|l| *l.borrow())),
+        placement_surge: Some((SURGE_TIER_CELLS.with(|t| *t.borrow()), SURGE_WINDOW_SECS.with(|w| *w.borrow()))),
+        min_players_to_start: Some(MIN_PLAYERS_TO_START.with(|m| *m.borrow())),
+        game_started: Some(GAME_STARTED.with(|s| *s.borrow())),
+    }
 }
 
 #[ic_cdk::post_upgrade]
 fn post_upgrade() {
-    let (state,): (PersistedState,) =
+    let (mut state,): (PersistedState,) =
         ic_cdk::storage::stable_restore().expect("Failed to restore state");
+    state.last_activity_ns.get_or_insert_with(ic_cdk::api::time);
+    // Pre-decay snapshots: treat every player as active at upgrade time
+    state.last_active_ns.get_or_insert_with(|| vec![ic_cdk::api::time(); MAX_PLAYERS]);
+
+    if let Err(e) = restore_state(state) {
+        ic_cdk::println!("WARNING post_upgrade: {}", e);
+    }
+
+    // Restart timer
+    start_timer();
+}
 
+/// Load a snapshot into the live state. Returns Err (after restoring everything) if the
+/// restored alive count does not match the checksum recorded at snapshot time.
+fn restore_state(state: PersistedState) -> Result<(), String> {
     ALIVE.with(|a| {
         let mut alive = a.borrow_mut();
         for (i, &v) in state.alive.iter().enumerate().take(TOTAL_WORDS) {
@@ -2022,7 +3704,63 @@ fn post_upgrade() {
     IS_RUNNING.with(|r| *r.borrow_mut() = state.is_running);
     NEXT_WIPE_QUADRANT.with(|q| *q.borrow_mut() = state.next_wipe_quadrant);
     LAST_WIPE_NS.with(|lw| *lw.borrow_mut() = state.last_wipe_ns);
-    LAST_ACTIVITY_NS.with(|la| *la.borrow_mut() = state.last_activity_ns.unwrap_or_else(ic_cdk::api::time));
+    LAST_ACTIVITY_NS.with(|la| *la.borrow_mut() = state.last_activity_ns.unwrap_or_default());
+
+    if let Some(lockout_ns) = state.placement_lockout_ns {
+        PLACEMENT_LOCKOUT_NS.with(|l| *l.borrow_mut() = lockout_ns);
+    }
+
+    if let Some(join_cost) = state.join_cost {
+        JOIN_COST.with(|c| *c.borrow_mut() = join_cost);
+    }
+    if let Some(placement_cost) = state.placement_cost {
+        PLACEMENT_COST.with(|c| *c.borrow_mut() = placement_cost);
+    }
+    if let Some(siege_damage) = state.siege_damage {
+        SIEGE_DAMAGE.with(|d| *d.borrow_mut() = siege_damage);
+    }
+    if let Some(max_players) = state.max_players {
+        PLAYER_CAP.with(|c| *c.borrow_mut() = max_players);
+    }
+    if let Some(rate) = state.territory_decay_rate {
+        TERRITORY_DECAY_RATE.with(|r| *r.borrow_mut() = rate);
+    }
+    if let Some(percent) = state.quadrant_control_percent {
+        QUADRANT_CONTROL_PERCENT.with(|t| *t.borrow_mut() = percent);
+    }
+    GENERATION_LIMIT.with(|l| *l.borrow_mut() = state.generation_limit);
+    if let Some(grant) = state.starting_grant {
+        STARTING_GRANT.with(|g| *g.borrow_mut() = grant);
+    }
+    if let Some(limit) = state.place_cells_limit {
+        PLACE_CELLS_LIMIT.with(|l| *l.borrow_mut() = limit);
+    }
+    if let Some((tier_cells, window_secs)) = state.placement_surge {
+        SURGE_TIER_CELLS.with(|t| *t.borrow_mut() = tier_cells);
+        SURGE_WINDOW_SECS.with(|w| *w.borrow_mut() = window_secs);
+    }
+    if let Some(players) = state.min_players_to_start {
+        MIN_PLAYERS_TO_START.with(|m| *m.borrow_mut() = players);
+    }
+    // Worlds saved before the lobby existed are already running
+    GAME_STARTED.with(|s| *s.borrow_mut() = state.game_started.unwrap_or(true));
+    if let Some(last_active) = state.last_active_ns {
+        LAST_ACTIVE_NS.with(|la| {
+            let mut la = la.borrow_mut();
+            for (i, &t) in last_active.iter().enumerate().take(MAX_PLAYERS) {
+                la[i] = t;
+            }
+        });
+    }
+
+    if let Some(saved_walls) = state.walls {
+        WALLS.with(|w| {
+            let mut walls = w.borrow_mut();
+            for (i, &v) in saved_walls.iter().enumerate().take(TOTAL_WORDS) {
+                walls[i] = v;
+            }
+        });
+    }
 
     // Restore OWNER cache
     OWNER.with(|o| {
@@ -2034,12 +3772,19 @@ fn post_upgrade() {
 
     // Rebuild transient structures
     rebuild_potential_from_alive();
+    rebuild_quadrant_territory();
     BFS_WORKSPACE.with(|ws| {
         *ws.borrow_mut() = BFSWorkspace::new();
     });
 
-    // Restart timer
-    start_timer();
+    // Older snapshots have no checksum
+    let restored = ALIVE.with(|a| alive_checksum(&a.borrow()[..]));
+    match state.alive_checksum {
+        Some(expected) if expected != restored => Err(format!(
+            "Alive cell checksum mismatch: saved {}, restored {}", expected, restored
+        )),
+        _ => Ok(()),
+    }
 }
 
 #[ic_cdk::init]
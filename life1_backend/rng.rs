@@ -0,0 +1,39 @@
+//! Deterministic pseudo-random numbers for Life features
+//!
+//! `raw_rand` returns one 32-byte seed per call. Features that need many draws from it
+//! (e.g. one per cell when seeding soup) expand it with this generator.
+//!
+//! NOT cryptographic: anyone who knows the seed can predict every draw. Use it only for
+//! gameplay or cosmetic variety, never for anything that moves coins or money.
+
+/// SplitMix64 generator: fast, no zero-seed fixed point, good statistical quality
+#[derive(Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Seed from a 32-byte VRF seed (`raw_rand` output) by folding its words together
+    pub fn from_bytes(seed: &[u8; 32]) -> Self {
+        let folded = seed.chunks_exact(8)
+            .fold(0u64, |acc, c| acc ^ u64::from_le_bytes(c.try_into().expect("chunks_exact(8) yields 8 bytes")));
+        Self::new(folded)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Draw in `0..bound` (modulo bias is negligible for the small bounds used here)
+    pub fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+}
@@ -10,24 +10,24 @@ fn count_neighbors_reference(
     let (nw, n, ne, w, e, sw, s, se) = if bit_pos == 0 {
         (
             ((left_above >> 63) & 1) as u8,
-            ((above >> 0) & 1) as u8,
+            (above & 1) as u8,
             ((above >> 1) & 1) as u8,
             ((left_same >> 63) & 1) as u8,
             ((same >> 1) & 1) as u8,
             ((left_below >> 63) & 1) as u8,
-            ((below >> 0) & 1) as u8,
+            (below & 1) as u8,
             ((below >> 1) & 1) as u8,
         )
     } else if bit_pos == 63 {
         (
             ((above >> 62) & 1) as u8,
             ((above >> 63) & 1) as u8,
-            ((right_above >> 0) & 1) as u8,
+            (right_above & 1) as u8,
             ((same >> 62) & 1) as u8,
-            ((right_same >> 0) & 1) as u8,
+            (right_same & 1) as u8,
             ((below >> 62) & 1) as u8,
             ((below >> 63) & 1) as u8,
-            ((right_below >> 0) & 1) as u8,
+            (right_below & 1) as u8,
         )
     } else {
         (
@@ -303,3 +303,1395 @@ fn test_orthogonal_neighbors_corner_bottom_right() {
     assert_eq!(neighbors[2], (511, 510));  // North normal
     assert_eq!(neighbors[3], (511, 0));    // South wraps
 }
+
+// =============================================================================
+// ADMIN / GRID EXPORT
+// =============================================================================
+
+#[test]
+fn test_require_admin_rejects_non_admin() {
+    let admin = Principal::from_text(ADMIN_PRINCIPAL).unwrap();
+    assert!(require_admin(admin).is_ok());
+    assert!(require_admin(Principal::anonymous()).is_err());
+    assert!(require_admin(Principal::from_slice(&[7; 29])).is_err());
+}
+
+#[test]
+fn test_grid_export_import_roundtrip() {
+    set_alive(10, 20);
+    set_territory(2, 10, 20);
+    set_territory(2, 11, 20);
+    set_alive(300, 400);
+
+    let data = encode_grid();
+    assert_eq!(data.len(), GRID_EXPORT_LEN);
+    assert_eq!(data[0], GRID_EXPORT_VERSION);
+
+    // Wipe local state, then restore
+    ALIVE.with(|a| a.borrow_mut().fill(0));
+    OWNER.with(|o| o.borrow_mut().fill(255));
+    decode_grid(&data).unwrap();
+
+    assert!(is_alive(10, 20));
+    assert!(is_alive(300, 400));
+    assert!(!is_alive(11, 20));
+    assert_eq!(find_owner(11, 20), Some(2));
+    assert_eq!(find_owner(300, 400), None);
+    assert!(player_owns(2, 10, 20));
+    assert_eq!(count_territory_cells(2), 2);
+    assert_eq!(CELL_COUNTS.with(|cc| cc.borrow()[2]), 1);
+    // POTENTIAL rebuilt around the live cell
+    let idx = coords_to_idx(10, 21);
+    assert_eq!(POTENTIAL.with(|p| (p.borrow()[idx >> 6] >> (idx & 63)) & 1), 1);
+}
+
+#[test]
+fn test_grid_import_rejects_bad_data() {
+    assert!(decode_grid(&[GRID_EXPORT_VERSION]).is_err());
+
+    let mut data = vec![GRID_UNOWNED; GRID_EXPORT_LEN];
+    data[0] = GRID_EXPORT_VERSION + 1;
+    assert!(decode_grid(&data).is_err());
+
+    data[0] = GRID_EXPORT_VERSION;
+    data[1] = MAX_PLAYERS as u8;
+    assert!(decode_grid(&data).is_err());
+
+    data[1] = GRID_UNOWNED;
+    assert!(decode_grid(&data).is_ok());
+}
+
+#[test]
+fn test_reset_world_rejects_non_admin() {
+    set_alive(10, 20);
+    GENERATION.with(|g| *g.borrow_mut() = 42);
+
+    let err = reset_world_internal(Principal::from_slice(&[7; 29]), false, 1_000).unwrap_err();
+    assert_eq!(err, "Unauthorized: admin only");
+    assert!(is_alive(10, 20), "Rejected reset must not touch the grid");
+    assert_eq!(GENERATION.with(|g| *g.borrow()), 42);
+}
+
+#[test]
+fn test_reset_world_clears_state() {
+    let admin = Principal::from_text(ADMIN_PRINCIPAL).unwrap();
+    let player = Principal::from_slice(&[3; 29]);
+    set_alive(10, 20);
+    set_territory(1, 10, 20);
+    set_wall(5, 5);
+    PLAYERS.with(|p| p.borrow_mut()[1] = Some(player));
+    WALLETS.with(|w| w.borrow_mut().insert(player, 500));
+    GENERATION.with(|g| *g.borrow_mut() = 42);
+
+    reset_world_internal(admin, true, 1_000).unwrap();
+    assert!(!is_alive(10, 20));
+    assert!(!is_wall(5, 5));
+    assert_eq!(find_owner(10, 20), None);
+    assert_eq!(count_territory_cells(1), 0);
+    assert_eq!(find_player_slot(player), None);
+    assert_eq!(GENERATION.with(|g| *g.borrow()), 0);
+    assert_eq!(LAST_WIPE_NS.with(|lw| *lw.borrow()), 1_000);
+    assert_eq!(WALLETS.with(|w| w.borrow().get(&player).copied()), Some(500));
+
+    reset_world_internal(admin, false, 2_000).unwrap();
+    assert!(WALLETS.with(|w| w.borrow().is_empty()));
+}
+
+// =============================================================================
+// PLACEMENT OWNERSHIP
+// =============================================================================
+
+#[test]
+fn test_place_on_enemy_dead_territory_rejected() {
+    // Player 1 owns a large dead region far from player 2's base
+    for x in 200..210 {
+        set_territory(1, x, 200);
+    }
+    let attacker_base = Base { x: 10, y: 10, coins: 100 };
+
+    let err = validate_placement(2, &attacker_base, &[(205, 200)]).unwrap_err();
+    assert_eq!(err, "Cannot place on enemy territory");
+    assert_eq!(find_owner(205, 200), Some(1), "Ownership must not change");
+
+    // Neutral ground is not claimable by placement either
+    assert_eq!(validate_placement(2, &attacker_base, &[(300, 300)]).unwrap_err(), "Not your territory");
+
+    // Own base and own territory are fine
+    set_territory(2, 30, 30);
+    assert!(validate_placement(2, &attacker_base, &[(12, 12), (30, 30)]).is_ok());
+}
+
+#[test]
+fn test_place_cells_batch_with_enemy_dead_cell_changes_nothing() {
+    let player = Principal::from_slice(&[8; 29]);
+    PLAYERS.with(|p| p.borrow_mut()[2] = Some(player));
+    BASES.with(|b| b.borrow_mut()[2] = Some(Base { x: 10, y: 10, coins: 0 }));
+    WALLETS.with(|w| w.borrow_mut().insert(player, 50));
+    set_territory(1, 205, 200);
+
+    // One enemy-owned dead cell fails the whole batch, including the valid base cell
+    let err = place_cells_internal(player, vec![(12, 12), (205, 200)], None, 0).unwrap_err();
+    assert_eq!(err, "Cannot place on enemy territory");
+    assert_eq!(find_owner(205, 200), Some(1));
+    assert!(!is_alive(12, 12) && !is_alive(205, 200));
+    assert_eq!(WALLETS.with(|w| w.borrow()[&player]), 50);
+    assert_eq!(CELL_COUNTS.with(|cc| cc.borrow()[2]), 0);
+}
+
+#[test]
+fn test_out_of_range_rejected_strict_and_wrapped_lenient() {
+    let player = Principal::from_slice(&[6; 29]);
+    PLAYERS.with(|p| p.borrow_mut()[0] = Some(player));
+    BASES.with(|b| b.borrow_mut()[0] = Some(Base { x: 0, y: 0, coins: 0 }));
+    WALLETS.with(|w| w.borrow_mut().insert(player, 50));
+
+    let err = place_cells_internal(player, vec![(2, 2), (100_000, 2)], None, 0).unwrap_err();
+    assert!(err.contains("(100000, 2) out of range"), "{}", err);
+    assert!(!is_alive(2, 2), "Strict mode rejects the whole batch");
+    assert_eq!(WALLETS.with(|w| w.borrow()[&player]), 50);
+
+    // Lenient mode wraps onto the torus: 514 -> 2, -510 -> 2
+    let wrapped = wrap_cells(vec![(514, 3), (-510, 4)]);
+    assert_eq!(wrapped, vec![(2, 3), (2, 4)]);
+    assert_eq!(place_cells_internal(player, wrapped, None, 0), Ok(2));
+    assert!(is_alive(2, 3) && is_alive(2, 4));
+}
+
+#[test]
+fn test_duplicate_coordinates_charged_once() {
+    let player = Principal::from_slice(&[7; 29]);
+    PLAYERS.with(|p| p.borrow_mut()[3] = Some(player));
+    BASES.with(|b| b.borrow_mut()[3] = Some(Base { x: 100, y: 100, coins: 0 }));
+    WALLETS.with(|w| w.borrow_mut().insert(player, 50));
+
+    let cells = vec![(102, 102), (102, 102), (103, 103), (102, 102)];
+    assert_eq!(place_cells_internal(player, cells, None, 0), Ok(2));
+
+    assert_eq!(WALLETS.with(|w| w.borrow().get(&player).copied()), Some(50 - 2 * DEFAULT_PLACEMENT_COST));
+    assert_eq!(BASES.with(|b| b.borrow()[3].as_ref().map(|base| base.coins)), Some(2 * DEFAULT_PLACEMENT_COST));
+    assert_eq!(CELL_COUNTS.with(|cc| cc.borrow()[3]), 2);
+    assert!(is_alive(102, 102) && is_alive(103, 103));
+}
+
+#[test]
+fn test_place_cells_limit() {
+    let admin = Principal::from_text(ADMIN_PRINCIPAL).unwrap();
+    let player = Principal::from_slice(&[8; 29]);
+    assert_eq!(get_place_cells_limit(), DEFAULT_PLACE_CELLS_LIMIT);
+    assert!(set_place_cells_limit_internal(player, 16).is_err());
+    assert!(set_place_cells_limit_internal(admin, 0).is_err());
+    assert!(set_place_cells_limit_internal(admin, MAX_PLACE_CELLS_LIMIT + 1).is_err());
+    set_place_cells_limit_internal(admin, 16).unwrap();
+
+    PLAYERS.with(|p| p.borrow_mut()[2] = Some(player));
+    BASES.with(|b| b.borrow_mut()[2] = Some(Base { x: 100, y: 100, coins: 0 }));
+    WALLETS.with(|w| w.borrow_mut().insert(player, 50));
+
+    // One over the limit: rejected before anything is placed or charged
+    let mut cells: Vec<(i32, i32)> = (0..16).map(|i| (101 + i % 4, 101 + i / 4)).collect();
+    cells.push((105, 101));
+    assert_eq!(place_cells_internal(player, cells.clone(), None, 0).unwrap_err(), "Max 16 cells per call");
+    assert_eq!(get_alive_cell_count(), 0);
+    assert_eq!(WALLETS.with(|w| w.borrow()[&player]), 50);
+
+    // Exactly the limit is accepted
+    cells.pop();
+    assert_eq!(place_cells_internal(player, cells, None, 0), Ok(16));
+    assert_eq!(CELL_COUNTS.with(|cc| cc.borrow()[2]), 16);
+}
+
+#[test]
+fn test_placement_surge_pricing() {
+    let admin = Principal::from_text(ADMIN_PRINCIPAL).unwrap();
+    let player = Principal::from_slice(&[9; 29]);
+    PLAYERS.with(|p| p.borrow_mut()[3] = Some(player));
+    BASES.with(|b| b.borrow_mut()[3] = Some(Base { x: 100, y: 100, coins: 0 }));
+    WALLETS.with(|w| w.borrow_mut().insert(player, 1_000));
+    let wallet = || WALLETS.with(|w| w.borrow()[&player]);
+    let row = |y: i32| -> Vec<(i32, i32)> { (0..4).map(|x| (101 + x, y)).collect() };
+    const SEC: u64 = 1_000_000_000;
+    const T: u64 = 1_000 * SEC;
+
+    // Default is flat: rapid placement costs one coin per cell
+    assert_eq!(get_economy().surge_tier_cells, 0);
+    place_cells_internal(player, row(101), None, T).unwrap();
+    place_cells_internal(player, row(102), None, T).unwrap();
+    assert_eq!(wallet(), 992);
+
+    assert!(set_placement_surge_internal(player, 4, 60).is_err());
+    assert!(set_placement_surge_internal(admin, 4, 0).is_err());
+    assert!(set_placement_surge_internal(admin, 4, MAX_SURGE_WINDOW_SECS + 1).is_err());
+    set_placement_surge_internal(admin, 4, 60).unwrap();
+
+    // Rapid placement escalates: 4 cells at 1, then 4 at 2, then 4 at 3
+    place_cells_internal(player, row(103), None, T).unwrap();
+    assert_eq!(wallet(), 988);
+    place_cells_internal(player, row(104), None, T + SEC).unwrap();
+    assert_eq!(wallet(), 980);
+    place_cells_internal(player, row(105), None, T + 2 * SEC).unwrap();
+    assert_eq!(wallet(), 968);
+
+    // Normal pace: once the window lapses the price is back to flat
+    place_cells_internal(player, row(106), None, T + 60 * SEC).unwrap();
+    assert_eq!(wallet(), 964);
+    place_cells_internal(player, row(107), None, T + 125 * SEC).unwrap();
+    assert_eq!(wallet(), 960);
+
+    // One call crossing a tier boundary pays each cell at its own tier
+    assert_eq!(surge_placement_cost(1, 4, 2, 4), 1 + 1 + 2 + 2);
+    assert_eq!(surge_placement_cost(3, 0, 100, 4), 12);
+}
+
+#[test]
+fn test_lobby_waits_for_min_players() {
+    let admin = Principal::from_text(ADMIN_PRINCIPAL).unwrap();
+    let alice = Principal::from_slice(&[34; 29]);
+    let bob = Principal::from_slice(&[35; 29]);
+    WALLETS.with(|w| {
+        w.borrow_mut().insert(alice, 1_000);
+        w.borrow_mut().insert(bob, 1_000);
+    });
+    const T: u64 = 1_000 * WIPE_INTERVAL_NS;
+    assert_eq!(game_status(), GameStatus::Active, "No lobby by default");
+    assert!(set_min_players_to_start_internal(alice, 2).is_err());
+    assert!(set_min_players_to_start_internal(admin, DEFAULT_PLAYER_CAP + 1).is_err());
+    set_min_players_to_start_internal(admin, 2).unwrap();
+
+    join_game_internal(alice, 40, 40, 0).unwrap();
+    assert!(!start_game_if_ready(T));
+    assert_eq!(game_status(), GameStatus::Waiting);
+    assert_eq!(
+        place_cells_internal(alice, vec![(42, 42)], None, T).unwrap_err(),
+        "Waiting for players: 1/2 joined"
+    );
+    let generation = GENERATION.with(|g| *g.borrow());
+    tick_at(T + 1_000_000_000);
+    assert_eq!(GENERATION.with(|g| *g.borrow()), generation, "Simulation paused in the lobby");
+
+    join_game_internal(bob, 300, 300, 1).unwrap();
+    let start = T + 30_000_000_000;
+    assert!(start_game_if_ready(start));
+    assert_eq!(game_status(), GameStatus::Active);
+    assert_eq!(LAST_WIPE_NS.with(|lw| *lw.borrow()), start, "Wipe schedule starts with the game");
+    assert_eq!(place_cells_internal(alice, vec![(42, 42)], None, start), Ok(1));
+
+    // Once started, losing a player does not send the world back to the lobby
+    eliminate_player(1);
+    assert_eq!(game_status(), GameStatus::Active);
+}
+
+#[test]
+fn test_stale_expected_generation_rejected() {
+    let player = Principal::from_slice(&[6; 29]);
+    PLAYERS.with(|p| p.borrow_mut()[4] = Some(player));
+    BASES.with(|b| b.borrow_mut()[4] = Some(Base { x: 100, y: 100, coins: 0 }));
+    WALLETS.with(|w| w.borrow_mut().insert(player, 50));
+    GENERATION.with(|g| *g.borrow_mut() = 1_000);
+
+    let err = place_cells_internal(player, vec![(102, 102)], Some(1_000 - STALE_VIEW_TOLERANCE - 1), 0).unwrap_err();
+    assert!(err.starts_with("Stale view: board is at generation 1000"), "{}", err);
+    assert!(place_cells_internal(player, vec![(102, 102)], Some(1_000 + STALE_VIEW_TOLERANCE + 1), 0).is_err());
+    assert!(!is_alive(102, 102));
+    assert_eq!(WALLETS.with(|w| w.borrow()[&player]), 50, "Nothing is charged");
+
+    // Within tolerance is accepted
+    assert_eq!(place_cells_internal(player, vec![(102, 102)], Some(1_000 - STALE_VIEW_TOLERANCE), 0), Ok(1));
+}
+
+#[test]
+fn test_lenient_expected_generation() {
+    let player = Principal::from_slice(&[6; 29]);
+    PLAYERS.with(|p| p.borrow_mut()[4] = Some(player));
+    BASES.with(|b| b.borrow_mut()[4] = Some(Base { x: 100, y: 100, coins: 0 }));
+    WALLETS.with(|w| w.borrow_mut().insert(player, 50));
+    GENERATION.with(|g| *g.borrow_mut() = 1_000);
+
+    // 0 and None skip the check however far behind the board is
+    assert_eq!(place_cells_internal(player, vec![(102, 102)], Some(0), 0), Ok(1));
+    assert_eq!(place_cells_internal(player, vec![(103, 102)], None, 0), Ok(1));
+}
+
+// =============================================================================
+// WALLS
+// =============================================================================
+
+/// Place a horizontal blinker centred at (x, y) and seed POTENTIAL
+fn place_blinker(x: u16, y: u16) {
+    for dx in [0u16, 1, 2] {
+        set_alive(x - 1 + dx, y);
+    }
+    rebuild_potential_from_alive();
+}
+
+#[test]
+fn test_wall_survives_generations() {
+    set_wall(50, 50);
+    // Blinker right next to the wall: its cells oscillate around (50, 50) but never onto it
+    place_blinker(51, 51);
+
+    for _ in 0..10 {
+        step_generation();
+        assert!(is_wall(50, 50));
+        assert!(!is_alive(50, 50));
+    }
+}
+
+#[test]
+fn test_wall_blocks_birth() {
+    // Blinker at row 100 births (100, 99) and (100, 101) on the next generation
+    place_blinker(100, 100);
+    set_wall(100, 99);
+
+    step_generation();
+    assert!(!is_alive(100, 99), "Wall cell must never be born");
+    assert!(is_alive(100, 101));
+    assert!(is_wall(100, 99));
+}
+
+#[test]
+fn test_wall_survives_wipe() {
+    // Quadrant 0 covers (0..128, 0..128)
+    set_wall(10, 10);
+    set_alive(20, 20);
+
+    wipe_quadrant(0);
+    assert!(is_wall(10, 10));
+    assert!(!is_alive(20, 20));
+    assert_eq!(get_wall_cells(), vec![(10, 10)]);
+}
+
+#[test]
+fn test_place_walls_respects_game_state_guards() {
+    let player = Principal::from_slice(&[40; 29]);
+    PLAYERS.with(|p| p.borrow_mut()[0] = Some(player));
+    BASES.with(|b| b.borrow_mut()[0] = Some(Base { x: 10, y: 10, coins: 0 }));
+    WALLETS.with(|w| w.borrow_mut().insert(player, 1_000));
+
+    MIN_PLAYERS_TO_START.with(|m| *m.borrow_mut() = 2);
+    assert_eq!(
+        place_walls_internal(player, vec![(12, 12)], 0).unwrap_err(),
+        "Waiting for players: 1/2 joined"
+    );
+    GAME_STARTED.with(|s| *s.borrow_mut() = true);
+
+    // The base sits in quadrant 0, which was just wiped
+    LAST_WIPE_NS.with(|lw| *lw.borrow_mut() = 0);
+    NEXT_WIPE_QUADRANT.with(|q| *q.borrow_mut() = 1);
+    let err = place_walls_internal(player, vec![(12, 12)], 1_000_000_000).unwrap_err();
+    assert!(err.starts_with("Quadrant 0 was just wiped"), "{}", err);
+
+    GENERATION_LIMIT.with(|l| *l.borrow_mut() = Some(0));
+    let err = place_walls_internal(player, vec![(12, 12)], DEFAULT_PLACEMENT_LOCKOUT_NS).unwrap_err();
+    assert!(err.starts_with("Game over"), "{}", err);
+    assert!(!is_wall(12, 12));
+    assert_eq!(WALLETS.with(|w| w.borrow()[&player]), 1_000);
+
+    GENERATION_LIMIT.with(|l| *l.borrow_mut() = None);
+    assert_eq!(place_walls_internal(player, vec![(12, 12)], DEFAULT_PLACEMENT_LOCKOUT_NS), Ok(1));
+    assert!(is_wall(12, 12));
+}
+
+// =============================================================================
+// ADAPTIVE TICK RATE
+// =============================================================================
+
+#[test]
+fn test_tick_rate_downshift_and_upshift() {
+    assert_eq!(current_tick_interval_ms(), TICK_INTERVAL_MS);
+
+    // Quiet ticks below the threshold: stay fast until the limit is reached
+    for _ in 0..QUIET_TICKS_BEFORE_DOWNSHIFT - 1 {
+        assert!(!update_tick_rate(QUIET_POTENTIAL_THRESHOLD - 1));
+    }
+    assert_eq!(current_tick_interval_ms(), TICK_INTERVAL_MS);
+    assert!(update_tick_rate(0), "Should downshift after enough quiet ticks");
+    assert_eq!(current_tick_interval_ms(), SLOW_TICK_INTERVAL_MS);
+    assert!(!update_tick_rate(0), "Already slow");
+
+    // Activity resumes
+    assert!(update_tick_rate(QUIET_POTENTIAL_THRESHOLD));
+    assert_eq!(current_tick_interval_ms(), TICK_INTERVAL_MS);
+
+    // A busy tick resets the quiet streak
+    for _ in 0..QUIET_TICKS_BEFORE_DOWNSHIFT - 1 {
+        update_tick_rate(0);
+    }
+    update_tick_rate(QUIET_POTENTIAL_THRESHOLD);
+    assert!(!update_tick_rate(0));
+}
+
+#[test]
+fn test_tick_rate_upshift_on_placement() {
+    for _ in 0..QUIET_TICKS_BEFORE_DOWNSHIFT {
+        update_tick_rate(0);
+    }
+    assert_eq!(current_tick_interval_ms(), SLOW_TICK_INTERVAL_MS);
+
+    assert!(upshift_on_activity());
+    assert_eq!(current_tick_interval_ms(), TICK_INTERVAL_MS);
+    assert!(!upshift_on_activity(), "Already fast");
+}
+
+#[test]
+fn test_wipe_schedule_stays_on_wall_clock() {
+    let last = 1_000;
+    // A tick that lands 3s late still anchors the next wipe to the interval boundary
+    assert_eq!(next_wipe_anchor(last, last + WIPE_INTERVAL_NS + 3_000_000_000), last + WIPE_INTERVAL_NS);
+    // Long pause: one wipe, schedule stays aligned
+    assert_eq!(next_wipe_anchor(last, last + 5 * WIPE_INTERVAL_NS + 7), last + 5 * WIPE_INTERVAL_NS);
+}
+
+#[test]
+fn test_predicted_wipe_matches_actual_wipe() {
+    const T: u64 = 1_000 * WIPE_INTERVAL_NS;
+    const TICK_NS: u64 = TICK_INTERVAL_MS * 1_000_000;
+    place_blinker(300, 300); // Quadrant 10: survives the wipe of quadrant 0
+    LAST_ACTIVITY_NS.with(|la| *la.borrow_mut() = T);
+    LAST_TICK_NS.with(|t| *t.borrow_mut() = T);
+    // Due 4.5s from now, i.e. between two ticks
+    LAST_WIPE_NS.with(|lw| *lw.borrow_mut() = T + 4_500_000_000 - WIPE_INTERVAL_NS);
+    let start_generation = GENERATION.with(|g| *g.borrow());
+
+    let (wipe_at, wipe_generation) = predict_next_wipe(
+        LAST_WIPE_NS.with(|lw| *lw.borrow()), T, start_generation, TICK_INTERVAL_MS, true,
+    );
+    assert_eq!(wipe_at, T + 5 * TICK_NS, "The wipe lands on the first tick after it is due");
+    assert_eq!(wipe_generation, start_generation + 5 * GENERATIONS_PER_TICK as u64);
+
+    let mut now = T;
+    while NEXT_WIPE_QUADRANT.with(|q| *q.borrow()) == 0 {
+        now += TICK_NS;
+        tick_at(now);
+        assert!(now <= wipe_at, "Wipe ran later than predicted");
+    }
+    assert_eq!(now, wipe_at);
+    assert_eq!(GENERATION.with(|g| *g.borrow()), wipe_generation);
+
+    // A frozen board wipes exactly when due, at its current generation
+    assert_eq!(predict_next_wipe(T, T + 7, 42, TICK_INTERVAL_MS, false), (T + WIPE_INTERVAL_NS, 42));
+}
+
+
+// =============================================================================
+// IDLE FREEZE
+// =============================================================================
+
+const FROZEN_AT: u64 = 10 * IDLE_FREEZE_NS;
+
+#[test]
+fn test_placement_unfreezes_and_tick_resumes() {
+    let player = Principal::from_slice(&[9; 29]);
+    PLAYERS.with(|p| p.borrow_mut()[3] = Some(player));
+    BASES.with(|b| b.borrow_mut()[3] = Some(Base { x: 100, y: 100, coins: 0 }));
+    WALLETS.with(|w| w.borrow_mut().insert(player, 50));
+    LAST_WIPE_NS.with(|lw| *lw.borrow_mut() = FROZEN_AT);
+    place_blinker(400, 400);
+
+    // Nobody has acted for the idle window: the board freezes
+    assert_eq!(tick_at(FROZEN_AT), TickOutcome::Freeze);
+    let frozen_generation = GENERATION.with(|g| *g.borrow());
+
+    // place_cells records activity before placing (and restarts the timer)
+    let now = FROZEN_AT + 1_000_000_000;
+    LAST_ACTIVITY_NS.with(|la| *la.borrow_mut() = now);
+    let block = vec![(102, 102), (103, 102), (102, 103), (103, 103)];
+    assert_eq!(place_cells_internal(player, block.clone(), None, now), Ok(4));
+
+    assert_eq!(tick_at(now + 1_000_000_000), TickOutcome::Keep);
+    assert_eq!(GENERATION.with(|g| *g.borrow()), frozen_generation + GENERATIONS_PER_TICK as u64);
+    assert!(block.iter().all(|&(x, y)| is_alive(x as u16, y as u16)));
+    // The blinker went through a whole number of periods
+    assert!(is_alive(399, 400) && is_alive(400, 400) && is_alive(401, 400));
+    assert!(!is_alive(400, 399));
+}
+
+#[test]
+fn test_frozen_board_still_wipes_on_schedule() {
+    LAST_WIPE_NS.with(|lw| *lw.borrow_mut() = FROZEN_AT);
+    place_blinker(20, 20); // Quadrant 0, next in the rotation
+
+    assert_eq!(tick_at(FROZEN_AT), TickOutcome::Freeze);
+
+    // Not due yet: nothing wiped, keep waiting
+    assert!(run_frozen_wipe(FROZEN_AT + WIPE_INTERVAL_NS - 1));
+    assert!(!is_board_empty());
+
+    // Due while frozen: the wipe runs and the rotation advances
+    assert!(!run_frozen_wipe(FROZEN_AT + WIPE_INTERVAL_NS), "Board is empty, no more wipes needed");
+    assert!(is_board_empty());
+    assert_eq!(NEXT_WIPE_QUADRANT.with(|q| *q.borrow()), 1);
+    assert_eq!(LAST_WIPE_NS.with(|lw| *lw.borrow()), FROZEN_AT + WIPE_INTERVAL_NS);
+}
+
+#[test]
+fn test_paused_board_does_not_wipe_while_frozen() {
+    LAST_WIPE_NS.with(|lw| *lw.borrow_mut() = FROZEN_AT);
+    place_blinker(20, 20);
+    IS_RUNNING.with(|r| *r.borrow_mut() = false);
+
+    assert!(!run_frozen_wipe(FROZEN_AT + WIPE_INTERVAL_NS));
+    assert!(!is_board_empty());
+    assert_eq!(NEXT_WIPE_QUADRANT.with(|q| *q.borrow()), 0);
+}
+
+// =============================================================================
+// GENERATION LIMIT
+// =============================================================================
+
+#[test]
+fn test_world_finishes_at_generation_limit() {
+    let admin = Principal::from_text(ADMIN_PRINCIPAL).unwrap();
+    let player = Principal::from_slice(&[5; 29]);
+    PLAYERS.with(|p| p.borrow_mut()[3] = Some(player));
+    BASES.with(|b| b.borrow_mut()[3] = Some(Base { x: 100, y: 100, coins: 0 }));
+    WALLETS.with(|w| w.borrow_mut().insert(player, 50));
+    LAST_ACTIVITY_NS.with(|la| *la.borrow_mut() = FROZEN_AT);
+    LAST_WIPE_NS.with(|lw| *lw.borrow_mut() = FROZEN_AT);
+    place_blinker(400, 400);
+
+    assert!(set_generation_limit_internal(player, Some(20)).is_err());
+    set_generation_limit_internal(admin, Some(20)).unwrap();
+    assert_eq!(get_generation_limit(), Some(20));
+
+    assert_eq!(tick_at(FROZEN_AT), TickOutcome::Keep);
+    assert_eq!(tick_at(FROZEN_AT), TickOutcome::Keep);
+    assert!(!is_finished());
+    // Last tick runs only the 4 generations left
+    assert_eq!(tick_at(FROZEN_AT), TickOutcome::Stop);
+    assert_eq!(GENERATION.with(|g| *g.borrow()), 20);
+    assert!(is_finished());
+    assert_eq!(tick_at(FROZEN_AT), TickOutcome::Stop);
+    assert_eq!(GENERATION.with(|g| *g.borrow()), 20, "A finished world never advances");
+
+    let err = place_cells_internal(player, vec![(102, 102)], None, FROZEN_AT).unwrap_err();
+    assert!(err.starts_with("Game over"), "{}", err);
+    assert!(!is_alive(102, 102));
+    assert!(set_generation_limit_internal(admin, Some(20)).is_err(), "Limit must be ahead of the board");
+
+    // Clearing the limit reopens placement
+    set_generation_limit_internal(admin, None).unwrap();
+    assert_eq!(place_cells_internal(player, vec![(102, 102)], None, FROZEN_AT), Ok(1));
+}
+
+// =============================================================================
+// PLACEMENT LOCKOUT AFTER WIPE
+// =============================================================================
+
+#[test]
+fn test_place_immediately_after_wipe_rejected() {
+    let wiped_at = 1_000_000_000_000;
+    LAST_WIPE_NS.with(|lw| *lw.borrow_mut() = wiped_at);
+    NEXT_WIPE_QUADRANT.with(|q| *q.borrow_mut() = 1); // Quadrant 0 was just wiped
+    assert_eq!(last_wiped_quadrant(), 0);
+
+    // Quadrant 0 is locked, 2s into a 5s window => 3s remaining
+    let err = check_placement_lockout(&[(10, 10)], wiped_at + 2_000_000_000).unwrap_err();
+    assert!(err.contains("Quadrant 0") && err.contains("3s"), "{}", err);
+
+    // Any cell in the wiped quadrant rejects the whole batch; other quadrants are open
+    assert!(check_placement_lockout(&[(200, 10), (127, 127)], wiped_at).is_err());
+    assert!(check_placement_lockout(&[(200, 10)], wiped_at).is_ok());
+
+    // Window elapsed
+    assert!(check_placement_lockout(&[(10, 10)], wiped_at + DEFAULT_PLACEMENT_LOCKOUT_NS).is_ok());
+}
+
+#[test]
+fn test_placement_lockout_admin_config() {
+    let admin = Principal::from_text(ADMIN_PRINCIPAL).unwrap();
+    assert!(set_placement_lockout_internal(Principal::anonymous(), 10).is_err());
+    assert!(set_placement_lockout_internal(admin, MAX_PLACEMENT_LOCKOUT_NS / 1_000_000_000 + 1).is_err());
+
+    LAST_WIPE_NS.with(|lw| *lw.borrow_mut() = 0);
+    NEXT_WIPE_QUADRANT.with(|q| *q.borrow_mut() = 0); // Quadrant 15 was just wiped
+
+    set_placement_lockout_internal(admin, 0).unwrap();
+    assert!(check_placement_lockout(&[(500, 500)], 0).is_ok(), "Zero disables the lockout");
+
+    set_placement_lockout_internal(admin, 30).unwrap();
+    assert_eq!(get_placement_lockout(), 30);
+    assert!(check_placement_lockout(&[(500, 500)], 29_000_000_000).is_err());
+}
+
+// =============================================================================
+// UPGRADE SNAPSHOT
+// =============================================================================
+
+#[test]
+fn test_upgrade_snapshot_roundtrip() {
+    set_alive(10, 20);
+    set_alive(300, 400);
+    set_territory(1, 10, 20);
+    GENERATION.with(|g| *g.borrow_mut() = 77);
+
+    let state = snapshot_state();
+    assert_eq!(state.alive_checksum, Some(2));
+
+    // Round-trip through candid as stable_save/stable_restore would
+    let bytes = candid::encode_one(&state).unwrap();
+    let restored: PersistedState = candid::decode_one(&bytes).unwrap();
+
+    ALIVE.with(|a| a.borrow_mut().fill(0));
+    GENERATION.with(|g| *g.borrow_mut() = 0);
+    restore_state(restored).unwrap();
+
+    assert!(is_alive(10, 20));
+    assert!(is_alive(300, 400));
+    assert_eq!(get_alive_cell_count(), 2);
+    assert_eq!(GENERATION.with(|g| *g.borrow()), 77);
+    assert!(player_owns(1, 10, 20));
+}
+
+#[test]
+fn test_upgrade_restore_detects_checksum_mismatch() {
+    set_alive(10, 20);
+    let mut state = snapshot_state();
+
+    // Drop a live cell from the saved bitmap without updating the checksum
+    state.alive[coords_to_idx(10, 20) >> 6] = 0;
+    let err = restore_state(state).unwrap_err();
+    assert!(err.contains("saved 1, restored 0"), "{}", err);
+
+    // Snapshots from before the checksum existed restore without complaint
+    let mut legacy = snapshot_state();
+    legacy.alive_checksum = None;
+    assert!(restore_state(legacy).is_ok());
+}
+
+// =============================================================================
+// JOIN COST
+// =============================================================================
+
+#[test]
+fn test_one_slot_per_principal() {
+    let player = Principal::from_slice(&[4; 29]);
+    WALLETS.with(|w| w.borrow_mut().insert(player, 1_000));
+
+    assert_eq!(join_game_internal(player, 10, 10, 0), Ok(0));
+    let err = join_game_internal(player, 200, 200, 1).unwrap_err();
+    assert_eq!(err, "Already in game");
+
+    assert_eq!(PLAYERS.with(|p| p.borrow()[1]), None);
+    assert_eq!(WALLETS.with(|w| w.borrow()[&player]), 1_000 - DEFAULT_JOIN_COST);
+}
+
+#[test]
+fn test_join_cost_moves_into_base_treasury() {
+    let admin = Principal::from_text(ADMIN_PRINCIPAL).unwrap();
+    let player = Principal::from_slice(&[5; 29]);
+    let poor = Principal::from_slice(&[6; 29]);
+    assert!(set_join_cost_internal(player, 250).is_err());
+    assert!(set_join_cost_internal(admin, 0).is_err());
+    assert!(set_join_cost_internal(admin, MAX_JOIN_COST + 1).is_err());
+    set_join_cost_internal(admin, 250).unwrap();
+    assert_eq!(get_join_cost(), 250);
+
+    WALLETS.with(|w| {
+        w.borrow_mut().insert(player, 300);
+        w.borrow_mut().insert(poor, 249);
+    });
+    assert_eq!(join_game_internal(poor, 200, 200, 1).unwrap_err(), "Need 250 coins to join");
+
+    join_game_internal(player, 10, 10, 0).unwrap();
+    assert_eq!(WALLETS.with(|w| w.borrow()[&player]), 50);
+    assert_eq!(BASES.with(|b| b.borrow()[0].as_ref().map(|base| base.coins)), Some(250));
+
+    // Freeing the slot forfeits the treasury
+    eliminate_player(0);
+    assert_eq!(find_player_slot(player), None);
+    assert_eq!(WALLETS.with(|w| w.borrow()[&player]), 50);
+}
+
+#[test]
+fn test_elimination_leaves_no_coins_on_grid() {
+    let leaver = Principal::from_slice(&[8; 29]);
+    let stayer = Principal::from_slice(&[9; 29]);
+    WALLETS.with(|w| {
+        w.borrow_mut().insert(leaver, 1_000);
+        w.borrow_mut().insert(stayer, 1_000);
+    });
+    let supply = || -> u64 {
+        let wallets: u64 = WALLETS.with(|w| w.borrow().values().sum());
+        let treasuries: u64 = BASES.with(|b| b.borrow().iter().flatten().map(|base| base.coins).sum());
+        wallets + treasuries
+    };
+
+    join_game_internal(leaver, 10, 10, 0).unwrap();
+    join_game_internal(stayer, 200, 200, 1).unwrap();
+    place_cells_internal(leaver, vec![(12, 12), (13, 12), (14, 12)], None, 0).unwrap();
+    assert_eq!(supply(), 2_000);
+
+    // Placement fees sit in the treasury, never on the cells themselves
+    let treasury = BASES.with(|b| b.borrow()[0].as_ref().unwrap().coins);
+    assert_eq!(WALLETS.with(|w| w.borrow()[&leaver]) + treasury, 1_000);
+
+    eliminate_player(0);
+
+    // The treasury is the only thing forfeited; every other coin is where it was
+    assert_eq!(supply(), 2_000 - treasury);
+    assert_eq!(WALLETS.with(|w| w.borrow()[&leaver]), 1_000 - treasury);
+    assert_eq!(find_owner(12, 12), None);
+    assert!(!is_alive(12, 12));
+}
+
+#[test]
+fn test_alternating_capture_mints_no_coins() {
+    let attacker = Principal::from_slice(&[10; 29]);
+    let defender = Principal::from_slice(&[11; 29]);
+    WALLETS.with(|w| {
+        w.borrow_mut().insert(attacker, 1_000);
+        w.borrow_mut().insert(defender, 1_000);
+    });
+    // Bases either side of the quadrant 0/1 border, with a 4-cell gap between them
+    join_game_internal(attacker, 118, 10, 0).unwrap();
+    join_game_internal(defender, 130, 10, 1).unwrap();
+    // Vertical blinker on the attacker's base edge: its horizontal phase births (126, 12)
+    place_cells_internal(attacker, vec![(125, 11), (125, 12), (125, 13)], None, 0).unwrap();
+    place_cells_internal(defender, vec![(132, 12), (133, 12), (132, 13), (133, 13)], None, 0).unwrap();
+    // Defender territory across the gap, connected to its base
+    for x in 126..130 {
+        set_territory(1, x, 12);
+    }
+
+    let coins = || -> Vec<u64> {
+        let mut coins: Vec<u64> = WALLETS.with(|w| w.borrow().values().copied().collect());
+        coins.extend(BASES.with(|b| b.borrow().iter().flatten().map(|base| base.coins).collect::<Vec<_>>()));
+        coins
+    };
+    let start = coins();
+
+    for cycle in 0..50 {
+        step_generation();
+        assert!(is_alive(126, 12), "Cycle {}: blinker tip should be born", cycle);
+        assert_eq!(find_owner(126, 12), Some(0), "Cycle {}: birth captures the cell", cycle);
+        step_generation();
+        assert!(!is_alive(126, 12));
+
+        // Hand the dead cell back so the next birth captures it again
+        clear_territory(0, 126, 12);
+        set_territory(1, 126, 12);
+        assert_eq!(coins(), start, "Cycle {}: capture moved coins", cycle);
+    }
+    assert_eq!(find_owner(129, 12), Some(1), "Defender strip stays connected");
+}
+
+#[test]
+fn test_starting_grant_places_free_cells() {
+    let admin = Principal::from_text(ADMIN_PRINCIPAL).unwrap();
+    let player = Principal::from_slice(&[7; 29]);
+    WALLETS.with(|w| w.borrow_mut().insert(player, 1_000));
+    assert_eq!(get_starting_grant(), 0);
+    assert!(set_starting_grant_internal(player, 6).is_err());
+    assert!(set_starting_grant_internal(admin, MAX_STARTING_GRANT + 1).is_err());
+    set_starting_grant_internal(admin, 6).unwrap();
+
+    join_game_internal(player, 10, 10, 0).unwrap();
+
+    // Six cells fill the core row by row: (12..16, 12) then (12..14, 13)
+    for (x, y) in [(12, 12), (13, 12), (14, 12), (15, 12), (12, 13), (13, 13)] {
+        assert!(is_alive(x, y), "({}, {}) should be granted", x, y);
+        assert_eq!(find_owner(x, y), Some(0));
+    }
+    assert!(!is_alive(14, 13));
+    assert_eq!(get_alive_cell_count(), 6);
+    assert_eq!(CELL_COUNTS.with(|cc| cc.borrow()[0]), 6);
+    assert!(POTENTIAL.with(|p| p.borrow()[coords_to_idx(12, 12) / 64] != 0), "Next tick must see the grant");
+    // Only the join cost is charged
+    assert_eq!(WALLETS.with(|w| w.borrow()[&player]), 1_000 - DEFAULT_JOIN_COST);
+}
+
+#[test]
+fn test_join_up_to_configured_player_cap() {
+    let admin = Principal::from_text(ADMIN_PRINCIPAL).unwrap();
+    let player = |id: u8| Principal::from_slice(&[id; 29]);
+    assert!(set_max_players_internal(player(1), 4).is_err());
+    assert!(set_max_players_internal(admin, MIN_PLAYER_CAP - 1).is_err());
+    assert!(set_max_players_internal(admin, MAX_PLAYERS as u8 + 1).is_err());
+    set_max_players_internal(admin, 4).unwrap();
+    assert_eq!(get_max_players(), 4);
+
+    // One base per quadrant: quadrant q has its top-left corner at (q % 4, q / 4) * 128
+    let join = |id: u8, quadrant: i32, slot: u8| {
+        WALLETS.with(|w| w.borrow_mut().insert(player(id), 1_000));
+        join_game_internal(player(id), (quadrant % 4) * 128 + 10, (quadrant / 4) * 128 + 10, slot)
+    };
+    for slot in 0..4 {
+        assert_eq!(join(10 + slot, slot as i32, slot), Ok(slot));
+    }
+    assert_eq!(join(20, 4, 4).unwrap_err(), "Invalid slot 4 (max 3)");
+
+    // Lowering the cap keeps existing players and only blocks new joins
+    set_max_players_internal(admin, 2).unwrap();
+    eliminate_player(0);
+    assert_eq!(join(21, 5, 0).unwrap_err(), "Game is full (2 players)");
+    assert_eq!(find_player_slot(player(13)), Some(3));
+    assert_eq!(snapshot_state().max_players, Some(2));
+
+    // Slots past the old default of 8 are usable once the cap allows them
+    set_max_players_internal(admin, MAX_PLAYERS as u8).unwrap();
+    assert_eq!(join(22, 15, MAX_PLAYERS as u8 - 1), Ok(MAX_PLAYERS as u8 - 1));
+    assert_eq!(PLAYERS.with(|p| p.borrow().iter().flatten().count()), 4);
+}
+
+// =============================================================================
+// TRUE GENERATION
+// =============================================================================
+
+#[test]
+fn test_true_generation_never_decreases() {
+    let step = GENERATIONS_PER_TICK as u64;
+    let ms = 1_000_000u64;
+    let mut stored = 0u64;
+    let mut last_tick = 0u64;
+    let mut previous = 0u64;
+
+    // Ticks at 1s, 2s, a late tick at 3.7s, then 4.7s
+    let ticks = [1_000 * ms, 2_000 * ms, 3_700 * ms, 4_700 * ms];
+    let mut next_tick = 0;
+    for now in (0..5_000 * ms).step_by(37 * ms as usize) {
+        while next_tick < ticks.len() && ticks[next_tick] <= now {
+            stored += step;
+            last_tick = ticks[next_tick];
+            next_tick += 1;
+        }
+        let true_gen = true_generation(stored, last_tick, now, TICK_INTERVAL_MS, true);
+        assert!(true_gen >= previous, "Decreased at {}ns: {} < {}", now, true_gen, previous);
+        assert!(true_gen >= stored && true_gen <= stored + step);
+        previous = true_gen;
+    }
+
+    // Half a tick in: half the generations; stopped boards do not extrapolate
+    assert_eq!(true_generation(40, 0, 500 * ms, TICK_INTERVAL_MS, true), 40 + step / 2);
+    assert_eq!(true_generation(40, 0, 500 * ms, TICK_INTERVAL_MS, false), 40);
+}
+
+// =============================================================================
+// ECONOMY CONFIG
+// =============================================================================
+
+#[test]
+fn test_siege_damage_change_applies_to_next_blocked_birth() {
+    let admin = Principal::from_text(ADMIN_PRINCIPAL).unwrap();
+    let defender = Principal::from_slice(&[7; 29]);
+    let attacker = Principal::from_slice(&[8; 29]);
+    PLAYERS.with(|p| {
+        p.borrow_mut()[0] = Some(defender);
+        p.borrow_mut()[1] = Some(attacker);
+    });
+    BASES.with(|b| {
+        b.borrow_mut()[0] = Some(Base { x: 100, y: 100, coins: 500 });
+        b.borrow_mut()[1] = Some(Base { x: 300, y: 300, coins: 500 });
+    });
+    let inside_defender_base = coords_to_idx(103, 103);
+
+    apply_changes(&[(inside_defender_base, Some(1))], &[], &[]);
+    assert_eq!(WALLETS.with(|w| w.borrow()[&attacker]), DEFAULT_SIEGE_DAMAGE);
+
+    assert!(set_siege_damage_internal(attacker, 50).is_err());
+    assert!(set_siege_damage_internal(admin, 0).is_err());
+    assert!(set_siege_damage_internal(admin, MAX_SIEGE_DAMAGE + 1).is_err());
+    set_siege_damage_internal(admin, 50).unwrap();
+
+    apply_changes(&[(inside_defender_base, Some(1))], &[], &[]);
+    assert!(!is_alive(103, 103), "Birth in an enemy base is blocked");
+    assert_eq!(WALLETS.with(|w| w.borrow()[&attacker]), DEFAULT_SIEGE_DAMAGE + 50);
+    assert_eq!(BASES.with(|b| b.borrow()[0].as_ref().unwrap().coins), 500 - DEFAULT_SIEGE_DAMAGE - 50);
+    assert_eq!(get_economy().siege_damage, 50);
+}
+
+#[test]
+fn test_placement_cost_config() {
+    let admin = Principal::from_text(ADMIN_PRINCIPAL).unwrap();
+    let player = Principal::from_slice(&[9; 29]);
+    assert!(set_placement_cost_internal(admin, 0).is_err());
+    assert!(set_placement_cost_internal(admin, MAX_PLACEMENT_COST + 1).is_err());
+    set_placement_cost_internal(admin, 5).unwrap();
+
+    PLAYERS.with(|p| p.borrow_mut()[2] = Some(player));
+    BASES.with(|b| b.borrow_mut()[2] = Some(Base { x: 100, y: 100, coins: 100 }));
+    WALLETS.with(|w| w.borrow_mut().insert(player, 12));
+
+    assert_eq!(place_cells_internal(player, vec![(102, 102), (103, 102), (104, 102)], None, 0).unwrap_err(), "Insufficient coins");
+    assert_eq!(place_cells_internal(player, vec![(102, 102), (103, 102)], None, 0), Ok(2));
+    assert_eq!(WALLETS.with(|w| w.borrow()[&player]), 2);
+    assert_eq!(BASES.with(|b| b.borrow()[2].as_ref().unwrap().coins), 110);
+}
+
+// =============================================================================
+// PAGED STATE
+// =============================================================================
+
+#[test]
+fn test_state_pages_reproduce_full_cell_set() {
+    use std::collections::HashSet;
+
+    set_alive(10, 20);
+    set_territory(1, 10, 20);
+    set_alive(300, 400); // Alive, unowned
+    set_territory(2, 5, 500); // Owned, dead
+    set_territory(2, 6, 500);
+    set_alive(511, 511);
+    set_territory(0, 0, 0);
+
+    // Expected set decoded from the same bitmaps get_state exports
+    let mut expected = HashSet::new();
+    for (x, y) in get_alive_cells() {
+        expected.insert(PagedCell { x, y, alive: true, owner: find_owner(x, y).map(|o| o as u8) });
+    }
+    for player in 0..MAX_PLAYERS {
+        for idx in 0..TOTAL_CELLS {
+            let (x, y) = idx_to_coords(idx);
+            if player_owns(player, x, y) && !is_alive(x, y) {
+                expected.insert(PagedCell { x, y, alive: false, owner: Some(player as u8) });
+            }
+        }
+    }
+    assert_eq!(expected.len(), 6);
+
+    let mut pages = Vec::new();
+    let mut offset = 0;
+    loop {
+        let page = get_state_paged(offset, 4);
+        assert_eq!(page.total, 6);
+        assert_eq!(page.slots.is_some(), offset == 0, "Metadata only on the first page");
+        offset += page.cells.len() as u32;
+        pages.extend(page.cells);
+        if !page.has_more {
+            break;
+        }
+    }
+
+    assert_eq!(pages.len(), 6, "No cell repeated or skipped across pages");
+    assert_eq!(pages.into_iter().collect::<HashSet<_>>(), expected);
+    assert!(get_state_paged(6, 4).cells.is_empty());
+}
+
+// =============================================================================
+// SPECTATOR VIEW
+// =============================================================================
+
+#[test]
+fn test_spectator_view_heatmap_and_standings() {
+    set_alive(0, 0);
+    set_alive(63, 63);
+    set_alive(64, 0);
+    set_alive(511, 511);
+
+    let heatmap = alive_heatmap();
+    assert_eq!(heatmap.len(), TOTAL_CHUNKS);
+    assert_eq!(heatmap[0], 2);
+    assert_eq!(heatmap[1], 1);
+    assert_eq!(heatmap[TOTAL_CHUNKS - 1], 1);
+    assert_eq!(heatmap.iter().sum::<u32>(), get_alive_cell_count());
+
+    let (a, b) = (Principal::from_slice(&[7; 29]), Principal::from_slice(&[8; 29]));
+    PLAYERS.with(|p| {
+        p.borrow_mut()[1] = Some(a);
+        p.borrow_mut()[3] = Some(b);
+    });
+    set_territory(1, 10, 10);
+    for x in 100..103 {
+        set_territory(3, x, 100);
+    }
+    CELL_COUNTS.with(|cc| cc.borrow_mut()[1] = 1);
+
+    let view = get_spectator_view();
+    assert_eq!(view.heatmap, heatmap);
+    let order: Vec<u8> = view.standings.iter().map(|s| s.slot).collect();
+    assert_eq!(order, vec![3, 1], "Most territory first");
+    assert_eq!(view.standings[0].principal, b);
+    assert_eq!(view.standings[0].territory_cells, 3);
+    assert_eq!(view.standings[1].alive_cells, 1);
+}
+
+// =============================================================================
+// COIN TRANSFERS
+// =============================================================================
+
+#[test]
+fn test_transfer_coins_conserves_supply() {
+    let (alice, bob) = (Principal::from_slice(&[9; 29]), Principal::from_slice(&[10; 29]));
+    WALLETS.with(|w| {
+        w.borrow_mut().insert(alice, 500);
+        w.borrow_mut().insert(bob, 20);
+    });
+    let supply = || WALLETS.with(|w| w.borrow().values().sum::<u64>());
+    let before = supply();
+
+    assert_eq!(transfer_coins_internal(alice, bob, 150), Ok(350));
+    assert_eq!(WALLETS.with(|w| w.borrow()[&bob]), 170);
+    assert_eq!(supply(), before);
+
+    assert!(transfer_coins_internal(alice, bob, 351).unwrap_err().contains("Insufficient balance"));
+    assert!(transfer_coins_internal(alice, alice, 1).is_err());
+    assert!(transfer_coins_internal(Principal::anonymous(), bob, 1).is_err());
+    assert!(transfer_coins_internal(alice, Principal::anonymous(), 1).is_err());
+    assert!(transfer_coins_internal(alice, bob, 0).is_err());
+    assert_eq!(WALLETS.with(|w| w.borrow()[&alice]), 350);
+    assert_eq!(supply(), before);
+}
+
+// =============================================================================
+// SOUP SEEDING
+// =============================================================================
+
+#[test]
+fn test_seed_random_soup_density() {
+    let admin = Principal::from_text(ADMIN_PRINCIPAL).unwrap();
+    let player = Principal::from_slice(&[7; 29]);
+    assert!(seed_soup_internal(player, 30, None, [1; 32]).is_err());
+    assert!(seed_soup_internal(admin, 0, None, [1; 32]).is_err());
+    assert!(seed_soup_internal(admin, MAX_SOUP_DENSITY_PERCENT + 1, None, [1; 32]).is_err());
+    assert!(seed_soup_internal(admin, 30, Some((0, 0, 0, 10)), [1; 32]).is_err());
+
+    // A 100x100 region at 30% should land close to 3,000 cells
+    let seeded = seed_soup_internal(admin, 30, Some((100, 100, 100, 100)), [42; 32]).unwrap();
+    assert!((2_700..=3_300).contains(&seeded), "Seeded {} cells", seeded);
+
+    let alive = get_alive_cells();
+    assert_eq!(alive.len(), seeded as usize);
+    assert!(alive.iter().all(|&(x, y)| (100..200).contains(&x) && (100..200).contains(&y)));
+    assert!(alive.iter().all(|&(x, y)| find_owner(x, y).is_none()), "Seeded cells are neutral");
+    assert!(CELL_COUNTS.with(|cc| cc.borrow().iter().all(|&c| c == 0)));
+
+    // Every seeded cell is queued for the next tick
+    assert!(alive.iter().all(|&(x, y)| {
+        let idx = coords_to_idx(x, y);
+        POTENTIAL.with(|p| (p.borrow()[idx >> 6] >> (idx & 63)) & 1 == 1)
+    }));
+}
+
+#[test]
+fn test_soup_offspring_stay_unowned() {
+    let admin = Principal::from_text(ADMIN_PRINCIPAL).unwrap();
+    let (first, second) = (Principal::from_slice(&[7; 29]), Principal::from_slice(&[8; 29]));
+    PLAYERS.with(|p| {
+        p.borrow_mut()[0] = Some(first);
+        p.borrow_mut()[1] = Some(second);
+    });
+    WALLETS.with(|w| {
+        w.borrow_mut().insert(first, 1_000);
+        w.borrow_mut().insert(second, 1_000);
+    });
+    // Slot 0 sits far away; slot 1's base borders the soup, so soup births reach its zone
+    BASES.with(|b| {
+        b.borrow_mut()[0] = Some(Base { x: 400, y: 400, coins: 500 });
+        b.borrow_mut()[1] = Some(Base { x: 200, y: 140, coins: 500 });
+    });
+
+    seed_soup_internal(admin, 40, Some((100, 100, 100, 100)), [42; 32]).unwrap();
+    for _ in 0..20 {
+        step_generation();
+    }
+
+    assert!(!get_alive_cells().is_empty(), "The soup is still evolving");
+    assert!(get_alive_cells().iter().all(|&(x, y)| find_owner(x, y).is_none()), "Offspring are unowned");
+    assert!(CELL_COUNTS.with(|cc| cc.borrow().iter().all(|&c| c == 0)));
+    assert!((0..MAX_PLAYERS).all(|slot| territory_cells(slot) == 0));
+    assert_eq!(WALLETS.with(|w| (w.borrow()[&first], w.borrow()[&second])), (1_000, 1_000));
+    assert_eq!(BASES.with(|b| b.borrow()[1].as_ref().unwrap().coins), 500, "No siege from soup");
+}
+
+// =============================================================================
+// TERRITORY DECAY
+// =============================================================================
+
+#[test]
+fn test_inactive_player_territory_decays_over_ticks() {
+    let admin = Principal::from_text(ADMIN_PRINCIPAL).unwrap();
+    assert!(set_territory_decay_rate_internal(Principal::from_slice(&[9; 29]), 10).is_err());
+    assert!(set_territory_decay_rate_internal(admin, MAX_TERRITORY_DECAY_RATE + 1).is_err());
+    set_territory_decay_rate_internal(admin, 10).unwrap();
+
+    // Slot 0 is idle, slot 1 placed recently; both own 30 dead cells outside their bases
+    let now = TERRITORY_DECAY_AFTER_NS + 1_000;
+    for (slot, base_x) in [(0usize, 10u16), (1, 300)] {
+        BASES.with(|b| b.borrow_mut()[slot] = Some(Base { x: base_x, y: 10, coins: 100 }));
+        for dy in 0..BASE_SIZE {
+            for dx in 0..BASE_SIZE {
+                set_territory(slot, base_x + dx, 10 + dy);
+            }
+        }
+        for x in 0..30 {
+            set_territory(slot, base_x + x, 40);
+        }
+    }
+    LAST_ACTIVE_NS.with(|la| la.borrow_mut()[1] = now - 1);
+    set_alive(15, 40); // Live cell on the idle player's territory
+
+    let base_area = (BASE_SIZE * BASE_SIZE) as u32;
+    assert_eq!(count_territory_cells(0), base_area + 30);
+
+    decay_inactive_territory(now);
+    assert_eq!(count_territory_cells(0), base_area + 20);
+    decay_inactive_territory(now);
+    decay_inactive_territory(now);
+    assert_eq!(count_territory_cells(0), base_area + 1, "Only the live cell and base remain");
+    decay_inactive_territory(now);
+    assert_eq!(count_territory_cells(0), base_area + 1);
+    assert_eq!(find_owner(15, 40), Some(0));
+    assert!(is_alive(15, 40));
+    assert_eq!(find_owner(20, 40), None);
+
+    // The active player keeps everything; rate 0 disables decay
+    assert_eq!(count_territory_cells(1), base_area + 30);
+    set_territory_decay_rate_internal(admin, 0).unwrap();
+    decay_inactive_territory(now + TERRITORY_DECAY_AFTER_NS);
+    assert_eq!(count_territory_cells(1), base_area + 30);
+}
+
+#[test]
+fn test_decay_keeps_live_cells_connected_to_base() {
+    let admin = Principal::from_text(ADMIN_PRINCIPAL).unwrap();
+    set_territory_decay_rate_internal(admin, MAX_TERRITORY_DECAY_RATE).unwrap();
+    let now = TERRITORY_DECAY_AFTER_NS + 1_000;
+
+    // Base at (10, 10); a dead corridor along y = 12 leads to a live cell at (30, 12), and
+    // a dead branch along y = 14 leads nowhere
+    let base = Base { x: 10, y: 10, coins: 100 };
+    BASES.with(|b| b.borrow_mut()[0] = Some(base.clone()));
+    for dy in 0..BASE_SIZE {
+        for dx in 0..BASE_SIZE {
+            set_territory(0, 10 + dx, 10 + dy);
+        }
+    }
+    for x in 18..=30 {
+        set_territory(0, x, 12);
+    }
+    for x in 18..26 {
+        set_territory(0, x, 14);
+    }
+    set_alive(30, 12);
+    CELL_COUNTS.with(|cc| cc.borrow_mut()[0] = 1);
+
+    for _ in 0..3 {
+        decay_inactive_territory(now);
+    }
+
+    let base_area = (BASE_SIZE * BASE_SIZE) as u32;
+    assert_eq!(count_territory_cells(0), base_area + 13, "Only the dead branch decays");
+    assert!((18..=30).all(|x| find_owner(x, 12) == Some(0)), "The corridor to the live cell stays");
+    assert!((18..26).all(|x| find_owner(x, 14).is_none()));
+    assert!(is_alive(30, 12));
+    assert_eq!(CELL_COUNTS.with(|cc| cc.borrow()[0]), 1);
+
+    // The live cell is still reachable from the base through owned territory
+    BFS_WORKSPACE.with(|ws| {
+        let mut ws = ws.borrow_mut();
+        ws.clear();
+        assert!(bfs_find_unreached(&mut ws, 0, &base, &[(30, 12)]).is_empty());
+    });
+}
+
+// =============================================================================
+// ACTIVITY TELEMETRY
+// =============================================================================
+
+#[test]
+fn test_active_player_count_window() {
+    let now = 3_600_000_000_000;
+    for (slot, id, last_active) in [(0usize, 1u8, now - 30_000_000_000), (2, 2, now - 600_000_000_000)] {
+        PLAYERS.with(|p| p.borrow_mut()[slot] = Some(Principal::from_slice(&[id; 29])));
+        LAST_ACTIVE_NS.with(|la| la.borrow_mut()[slot] = last_active);
+    }
+    LAST_ACTIVE_NS.with(|la| la.borrow_mut()[5] = now); // Freed slot: not a player
+
+    assert_eq!(active_player_count(60, now), 1, "Only the action 30s ago is recent");
+    assert_eq!(active_player_count(600, now), 2);
+    assert_eq!(active_player_count(0, now), 0);
+}
+
+#[test]
+fn test_get_balance_of_any_principal() {
+    let player = Principal::from_slice(&[4; 29]);
+    PLAYERS.with(|p| p.borrow_mut()[1] = Some(player));
+    WALLETS.with(|w| w.borrow_mut().insert(player, 730));
+
+    assert_eq!(get_balance_of(player), 730);
+    assert_eq!(get_balance_of(Principal::from_slice(&[9; 29])), 0, "Unknown principal has no coins");
+}
+
+// =============================================================================
+// QUADRANT CONTROL
+// =============================================================================
+
+/// Claim `count` cells for `slot` in quadrant 5 (x 128..256, y 128..256), one row per 100 cells
+fn claim_in_quadrant_5(slot: usize, row: u16, count: u16) {
+    for i in 0..count {
+        set_territory(slot, 128 + i % 100, 128 + row + i / 100);
+    }
+}
+
+#[test]
+fn test_quadrant_info_empty() {
+    let info = get_quadrant_info(5).unwrap();
+    assert_eq!(info.controller, None);
+    assert!(!info.contested);
+    assert_eq!(info.leading_margin_percent, 0);
+    assert!(info.territory.iter().all(|&c| c == 0));
+    assert_eq!(get_quadrant_info(TOTAL_QUADRANTS), None);
+}
+
+#[test]
+fn test_quadrant_info_clearly_controlled() {
+    claim_in_quadrant_5(2, 0, 900);
+    claim_in_quadrant_5(3, 20, 100);
+    set_territory(4, 10, 10); // Quadrant 0 doesn't count
+
+    let info = get_quadrant_info(5).unwrap();
+    assert_eq!(info.territory[2], 900);
+    assert_eq!(info.territory[3], 100);
+    assert_eq!(info.territory[4], 0);
+    assert_eq!(info.controller, Some(2));
+    assert!(!info.contested);
+    assert_eq!(info.leading_margin_percent, 80);
+}
+
+#[test]
+fn test_quadrant_info_contested() {
+    claim_in_quadrant_5(1, 0, 450);
+    claim_in_quadrant_5(6, 20, 400);
+    claim_in_quadrant_5(7, 40, 150);
+
+    let info = get_quadrant_info(5).unwrap();
+    assert_eq!(info.controller, None);
+    assert!(info.contested);
+    assert_eq!(info.leading_margin_percent, 5);
+
+    // A wide lead without control is not contested
+    claim_in_quadrant_5(1, 60, 300);
+    let info = get_quadrant_info(5).unwrap();
+    assert_eq!(info.controller, None);
+    assert!(!info.contested);
+    assert_eq!(info.leading_margin_percent, 26);
+}
+
+#[test]
+fn test_quadrant_control_threshold_config() {
+    let admin = Principal::from_text(ADMIN_PRINCIPAL).unwrap();
+    assert!(set_quadrant_control_threshold_internal(Principal::anonymous(), 60).is_err());
+    assert!(set_quadrant_control_threshold_internal(admin, MIN_QUADRANT_CONTROL_PERCENT - 1).is_err());
+    assert!(set_quadrant_control_threshold_internal(admin, 101).is_err());
+
+    claim_in_quadrant_5(2, 0, 650);
+    claim_in_quadrant_5(3, 20, 350);
+    assert_eq!(get_quadrant_info(5).unwrap().controller, None, "65% is short of the default 80%");
+
+    // Lowering the threshold hands over control without any board change
+    set_quadrant_control_threshold_internal(admin, 60).unwrap();
+    assert_eq!(get_quadrant_control_threshold(), 60);
+    assert_eq!(get_quadrant_info(5).unwrap().controller, Some(2));
+
+    set_quadrant_control_threshold_internal(admin, 70).unwrap();
+    assert_eq!(get_quadrant_info(5).unwrap().controller, None);
+}
+
+#[test]
+fn test_quadrant_territory_cache_matches_rescan() {
+    let assert_cache_matches = |stage: &str| {
+        for q in 0..TOTAL_QUADRANTS {
+            assert_eq!(quadrant_territory(q), scan_quadrant_territory(q), "Quadrant {} after {}", q, stage);
+        }
+    };
+    let alice = Principal::from_slice(&[30; 29]);
+    let bob = Principal::from_slice(&[31; 29]);
+    WALLETS.with(|w| {
+        w.borrow_mut().insert(alice, 1_000);
+        w.borrow_mut().insert(bob, 1_000);
+    });
+
+    // Alice's base straddles the corner of quadrants 0, 1, 4 and 5
+    join_game_internal(alice, 124, 124, 0).unwrap();
+    join_game_internal(bob, 300, 300, 1).unwrap();
+    place_cells_internal(alice, vec![(126, 128), (127, 128), (128, 128)], None, 0).unwrap();
+    place_cells_internal(bob, vec![(302, 302), (303, 302), (304, 302)], None, 0).unwrap();
+    assert_cache_matches("placement");
+
+    for _ in 0..3 {
+        step_generation();
+    }
+    assert_cache_matches("births");
+
+    // Bob captures some of Alice's claimed ground
+    clear_territory(0, 127, 127);
+    set_territory(1, 127, 127);
+    assert_eq!(quadrant_territory(0)[1], 1);
+    claim_in_quadrant_5(1, 10, 250);
+    assert_cache_matches("capture");
+
+    wipe_quadrant(5);
+    assert_cache_matches("wipe");
+
+    eliminate_player(0);
+    assert_cache_matches("elimination");
+    assert!((0..TOTAL_QUADRANTS).all(|q| quadrant_territory(q)[0] == 0));
+
+    // Bulk OWNER restores rebuild the cache
+    let data = encode_grid();
+    OWNER.with(|o| o.borrow_mut().fill(255));
+    decode_grid(&data).unwrap();
+    assert_cache_matches("grid import");
+}
+
+#[test]
+fn test_standings_counts_match_full_recount() {
+    let alice = Principal::from_slice(&[32; 29]);
+    let bob = Principal::from_slice(&[33; 29]);
+    WALLETS.with(|w| {
+        w.borrow_mut().insert(alice, 1_000);
+        w.borrow_mut().insert(bob, 1_000);
+    });
+    join_game_internal(alice, 40, 40, 0).unwrap();
+    join_game_internal(bob, 200, 200, 1).unwrap();
+    place_cells_internal(alice, vec![(42, 42), (43, 42), (44, 42)], None, 0).unwrap();
+    place_cells_internal(bob, vec![(202, 202), (203, 202), (202, 203), (203, 203)], None, 0).unwrap();
+    for _ in 0..5 {
+        step_generation();
+    }
+
+    let standings = player_standings();
+    assert_eq!(standings.len(), 2);
+    for standing in &standings {
+        let slot = standing.slot as usize;
+        let alive = (0..TOTAL_CELLS)
+            .filter(|&idx| is_alive_idx(idx) && OWNER.with(|o| o.borrow()[idx]) as usize == slot)
+            .count() as u32;
+        assert_eq!(standing.alive_cells, alive, "Slot {} alive cells", slot);
+        assert_eq!(standing.territory_cells, count_territory_cells(slot), "Slot {} territory", slot);
+    }
+}
+
+// =============================================================================
+// DETERMINISTIC RNG
+// =============================================================================
+
+#[test]
+fn test_rng_deterministic_for_same_seed() {
+    let mut a = rng::Rng::new(1_234);
+    let mut b = rng::Rng::new(1_234);
+    let first: Vec<u64> = (0..64).map(|_| a.next_u64()).collect();
+    assert_eq!(first, (0..64).map(|_| b.next_u64()).collect::<Vec<_>>());
+
+    let mut other = rng::Rng::new(1_235);
+    assert_ne!(first, (0..64).map(|_| other.next_u64()).collect::<Vec<_>>());
+
+    // A zero seed still produces a varied sequence
+    let mut zero = rng::Rng::new(0);
+    let draws: std::collections::HashSet<u64> = (0..64).map(|_| zero.next_u64()).collect();
+    assert_eq!(draws.len(), 64);
+}
+
+#[test]
+fn test_rng_roughly_uniform() {
+    const BUCKETS: usize = 16;
+    const DRAWS: usize = 160_000;
+    let mut rng = rng::Rng::new(42);
+    let mut counts = [0usize; BUCKETS];
+    for _ in 0..DRAWS {
+        counts[rng.below(BUCKETS as u64) as usize] += 1;
+    }
+    let expected = DRAWS / BUCKETS;
+    for (bucket, &count) in counts.iter().enumerate() {
+        assert!(count.abs_diff(expected) < expected / 20, "Bucket {} got {} draws, expected ~{}", bucket, count, expected);
+    }
+}
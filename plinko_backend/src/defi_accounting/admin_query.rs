@@ -4,10 +4,11 @@ use super::liquidity_pool;
 use super::types::*;
 
 const ADMIN_PRINCIPAL: &str = "p7336-jmpo5-pkjsf-7dqkd-ea3zu-g2ror-ctcn2-sxtuo-tjve3-ulrx7-wae";
+#[cfg(target_arch = "wasm32")]
 const WASM_PAGE_SIZE_BYTES: u64 = 65536;
 const REASONABLE_MAX_LIMIT: usize = 10_000; // Safety net for unbounded queries
 
-fn require_admin() -> Result<(), String> {
+pub(crate) fn require_admin() -> Result<(), String> {
     let caller = ic_cdk::api::msg_caller();
     let admin = Principal::from_text(ADMIN_PRINCIPAL)
         .map_err(|e| format!("Invalid admin principal: {:?}", e))?;
@@ -39,9 +40,18 @@ pub async fn admin_health_check() -> Result<HealthCheck, String> {
     // NEW: Explicit solvency check
     let is_solvent = excess >= 0;
 
+    // Cross-subsystem invariants
+    let invariants = super::invariants::check_all(canister_balance);
+    let failed: Vec<&str> = invariants.iter()
+        .filter(|c| !c.passed)
+        .map(|c| c.name.as_str())
+        .collect();
+
     // Health status (update logic to reflect solvency)
     let (is_healthy, health_status) = if !is_solvent {
         (false, format!("CRITICAL: INSOLVENT (deficit {} USDT)", excess_usdt.abs()))
+    } else if !failed.is_empty() {
+        (false, format!("CRITICAL: INVARIANT FAILED ({})", failed.join(", ")))
     } else if excess < 1_000_000 {
         (true, "HEALTHY".to_string())
     } else if excess < 5_000_000 {
@@ -86,6 +96,7 @@ pub async fn admin_health_check() -> Result<HealthCheck, String> {
         unique_users,
         unique_lps,
         is_solvent,  // NEW field
+        invariants,
     })
 }
 
@@ -102,6 +113,13 @@ pub fn get_orphaned_funds_report(recent_limit: Option<u64>) -> Result<OrphanedFu
     Ok(accounting::build_orphaned_funds_report_internal(limit))
 }
 
+/// Compare a user's stored balance with the one replayed from the audit log and game history.
+/// Diagnostic only: drift is reported, nothing is corrected.
+pub fn reconcile_user(user: Principal) -> Result<ReconcileReport, String> {
+    require_admin()?;
+    Ok(accounting::reconcile_user_internal(user))
+}
+
 /// Paginated list of all user balances
 pub fn get_all_balances(offset: u64, limit: u64) -> Result<Vec<UserBalance>, String> {
     require_admin()?;
@@ -148,4 +166,117 @@ pub fn get_audit_log(limit: u64, offset: u64) -> Result<Vec<AuditEntry>, String>
 pub fn get_audit_log_count() -> Result<u64, String> {
     require_admin()?;
     Ok(accounting::get_audit_count())
-}
\ No newline at end of file
+}
+
+/// Get the audit log retention limit (compare with `get_audit_log_count`).
+pub fn get_audit_retention() -> Result<u64, String> {
+    require_admin()?;
+    Ok(accounting::get_audit_retention())
+}
+
+/// Set the audit log retention limit (100..=100000). Lowering it prunes the oldest entries now.
+pub fn set_audit_retention(limit: u64) -> Result<(), String> {
+    require_admin()?;
+    accounting::set_audit_retention(limit)
+}
+
+/// Parent fee auto-withdrawal threshold and recipient
+pub fn get_parent_config() -> Result<ParentWithdrawalConfig, String> {
+    require_admin()?;
+    Ok(super::parent::get_parent_config())
+}
+
+/// Set the parent auto-withdrawal threshold and the account it pays
+pub fn set_parent_config(threshold: u64, recipient: crate::types::Account) -> Result<(), String> {
+    require_admin()?;
+    super::parent::set_parent_config(threshold, recipient, ic_cdk::api::time())
+}
+
+/// Set the solvency safety margin in basis points (applies to every betting endpoint)
+pub fn set_solvency_margin_bp(margin_bp: u64) -> Result<(), String> {
+    require_admin()?;
+    super::solvency::set_solvency_margin_bp(margin_bp)
+}
+
+/// Set the pool reserve target for excess distribution (0 disables it)
+pub fn set_target_reserve(target: u64) -> Result<(), String> {
+    require_admin()?;
+    liquidity_pool::set_target_reserve(target)
+}
+
+/// Switch the ICRC ledger (e.g. a local test ledger), then reload its token metadata and
+/// our balance on it. Rejected while any deposits, pool reserve or pending withdrawals exist.
+pub async fn set_ledger_canister_id(ledger: Principal) -> Result<TokenInfo, String> {
+    require_admin()?;
+    accounting::set_ledger_canister_id(ledger)?;
+    accounting::refresh_canister_balance().await;
+    Ok(accounting::refresh_token_info().await)
+}
+
+/// Configure loyalty rebates (rebate_bp = 0 disables them)
+pub fn set_rebate_config(config: RebateConfig) -> Result<(), String> {
+    require_admin()?;
+    super::rebates::set_rebate_config(config)
+}
+
+/// Pay due loyalty rebates now instead of waiting for the daily timer
+pub fn process_rebates() -> Result<u64, String> {
+    require_admin()?;
+    super::rebates::process_rebates()
+}
+
+/// Pause (or resume) betting and LP deposits, e.g. before a pool drain
+pub fn set_betting_paused(paused: bool) -> Result<(), String> {
+    require_admin()?;
+    super::pool_drain::set_betting_paused(paused, ic_cdk::api::time())
+}
+
+/// Send the whole pool reserve to `recipient` for a migration (betting must be paused and
+/// no withdrawal pending). Returns the amount received.
+pub async fn initiate_pool_drain(recipient: crate::types::Account) -> Result<u64, String> {
+    require_admin()?;
+    super::pool_drain::initiate_pool_drain(recipient).await
+}
+
+/// The pool drain awaiting confirmation, if any
+pub fn get_pending_drain() -> Result<Option<super::types::PendingDrain>, String> {
+    require_admin()?;
+    Ok(super::pool_drain::get_pending_drain())
+}
+
+/// Re-send an uncertain pool drain with its original `created_at_time` and memo
+pub async fn retry_pool_drain() -> Result<u64, String> {
+    require_admin()?;
+    super::pool_drain::retry_pool_drain().await
+}
+
+/// Settle a pending pool drain after checking the ledger: finalize it if the transfer
+/// landed, otherwise return the reserve to the pool
+pub fn resolve_pool_drain(landed: bool) -> Result<u64, String> {
+    require_admin()?;
+    super::pool_drain::resolve_pool_drain(landed, ic_cdk::api::time())
+}
+
+/// Canister notified of deposits, withdrawals and large wins, if any
+pub fn get_event_subscriber() -> Result<Option<Principal>, String> {
+    require_admin()?;
+    Ok(super::events::get_event_subscriber())
+}
+
+/// Notify `canister` of deposits, withdrawals and large wins (see `events`)
+pub fn set_event_subscriber(canister: Principal) -> Result<(), String> {
+    require_admin()?;
+    super::events::set_event_subscriber(Some(canister), ic_cdk::api::time())
+}
+
+/// Stop sending event notifications
+pub fn clear_event_subscriber() -> Result<(), String> {
+    require_admin()?;
+    super::events::set_event_subscriber(None, ic_cdk::api::time())
+}
+
+/// Distribute pool reserve above the target to LPs. Returns the amount distributed.
+pub fn distribute_excess() -> Result<u64, String> {
+    require_admin()?;
+    liquidity_pool::distribute_excess_to_lps()
+}
@@ -0,0 +1,97 @@
+//! Best-effort notifications to an admin-registered subscriber canister.
+//!
+//! Integrators register a canister with `admin_set_event_subscriber` and receive a
+//! one-way `on_game_event(GameEvent)` call on every credited deposit, completed
+//! withdrawal and large win, instead of polling. Sends are fire-and-forget (`notify`):
+//! no reply is awaited and a rejected send is only logged, so a slow or broken
+//! subscriber can never block or roll back the operation that triggered it.
+
+use candid::Principal;
+use ic_stable_structures::memory_manager::MemoryId;
+use ic_stable_structures::StableCell;
+use std::cell::RefCell;
+
+use crate::{MEMORY_MANAGER, Memory};
+use super::accounting;
+use super::memory_ids::EVENT_SUBSCRIBER_MEMORY_ID;
+use super::types::{AuditEvent, GameEvent};
+
+/// Method the subscriber must expose: `on_game_event : (GameEvent) -> ()`
+pub const SUBSCRIBER_METHOD: &str = "on_game_event";
+/// Wins with at least this much profit, in whole tokens, are reported as `LargeWin`
+const LARGE_WIN_MIN_PROFIT_TOKENS: u64 = 100; // 100 USDT
+
+thread_local! {
+    static EVENT_SUBSCRIBER: RefCell<StableCell<Option<Principal>, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(EVENT_SUBSCRIBER_MEMORY_ID))),
+            None
+        )
+    );
+
+    // Events handed to `send` off-chain (tests), where there is no canister to notify
+    #[cfg(not(target_arch = "wasm32"))]
+    static SENT_EVENTS: RefCell<Vec<(Principal, &'static str, GameEvent)>> = const { RefCell::new(Vec::new()) };
+}
+
+pub fn get_event_subscriber() -> Option<Principal> {
+    EVENT_SUBSCRIBER.with(|s| *s.borrow().get())
+}
+
+pub(crate) fn validate_event_subscriber(subscriber: Option<Principal>) -> Result<(), String> {
+    if subscriber == Some(Principal::anonymous()) || subscriber == Some(Principal::management_canister()) {
+        return Err("Subscriber must be a canister principal".to_string());
+    }
+    Ok(())
+}
+
+/// Register the subscriber, or remove it with `None` (admin check is done by the caller in `admin_query`)
+pub(crate) fn set_event_subscriber(subscriber: Option<Principal>, now: u64) -> Result<(), String> {
+    validate_event_subscriber(subscriber)?;
+    EVENT_SUBSCRIBER.with(|s| s.borrow_mut().set(subscriber));
+    accounting::log_audit_at(AuditEvent::SystemInfo {
+        message: match subscriber {
+            Some(canister) => format!("Event subscriber: {}", canister),
+            None => "Event subscriber removed".to_string(),
+        },
+    }, now);
+    Ok(())
+}
+
+/// Send `event` to the subscriber, if one is registered. Never fails.
+pub(crate) fn notify(event: GameEvent) {
+    if let Some(subscriber) = get_event_subscriber() {
+        send(subscriber, event);
+    }
+}
+
+/// Smallest profit reported as a `LargeWin`, scaled by the ledger's decimals
+pub fn large_win_min_profit() -> u64 {
+    LARGE_WIN_MIN_PROFIT_TOKENS.saturating_mul(accounting::token_unit())
+}
+
+/// Report a settled bet as a `LargeWin` if its profit reaches `large_win_min_profit`
+pub(crate) fn notify_if_large_win(user: Principal, bet_amount: u64, payout_amount: u64) {
+    if payout_amount.saturating_sub(bet_amount) >= large_win_min_profit() {
+        notify(GameEvent::LargeWin { user, bet_amount, payout_amount });
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+#[allow(deprecated)]
+fn send(subscriber: Principal, event: GameEvent) {
+    if let Err(code) = ic_cdk::api::call::notify(subscriber, SUBSCRIBER_METHOD, (event,)) {
+        ic_cdk::println!("Event notification to {} failed: {:?}", subscriber, code);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn send(subscriber: Principal, event: GameEvent) {
+    SENT_EVENTS.with(|s| s.borrow_mut().push((subscriber, SUBSCRIBER_METHOD, event)));
+}
+
+/// Drain the events recorded by the off-chain `send`
+#[cfg(test)]
+pub(crate) fn take_sent_events() -> Vec<(Principal, &'static str, GameEvent)> {
+    SENT_EVENTS.with(|s| std::mem::take(&mut *s.borrow_mut()))
+}
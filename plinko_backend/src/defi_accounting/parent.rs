@@ -0,0 +1,77 @@
+//! Where the parent's fee balance goes, and when.
+//!
+//! Protocol fees are credited to the parent principal's betting balance (the bookkeeping
+//! identity never changes). The weekly timer withdraws that balance once it exceeds
+//! `threshold`, paying it to `recipient`. Both are admin-set and persisted, so operators
+//! can redirect the fee flow without an upgrade.
+
+use candid::Principal;
+use ic_stable_structures::memory_manager::MemoryId;
+use ic_stable_structures::StableCell;
+use std::cell::RefCell;
+
+use crate::types::Account;
+use crate::{MEMORY_MANAGER, Memory};
+use super::accounting::{self, PENDING_WITHDRAWALS};
+use super::liquidity_pool::get_parent_principal;
+use super::memory_ids::PARENT_CONFIG_MEMORY_ID;
+use super::types::{AuditEvent, ParentWithdrawalConfig};
+
+/// Default auto-withdrawal threshold. 10 USDT keeps ledger fees small while still
+/// collecting fees in good time.
+pub const DEFAULT_PARENT_WITHDRAW_THRESHOLD: u64 = 10_000_000;
+
+thread_local! {
+    static PARENT_CONFIG: RefCell<StableCell<ParentWithdrawalConfig, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(PARENT_CONFIG_MEMORY_ID))),
+            ParentWithdrawalConfig {
+                threshold: DEFAULT_PARENT_WITHDRAW_THRESHOLD,
+                recipient: Account { owner: get_parent_principal(), subaccount: None },
+            }
+        )
+    );
+}
+
+pub fn get_parent_config() -> ParentWithdrawalConfig {
+    PARENT_CONFIG.with(|c| c.borrow().get().clone())
+}
+
+pub(crate) fn validate_parent_config(threshold: u64, recipient: &Account) -> Result<(), String> {
+    let minimum = accounting::min_withdraw() + accounting::transfer_fee();
+    if threshold <= minimum {
+        return Err(format!("Threshold must be above {} (minimum withdrawal plus ledger fee)", minimum));
+    }
+    if recipient.owner == Principal::anonymous() || recipient.owner == Principal::management_canister() {
+        return Err("Recipient must be a canister or user principal".to_string());
+    }
+    // A retry must pay the same account the original attempt did
+    if PENDING_WITHDRAWALS.with(|p| p.borrow().contains_key(&get_parent_principal())) {
+        return Err("Parent withdrawal pending: resolve it before changing the settings".to_string());
+    }
+    Ok(())
+}
+
+/// Set the auto-withdrawal threshold and recipient (admin check is done by the caller in `admin_query`)
+pub(crate) fn set_parent_config(threshold: u64, recipient: Account, now: u64) -> Result<(), String> {
+    validate_parent_config(threshold, &recipient)?;
+    PARENT_CONFIG.with(|c| c.borrow_mut().set(ParentWithdrawalConfig { threshold, recipient: recipient.clone() }));
+    accounting::log_audit_at(AuditEvent::SystemInfo {
+        message: format!("Parent auto-withdrawal: threshold {} to {}", threshold, recipient.owner),
+    }, now);
+    Ok(())
+}
+
+/// The weekly timer withdraws the parent's balance only above the threshold
+pub(crate) fn parent_withdrawal_due(balance: u64) -> bool {
+    balance > get_parent_config().threshold
+}
+
+/// Ledger account a withdrawal of `user`'s balance is paid to
+pub(crate) fn payout_account(user: Principal) -> Account {
+    if user == get_parent_principal() {
+        get_parent_config().recipient
+    } else {
+        Account { owner: user, subaccount: None }
+    }
+}
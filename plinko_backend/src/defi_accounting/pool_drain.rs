@@ -0,0 +1,240 @@
+//! Emergency pool drain for migrating a game to a new canister.
+//!
+//! Two admin steps: pause betting with `set_betting_paused(true)`, then call
+//! `initiate_pool_drain(recipient)`. Bets deduct and settle in one message after their
+//! last await, so once betting is paused no bet is left half-settled. The drain also
+//! refuses while any withdrawal is pending: a rolled-back LP withdrawal would return its
+//! reserve to a pool that no longer holds the tokens.
+//!
+//! The whole pool reserve (less the ledger fee) goes to the recipient via ICRC-1, then the
+//! pool is reset: reserve 0 and every LP position cleared. Export LP positions first
+//! (`admin_get_all_lp_positions_complete`) to recreate them on the new canister. Player
+//! betting balances are not part of the pool; they stay here and remain withdrawable.
+//!
+//! The drain (amount, recipient, `created_at`) is kept in stable memory until it is
+//! resolved. If the ledger call is uncertain, `retry_pool_drain` re-sends the identical
+//! transfer (same `created_at_time` and memo, so the ledger deduplicates it) and
+//! `resolve_pool_drain` settles it by hand once the ledger has been checked. Betting
+//! cannot be resumed while a drain is unresolved.
+
+use candid::Nat;
+use ic_stable_structures::memory_manager::MemoryId;
+use ic_stable_structures::StableCell;
+use std::cell::RefCell;
+
+use crate::types::{Account, TransferArg, TransferError};
+use crate::{MEMORY_MANAGER, Memory};
+use super::accounting::{self, TransferResult, PENDING_WITHDRAWALS};
+use super::liquidity_pool;
+use super::memory_ids::{BETTING_PAUSED_MEMORY_ID, PENDING_DRAIN_MEMORY_ID};
+use super::types::{AuditEvent, PendingDrain};
+
+/// Memo on every drain transfer; part of the ledger's deduplication key on retries
+const DRAIN_MEMO: &[u8] = b"OpenHouse pool drain";
+
+thread_local! {
+    static BETTING_PAUSED: RefCell<StableCell<bool, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(BETTING_PAUSED_MEMORY_ID))),
+            false
+        )
+    );
+
+    // The drain taken out of the pool but not yet finalized or restored
+    static PENDING_DRAIN: RefCell<StableCell<Option<PendingDrain>, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(PENDING_DRAIN_MEMORY_ID))),
+            None
+        )
+    );
+
+    // Set while the drain transfer is awaiting the ledger (heap only: nothing is in flight after an upgrade)
+    static DRAIN_IN_FLIGHT: RefCell<bool> = const { RefCell::new(false) };
+}
+
+pub fn is_betting_paused() -> bool {
+    BETTING_PAUSED.with(|p| *p.borrow().get())
+}
+
+/// The unresolved drain, if any
+pub fn get_pending_drain() -> Option<PendingDrain> {
+    PENDING_DRAIN.with(|d| d.borrow().get().clone())
+}
+
+fn set_pending_drain(drain: Option<PendingDrain>) {
+    PENDING_DRAIN.with(|d| d.borrow_mut().set(drain));
+}
+
+fn is_drain_in_flight() -> bool {
+    DRAIN_IN_FLIGHT.with(|d| *d.borrow())
+}
+
+#[cfg(test)]
+pub(crate) fn clear_drain_in_flight() {
+    DRAIN_IN_FLIGHT.with(|d| *d.borrow_mut() = false);
+}
+
+/// Pause or resume betting and LP deposits (admin check is done by the caller in `admin_query`)
+pub(crate) fn set_betting_paused(paused: bool, now: u64) -> Result<(), String> {
+    if !paused && is_drain_in_flight() {
+        return Err("Cannot resume betting while a pool drain is in flight".to_string());
+    }
+    if !paused && get_pending_drain().is_some() {
+        return Err("Cannot resume betting while a pool drain is unresolved".to_string());
+    }
+    BETTING_PAUSED.with(|p| p.borrow_mut().set(paused));
+    accounting::log_audit_at(AuditEvent::SystemInfo {
+        message: format!("Betting {}", if paused { "paused" } else { "resumed" }),
+    }, now);
+    Ok(())
+}
+
+/// Transfer the whole pool reserve to `recipient` and reset the pool. Returns the amount
+/// the recipient received (reserve minus the ledger fee).
+pub(crate) async fn initiate_pool_drain(recipient: Account) -> Result<u64, String> {
+    let drain = begin_drain(recipient, ic_cdk::api::time())?;
+    let result = transfer_drain(&drain).await;
+    DRAIN_IN_FLIGHT.with(|d| *d.borrow_mut() = false);
+
+    match result {
+        TransferResult::Success(_) => Ok(finish_drain(ic_cdk::api::time())),
+        TransferResult::DefiniteError(err) => {
+            // First attempt with a fresh created_at: nothing can have landed
+            restore_drain(ic_cdk::api::time());
+            Err(format!("Pool drain failed, reserve restored: {}", err))
+        }
+        TransferResult::UncertainError(msg) => {
+            accounting::log_audit(AuditEvent::SystemError {
+                error: format!("Pool drain of {} uncertain: {}", drain.amount, msg),
+            });
+            Err(format!(
+                "Pool drain outcome uncertain ({}). Reserve of {} is held at 0; retry with admin_retry_pool_drain.",
+                msg, drain.amount
+            ))
+        }
+    }
+}
+
+/// Re-send the unresolved drain with the same `created_at_time` and memo. The ledger
+/// either executes it or reports the original as a duplicate; both finalize the drain.
+///
+/// Any other error leaves the drain pending: past the ledger's deduplication window a
+/// `TooOld` rejection says nothing about the original transfer. Check the ledger and
+/// settle it with `resolve_pool_drain`.
+pub(crate) async fn retry_pool_drain() -> Result<u64, String> {
+    let drain = begin_retry()?;
+    let result = transfer_drain(&drain).await;
+    DRAIN_IN_FLIGHT.with(|d| *d.borrow_mut() = false);
+
+    match result {
+        TransferResult::Success(_) => Ok(finish_drain(ic_cdk::api::time())),
+        TransferResult::DefiniteError(err) | TransferResult::UncertainError(err) => Err(format!(
+            "Pool drain retry failed ({}). Drain of {} is still pending; check the ledger and call admin_resolve_pool_drain.",
+            err, drain.amount
+        )),
+    }
+}
+
+/// Check the drain preconditions, then take the whole reserve out of the pool and record
+/// the drain as pending
+pub(crate) fn begin_drain(recipient: Account, now: u64) -> Result<PendingDrain, String> {
+    if !is_betting_paused() {
+        return Err("Pause betting before draining the pool".to_string());
+    }
+    if is_drain_in_flight() {
+        return Err("A pool drain is already in flight".to_string());
+    }
+    if get_pending_drain().is_some() {
+        return Err("A pool drain is unresolved; retry or resolve it first".to_string());
+    }
+    let pending = PENDING_WITHDRAWALS.with(|p| p.borrow().len());
+    if pending > 0 {
+        return Err(format!("{} pending withdrawals must complete before draining the pool", pending));
+    }
+    let reserve = liquidity_pool::get_pool_reserve();
+    let fee = accounting::transfer_fee();
+    if reserve <= fee {
+        return Err(format!("Pool reserve {} is too small to drain", reserve));
+    }
+
+    // Zero the reserve BEFORE the transfer so nothing else can spend it meanwhile
+    liquidity_pool::take_reserve_for_drain();
+    let drain = PendingDrain { amount: reserve, fee, recipient, created_at: now };
+    set_pending_drain(Some(drain.clone()));
+    DRAIN_IN_FLIGHT.with(|d| *d.borrow_mut() = true);
+    Ok(drain)
+}
+
+fn begin_retry() -> Result<PendingDrain, String> {
+    if is_drain_in_flight() {
+        return Err("A pool drain is already in flight".to_string());
+    }
+    let drain = get_pending_drain().ok_or("No pool drain to retry")?;
+    DRAIN_IN_FLIGHT.with(|d| *d.borrow_mut() = true);
+    Ok(drain)
+}
+
+/// Settle the pending drain by hand after checking the ledger. `landed = true` finalizes
+/// it (returns the amount the recipient received); `false` returns the reserve to the
+/// pool (returns the amount restored).
+pub(crate) fn resolve_pool_drain(landed: bool, now: u64) -> Result<u64, String> {
+    if is_drain_in_flight() {
+        return Err("A pool drain is in flight; wait for it to return".to_string());
+    }
+    let drain = get_pending_drain().ok_or("No pool drain to resolve")?;
+    if landed {
+        Ok(finish_drain(now))
+    } else {
+        restore_drain(now);
+        Ok(drain.amount)
+    }
+}
+
+/// The drain transfer landed: account for the tokens leaving, clear LP positions and
+/// record the drain. Returns the amount the recipient received.
+pub(crate) fn finish_drain(now: u64) -> u64 {
+    let Some(drain) = get_pending_drain() else { return 0 };
+    accounting::decrement_cached_balance(drain.amount);
+    let lp_positions = liquidity_pool::clear_lp_positions();
+    set_pending_drain(None);
+    accounting::log_audit_at(AuditEvent::PoolDrained {
+        recipient: drain.recipient.owner,
+        amount: drain.amount,
+        lp_positions,
+    }, now);
+    drain.amount - drain.fee
+}
+
+/// The drain transfer did not land: return the reserve to the pool
+fn restore_drain(now: u64) {
+    let Some(drain) = get_pending_drain() else { return };
+    liquidity_pool::add_to_reserve(drain.amount);
+    set_pending_drain(None);
+    accounting::log_audit_at(AuditEvent::SystemInfo {
+        message: format!("Pool drain of {} not sent; reserve restored", drain.amount),
+    }, now);
+}
+
+#[allow(deprecated)]
+async fn transfer_drain(drain: &PendingDrain) -> TransferResult {
+    let args = TransferArg {
+        from_subaccount: None,
+        to: drain.recipient.clone(),
+        amount: Nat::from(drain.amount - drain.fee),
+        fee: Some(Nat::from(drain.fee)),
+        memo: Some(DRAIN_MEMO.to_vec()),
+        created_at_time: Some(drain.created_at),
+    };
+    let call_result: Result<(Result<Nat, TransferError>,), _> =
+        ic_cdk::api::call::call(accounting::get_ledger_canister_id(), "icrc1_transfer", (args,)).await;
+
+    match call_result {
+        Ok((Ok(block_index),)) => TransferResult::Success(block_index.0.try_into().unwrap_or(0)),
+        // Same created_at_time and memo: the original transfer already landed
+        Ok((Err(TransferError::Duplicate { duplicate_of }),)) => {
+            TransferResult::Success(duplicate_of.0.try_into().unwrap_or(0))
+        }
+        Ok((Err(e),)) => TransferResult::DefiniteError(format!("{:?}", e)),
+        Err((code, msg)) => TransferResult::UncertainError(format!("{:?} {}", code, msg)),
+    }
+}
@@ -257,7 +257,7 @@ fn attack_share_price_manipulation_via_games() {
     println!("\n🚨 ATTACK EXECUTION (Phase 4): Attacker withdraws...");
 
     let attacker_initial_investment = 50_000_000 + attacker_deposit; // Initial deposit + LP deposit
-    let attacker_balance_before_lp_withdraw = *model.user_balances.get(&attacker).unwrap_or(&0);
+    let _attacker_balance_before_lp_withdraw = *model.user_balances.get(&attacker).unwrap_or(&0);
 
     model.lp_withdraw(attacker).expect_success();
 
@@ -1,4 +1,24 @@
+pub mod test_audit_retention;
+pub mod test_authentication;
+pub mod test_bank_run;
+pub mod test_config_blob;
+pub mod test_deposit_and_play;
+pub mod test_deposit_keys;
+pub mod test_event_subscriber;
+pub mod test_excess_distribution;
+pub mod test_game_error;
+pub mod test_game_history;
+pub mod test_invariants;
+pub mod test_ledger_config;
+pub mod test_parent_config;
+pub mod test_player_stats;
+pub mod test_pool_drain;
+pub mod test_rebates;
+pub mod test_reconcile_user;
 pub mod test_serialization;
 pub mod test_slippage_audit;
+pub mod test_solvency;
+pub mod test_withdraw_keeping;
+pub mod test_withdrawal_status;
 mod stress_tests;
 mod adversarial;
\ No newline at end of file
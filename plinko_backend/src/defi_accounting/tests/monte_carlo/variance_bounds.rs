@@ -1,8 +1,8 @@
 //! Variance Bounds Verification Tests
 //!
-//! These tests verify that the 4-sigma multi-ball betting limit is conservative
-//! enough to protect the house from statistical extremes. This is critical for
-//! allowing higher bets on multi-ball games safely.
+//! These tests measure how far multi-ball averages stray from the 4-sigma statistical
+//! bound. Betting limits do not rely on it: they assume every ball lands on an edge
+//! (see `game::play_multi_plinko`), so these results are informational.
 
 use rand::{SeedableRng, Rng};
 use rand_chacha::ChaCha8Rng;
@@ -0,0 +1,47 @@
+// Tests for the admin-configurable audit log retention limit
+
+use crate::defi_accounting::accounting::{
+    get_audit_count, get_audit_entries, get_audit_retention, log_audit_at, set_audit_retention,
+    DEFAULT_AUDIT_RETENTION, MAX_AUDIT_RETENTION, MIN_AUDIT_RETENTION,
+};
+use crate::defi_accounting::types::AuditEvent;
+
+fn log_numbered(from: u64, to: u64) {
+    for i in from..to {
+        log_audit_at(AuditEvent::SystemInfo { message: format!("entry {}", i) }, i);
+    }
+}
+
+#[test]
+fn test_retention_bounds() {
+    assert_eq!(get_audit_retention(), DEFAULT_AUDIT_RETENTION);
+    assert!(set_audit_retention(MIN_AUDIT_RETENTION - 1).is_err());
+    assert!(set_audit_retention(MAX_AUDIT_RETENTION + 1).is_err());
+    assert_eq!(get_audit_retention(), DEFAULT_AUDIT_RETENTION);
+}
+
+#[test]
+fn test_pruning_respects_configured_limit() {
+    set_audit_retention(MIN_AUDIT_RETENTION).unwrap();
+    log_numbered(0, MIN_AUDIT_RETENTION + 25);
+    assert_eq!(get_audit_count(), MIN_AUDIT_RETENTION);
+    assert_eq!(get_audit_entries(1, MIN_AUDIT_RETENTION - 1)[0].timestamp, 25, "Oldest entries pruned first");
+
+    // Raising the limit keeps everything and lets the log grow
+    set_audit_retention(2 * MIN_AUDIT_RETENTION).unwrap();
+    assert_eq!(get_audit_count(), MIN_AUDIT_RETENTION);
+    log_numbered(MIN_AUDIT_RETENTION + 25, 2 * MIN_AUDIT_RETENTION + 25);
+    assert_eq!(get_audit_count(), 2 * MIN_AUDIT_RETENTION);
+}
+
+#[test]
+fn test_lowering_limit_removes_oldest_entries() {
+    log_numbered(0, 300);
+    assert_eq!(get_audit_count(), 300);
+
+    set_audit_retention(MIN_AUDIT_RETENTION).unwrap();
+    assert_eq!(get_audit_count(), MIN_AUDIT_RETENTION);
+    let kept = get_audit_entries(MIN_AUDIT_RETENTION, 0);
+    assert_eq!(kept.first().map(|e| e.timestamp), Some(299), "Newest entry kept");
+    assert_eq!(kept.last().map(|e| e.timestamp), Some(300 - MIN_AUDIT_RETENTION));
+}
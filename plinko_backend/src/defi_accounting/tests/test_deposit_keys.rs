@@ -0,0 +1,117 @@
+// Tests for deposit idempotency keys (retry handling without the ledger)
+
+use crate::defi_accounting::accounting::{credit_deposit, get_audit_entries, get_balance_internal};
+use crate::defi_accounting::deposit_keys::{self, DepositKeyStart, DEPOSIT_KEY_TTL_NANOS, MAX_DEPOSIT_KEYS_PER_USER};
+use crate::defi_accounting::types::{AuditEvent, DepositReceipt};
+use candid::Principal;
+
+fn user(id: u8) -> Principal {
+    Principal::from_slice(&[id; 29])
+}
+
+fn receipt(new_balance: u64, block_index: u64) -> DepositReceipt {
+    DepositReceipt { new_balance, block_index }
+}
+
+#[test]
+fn test_retried_deposit_returns_original_result() {
+    let u = user(1);
+    let key = [1u8; 16];
+
+    // First attempt: transfer goes ahead and is credited
+    assert_eq!(deposit_keys::begin(u, key, 5_000_000, 100), Ok(DepositKeyStart::Proceed { created_at: 100 }));
+    deposit_keys::complete(u, key, &receipt(5_000_000, 42));
+
+    // Frontend retry with the same key: original receipt, no second transfer
+    assert_eq!(deposit_keys::begin(u, key, 5_000_000, 200), Ok(DepositKeyStart::Completed(receipt(5_000_000, 42))));
+}
+
+#[test]
+fn test_retry_while_in_flight_rejected() {
+    let u = user(2);
+    let key = [2u8; 16];
+    assert!(deposit_keys::begin(u, key, 5_000_000, 100).is_ok());
+    assert!(deposit_keys::begin(u, key, 5_000_000, 150).is_err());
+}
+
+#[test]
+fn test_uncertain_retry_reuses_created_at() {
+    let u = user(3);
+    let key = [3u8; 16];
+    assert!(deposit_keys::begin(u, key, 5_000_000, 100).is_ok());
+    deposit_keys::mark_uncertain(u, key);
+
+    // Same created_at so the ledger reports Duplicate if the first transfer landed
+    assert_eq!(deposit_keys::begin(u, key, 5_000_000, 500), Ok(DepositKeyStart::Proceed { created_at: 100 }));
+}
+
+#[test]
+fn test_key_reuse_with_different_amount_rejected() {
+    let u = user(4);
+    let key = [4u8; 16];
+    assert!(deposit_keys::begin(u, key, 5_000_000, 100).is_ok());
+    deposit_keys::complete(u, key, &receipt(5_000_000, 42));
+    assert!(deposit_keys::begin(u, key, 6_000_000, 200).is_err());
+}
+
+#[test]
+fn test_aborted_and_expired_keys_start_fresh() {
+    let u = user(5);
+    let key = [5u8; 16];
+    assert!(deposit_keys::begin(u, key, 5_000_000, 100).is_ok());
+    deposit_keys::abort(u, key);
+    assert_eq!(deposit_keys::begin(u, key, 5_000_000, 200), Ok(DepositKeyStart::Proceed { created_at: 200 }));
+    deposit_keys::complete(u, key, &receipt(5_000_000, 42));
+
+    let later = 200 + DEPOSIT_KEY_TTL_NANOS;
+    assert_eq!(deposit_keys::begin(u, key, 5_000_000, later), Ok(DepositKeyStart::Proceed { created_at: later }));
+}
+
+#[test]
+fn test_keys_bounded_per_user() {
+    let u = user(6);
+    for i in 0..=MAX_DEPOSIT_KEYS_PER_USER as u8 {
+        let key = [i; 16];
+        assert!(deposit_keys::begin(u, key, 5_000_000, 100 + i as u64).is_ok());
+        deposit_keys::complete(u, key, &receipt(5_000_000, 42));
+    }
+    // Oldest key was evicted, newest is still remembered
+    assert!(matches!(deposit_keys::begin(u, [0u8; 16], 5_000_000, 1_000), Ok(DepositKeyStart::Proceed { .. })));
+    let newest = [MAX_DEPOSIT_KEYS_PER_USER as u8; 16];
+    assert_eq!(deposit_keys::begin(u, newest, 5_000_000, 1_000), Ok(DepositKeyStart::Completed(receipt(5_000_000, 42))));
+}
+
+#[test]
+fn test_unresolved_keys_never_evicted() {
+    let u = user(8);
+    for i in 0..MAX_DEPOSIT_KEYS_PER_USER as u8 {
+        assert!(deposit_keys::begin(u, [i; 16], 5_000_000, 100 + i as u64).is_ok());
+        deposit_keys::mark_uncertain(u, [i; 16]);
+    }
+    // Every slot is awaiting a retry: a new key would push one of them out
+    let extra = [200u8; 16];
+    assert!(deposit_keys::begin(u, extra, 5_000_000, 1_000).is_err());
+    assert_eq!(deposit_keys::begin(u, [0u8; 16], 5_000_000, 1_000), Ok(DepositKeyStart::Proceed { created_at: 100 }));
+
+    // Once one settles, its slot is reused and the unresolved keys keep their created_at
+    deposit_keys::complete(u, [0u8; 16], &receipt(5_000_000, 42));
+    assert_eq!(deposit_keys::begin(u, extra, 5_000_000, 1_000), Ok(DepositKeyStart::Proceed { created_at: 1_000 }));
+    assert_eq!(deposit_keys::begin(u, [1u8; 16], 5_000_000, 2_000), Ok(DepositKeyStart::Proceed { created_at: 101 }));
+}
+
+#[test]
+fn test_credited_deposit_receipt_carries_block_index() {
+    let u = user(7);
+    let key = [7u8; 16];
+    assert!(deposit_keys::begin(u, key, 5_000_000, 100).is_ok());
+
+    let credited = credit_deposit(u, 5_000_000, 1_234, Some(key), 100).unwrap();
+    assert_eq!(credited, receipt(5_000_000, 1_234));
+    assert_eq!(get_balance_internal(u), 5_000_000);
+
+    let entry = &get_audit_entries(1, 0)[0];
+    assert!(matches!(entry.event, AuditEvent::BalanceCredited { user, block_index: Some(1_234), .. } if user == u));
+
+    // A keyed retry gets the same ledger reference back
+    assert_eq!(deposit_keys::begin(u, key, 5_000_000, 200), Ok(DepositKeyStart::Completed(credited)));
+}
@@ -0,0 +1,95 @@
+// Tests for the structured accounting errors and their text form at the candid boundary
+
+use candid::Principal;
+use std::future::Future;
+use std::pin::pin;
+use std::task::{Context, Poll, Waker};
+
+use crate::defi_accounting::accounting::{self, min_deposit, min_withdraw, PENDING_WITHDRAWALS};
+use crate::defi_accounting::types::{GameError, PendingWithdrawal, WithdrawalType};
+
+/// Poll a future once. Every error below must be returned before the first await.
+fn poll_once<F: Future>(future: F) -> F::Output {
+    match pin!(future).poll(&mut Context::from_waker(Waker::noop())) {
+        Poll::Ready(output) => output,
+        Poll::Pending => panic!("Call reached an await instead of returning an error"),
+    }
+}
+
+fn user(id: u8) -> Principal {
+    Principal::from_slice(&[id; 29])
+}
+
+#[test]
+fn test_game_error_text_is_stable() {
+    assert_eq!(String::from(GameError::Unauthorized), "Authentication required");
+    assert_eq!(
+        String::from(GameError::BelowMinimum { amount: 500_000, minimum: 1_000_000 }),
+        "Amount 0.5 ckUSDT is below the minimum of 1 ckUSDT"
+    );
+    assert_eq!(
+        String::from(GameError::AboveMaximum { amount: 11_000_010, maximum: 10_000_000 }),
+        "Amount 11.00001 ckUSDT exceeds the maximum of 10 ckUSDT"
+    );
+    assert_eq!(
+        String::from(GameError::InsufficientBalance { have: 1_250_000, need: 2_000_000 }),
+        "Insufficient balance: 1.25 ckUSDT available, 2 ckUSDT needed"
+    );
+    assert!(String::from(GameError::WithdrawalPending).starts_with("Withdrawal already pending"));
+    assert!(String::from(GameError::Insolvent).contains("insufficient funds"));
+    assert_eq!(String::from(GameError::BettingPaused), "Betting is paused");
+    assert_eq!(String::from(GameError::NotFound), "No pending withdrawal");
+    assert_eq!(GameError::from("Transfer failed".to_string()), GameError::Other("Transfer failed".to_string()));
+    assert_eq!(String::from(GameError::Other("Transfer failed".to_string())), "Transfer failed");
+}
+
+#[test]
+fn test_deposit_below_minimum() {
+    let amount = min_deposit() - 1;
+    let result = poll_once(accounting::deposit_internal(user(1), amount, None));
+    assert_eq!(result, Err(GameError::BelowMinimum { amount, minimum: min_deposit() }));
+}
+
+#[test]
+fn test_withdraw_all_without_balance() {
+    let result = poll_once(accounting::withdraw_internal(user(2)));
+    assert_eq!(result, Err(GameError::BelowMinimum { amount: 0, minimum: min_withdraw() }));
+}
+
+#[test]
+fn test_withdraw_all_while_pending() {
+    let u = user(3);
+    accounting::update_balance(u, 50_000_000).unwrap();
+    PENDING_WITHDRAWALS.with(|p| p.borrow_mut().insert(u, PendingWithdrawal {
+        withdrawal_type: WithdrawalType::User { amount: 10_000_000 },
+        created_at: 1,
+    }));
+
+    let result = poll_once(accounting::withdraw_internal(u));
+    assert_eq!(result, Err(GameError::WithdrawalPending));
+    assert_eq!(accounting::get_balance_internal(u), 50_000_000);
+}
+
+#[test]
+fn test_bet_above_balance() {
+    let u = user(4);
+    accounting::update_balance(u, 3_000_000).unwrap();
+    assert_eq!(
+        accounting::try_deduct_balance(u, 5_000_000),
+        Err(GameError::InsufficientBalance { have: 3_000_000, need: 5_000_000 })
+    );
+    assert_eq!(accounting::get_balance_internal(u), 3_000_000);
+}
+
+#[test]
+fn test_retry_and_abandon_without_pending() {
+    assert_eq!(poll_once(accounting::retry_withdrawal_internal(user(5))), Err(GameError::NotFound));
+    assert_eq!(accounting::abandon_withdrawal_internal(user(5)), Err(GameError::NotFound));
+}
+
+#[test]
+fn test_game_error_candid_round_trip() {
+    let err = GameError::InsufficientBalance { have: 1, need: 2 };
+    let bytes = candid::encode_one(&err).unwrap();
+    assert_eq!(candid::decode_one::<GameError>(&bytes).unwrap(), err);
+}
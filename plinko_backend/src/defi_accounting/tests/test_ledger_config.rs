@@ -0,0 +1,101 @@
+// Tests for the admin-configurable ledger canister id
+
+use candid::{Nat, Principal};
+use std::future::Future;
+use std::pin::pin;
+use std::task::{Context, Poll, Waker};
+use crate::defi_accounting::accounting::{
+    build_withdrawal_transfer, get_balance_internal, get_ledger_canister_id, min_deposit, set_ledger_canister_id,
+    set_token_info, transfer_fee, withdraw_internal, PENDING_WITHDRAWALS, USER_BALANCES_STABLE,
+};
+use crate::defi_accounting::events::large_win_min_profit;
+use crate::defi_accounting::liquidity_pool::{can_accept_bets, min_operating_balance, validate_target_reserve};
+use crate::defi_accounting::types::{GameError, TokenInfo};
+use crate::types::{CKUSDT_CANISTER_ID, CKUSDT_TRANSFER_FEE};
+
+const TEST_LEDGER: &str = "ryjl3-tyaaa-aaaaa-aaaba-cai";
+
+/// Poll a future once. The futures below never suspend.
+fn poll_once<F: Future>(future: F) -> F::Output {
+    match pin!(future).poll(&mut Context::from_waker(Waker::noop())) {
+        Poll::Ready(output) => output,
+        Poll::Pending => panic!("Future suspended"),
+    }
+}
+
+fn user(id: u8) -> Principal {
+    Principal::from_slice(&[id; 29])
+}
+
+#[test]
+fn test_defaults_to_ckusdt() {
+    assert_eq!(get_ledger_canister_id(), Principal::from_text(CKUSDT_CANISTER_ID).unwrap());
+}
+
+#[test]
+fn test_configured_ledger_used_for_transfers() {
+    let ledger = Principal::from_text(TEST_LEDGER).unwrap();
+    set_ledger_canister_id(ledger).unwrap();
+    assert_eq!(get_ledger_canister_id(), ledger);
+
+    let (target, args) = build_withdrawal_transfer(user(2), 5_000_000, 42).unwrap();
+    assert_eq!(target, ledger);
+    assert_eq!(args.to.owner, user(2));
+    assert_eq!(args.amount, Nat::from(5_000_000 - CKUSDT_TRANSFER_FEE));
+    assert_eq!(args.created_at_time, Some(42));
+}
+
+#[test]
+fn test_rejects_non_canister_principals() {
+    assert!(set_ledger_canister_id(Principal::anonymous()).is_err());
+    assert!(set_ledger_canister_id(Principal::management_canister()).is_err());
+    assert!(set_ledger_canister_id(user(2)).is_err());
+    assert_eq!(get_ledger_canister_id(), Principal::from_text(CKUSDT_CANISTER_ID).unwrap());
+}
+
+#[test]
+fn test_rejects_switch_while_deposits_exist() {
+    USER_BALANCES_STABLE.with(|b| b.borrow_mut().insert(user(3), 1_000_000));
+
+    let err = set_ledger_canister_id(Principal::from_text(TEST_LEDGER).unwrap()).unwrap_err();
+    assert!(err.contains("nothing is owed"), "Unexpected error: {}", err);
+    assert_eq!(get_ledger_canister_id(), Principal::from_text(CKUSDT_CANISTER_ID).unwrap());
+}
+
+#[test]
+fn test_sizes_and_fee_follow_token_info() {
+    assert_eq!(transfer_fee(), CKUSDT_TRANSFER_FEE);
+    assert_eq!(min_operating_balance(), 100_000_000);
+
+    // An 8-decimal token with its own fee
+    set_token_info(TokenInfo { symbol: "TEST".to_string(), decimals: 8, fee: 20_000 });
+    assert_eq!(transfer_fee(), 20_000);
+    assert_eq!(min_deposit(), 100_000_000);
+    assert_eq!(min_operating_balance(), 10_000_000_000);
+    assert_eq!(large_win_min_profit(), 10_000_000_000);
+    assert!(!can_accept_bets());
+    assert_eq!(
+        validate_target_reserve(100_000_000).unwrap_err(),
+        "Target reserve must be 0 (disabled) or at least 100 TEST"
+    );
+
+    let (_, args) = build_withdrawal_transfer(user(3), 5_000_000, 42).unwrap();
+    assert_eq!(args.fee, Some(Nat::from(20_000u64)));
+    assert_eq!(args.amount, Nat::from(5_000_000u64 - 20_000));
+}
+
+#[test]
+fn test_withdrawal_below_runtime_fee_rejected_before_pending() {
+    // The ledger now charges 2 tokens: above the 1 token minimum withdrawal
+    set_token_info(TokenInfo { symbol: "TEST".to_string(), decimals: 6, fee: 2_000_000 });
+    let u = user(4);
+    USER_BALANCES_STABLE.with(|b| b.borrow_mut().insert(u, 1_500_000));
+
+    let err = poll_once(withdraw_internal(u)).unwrap_err();
+    assert_eq!(err, GameError::BelowMinimum { amount: 1_500_000, minimum: 2_000_001 });
+    assert!(PENDING_WITHDRAWALS.with(|p| p.borrow().is_empty()), "No pending record");
+    assert_eq!(get_balance_internal(u), 1_500_000);
+
+    assert!(build_withdrawal_transfer(u, 2_000_000, 42).is_err(), "Never underflows");
+    assert!(build_withdrawal_transfer(u, 1_500_000, 42).is_err());
+}
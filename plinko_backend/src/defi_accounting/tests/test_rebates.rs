@@ -0,0 +1,99 @@
+// Tests for loyalty rebates funded from the house pool
+
+use candid::Principal;
+use crate::defi_accounting::accounting::{get_balance_internal, increment_cached_balance};
+use crate::defi_accounting::liquidity_pool::{add_to_reserve, get_pool_reserve, min_operating_balance, settle_bet};
+use crate::defi_accounting::rebates::{
+    get_rebate_account, get_rebate_config, house_edge_bp, process_rebates_internal, rebate_for, set_rebate_config, MAX_REBATE_BP,
+};
+use crate::defi_accounting::types::{RebateAccount, RebateConfig};
+
+const NOW: u64 = 1_700_000_000_000_000_000;
+const USDT: u64 = 1_000_000;
+
+fn user(id: u8) -> Principal {
+    Principal::from_slice(&[id; 29])
+}
+
+/// Pool of 1,000 USDT held by a solvent canister, with 10% of collected edge rebated
+/// to players who wagered at least 50 USDT
+fn setup() {
+    add_to_reserve(1_000 * USDT);
+    increment_cached_balance(1_000 * USDT);
+    set_rebate_config(RebateConfig { rebate_bp: 1_000, min_volume: 50 * USDT }).unwrap();
+}
+
+#[test]
+fn test_rebate_config_bounds() {
+    assert_eq!(get_rebate_config(), RebateConfig::default());
+    assert!(process_rebates_internal(NOW).is_err(), "Disabled by default");
+    assert!(set_rebate_config(RebateConfig { rebate_bp: MAX_REBATE_BP + 1, min_volume: 0 }).is_err());
+
+    // Nothing accrues while rebates are off
+    add_to_reserve(1_000 * USDT);
+    settle_bet(user(1), 10 * USDT, 0).unwrap();
+    assert_eq!(get_rebate_account(user(1)), RebateAccount::default());
+}
+
+#[test]
+fn test_high_volume_player_receives_share_of_collected_edge() {
+    setup();
+    let regular = user(1);
+    // 100 bets of 1 USDT: 60 lost, 40 paid 2x, so the house kept 20 USDT
+    for i in 0..100 {
+        settle_bet(regular, USDT, if i < 40 { 2 * USDT } else { 0 }).unwrap();
+    }
+    assert_eq!(get_rebate_account(regular), RebateAccount { wagered: 100 * USDT, house_net: 20 * USDT as i64 });
+
+    let casual = user(2);
+    settle_bet(casual, 10 * USDT, 0).unwrap();
+    let lucky = user(3);
+    settle_bet(lucky, 60 * USDT, 90 * USDT).unwrap();
+
+    // 10% of the edge on 100 USDT wagered, not 10% of the 20 USDT the player happened to lose
+    let expected = 100 * USDT * house_edge_bp() / 100_000;
+    assert!(expected > 0 && expected < 2 * USDT, "Not 10% of the 20 USDT loss");
+    let reserve_before = get_pool_reserve();
+    assert_eq!(process_rebates_internal(NOW), Ok(expected));
+    assert_eq!(get_balance_internal(regular), expected);
+    assert_eq!(get_pool_reserve(), reserve_before - expected, "Rebates are funded by the pool");
+    assert_eq!(get_rebate_account(regular), RebateAccount::default(), "Accrual restarts after a rebate");
+
+    // Below the volume threshold, or ahead of the house: nothing paid, accrual carries forward
+    assert_eq!(get_balance_internal(casual), 0);
+    assert_eq!(get_rebate_account(casual).wagered, 10 * USDT);
+    assert_eq!(get_balance_internal(lucky), 0);
+    assert_eq!(get_rebate_account(lucky).house_net, -30 * USDT as i64);
+
+    assert_eq!(process_rebates_internal(NOW), Ok(0), "Nothing is paid twice");
+}
+
+#[test]
+fn test_rebates_never_drain_pool_below_operating_minimum() {
+    setup();
+    // The house keeps 950 USDT from one player, leaving 1,950 USDT in the pool
+    settle_bet(user(1), 950 * USDT, 0).unwrap();
+    set_rebate_config(RebateConfig { rebate_bp: MAX_REBATE_BP, min_volume: 0 }).unwrap();
+    // Pool payouts shrink the reserve to just above the operating minimum: the rebate on
+    // 950 USDT wagered cannot be afforded
+    let headroom = rebate_for(&get_rebate_account(user(1)), MAX_REBATE_BP) - 1;
+    let reserve = get_pool_reserve();
+    crate::defi_accounting::liquidity_pool::update_pool_on_win(reserve - min_operating_balance() - headroom);
+
+    assert_eq!(process_rebates_internal(NOW), Ok(0));
+    assert_eq!(get_balance_internal(user(1)), 0);
+    assert_eq!(get_rebate_account(user(1)).house_net, 950 * USDT as i64, "Owed rebate keeps accruing");
+}
+
+#[test]
+fn test_rebate_is_a_share_of_edge_capped_at_house_net() {
+    // A volatile player down 50 USDT on 100 USDT wagered earns rebate on the edge only
+    let down = RebateAccount { wagered: 100 * USDT, house_net: 50 * USDT as i64 };
+    assert_eq!(rebate_for(&down, 1_000), 100 * USDT * house_edge_bp() / 100_000);
+
+    // Never more than the house kept, and nothing while the player is ahead
+    let barely_down = RebateAccount { wagered: 10_000 * USDT, house_net: 1 };
+    assert_eq!(rebate_for(&barely_down, MAX_REBATE_BP), 1);
+    let ahead = RebateAccount { wagered: 10_000 * USDT, house_net: -(USDT as i64) };
+    assert_eq!(rebate_for(&ahead, MAX_REBATE_BP), 0);
+}
@@ -0,0 +1,72 @@
+// Tests for the shared solvency margin
+
+use candid::Principal;
+use crate::defi_accounting::accounting::{
+    decrement_cached_balance, get_balance_internal, get_max_allowed_payout_internal, increment_cached_balance, update_balance,
+};
+use crate::defi_accounting::liquidity_pool::{add_to_reserve, get_pool_reserve, settle_bet};
+use crate::defi_accounting::solvency::{
+    get_solvency_margin_bp, get_solvency_status, init_solvency_margin, payout_backing, required_balance, set_solvency_margin_bp,
+    DEFAULT_SOLVENCY_MARGIN_BP, MAX_SOLVENCY_MARGIN_BP,
+};
+
+const USDT: u64 = 1_000_000;
+
+#[test]
+fn test_required_balance_applies_margin() {
+    assert_eq!(required_balance(1_000_000, 0), Some(1_000_000));
+    assert_eq!(required_balance(1_000_000, 200), Some(1_020_000));
+    // Rounds up so a fractional unit of headroom is never lost
+    assert_eq!(required_balance(1, 200), Some(2));
+    assert_eq!(required_balance(u64::MAX, 200), None);
+}
+
+#[test]
+fn test_margin_setting_and_bounds() {
+    // Memory that never held a margin (an upgraded canister) reads as zero
+    assert_eq!(get_solvency_margin_bp(), 0);
+    init_solvency_margin();
+    assert_eq!(get_solvency_margin_bp(), DEFAULT_SOLVENCY_MARGIN_BP);
+    assert!(set_solvency_margin_bp(MAX_SOLVENCY_MARGIN_BP + 1).is_err());
+
+    set_solvency_margin_bp(500).unwrap();
+    assert_eq!(get_solvency_margin_bp(), 500);
+    assert_eq!(get_solvency_status().margin_bp, 500);
+
+    set_solvency_margin_bp(0).unwrap();
+    assert_eq!(get_solvency_margin_bp(), 0);
+}
+
+#[test]
+fn test_empty_canister_is_solvent() {
+    let status = get_solvency_status();
+    assert_eq!(status.obligations, 0);
+    assert_eq!(status.required_balance, 0);
+    assert!(status.is_solvent);
+}
+
+#[test]
+fn test_payouts_never_backed_by_user_deposits() {
+    let player = Principal::from_slice(&[7; 29]);
+    update_balance(player, 50 * USDT).unwrap();
+    add_to_reserve(100 * USDT);
+    increment_cached_balance(150 * USDT);
+    assert_eq!(payout_backing(), 100 * USDT);
+    assert_eq!(get_max_allowed_payout_internal(), 15 * USDT);
+
+    // The cached balance lags the books by 30 USDT: the shortfall comes out of the LP side
+    decrement_cached_balance(30 * USDT);
+    assert_eq!(payout_backing(), 70 * USDT);
+    assert_eq!(get_max_allowed_payout_internal(), 10_500_000);
+
+    // Only depositors' funds left: no bet can be backed at all
+    decrement_cached_balance(80 * USDT);
+    assert_eq!(payout_backing(), 0);
+    assert_eq!(get_max_allowed_payout_internal(), 0);
+
+    // Settlement still refuses a win larger than the reserve and leaves the books alone
+    let err = settle_bet(player, USDT, 102 * USDT).unwrap_err();
+    assert!(err.starts_with("POOL_INSOLVENT"), "{}", err);
+    assert_eq!(get_pool_reserve(), 100 * USDT);
+    assert_eq!(get_balance_internal(player), 50 * USDT);
+}
@@ -1,21 +1,14 @@
 use candid::{CandidType, Deserialize, Principal};
-use ic_cdk::management_canister::raw_rand;
+use crate::vrf::{raw_rand_with_retries, VRF_RETRIES};
 use crate::types::MIN_BET;
 use crate::defi_accounting::{self as accounting, liquidity_pool};
-use crate::{calculate_multiplier_bp, MULTIPLIER_SCALE, ROWS};
+use crate::{apply_multiplier, calculate_multiplier_bp, calculate_peg_offsets, MULTIPLIER_SCALE, ROWS};
 use serde::Serialize;
 
 // Max multiplier for bet validation (6.52x at edges)
 // This must match calculate_multiplier_bp(0) or calculate_multiplier_bp(8)
 const MAX_MULTIPLIER_BP: u64 = 65_200;
 
-// Statistical constants for variance-aware betting
-// These are derived from the multiplier probability distribution:
-// E[X] = 0.99, Var[X] ≈ 1.092, StdDev[X] ≈ 1.045
-const EV_PER_BALL: f64 = 0.99;
-const STD_PER_BALL: f64 = 1.045;
-const SIGMA_FACTOR: f64 = 4.0; // 4-sigma = 99.994% confidence
-
 // =============================================================================
 // GAME RESULT TYPES
 // =============================================================================
@@ -30,6 +23,7 @@ pub struct PlinkoGameResult {
     pub payout: u64,
     pub profit: i64,
     pub is_win: bool,
+    pub peg_offsets: Vec<i8>,
 }
 
 #[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
@@ -60,42 +54,27 @@ pub fn calculate_max_bet() -> u64 {
     max_bet as u64
 }
 
-/// Calculate payout from bet and multiplier using safe math
-fn calculate_payout(bet_amount: u64, multiplier_bp: u64) -> Result<u64, String> {
-    // (bet * multiplier_bp) / SCALE
-    let scaled = (bet_amount as u128)
-        .checked_mul(multiplier_bp as u128)
-        .ok_or("Payout calculation overflow")?;
-        
-    let payout = scaled / (MULTIPLIER_SCALE as u128);
-    
-    // Check if result fits in u64
-    if payout > u64::MAX as u128 {
-        return Err("Payout exceeds u64 limit".to_string());
-    }
-    
-    Ok(payout as u64)
-}
-
 // =============================================================================
 // MAIN GAME LOGIC
 // =============================================================================
 
 pub async fn play_plinko(bet_amount: u64, caller: Principal) -> Result<PlinkoGameResult, String> {
+    accounting::require_authenticated(caller)?;
+
     // 1. Validate minimum bet (0.01 USDT)
     if bet_amount < MIN_BET {
         return Err("Invalid bet: minimum is 0.01 USDT".to_string());
     }
 
     // 2. Check max payout against house limit
-    let max_potential_payout = calculate_payout(bet_amount, MAX_MULTIPLIER_BP)?;
+    let max_potential_payout = apply_multiplier(bet_amount, MAX_MULTIPLIER_BP);
     let max_allowed = accounting::get_max_allowed_payout();
     if max_potential_payout > max_allowed {
         return Err("Invalid bet: exceeds house limit".to_string());
     }
 
     // 3. Get VRF randomness (async call - execution may suspend here)
-    let random_bytes = raw_rand().await
+    let random_bytes = raw_rand_with_retries(VRF_RETRIES).await
         .map_err(|e| format!("Randomness unavailable: {:?}", e))?;
 
     if random_bytes.is_empty() {
@@ -115,10 +94,11 @@ pub async fn play_plinko(bet_amount: u64, caller: Principal) -> Result<PlinkoGam
 
     // 8. Calculate multiplier and payout
     let multiplier_bp = calculate_multiplier_bp(final_position)?;
-    let payout = calculate_payout(bet_amount, multiplier_bp)?;
+    let payout = apply_multiplier(bet_amount, multiplier_bp);
     let multiplier = multiplier_bp as f64 / MULTIPLIER_SCALE as f64;
     let is_win = multiplier_bp >= MULTIPLIER_SCALE;
     let profit = (payout as i64) - (bet_amount as i64);
+    let peg_offsets = calculate_peg_offsets(&random_bytes, 0, &path);
 
     // 9. Credit payout to user
     let current_balance = accounting::get_balance(caller);
@@ -128,7 +108,7 @@ pub async fn play_plinko(bet_amount: u64, caller: Principal) -> Result<PlinkoGam
 
     // 10. Settle with pool
     // This updates the LP shares/values based on net profit/loss of the house
-    if let Err(e) = liquidity_pool::settle_bet(bet_amount, payout) {
+    if let Err(e) = liquidity_pool::settle_bet(caller, bet_amount, payout) {
         // CRITICAL: Rollback if pool settlement fails
         // Refund the bet amount to the user (current_balance is balance BEFORE payout)
         // refund = (original - bet) + bet = original
@@ -140,6 +120,12 @@ pub async fn play_plinko(bet_amount: u64, caller: Principal) -> Result<PlinkoGam
         return Err(format!("House settlement failed. Bet refunded. Error: {}", e));
     }
 
+    crate::defi_accounting::game_history::record_game(
+        caller, bet_amount, payout,
+        format!("Landed in slot {} ({:.2}x)", final_position, multiplier),
+        ic_cdk::api::time(),
+    );
+
     Ok(PlinkoGameResult { 
         path, 
         final_position, 
@@ -148,11 +134,14 @@ pub async fn play_plinko(bet_amount: u64, caller: Principal) -> Result<PlinkoGam
         bet_amount, 
         payout, 
         profit, 
-        is_win 
+        is_win,
+        peg_offsets,
     })
 }
 
 pub async fn play_multi_plinko(ball_count: u8, bet_per_ball: u64, caller: Principal) -> Result<MultiBallGameResult, String> {
+    accounting::require_authenticated(caller)?;
+
     const MAX_BALLS: u8 = 30;
 
     // 1. Validate inputs
@@ -166,13 +155,9 @@ pub async fn play_multi_plinko(ball_count: u8, bet_per_ball: u64, caller: Princi
         return Err("Invalid bet: minimum is 0.01 USDT per ball".to_string());
     }
 
-    let total_bet = bet_per_ball.checked_mul(ball_count as u64)
-        .ok_or("Total bet calculation overflow")?;
-
-    // 2. Check max payout against house limit (using variance-aware calculation)
-    // Use the effective multiplier based on ball count, not the theoretical max
-    let effective_mult_bp = calculate_effective_max_multiplier_bp(ball_count);
-    let max_potential_payout_per_ball = calculate_payout(bet_per_ball, effective_mult_bp)?;
+    // 2. Check the worst case (every ball on an edge) against the house limit BEFORE any
+    // randomness is drawn, so every realized drop can be honored
+    let max_potential_payout_per_ball = apply_multiplier(bet_per_ball, MAX_MULTIPLIER_BP);
     let max_potential_payout = max_potential_payout_per_ball.checked_mul(ball_count as u64)
         .ok_or("Max payout calculation overflow")?;
 
@@ -182,60 +167,49 @@ pub async fn play_multi_plinko(ball_count: u8, bet_per_ball: u64, caller: Princi
     }
 
     // 3. Get VRF randomness (async call - execution may suspend here)
-    let random_bytes = raw_rand().await
+    let random_bytes = raw_rand_with_retries(VRF_RETRIES).await
         .map_err(|e| format!("Randomness unavailable: {:?}", e))?;
 
     if random_bytes.len() < ball_count as usize {
         return Err("Insufficient randomness".to_string());
     }
 
-    // 4. Atomically deduct total bet AFTER await to prevent TOCTOU race condition
-    let _balance_after_bet = accounting::try_deduct_balance(caller, total_bet)?;
-
-    // 5. Record volume
-    crate::defi_accounting::record_bet_volume(total_bet);
-
-    // 7. Process each ball
-    let mut results = Vec::with_capacity(ball_count as usize);
-    let mut total_payout: u64 = 0;
-
-    for i in 0..ball_count {
-        let random_byte = random_bytes[i as usize];
+    settle_multi_ball(caller, ball_count, bet_per_ball, &random_bytes, ic_cdk::api::time())
+}
 
-        // Path generation
-        let path: Vec<bool> = (0..ROWS).map(|bit| (random_byte >> bit) & 1 == 1).collect();
-        let final_position = path.iter().filter(|&&d| d).count() as u8;
+/// Resolve every ball first, then apply the whole drop as a single settlement.
+///
+/// The caller's balance is untouched until all outcomes and the total payout are known.
+/// The house limit was already checked against the worst case before randomness was
+/// drawn, so the realized result is always honored; only a pool that can no longer pay
+/// at all (`settle_bet` failing) refunds the whole stake.
+pub(crate) fn settle_multi_ball(
+    caller: Principal,
+    ball_count: u8,
+    bet_per_ball: u64,
+    random_bytes: &[u8],
+    now: u64,
+) -> Result<MultiBallGameResult, String> {
+    let total_bet = bet_per_ball.checked_mul(ball_count as u64)
+        .ok_or("Total bet calculation overflow")?;
 
-        // Calc result
-        let multiplier_bp = calculate_multiplier_bp(final_position)?;
-        let payout = calculate_payout(bet_per_ball, multiplier_bp)?;
-        let multiplier = multiplier_bp as f64 / MULTIPLIER_SCALE as f64;
-        let is_win = multiplier_bp >= MULTIPLIER_SCALE;
-        let profit = (payout as i64) - (bet_per_ball as i64);
+    // 4. Resolve all balls (no state changes yet)
+    let (results, total_payout) = resolve_balls(ball_count, bet_per_ball, random_bytes)?;
 
-        total_payout = total_payout.checked_add(payout)
-            .ok_or("Total payout overflow")?;
+    // 5. Atomically deduct total bet
+    let _balance_after_bet = accounting::try_deduct_balance(caller, total_bet)?;
 
-        results.push(PlinkoGameResult {
-            path,
-            final_position,
-            multiplier_bp,
-            multiplier,
-            bet_amount: bet_per_ball,
-            payout,
-            profit,
-            is_win,
-        });
-    }
+    // 6. Record volume once for the whole drop
+    crate::defi_accounting::statistics::record_bet_volume_at(total_bet, now);
 
-    // 8. Credit total payout
+    // 7. Credit total payout
     let current_balance = accounting::get_balance(caller);
     let new_balance = current_balance.checked_add(total_payout)
         .ok_or("Balance overflow when adding winnings")?;
     accounting::update_balance(caller, new_balance)?;
 
-    // 9. Settle with pool
-    if let Err(e) = liquidity_pool::settle_bet(total_bet, total_payout) {
+    // 8. Settle with pool
+    if let Err(e) = liquidity_pool::settle_bet(caller, total_bet, total_payout) {
         // Rollback on failure
         let refund_balance = current_balance.checked_add(total_bet)
             .ok_or("Refund calculation overflow")?;
@@ -245,11 +219,17 @@ pub async fn play_multi_plinko(ball_count: u8, bet_per_ball: u64, caller: Princi
         return Err(format!("House settlement failed. Bet refunded. Error: {}", e));
     }
 
-    // 10. Aggregate results
+    // 9. Aggregate results
     let net_profit = (total_payout as i64) - (total_bet as i64);
     let sum_multipliers: f64 = results.iter().map(|r| r.multiplier).sum();
     let average_multiplier = sum_multipliers / (ball_count as f64);
 
+    crate::defi_accounting::game_history::record_game(
+        caller, total_bet, total_payout,
+        format!("{} balls, average {:.2}x", ball_count, average_multiplier),
+        now,
+    );
+
     Ok(MultiBallGameResult {
         results,
         total_balls: ball_count,
@@ -260,34 +240,43 @@ pub async fn play_multi_plinko(ball_count: u8, bet_per_ball: u64, caller: Princi
     })
 }
 
-/// Calculate the effective max multiplier for a given ball count.
-///
-/// For 1-3 balls: Use actual max (6.52x) - single balls can realistically hit edges
-/// For 4+ balls: Use statistical worst-case (4-sigma) based on the Law of Large Numbers
-///
-/// The formula for 4+ balls:
-///   effective_max = E[X] + sigma_factor * StdDev[X] / sqrt(n)
-///                 = 0.99 + 4 * 1.045 / sqrt(n)
-///                 = 0.99 + 4.18 / sqrt(n)
-///
-/// This allows higher bets for multi-ball games while maintaining the same
-/// actual risk exposure to the house.
-fn calculate_effective_max_multiplier_bp(ball_count: u8) -> u64 {
-    // For low ball counts, use actual max - single balls CAN hit 6.52x
-    if ball_count <= 3 {
-        return MAX_MULTIPLIER_BP;
-    }
+/// Outcome of each ball (one random byte per ball) and the total payout
+fn resolve_balls(ball_count: u8, bet_per_ball: u64, random_bytes: &[u8]) -> Result<(Vec<PlinkoGameResult>, u64), String> {
+    let mut results = Vec::with_capacity(ball_count as usize);
+    let mut total_payout: u64 = 0;
+
+    for i in 0..ball_count {
+        let random_byte = *random_bytes.get(i as usize).ok_or("Insufficient randomness")?;
 
-    // For 4+ balls, use statistical worst-case
-    // Law of Large Numbers: variance decreases as n increases
-    let n = ball_count as f64;
-    let effective_max = EV_PER_BALL + SIGMA_FACTOR * STD_PER_BALL / n.sqrt();
+        // Path generation
+        let path: Vec<bool> = (0..ROWS).map(|bit| (random_byte >> bit) & 1 == 1).collect();
+        let final_position = path.iter().filter(|&&d| d).count() as u8;
 
-    // Convert to basis points
-    let effective_bp = (effective_max * MULTIPLIER_SCALE as f64) as u64;
+        // Calc result
+        let multiplier_bp = calculate_multiplier_bp(final_position)?;
+        let payout = apply_multiplier(bet_per_ball, multiplier_bp);
+        let multiplier = multiplier_bp as f64 / MULTIPLIER_SCALE as f64;
+        let is_win = multiplier_bp >= MULTIPLIER_SCALE;
+        let profit = (payout as i64) - (bet_per_ball as i64);
+        let peg_offsets = calculate_peg_offsets(random_bytes, i, &path);
+
+        total_payout = total_payout.checked_add(payout)
+            .ok_or("Total payout overflow")?;
+
+        results.push(PlinkoGameResult {
+            path,
+            final_position,
+            multiplier_bp,
+            multiplier,
+            bet_amount: bet_per_ball,
+            payout,
+            profit,
+            is_win,
+            peg_offsets,
+        });
+    }
 
-    // Safety cap at actual max (shouldn't be needed for n >= 4)
-    effective_bp.min(MAX_MULTIPLIER_BP)
+    Ok((results, total_payout))
 }
 
 pub fn calculate_max_bet_per_ball(ball_count: u8) -> Result<u64, String> {
@@ -296,10 +285,10 @@ pub fn calculate_max_bet_per_ball(ball_count: u8) -> Result<u64, String> {
     let max_allowed = accounting::get_max_allowed_payout();
     if max_allowed == 0 { return Ok(0); }
 
-    // Get the variance-aware effective multiplier for this ball count
-    let effective_mult_bp = calculate_effective_max_multiplier_bp(ball_count);
+    // Every ball may land on an edge: the same worst case play_multi_plinko checks
+    let effective_mult_bp = MAX_MULTIPLIER_BP;
 
-    // Max bet = Max Allowed Payout / (Balls * Effective Multiplier)
+    // Max bet = Max Allowed Payout / (Balls * Max Multiplier)
     // Use u128 for calculation to prevent overflow
     let max_allowed_u128 = max_allowed as u128;
     let balls_u128 = ball_count as u128;
@@ -324,11 +313,10 @@ pub fn calculate_max_bet_per_ball(ball_count: u8) -> Result<u64, String> {
     Ok(max_bet as u64)
 }
 
-/// Get the effective max multiplier used for bet validation (in basis points).
-/// This is exposed for frontend transparency - users can see how the limit scales.
+/// Get the max multiplier used for bet validation (in basis points).
+/// Limits use the true per-ball maximum for every ball count, so both values are equal.
 ///
 /// Returns: (effective_multiplier_bp, actual_max_multiplier_bp)
-pub fn get_effective_multiplier_bp(ball_count: u8) -> (u64, u64) {
-    let effective = calculate_effective_max_multiplier_bp(ball_count);
-    (effective, MAX_MULTIPLIER_BP)
+pub fn get_effective_multiplier_bp(_ball_count: u8) -> (u64, u64) {
+    (MAX_MULTIPLIER_BP, MAX_MULTIPLIER_BP)
 }
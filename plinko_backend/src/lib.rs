@@ -21,7 +21,7 @@
 
 use candid::{CandidType, Deserialize, Principal};
 use ic_cdk::{init, pre_upgrade, post_upgrade, query, update};
-use ic_cdk::management_canister::raw_rand;
+use crate::vrf::{raw_rand_with_retries, VRF_RETRIES};
 use ic_stable_structures::memory_manager::{MemoryManager, VirtualMemory};
 use ic_stable_structures::DefaultMemoryImpl;
 use std::cell::RefCell;
@@ -33,6 +33,7 @@ use std::cell::RefCell;
 mod defi_accounting;
 pub mod types;
 pub mod game;
+pub mod vrf;
 
 pub use game::{PlinkoGameResult, MultiBallGameResult};
 
@@ -53,6 +54,7 @@ pub struct PlinkoResult {
     pub final_position: u8,     // 0 to 8
     pub multiplier: f64,
     pub win: bool,              // true if multiplier >= 1.0
+    pub peg_offsets: Vec<i8>,   // cosmetic bounce strength per row, see calculate_peg_offsets
 }
 
 #[derive(CandidType, Deserialize, Clone, Debug)]
@@ -97,6 +99,12 @@ pub const BINOMIAL_COEFFICIENTS: [u64; 9] = [1, 8, 28, 56, 70, 56, 28, 8, 1];
 /// Total paths through 8-row board (2^8 = 256)
 pub const TOTAL_PATHS: u64 = 256;
 
+/// Advertised expected value (1% house edge), checked at startup
+pub const TARGET_EXPECTED_VALUE: f64 = 0.99;
+
+/// Allowed deviation from TARGET_EXPECTED_VALUE (the integer table is exact, so this only absorbs float rounding)
+pub const EXPECTED_VALUE_TOLERANCE: f64 = 0.000001;
+
 // ============================================================================
 // CORE LOGIC
 // ============================================================================
@@ -131,21 +139,109 @@ pub fn calculate_multiplier_bp(position: u8) -> Result<u64, String> {
         .ok_or("Overflow in final multiplier calculation".to_string())
 }
 
+/// Payout for `bet` at `multiplier_bp` (scaled by MULTIPLIER_SCALE), in pure integer math.
+///
+/// Rounding policy: round half up to the nearest unit, so the paid amount is the exact
+/// product to within half a unit and the edge is the one the multiplier table states.
+/// Saturates at u64::MAX, which no house limit can accept.
+pub fn apply_multiplier(bet: u64, multiplier_bp: u64) -> u64 {
+    let scaled = bet as u128 * multiplier_bp as u128 + (MULTIPLIER_SCALE / 2) as u128;
+    u64::try_from(scaled / MULTIPLIER_SCALE as u128).unwrap_or(u64::MAX)
+}
+
+/// Maximum absolute peg offset (offsets are in -3..=-1 or 1..=3)
+pub const MAX_PEG_OFFSET: i8 = 3;
+
+/// Derive cosmetic peg offsets for the frontend animation.
+///
+/// The final position is decided by `path` alone; offsets only describe how hard
+/// the ball bounces at each peg so every client renders the same trajectory.
+///
+/// Derivation (reproducible client-side from the revealed VRF bytes):
+/// ```text
+/// seed      = u64 big-endian from the last 8 random bytes (zero-padded) XOR ball_index
+/// x[r]      = splitmix64(seed + r)          for each row r
+/// strength  = 1 + (x[r] % 3)                in 1..=3
+/// offset[r] = +strength if path[r] (right) else -strength
+/// ```
+pub fn calculate_peg_offsets(random_bytes: &[u8], ball_index: u8, path: &[bool]) -> Vec<i8> {
+    let mut seed_bytes = [0u8; 8];
+    let tail = &random_bytes[random_bytes.len().saturating_sub(8)..];
+    seed_bytes[8 - tail.len()..].copy_from_slice(tail);
+    let seed = u64::from_be_bytes(seed_bytes) ^ ball_index as u64;
+
+    path.iter()
+        .enumerate()
+        .map(|(row, &right)| {
+            let strength = 1 + (splitmix64(seed.wrapping_add(row as u64)) % MAX_PEG_OFFSET as u64) as i8;
+            if right { strength } else { -strength }
+        })
+        .collect()
+}
+
+/// SplitMix64 finalizer - small, well-known mixer that is easy to port to JS (BigInt)
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Expected value of a multiplier table (basis points per position) under the binomial
+/// distribution of the 8-row board
+pub fn expected_value_of(multipliers_bp: &[u64]) -> f64 {
+    BINOMIAL_COEFFICIENTS.iter()
+        .zip(multipliers_bp)
+        .map(|(&coeff, &multiplier_bp)| {
+            let probability = coeff as f64 / TOTAL_PATHS as f64;
+            probability * (multiplier_bp as f64 / MULTIPLIER_SCALE as f64)
+        })
+        .sum()
+}
+
+/// Reject a multiplier table whose EV drifts from the advertised 0.99
+pub fn check_expected_value(ev: f64) -> Result<(), String> {
+    if (ev - TARGET_EXPECTED_VALUE).abs() > EXPECTED_VALUE_TOLERANCE {
+        return Err(format!(
+            "House edge self-check failed: expected value {} is not {} (rows: {})",
+            ev, TARGET_EXPECTED_VALUE, ROWS
+        ));
+    }
+    Ok(())
+}
+
+/// Trap install/upgrade if the compiled multiplier constants don't give the advertised EV,
+/// so a wrong table never goes live
+fn verify_house_edge() {
+    let ev = get_expected_value();
+    ic_cdk::println!("House edge self-check: {}-row expected value {}", ROWS, ev);
+    if let Err(e) = check_expected_value(ev) {
+        ic_cdk::trap(e);
+    }
+}
+
 // ============================================================================
 // LIFECYCLE HOOKS
 // ============================================================================
 
 #[init]
 fn init() {
+    verify_house_edge();
     ic_cdk::println!("Plinko Backend Initialized with DeFi Accounting");
+
+    // Fresh canisters start with the default solvency margin; upgrades keep theirs
+    defi_accounting::solvency::init_solvency_margin();
+
     defi_accounting::accounting::start_parent_withdrawal_timer();
     defi_accounting::accounting::start_balance_reconciliation_timer();
     defi_accounting::start_stats_timer();
+    defi_accounting::rebates::start_rebate_timer();
 
     // Initialize cached balance on fresh install using a one-shot timer
     // (spawn not allowed in init mode)
     ic_cdk_timers::set_timer(std::time::Duration::ZERO, async {
         defi_accounting::accounting::refresh_canister_balance().await;
+        defi_accounting::accounting::refresh_token_info().await;
         ic_cdk::println!("Init: balance cache initialized");
     });
 }
@@ -158,9 +254,11 @@ fn pre_upgrade() {
 
 #[post_upgrade]
 fn post_upgrade() {
+    verify_house_edge();
     defi_accounting::accounting::start_parent_withdrawal_timer();
     defi_accounting::accounting::start_balance_reconciliation_timer();
     defi_accounting::start_stats_timer();
+    defi_accounting::rebates::start_rebate_timer();
 
     // Initialize cached balance immediately after upgrade using a one-shot timer
     // This prevents games being blocked until hourly reconciliation
@@ -178,20 +276,7 @@ fn post_upgrade() {
 // ============================================================================
 
 fn is_canister_solvent() -> bool {
-    let pool_reserve = defi_accounting::liquidity_pool::get_pool_reserve();
-    let total_deposits = defi_accounting::accounting::calculate_total_deposits_internal();
-    let canister_balance = defi_accounting::accounting::get_cached_canister_balance_internal();
-
-    // Use checked_add to detect impossible overflow scenarios
-    let obligations = match pool_reserve.checked_add(total_deposits) {
-        Some(o) => o,
-        None => {
-            ic_cdk::println!("CRITICAL: Obligations overflow u64::MAX");
-            return false;
-        }
-    };
-
-    canister_balance >= obligations
+    defi_accounting::solvency::is_canister_solvent()
 }
 
 // ============================================================================
@@ -203,17 +288,30 @@ async fn play_plinko(bet_amount: u64) -> Result<PlinkoGameResult, String> {
     // Solvency check uses cached balance (no ledger query needed)
     // Balance is tracked internally on deposit/withdraw and reconciled hourly
     if !is_canister_solvent() {
-        return Err("Game temporarily paused - insufficient funds.".to_string());
+        return Err(defi_accounting::types::GameError::Insolvent.into());
     }
     game::play_plinko(bet_amount, ic_cdk::api::msg_caller()).await
 }
 
+/// Deposit via ICRC-2 and drop a ball in one call. If the bet fails, the deposit stays in the balance.
+#[update]
+async fn deposit_and_play(deposit_amount: u64, bet_amount: u64) -> Result<PlinkoGameResult, String> {
+    if !is_canister_solvent() {
+        return Err(defi_accounting::types::GameError::Insolvent.into());
+    }
+    let caller = ic_cdk::api::msg_caller();
+    defi_accounting::accounting::deposit_and_play(
+        defi_accounting::accounting::deposit_internal(caller, deposit_amount, None),
+        game::play_plinko(bet_amount, caller),
+    ).await
+}
+
 #[update]
 async fn play_multi_plinko(ball_count: u8, bet_per_ball: u64) -> Result<MultiBallGameResult, String> {
     // Solvency check uses cached balance (no ledger query needed)
     // Balance is tracked internally on deposit/withdraw and reconciled hourly
     if !is_canister_solvent() {
-        return Err("Game temporarily paused - insufficient funds.".to_string());
+        return Err(defi_accounting::types::GameError::Insolvent.into());
     }
     game::play_multi_plinko(ball_count, bet_per_ball, ic_cdk::api::msg_caller()).await
 }
@@ -228,14 +326,11 @@ fn get_max_bet_per_ball(ball_count: u8) -> Result<u64, String> {
     game::calculate_max_bet_per_ball(ball_count)
 }
 
-/// Get the effective max multiplier used for bet validation.
+/// Get the max multiplier used for bet validation.
 /// Returns (effective_multiplier_bp, actual_max_multiplier_bp).
 ///
-/// For 1-3 balls: effective = actual (6.52x) - high variance
-/// For 4+ balls: effective decreases based on Law of Large Numbers
-///
-/// This allows higher per-ball bets for multi-ball games while
-/// maintaining the same actual risk to the house.
+/// Multi-ball limits assume every ball can land on an edge (6.52x) and are checked
+/// before randomness is drawn, so both values are the actual max for every ball count.
 #[query]
 fn get_effective_multiplier(ball_count: u8) -> (u64, u64) {
     game::get_effective_multiplier_bp(ball_count)
@@ -246,23 +341,28 @@ fn get_effective_multiplier(ball_count: u8) -> (u64, u64) {
 // =============================================================================
 
 #[update]
-async fn deposit(amount: u64) -> Result<u64, String> {
-    defi_accounting::accounting::deposit(amount).await
+async fn deposit(amount: u64, idempotency_key: Option<[u8; 16]>) -> Result<defi_accounting::types::DepositReceipt, String> {
+    defi_accounting::accounting::deposit(amount, idempotency_key).await.map_err(|e| e.to_string())
 }
 
 #[update]
 async fn withdraw_all() -> Result<u64, String> {
-    defi_accounting::accounting::withdraw_all().await
+    defi_accounting::accounting::withdraw_all().await.map_err(|e| e.to_string())
+}
+
+#[update]
+async fn withdraw_keeping(keep_amount: u64) -> Result<u64, defi_accounting::types::GameError> {
+    defi_accounting::accounting::withdraw_keeping(keep_amount).await
 }
 
 #[update]
 async fn retry_withdrawal() -> Result<u64, String> {
-    defi_accounting::accounting::retry_withdrawal().await
+    defi_accounting::accounting::retry_withdrawal().await.map_err(|e| e.to_string())
 }
 
 #[update]
 fn abandon_withdrawal() -> Result<u64, String> {
-    defi_accounting::accounting::abandon_withdrawal()
+    defi_accounting::accounting::abandon_withdrawal().map_err(|e| e.to_string())
 }
 
 #[query]
@@ -270,6 +370,11 @@ fn get_my_withdrawal_status() -> Option<defi_accounting::types::PendingWithdrawa
     defi_accounting::accounting::get_withdrawal_status()
 }
 
+#[query]
+fn get_my_withdrawal_detail() -> Option<defi_accounting::types::WithdrawalStatusDetail> {
+    defi_accounting::accounting::get_withdrawal_status_detail()
+}
+
 #[query]
 fn get_balance(principal: Principal) -> u64 {
     defi_accounting::query::get_balance(principal)
@@ -290,6 +395,27 @@ fn get_max_allowed_payout() -> u64 {
     defi_accounting::query::get_max_allowed_payout()
 }
 
+#[query]
+fn get_token_info() -> defi_accounting::types::TokenInfo {
+    defi_accounting::query::get_token_info()
+}
+
+#[query]
+fn get_solvency_status() -> defi_accounting::types::SolvencyStatus {
+    defi_accounting::solvency::get_solvency_status()
+}
+
+#[query]
+fn get_my_lifetime_stats() -> defi_accounting::types::PlayerLifetimeStats {
+    defi_accounting::player_stats::get_player_stats(ic_cdk::api::msg_caller())
+}
+
+/// The caller's most recent settled games, newest first (at most 50 are kept)
+#[query]
+fn get_my_recent_games(limit: u32) -> Vec<defi_accounting::types::GameHistoryEntry> {
+    defi_accounting::game_history::get_recent_games(ic_cdk::api::msg_caller(), limit)
+}
+
 // =============================================================================
 // LIQUIDITY POOL ENDPOINTS
 // =============================================================================
@@ -309,6 +435,28 @@ fn get_pool_stats() -> defi_accounting::liquidity_pool::PoolStats {
     defi_accounting::query::get_pool_stats()
 }
 
+#[query]
+fn get_target_reserve() -> u64 {
+    defi_accounting::liquidity_pool::get_target_reserve()
+}
+
+#[query]
+fn get_rebate_config() -> defi_accounting::types::RebateConfig {
+    defi_accounting::rebates::get_rebate_config()
+}
+
+/// Caller's loyalty rebate accrual since their last rebate
+#[query]
+fn get_my_rebate_account() -> defi_accounting::types::RebateAccount {
+    defi_accounting::rebates::get_rebate_account(ic_cdk::api::msg_caller())
+}
+
+/// True while an admin has paused betting (e.g. during a migration)
+#[query]
+fn is_betting_paused() -> bool {
+    defi_accounting::pool_drain::is_betting_paused()
+}
+
 #[query]
 fn get_lp_position(principal: Principal) -> defi_accounting::liquidity_pool::LPPosition {
     defi_accounting::query::get_lp_position(principal)
@@ -358,6 +506,11 @@ fn admin_get_orphaned_funds_report_full() -> Result<defi_accounting::types::Orph
     defi_accounting::admin_query::get_orphaned_funds_report_full()
 }
 
+#[query]
+fn admin_reconcile_user(user: candid::Principal) -> Result<defi_accounting::types::ReconcileReport, String> {
+    defi_accounting::admin_query::reconcile_user(user)
+}
+
 #[query]
 fn admin_get_all_balances(offset: u64, limit: u64) -> Result<Vec<defi_accounting::types::UserBalance>, String> {
     defi_accounting::admin_query::get_all_balances(offset, limit)
@@ -388,6 +541,133 @@ fn admin_get_audit_log_count() -> Result<u64, String> {
     defi_accounting::admin_query::get_audit_log_count()
 }
 
+#[query]
+fn admin_get_audit_retention() -> Result<u64, String> {
+    defi_accounting::admin_query::get_audit_retention()
+}
+
+#[update]
+fn admin_set_audit_retention(limit: u64) -> Result<(), String> {
+    defi_accounting::admin_query::set_audit_retention(limit)
+}
+
+#[query]
+fn admin_get_parent_config() -> Result<defi_accounting::types::ParentWithdrawalConfig, String> {
+    defi_accounting::admin_query::get_parent_config()
+}
+
+#[update]
+fn admin_set_parent_config(threshold: u64, recipient: types::Account) -> Result<(), String> {
+    defi_accounting::admin_query::set_parent_config(threshold, recipient)
+}
+
+#[update]
+fn admin_set_betting_paused(paused: bool) -> Result<(), String> {
+    defi_accounting::admin_query::set_betting_paused(paused)
+}
+
+#[update]
+async fn admin_initiate_pool_drain(recipient: types::Account) -> Result<u64, String> {
+    defi_accounting::admin_query::initiate_pool_drain(recipient).await
+}
+
+#[query]
+fn admin_get_pending_drain() -> Result<Option<defi_accounting::types::PendingDrain>, String> {
+    defi_accounting::admin_query::get_pending_drain()
+}
+
+#[update]
+async fn admin_retry_pool_drain() -> Result<u64, String> {
+    defi_accounting::admin_query::retry_pool_drain().await
+}
+
+#[update]
+fn admin_resolve_pool_drain(landed: bool) -> Result<u64, String> {
+    defi_accounting::admin_query::resolve_pool_drain(landed)
+}
+
+#[update]
+fn admin_set_solvency_margin_bp(margin_bp: u64) -> Result<(), String> {
+    defi_accounting::admin_query::set_solvency_margin_bp(margin_bp)
+}
+
+#[update]
+fn admin_set_target_reserve(target: u64) -> Result<(), String> {
+    defi_accounting::admin_query::set_target_reserve(target)
+}
+
+#[update]
+fn admin_distribute_excess() -> Result<u64, String> {
+    defi_accounting::admin_query::distribute_excess()
+}
+
+#[update]
+fn admin_set_rebate_config(config: defi_accounting::types::RebateConfig) -> Result<(), String> {
+    defi_accounting::admin_query::set_rebate_config(config)
+}
+
+#[update]
+fn admin_process_rebates() -> Result<u64, String> {
+    defi_accounting::admin_query::process_rebates()
+}
+
+#[update]
+async fn admin_set_ledger_canister_id(ledger: candid::Principal) -> Result<defi_accounting::types::TokenInfo, String> {
+    defi_accounting::admin_query::set_ledger_canister_id(ledger).await
+}
+
+#[query]
+fn admin_get_event_subscriber() -> Result<Option<candid::Principal>, String> {
+    defi_accounting::admin_query::get_event_subscriber()
+}
+
+#[update]
+fn admin_set_event_subscriber(canister: candid::Principal) -> Result<(), String> {
+    defi_accounting::admin_query::set_event_subscriber(canister)
+}
+
+#[update]
+fn admin_clear_event_subscriber() -> Result<(), String> {
+    defi_accounting::admin_query::clear_event_subscriber()
+}
+
+/// Every admin-settable parameter as one candid blob, to clone onto another canister
+/// or restore later with `admin_import_config`
+#[query]
+fn admin_export_config() -> Result<types::GameConfigBlob, String> {
+    defi_accounting::admin_query::require_admin()?;
+    Ok(export_game_config())
+}
+
+/// Restore a blob from `admin_export_config`. Nothing changes unless every setting is valid.
+#[update]
+fn admin_import_config(blob: types::GameConfigBlob) -> Result<(), String> {
+    defi_accounting::admin_query::require_admin()?;
+    import_game_config(&blob, ic_cdk::api::time())
+}
+
+fn export_game_config() -> types::GameConfigBlob {
+    let config = types::GameConfig {
+        game: env!("CARGO_PKG_NAME").to_string(),
+        accounting: defi_accounting::config::export_config(),
+    };
+    candid::encode_one(config).expect("CRITICAL: Failed to encode GameConfig")
+}
+
+fn import_game_config(blob: &[u8], now: u64) -> Result<(), String> {
+    let config: types::GameConfig = candid::decode_one(blob)
+        .map_err(|e| format!("Invalid config blob: {}", e))?;
+    if config.game != env!("CARGO_PKG_NAME") {
+        return Err(format!("Config blob is for {}, not {}", config.game, env!("CARGO_PKG_NAME")));
+    }
+    defi_accounting::config::apply_config(config.accounting, now)
+}
+
+#[query]
+fn get_ledger_canister_id() -> candid::Principal {
+    defi_accounting::accounting::get_ledger_canister_id()
+}
+
 // =============================================================================
 // STATISTICS ENDPOINTS
 // =============================================================================
@@ -402,6 +682,17 @@ fn get_pool_apy(days: Option<u32>) -> defi_accounting::ApyInfo {
     defi_accounting::get_apy_info(days)
 }
 
+#[query]
+fn get_pool_apy_breakdown(days: Option<u32>) -> defi_accounting::ApyBreakdown {
+    defi_accounting::get_apy_breakdown(days)
+}
+
+/// (day_timestamp, reserve per share) for the last `days` days, to judge LP entry and exit timing
+#[query]
+fn get_share_price_history(days: u32) -> Vec<(u64, f64)> {
+    defi_accounting::get_share_price_history(days)
+}
+
 #[query]
 fn get_stats_range(start_ts: u64, end_ts: u64) -> Vec<defi_accounting::DailySnapshot> {
     defi_accounting::get_snapshots_range(start_ts, end_ts)
@@ -412,6 +703,20 @@ fn get_stats_count() -> u64 {
     defi_accounting::get_snapshot_count()
 }
 
+#[query]
+fn get_stats_summary() -> defi_accounting::types::StatsSummary {
+    defi_accounting::query::get_stats_summary("plinko")
+}
+
+/// Candid interface / stable layout version reported by `get_version_info`
+const SCHEMA_VERSION: u32 = 1;
+
+/// Game name, schema version, enabled features and build commit, so clients can adapt
+#[query]
+fn get_version_info() -> defi_accounting::types::VersionInfo {
+    defi_accounting::query::get_version_info("plinko", SCHEMA_VERSION, vec!["multi_ball".to_string()])
+}
+
 // ============================================================================
 // EXISTING PURE GAME LOGIC (PRESERVED)
 // ============================================================================
@@ -422,7 +727,7 @@ fn get_stats_count() -> u64 {
 #[update]
 async fn drop_ball() -> Result<PlinkoResult, String> {
     // Get randomness - fail safely if unavailable
-    let random_bytes = raw_rand().await
+    let random_bytes = raw_rand_with_retries(VRF_RETRIES).await
         .map_err(|e| format!("Randomness unavailable: {:?}", e))?
 ;
 
@@ -446,12 +751,14 @@ async fn drop_ball() -> Result<PlinkoResult, String> {
     let multiplier = multiplier_bp as f64 / MULTIPLIER_SCALE as f64;
 
     let win = multiplier >= 1.0;
+    let peg_offsets = calculate_peg_offsets(&random_bytes, 0, &path);
 
     Ok(PlinkoResult {
         path,
         final_position,
         multiplier,
         win,
+        peg_offsets,
     })
 }
 
@@ -470,7 +777,7 @@ async fn drop_multiple_balls(count: u8) -> Result<MultiBallResult, String> {
     }
 
     // Get randomness - one VRF call gives us 32 bytes
-    let random_bytes = raw_rand().await
+    let random_bytes = raw_rand_with_retries(VRF_RETRIES).await
         .map_err(|e| format!("Randomness unavailable: {:?}", e))?;
 
     if random_bytes.len() < count as usize {
@@ -497,12 +804,14 @@ async fn drop_multiple_balls(count: u8) -> Result<MultiBallResult, String> {
         let multiplier = multiplier_bp as f64 / MULTIPLIER_SCALE as f64;
         
         let win = multiplier >= 1.0;
+        let peg_offsets = calculate_peg_offsets(&random_bytes, i, &path);
 
         results.push(PlinkoResult {
             path,
             final_position,
             multiplier,
             win,
+            peg_offsets,
         });
     }
 
@@ -540,15 +849,19 @@ fn get_formula() -> String {
 /// Get expected value for transparency
 #[query]
 fn get_expected_value() -> f64 {
-    BINOMIAL_COEFFICIENTS.iter()
-        .enumerate()
-        .map(|(pos, &coeff)| {
-            let probability = coeff as f64 / TOTAL_PATHS as f64;
-            let multiplier_bp = calculate_multiplier_bp(pos as u8).unwrap_or(0);
-            let multiplier = multiplier_bp as f64 / MULTIPLIER_SCALE as f64;
-            probability * multiplier
-        })
-        .sum()
+    expected_value_of(&get_multipliers_bp())
+}
+
+/// Formula, edge, randomness source and verifiability in the shape shared by all games
+#[query]
+fn get_fairness_info() -> types::FairnessInfo {
+    types::FairnessInfo {
+        formula: get_formula(),
+        house_edge_bp: types::house_edge_bp(get_expected_value()),
+        randomness_source: "IC VRF (raw_rand), one byte per ball".to_string(),
+        // No seed commitment: results carry the path but cannot be re-derived by players
+        verifiable: false,
+    }
 }
 
 #[query]
@@ -611,6 +924,30 @@ mod tests {
             assert!(err.contains("Invalid position"));
         }
 
+        #[test]
+        fn test_peg_offsets_follow_path() {
+            let random_bytes: Vec<u8> = (0..32).collect();
+            for byte in [0u8, 0b1010_1010, 255] {
+                let path: Vec<bool> = (0..ROWS).map(|i| (byte >> i) & 1 == 1).collect();
+                let offsets = calculate_peg_offsets(&random_bytes, 0, &path);
+
+                assert_eq!(offsets.len(), ROWS as usize);
+                for (offset, right) in offsets.iter().zip(&path) {
+                    assert_eq!(*offset > 0, *right, "Offset sign must match path direction");
+                    assert!((1..=MAX_PEG_OFFSET).contains(&offset.abs()));
+                }
+                assert_eq!(offsets, calculate_peg_offsets(&random_bytes, 0, &path), "Must be deterministic");
+            }
+        }
+
+        #[test]
+        fn test_peg_offsets_vary_by_ball() {
+            let random_bytes = [7u8; 32];
+            let path = vec![true; ROWS as usize];
+            let first = calculate_peg_offsets(&random_bytes, 0, &path);
+            assert!((1..30).any(|i| calculate_peg_offsets(&random_bytes, i, &path) != first));
+        }
+
         #[test]
         fn test_expected_value_exactly_point_99() {
             let ev = get_expected_value();
@@ -620,5 +957,110 @@ mod tests {
                 ev
             );
         }
+
+        #[test]
+        fn test_house_edge_self_check() {
+            assert_eq!(check_expected_value(get_expected_value()), Ok(()));
+
+            // A miscompiled constant (center 0.21x instead of 0.2x, or a steeper curve) fails
+            let mut wrong_center = get_multipliers_bp();
+            wrong_center[CENTER_POSITION as usize] += 100;
+            assert!(check_expected_value(expected_value_of(&wrong_center)).is_err());
+
+            let steeper: Vec<u64> = (0..=ROWS)
+                .map(|pos| MIN_MULTIPLIER_BP + (QUADRATIC_FACTOR_BP + 1) * (pos.abs_diff(CENTER_POSITION) as u64).pow(2))
+                .collect();
+            let err = check_expected_value(expected_value_of(&steeper)).unwrap_err();
+            assert!(err.contains("House edge self-check failed"), "{}", err);
+        }
+
+        #[test]
+        fn test_fairness_info() {
+            let info = get_fairness_info();
+            assert_eq!(info.formula, get_formula());
+            assert_eq!(info.house_edge_bp, 100);
+            assert!(!info.verifiable, "Plinko has no seed verification");
+        }
+
+        #[test]
+        fn test_apply_multiplier_matches_rounded_float() {
+            for bp in get_multipliers_bp().into_iter().chain([1, MULTIPLIER_SCALE - 1, MULTIPLIER_SCALE]) {
+                for bet in [1u64, 3, 7, 10_000, 123_457, 999_999_999] {
+                    let exact = bet as f64 * bp as f64 / MULTIPLIER_SCALE as f64;
+                    assert_eq!(apply_multiplier(bet, bp), exact.round() as u64, "bet {} at {} bp", bet, bp);
+                }
+            }
+            // Half a unit rounds up, below half rounds down
+            assert_eq!(apply_multiplier(1, 5_000), 1);
+            assert_eq!(apply_multiplier(1, 4_999), 0);
+            assert_eq!(apply_multiplier(u64::MAX, 65_200), u64::MAX);
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    // Multi-ball settlement
+    // ------------------------------------------------------------------------
+    mod multi_ball {
+        use crate::defi_accounting::{self as accounting, liquidity_pool};
+        use crate::defi_accounting::accounting::increment_cached_balance;
+        use crate::game::{calculate_max_bet_per_ball, play_multi_plinko, settle_multi_ball};
+        use candid::Principal;
+        use std::future::Future;
+        use std::pin::pin;
+        use std::task::{Context, Poll, Waker};
+
+        /// Poll a future once; raw_rand is unavailable here, so reaching it would trap
+        fn poll_once<F: Future>(future: F) -> F::Output {
+            match pin!(future).poll(&mut Context::from_waker(Waker::noop())) {
+                Poll::Ready(output) => output,
+                Poll::Pending => panic!("Future suspended"),
+            }
+        }
+
+        #[test]
+        fn test_oversized_drop_rejected_before_randomness() {
+            let player = Principal::from_slice(&[3; 29]);
+            accounting::update_balance(player, 50_000_000).unwrap();
+            // 100 USDT reserve: 15 USDT max payout. Four 1 USDT balls all landing on an
+            // edge would pay 26.08 USDT, so the drop is refused up front.
+            liquidity_pool::add_to_reserve(100_000_000);
+            increment_cached_balance(150_000_000);
+
+            let err = poll_once(play_multi_plinko(4, 1_000_000, player)).unwrap_err();
+            assert!(err.contains("exceeds house limit"), "{}", err);
+            assert_eq!(accounting::get_balance(player), 50_000_000);
+            assert_eq!(liquidity_pool::get_pool_reserve(), 100_000_000);
+            assert_eq!(calculate_max_bet_per_ball(4), Ok(15_000_000 * 10_000 / (4 * 65_200)));
+        }
+
+        #[test]
+        fn test_worst_case_drop_within_limit_is_honored() {
+            let player = Principal::from_slice(&[5; 29]);
+            accounting::update_balance(player, 50_000_000).unwrap();
+            liquidity_pool::add_to_reserve(100_000_000);
+            increment_cached_balance(150_000_000);
+
+            // Two 1 USDT balls both on an edge: 13.04 USDT, within the 15 USDT limit
+            let result = settle_multi_ball(player, 2, 1_000_000, &[0u8; 32], 0).unwrap();
+            assert_eq!(result.total_payout, 13_040_000);
+            assert_eq!(accounting::get_balance(player), 50_000_000 - 2_000_000 + 13_040_000);
+        }
+
+        #[test]
+        fn test_multi_ball_records_total_volume_once() {
+            let player = Principal::from_slice(&[4; 29]);
+            accounting::update_balance(player, 50_000_000).unwrap();
+            liquidity_pool::add_to_reserve(1_000_000_000);
+            increment_cached_balance(1_050_000_000);
+            let day = 1_735_689_600_000_000_000u64;
+            let volume = || crate::defi_accounting::statistics::storage::DAILY_ACCUMULATOR
+                .with(|acc| acc.borrow().get().volume_accumulated);
+
+            settle_multi_ball(player, 5, 1_000_000, &[0x0f; 32], day).unwrap();
+            assert_eq!(volume(), 5_000_000, "Volume is the drop's total bet, recorded once");
+
+            settle_multi_ball(player, 3, 2_000_000, &[0x0f; 32], day + 60_000_000_000).unwrap();
+            assert_eq!(volume(), 11_000_000);
+        }
     }
 }
@@ -14,7 +14,7 @@ pub const CKUSDT_TRANSFER_FEE: u64 = 10_000; // 0.01 USDT
 // ICRC-2 TYPES
 // =============================================================================
 
-#[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
 pub struct Account {
     pub owner: Principal,
     pub subaccount: Option<[u8; 32]>,
@@ -74,3 +74,33 @@ pub enum TransferError {
     TemporarilyUnavailable,
     GenericError { error_code: candid::Nat, message: String },
 }
+
+// =============================================================================
+// FAIRNESS DISCLOSURE
+// =============================================================================
+
+/// Machine-readable fairness descriptor, the same shape in every game
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct FairnessInfo {
+    pub formula: String,
+    pub house_edge_bp: u16,
+    pub randomness_source: String,
+    /// Whether players can re-derive outcomes from revealed seeds
+    pub verifiable: bool,
+}
+
+/// House edge in basis points for a game returning `expected_value` per unit bet
+pub fn house_edge_bp(expected_value: f64) -> u16 {
+    ((1.0 - expected_value) * 10_000.0).round().clamp(0.0, u16::MAX as f64) as u16
+}
+
+/// Candid-encoded `GameConfig`, as exported by `admin_export_config`
+pub type GameConfigBlob = Vec<u8>;
+
+/// Every admin-settable parameter of this game
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct GameConfig {
+    /// Crate name of the exporting game: a blob only imports into the same game
+    pub game: String,
+    pub accounting: crate::defi_accounting::types::AccountingConfig,
+}
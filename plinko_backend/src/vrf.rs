@@ -0,0 +1,69 @@
+//! VRF randomness with bounded retries
+//!
+//! `raw_rand` occasionally fails on transient subnet issues. Failing the bet outright
+//! loses the player's turn for nothing, so each call is retried a few times first. There is
+//! deliberately no fallback to a weaker source: if every attempt fails, the call errors.
+
+use ic_cdk::call::CallResult;
+use ic_cdk::management_canister::raw_rand;
+
+/// Retries after the first failed `raw_rand` call (so up to VRF_RETRIES + 1 attempts)
+pub const VRF_RETRIES: u32 = 2;
+
+/// `raw_rand`, retried up to `retries` times on error. Returns the last error if all fail.
+pub async fn raw_rand_with_retries(retries: u32) -> CallResult<Vec<u8>> {
+    with_retries(retries, raw_rand).await
+}
+
+/// Retry loop over an injectable randomness source
+pub(crate) async fn with_retries<F, Fut, E>(retries: u32, mut source: F) -> Result<Vec<u8>, E>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<Vec<u8>, E>>,
+{
+    let mut attempt = 0;
+    loop {
+        match source().await {
+            Ok(bytes) => return Ok(bytes),
+            Err(e) if attempt >= retries => return Err(e),
+            Err(_) => attempt += 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::future::{ready, Future};
+    use std::pin::pin;
+    use std::task::{Context, Poll, Waker};
+
+    fn poll_once<T>(fut: impl Future<Output = T>) -> T {
+        match pin!(fut).poll(&mut Context::from_waker(Waker::noop())) {
+            Poll::Ready(v) => v,
+            Poll::Pending => panic!("source futures are always ready"),
+        }
+    }
+
+    #[test]
+    fn test_retries_after_first_error() {
+        let mut calls = 0;
+        let result = poll_once(with_retries(VRF_RETRIES, || {
+            calls += 1;
+            ready(if calls == 1 { Err("transient") } else { Ok(vec![7; 32]) })
+        }));
+        assert_eq!(result, Ok(vec![7; 32]));
+        assert_eq!(calls, 2);
+    }
+
+    #[test]
+    fn test_gives_up_after_retries_with_last_error() {
+        let mut calls = 0;
+        let result: Result<Vec<u8>, u32> = poll_once(with_retries(2, || {
+            calls += 1;
+            ready(Err(calls))
+        }));
+        assert_eq!(result, Err(3));
+        assert_eq!(calls, 3, "One attempt plus two retries");
+    }
+}
@@ -0,0 +1,40 @@
+//! Snapshot and restore of the admin-settable accounting parameters, for cloning a tuned
+//! configuration onto another canister or rolling back a bad change.
+//!
+//! The ledger is left out: it belongs to the deployment and can only be switched while
+//! the canister holds nothing. So is the betting pause, an operational switch rather
+//! than tuning.
+
+use super::types::AccountingConfig;
+use super::{accounting, events, liquidity_pool, parent, rebates, solvency};
+
+pub fn export_config() -> AccountingConfig {
+    AccountingConfig {
+        audit_retention: accounting::get_audit_retention(),
+        solvency_margin_bp: solvency::get_solvency_margin_bp(),
+        target_reserve: liquidity_pool::get_target_reserve(),
+        rebate: rebates::get_rebate_config(),
+        parent: parent::get_parent_config(),
+        event_subscriber: events::get_event_subscriber(),
+    }
+}
+
+pub(crate) fn validate_config(config: &AccountingConfig) -> Result<(), String> {
+    accounting::validate_audit_retention(config.audit_retention)?;
+    solvency::validate_solvency_margin_bp(config.solvency_margin_bp)?;
+    liquidity_pool::validate_target_reserve(config.target_reserve)?;
+    rebates::validate_rebate_config(&config.rebate)?;
+    parent::validate_parent_config(config.parent.threshold, &config.parent.recipient)?;
+    events::validate_event_subscriber(config.event_subscriber)
+}
+
+/// Apply every setting, or none of them if any fails validation
+pub(crate) fn apply_config(config: AccountingConfig, now: u64) -> Result<(), String> {
+    validate_config(&config)?;
+    accounting::set_audit_retention(config.audit_retention)?;
+    solvency::set_solvency_margin_bp(config.solvency_margin_bp)?;
+    liquidity_pool::set_target_reserve(config.target_reserve)?;
+    rebates::set_rebate_config(config.rebate)?;
+    parent::set_parent_config(config.parent.threshold, config.parent.recipient, now)?;
+    events::set_event_subscriber(config.event_subscriber, now)
+}
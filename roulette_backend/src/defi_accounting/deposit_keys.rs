@@ -0,0 +1,138 @@
+//! Deposit idempotency keys.
+//!
+//! `deposit` awaits the ledger, so a frontend retry after a timeout could transfer twice.
+//! When a client supplies an idempotency key, the first attempt records it here (per
+//! principal) together with the ledger `created_at_time` it used:
+//!
+//! - Retry after success: the stored receipt is returned, no second transfer.
+//! - Retry after an uncertain call failure: the transfer is re-sent with the SAME memo and
+//!   `created_at_time`, so the ledger either executes it once or reports `Duplicate`.
+//! - Retry while the first attempt is still awaiting the ledger: rejected.
+//!
+//! Keys expire after `DEPOSIT_KEY_TTL_NANOS` (the ledger's deduplication window) and at
+//! most `MAX_DEPOSIT_KEYS_PER_USER` are kept per principal. Only completed keys are
+//! evicted to make room: an unresolved key is what deduplicates its retry, so a new key
+//! is rejected while every slot is unresolved.
+
+use candid::Principal;
+use ic_stable_structures::memory_manager::MemoryId;
+use ic_stable_structures::StableBTreeMap;
+use std::cell::RefCell;
+use std::collections::HashSet;
+
+use crate::{MEMORY_MANAGER, Memory};
+use super::memory_ids::DEPOSIT_KEYS_MEMORY_ID;
+use super::types::{DepositKeyList, DepositKeyRecord, DepositReceipt};
+
+/// Keys are forgotten after 24 hours (matches the ICRC ledger deduplication window)
+pub const DEPOSIT_KEY_TTL_NANOS: u64 = 24 * 60 * 60 * 1_000_000_000;
+pub const MAX_DEPOSIT_KEYS_PER_USER: usize = 20;
+
+thread_local! {
+    static DEPOSIT_KEYS: RefCell<StableBTreeMap<Principal, DepositKeyList, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(DEPOSIT_KEYS_MEMORY_ID)))
+        )
+    );
+
+    // Keys whose ledger call is currently awaiting (heap only: nothing is in flight after an upgrade)
+    static IN_FLIGHT: RefCell<HashSet<(Principal, [u8; 16])>> = RefCell::new(HashSet::new());
+}
+
+/// Outcome of registering a keyed deposit attempt
+#[derive(Debug, PartialEq)]
+pub enum DepositKeyStart {
+    /// Key already settled: return this receipt without transferring
+    Completed(DepositReceipt),
+    /// Perform the transfer using this ledger `created_at_time`
+    Proceed { created_at: u64 },
+}
+
+/// Register a deposit attempt for `key`. Must be paired with `complete`, `abort` or `mark_uncertain`.
+pub fn begin(caller: Principal, key: [u8; 16], amount: u64, now: u64) -> Result<DepositKeyStart, String> {
+    if IN_FLIGHT.with(|f| f.borrow().contains(&(caller, key))) {
+        return Err("Deposit with this idempotency key is still in progress".to_string());
+    }
+
+    DEPOSIT_KEYS.with(|keys| {
+        let mut keys = keys.borrow_mut();
+        let mut list = keys.get(&caller).unwrap_or_default();
+        list.records.retain(|r| now.saturating_sub(r.created_at) < DEPOSIT_KEY_TTL_NANOS);
+
+        let start = match list.records.iter().find(|r| r.key == key) {
+            Some(existing) if existing.amount != amount => {
+                return Err(format!(
+                    "Idempotency key already used for a deposit of {} decimals",
+                    existing.amount
+                ));
+            }
+            Some(existing) => match existing.new_balance {
+                Some(new_balance) => DepositKeyStart::Completed(DepositReceipt {
+                    new_balance,
+                    block_index: existing.block_index.unwrap_or(0),
+                }),
+                None => DepositKeyStart::Proceed { created_at: existing.created_at },
+            },
+            None => {
+                if list.records.len() >= MAX_DEPOSIT_KEYS_PER_USER {
+                    // Records are kept oldest first
+                    let Some(oldest_completed) = list.records.iter().position(|r| r.new_balance.is_some()) else {
+                        return Err(format!(
+                            "{} deposits are still unresolved; retry them with their idempotency keys first",
+                            list.records.len()
+                        ));
+                    };
+                    list.records.remove(oldest_completed);
+                }
+                list.records.push(DepositKeyRecord { key, amount, created_at: now, new_balance: None, block_index: None });
+                DepositKeyStart::Proceed { created_at: now }
+            }
+        };
+
+        keys.insert(caller, list);
+        Ok(start)
+    })
+    .inspect(|start| {
+        if matches!(start, DepositKeyStart::Proceed { .. }) {
+            IN_FLIGHT.with(|f| f.borrow_mut().insert((caller, key)));
+        }
+    })
+}
+
+/// Transfer succeeded and was credited: later retries return `receipt`
+pub fn complete(caller: Principal, key: [u8; 16], receipt: &DepositReceipt) {
+    update_record(caller, key, |list| {
+        if let Some(record) = list.records.iter_mut().find(|r| r.key == key) {
+            record.new_balance = Some(receipt.new_balance);
+            record.block_index = Some(receipt.block_index);
+        }
+    });
+}
+
+/// Ledger definitely rejected the transfer: forget the key so a retry starts fresh
+pub fn abort(caller: Principal, key: [u8; 16]) {
+    update_record(caller, key, |list| list.records.retain(|r| r.key != key));
+}
+
+/// Outcome unknown: keep the key (and its `created_at`) so a retry is deduplicated by the ledger
+pub fn mark_uncertain(caller: Principal, key: [u8; 16]) {
+    clear_in_flight(caller, key);
+}
+
+fn clear_in_flight(caller: Principal, key: [u8; 16]) {
+    IN_FLIGHT.with(|f| f.borrow_mut().remove(&(caller, key)));
+}
+
+fn update_record(caller: Principal, key: [u8; 16], f: impl FnOnce(&mut DepositKeyList)) {
+    clear_in_flight(caller, key);
+    DEPOSIT_KEYS.with(|keys| {
+        let mut keys = keys.borrow_mut();
+        let Some(mut list) = keys.get(&caller) else { return };
+        f(&mut list);
+        if list.records.is_empty() {
+            keys.remove(&caller);
+        } else {
+            keys.insert(caller, list);
+        }
+    });
+}
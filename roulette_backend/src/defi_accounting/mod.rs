@@ -1,13 +1,23 @@
 pub mod accounting;
 pub mod admin_query;
+pub mod config;
+pub mod deposit_keys;
+pub mod events;
+pub mod game_history;
+pub mod invariants;
 pub mod liquidity_pool;
 pub mod memory_ids;
+pub mod parent;
+pub mod player_stats;
+pub mod pool_drain;
 pub mod query;
+pub mod rebates;
+pub mod solvency;
 pub mod statistics;
 pub mod types;
 
 // Re-export types and update functions from original modules
-pub use accounting::{update_balance, try_deduct_balance};
+pub use accounting::{update_balance, try_deduct_balance, require_authenticated};
 
 // Re-export query functions from query module
 pub use query::{
@@ -22,9 +32,12 @@ pub use statistics::{
     get_snapshots_range,
     get_snapshot_count,
     get_apy_info,
+    get_apy_breakdown,
+    get_share_price_history,
     start_stats_timer,
     DailySnapshot,
     ApyInfo,
+    ApyBreakdown,
 };
 
 #[cfg(test)]
@@ -0,0 +1,45 @@
+//! Per-principal lifetime statistics.
+//!
+//! Recorded from `liquidity_pool::settle_bet`, the single settlement path every game
+//! uses, so the totals move in lockstep with the pool and cannot drift from it.
+
+use candid::Principal;
+use ic_stable_structures::memory_manager::MemoryId;
+use ic_stable_structures::StableBTreeMap;
+use std::cell::RefCell;
+
+use crate::{MEMORY_MANAGER, Memory};
+use super::memory_ids::PLAYER_STATS_MEMORY_ID;
+use super::types::PlayerLifetimeStats;
+
+thread_local! {
+    static PLAYER_STATS: RefCell<StableBTreeMap<Principal, PlayerLifetimeStats, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(PLAYER_STATS_MEMORY_ID)))
+        )
+    );
+}
+
+/// Fold one settled game into the player's totals (saturating: stats must never trap a bet)
+pub(crate) fn record_settlement(player: Principal, bet_amount: u64, payout_amount: u64) {
+    PLAYER_STATS.with(|stats| {
+        let mut stats = stats.borrow_mut();
+        let mut entry = stats.get(&player).unwrap_or_default();
+
+        entry.total_wagered = entry.total_wagered.saturating_add(bet_amount);
+        entry.games_played = entry.games_played.saturating_add(1);
+        if payout_amount > bet_amount {
+            let profit = payout_amount - bet_amount;
+            entry.total_won = entry.total_won.saturating_add(profit);
+            entry.biggest_win = entry.biggest_win.max(profit);
+        } else {
+            entry.total_lost = entry.total_lost.saturating_add(bet_amount - payout_amount);
+        }
+
+        stats.insert(player, entry);
+    });
+}
+
+pub fn get_player_stats(player: Principal) -> PlayerLifetimeStats {
+    PLAYER_STATS.with(|stats| stats.borrow().get(&player).unwrap_or_default())
+}
@@ -0,0 +1,98 @@
+//! Shared solvency check used by every game endpoint before accepting a bet.
+//!
+//! The canister must hold `obligations * (1 + margin)` where obligations are the pool
+//! reserve plus all user deposits. The margin gives headroom for transfer fees and
+//! rounding so a bet cannot leave the canister exactly solvent but unable to pay out.
+
+use ic_stable_structures::memory_manager::MemoryId;
+use ic_stable_structures::StableCell;
+use std::cell::RefCell;
+
+use crate::{MEMORY_MANAGER, Memory};
+use super::accounting;
+use super::liquidity_pool;
+use super::memory_ids::SOLVENCY_MARGIN_MEMORY_ID;
+use super::types::SolvencyStatus;
+
+const BASIS_POINTS: u64 = 10_000;
+
+/// Margin applied on a fresh install: games pause unless the canister holds 2% headroom
+/// over its obligations. Tune it with `admin_set_solvency_margin_bp`.
+pub const DEFAULT_SOLVENCY_MARGIN_BP: u64 = 200;
+/// Margin for canisters upgraded from before the margin existed. Their balance tracks
+/// obligations exactly, so anything higher would pause games until the admin tops up.
+const UPGRADED_SOLVENCY_MARGIN_BP: u64 = 0;
+pub const MAX_SOLVENCY_MARGIN_BP: u64 = 1_000; // 10%
+
+thread_local! {
+    static SOLVENCY_MARGIN_BP: RefCell<StableCell<u64, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(SOLVENCY_MARGIN_MEMORY_ID))),
+            UPGRADED_SOLVENCY_MARGIN_BP
+        )
+    );
+}
+
+pub fn get_solvency_margin_bp() -> u64 {
+    SOLVENCY_MARGIN_BP.with(|m| *m.borrow().get())
+}
+
+pub(crate) fn validate_solvency_margin_bp(margin_bp: u64) -> Result<(), String> {
+    if margin_bp > MAX_SOLVENCY_MARGIN_BP {
+        return Err(format!("Margin {} bp exceeds maximum {} bp", margin_bp, MAX_SOLVENCY_MARGIN_BP));
+    }
+    Ok(())
+}
+
+/// Apply the default margin; only called from `init`, never `post_upgrade`
+pub fn init_solvency_margin() {
+    SOLVENCY_MARGIN_BP.with(|m| m.borrow_mut().set(DEFAULT_SOLVENCY_MARGIN_BP));
+}
+
+/// Set the margin (admin check is done by the caller in `admin_query`)
+pub(crate) fn set_solvency_margin_bp(margin_bp: u64) -> Result<(), String> {
+    validate_solvency_margin_bp(margin_bp)?;
+    SOLVENCY_MARGIN_BP.with(|m| m.borrow_mut().set(margin_bp));
+    Ok(())
+}
+
+/// `obligations * (1 + margin_bp / 10_000)`, rounded up. None on overflow.
+pub fn required_balance(obligations: u64, margin_bp: u64) -> Option<u64> {
+    let scaled = (obligations as u128) * ((BASIS_POINTS + margin_bp) as u128);
+    u64::try_from(scaled.div_ceil(BASIS_POINTS as u128)).ok()
+}
+
+pub fn get_solvency_status() -> SolvencyStatus {
+    let pool_reserve = liquidity_pool::get_pool_reserve();
+    let total_deposits = accounting::calculate_total_deposits_internal();
+    let canister_balance = accounting::get_cached_canister_balance_internal();
+    let margin_bp = get_solvency_margin_bp();
+
+    // Overflow is treated as insolvent
+    let obligations = pool_reserve.checked_add(total_deposits);
+    if obligations.is_none() {
+        ic_cdk::println!("CRITICAL: Obligations overflow u64::MAX");
+    }
+    let required = obligations.and_then(|o| required_balance(o, margin_bp));
+
+    SolvencyStatus {
+        canister_balance,
+        obligations: obligations.unwrap_or(u64::MAX),
+        margin_bp,
+        required_balance: required.unwrap_or(u64::MAX),
+        is_solvent: required.is_some_and(|r| canister_balance >= r),
+    }
+}
+
+/// LP funds that can actually back a payout: the pool reserve, capped at the part of the
+/// canister balance not owed to depositors. Player balances are never LP risk capital, so
+/// if the cached balance lags the books the shortfall comes out of the payout limit.
+pub fn payout_backing() -> u64 {
+    let unowed = accounting::get_cached_canister_balance_internal()
+        .saturating_sub(accounting::calculate_total_deposits_internal());
+    liquidity_pool::get_pool_reserve().min(unowed)
+}
+
+pub fn is_canister_solvent() -> bool {
+    get_solvency_status().is_solvent
+}
@@ -0,0 +1,89 @@
+// Tests for exporting and importing the admin-settable configuration
+
+use candid::Principal;
+use crate::defi_accounting::accounting::set_audit_retention;
+use crate::defi_accounting::config::export_config;
+use crate::defi_accounting::events::set_event_subscriber;
+use crate::defi_accounting::liquidity_pool::{set_target_reserve, min_operating_balance};
+use crate::defi_accounting::parent::{set_parent_config, DEFAULT_PARENT_WITHDRAW_THRESHOLD};
+use crate::defi_accounting::rebates::set_rebate_config;
+use crate::defi_accounting::solvency::{set_solvency_margin_bp, MAX_SOLVENCY_MARGIN_BP};
+use crate::defi_accounting::types::RebateConfig;
+use crate::limits::{get_table_limits, set_table_limits};
+use crate::prison::{get_zero_rule, set_zero_rule};
+use crate::types::{TableLimits, ZeroRule};
+use crate::types::{Account, GameConfig};
+use crate::{export_game_config, import_game_config};
+
+const USDT: u64 = 1_000_000;
+const T: u64 = 1_700_000_000_000_000_000;
+
+fn treasury() -> Account {
+    Account { owner: Principal::from_slice(&[42; 29]), subaccount: Some([7; 32]) }
+}
+
+/// Move every setting off its default
+fn tune() {
+    set_audit_retention(500).unwrap();
+    set_solvency_margin_bp(250).unwrap();
+    set_target_reserve(4 * min_operating_balance()).unwrap();
+    set_rebate_config(RebateConfig { rebate_bp: 1_000, min_volume: 50 * USDT }).unwrap();
+    set_parent_config(50 * USDT, treasury(), T).unwrap();
+    set_event_subscriber(Some(Principal::from_slice(&[77; 10])), T).unwrap();
+    set_zero_rule(ZeroRule::EnPrison);
+    set_table_limits(TableLimits { inside_min: USDT, inside_max: 5 * USDT, outside_min: 2 * USDT, outside_max: 50 * USDT }).unwrap();
+}
+
+#[test]
+fn test_export_then_import_restores_every_setting() {
+    tune();
+    let blob = export_game_config();
+    let exported = export_config();
+
+    // A bad change to every setting, rolled back with the blob
+    set_audit_retention(2_000).unwrap();
+    set_solvency_margin_bp(0).unwrap();
+    set_target_reserve(0).unwrap();
+    set_rebate_config(RebateConfig::default()).unwrap();
+    set_parent_config(DEFAULT_PARENT_WITHDRAW_THRESHOLD, Account { owner: Principal::from_slice(&[1; 29]), subaccount: None }, T).unwrap();
+    set_event_subscriber(None, T).unwrap();
+    set_zero_rule(ZeroRule::Standard);
+    set_table_limits(TableLimits::default()).unwrap();
+    assert_ne!(export_game_config(), blob);
+
+    import_game_config(&blob, T).unwrap();
+    assert_eq!(export_game_config(), blob);
+    assert_eq!(export_config(), exported);
+    assert_eq!(exported.audit_retention, 500);
+    assert_eq!(exported.parent.recipient, treasury());
+    assert_eq!(get_zero_rule(), ZeroRule::EnPrison);
+    assert_eq!(get_table_limits().outside_max, 50 * USDT);
+}
+
+#[test]
+fn test_invalid_import_changes_nothing() {
+    tune();
+    let before = export_config();
+
+    // One bad setting rejects the whole blob, including the valid ones before it
+    let mut config: GameConfig = candid::decode_one(&export_game_config()).unwrap();
+    config.accounting.audit_retention = 2_000;
+    config.accounting.solvency_margin_bp = MAX_SOLVENCY_MARGIN_BP + 1;
+    assert!(import_game_config(&candid::encode_one(config).unwrap(), T).is_err());
+    assert_eq!(export_config(), before);
+
+    // Table limits are checked before any accounting setting is applied
+    let mut config: GameConfig = candid::decode_one(&export_game_config()).unwrap();
+    config.accounting.audit_retention = 2_000;
+    config.table_limits.inside_min = config.table_limits.inside_max + 1;
+    assert!(import_game_config(&candid::encode_one(config).unwrap(), T).is_err());
+    assert_eq!(export_config(), before);
+
+    let mut other_game: GameConfig = candid::decode_one(&export_game_config()).unwrap();
+    other_game.game = "other_backend".to_string();
+    let err = import_game_config(&candid::encode_one(other_game).unwrap(), T).unwrap_err();
+    assert!(err.starts_with("Config blob is for other_backend"), "{}", err);
+
+    assert!(import_game_config(b"not candid", T).unwrap_err().starts_with("Invalid config blob"));
+    assert_eq!(export_config(), before);
+}
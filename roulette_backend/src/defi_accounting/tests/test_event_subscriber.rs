@@ -0,0 +1,61 @@
+// Tests for the admin-registered event subscriber. Off-chain, `events::send` records each
+// notification instead of calling the subscriber, which stands in as a mock subscriber.
+
+use candid::Principal;
+use crate::defi_accounting::accounting::credit_deposit;
+use crate::defi_accounting::events::{
+    get_event_subscriber, set_event_subscriber, take_sent_events, large_win_min_profit, SUBSCRIBER_METHOD,
+};
+use crate::defi_accounting::liquidity_pool::{add_to_reserve, settle_bet};
+use crate::defi_accounting::types::GameEvent;
+
+const USDT: u64 = 1_000_000;
+const T: u64 = 1_700_000_000_000_000_000;
+
+fn subscriber() -> Principal {
+    Principal::from_slice(&[77; 10])
+}
+
+#[test]
+fn test_deposit_notifies_subscriber_once() {
+    let user = Principal::from_slice(&[3; 29]);
+    credit_deposit(user, 10 * USDT, 1, None, T).unwrap();
+    assert!(take_sent_events().is_empty(), "No subscriber registered yet");
+
+    set_event_subscriber(Some(subscriber()), T).unwrap();
+    credit_deposit(user, 5 * USDT, 2, None, T).unwrap();
+    assert_eq!(
+        take_sent_events(),
+        vec![(subscriber(), SUBSCRIBER_METHOD, GameEvent::Deposit { user, amount: 5 * USDT, new_balance: 15 * USDT })]
+    );
+}
+
+#[test]
+fn test_only_large_wins_notify() {
+    set_event_subscriber(Some(subscriber()), T).unwrap();
+    add_to_reserve(1_000 * USDT);
+    let user = Principal::from_slice(&[4; 29]);
+
+    settle_bet(user, USDT, 2 * USDT).unwrap();
+    settle_bet(user, 10 * USDT, 0).unwrap();
+    settle_bet(user, USDT, USDT + large_win_min_profit() - 1).unwrap();
+    assert!(take_sent_events().is_empty());
+
+    settle_bet(user, USDT, USDT + large_win_min_profit()).unwrap();
+    assert_eq!(
+        take_sent_events(),
+        vec![(subscriber(), SUBSCRIBER_METHOD, GameEvent::LargeWin { user, bet_amount: USDT, payout_amount: USDT + large_win_min_profit() })]
+    );
+}
+
+#[test]
+fn test_subscriber_removal_and_validation() {
+    assert!(set_event_subscriber(Some(Principal::anonymous()), T).is_err());
+    assert!(set_event_subscriber(Some(Principal::management_canister()), T).is_err());
+    assert_eq!(get_event_subscriber(), None);
+
+    set_event_subscriber(Some(subscriber()), T).unwrap();
+    set_event_subscriber(None, T).unwrap();
+    credit_deposit(Principal::from_slice(&[5; 29]), 5 * USDT, 3, None, T).unwrap();
+    assert!(take_sent_events().is_empty());
+}
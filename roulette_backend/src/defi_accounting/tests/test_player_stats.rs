@@ -0,0 +1,50 @@
+// Tests for per-principal lifetime statistics recorded by settle_bet
+
+use candid::Principal;
+use crate::defi_accounting::liquidity_pool::{settle_bet, update_pool_on_loss};
+use crate::defi_accounting::player_stats::get_player_stats;
+use crate::defi_accounting::types::PlayerLifetimeStats;
+
+fn player(id: u8) -> Principal {
+    Principal::from_slice(&[id; 29])
+}
+
+#[test]
+fn test_loss_updates_stats() {
+    let p = player(1);
+    settle_bet(p, 1_000_000, 200_000).unwrap();
+
+    assert_eq!(get_player_stats(p), PlayerLifetimeStats {
+        total_wagered: 1_000_000,
+        total_won: 0,
+        total_lost: 800_000,
+        games_played: 1,
+        biggest_win: 0,
+    });
+}
+
+#[test]
+fn test_win_updates_stats() {
+    let p = player(2);
+    update_pool_on_loss(100_000_000); // Fund the pool so it can pay the profit
+
+    settle_bet(p, 1_000_000, 3_000_000).unwrap();
+    settle_bet(p, 1_000_000, 1_500_000).unwrap();
+    settle_bet(p, 2_000_000, 0).unwrap();
+
+    let stats = get_player_stats(p);
+    assert_eq!(stats.total_wagered, 4_000_000);
+    assert_eq!(stats.total_won, 2_500_000);
+    assert_eq!(stats.total_lost, 2_000_000);
+    assert_eq!(stats.games_played, 3);
+    assert_eq!(stats.biggest_win, 2_000_000);
+    assert_eq!(get_player_stats(player(3)), PlayerLifetimeStats::default());
+}
+
+#[test]
+fn test_rejected_settlement_not_recorded() {
+    let p = player(4);
+    // Empty pool cannot pay a profit
+    assert!(settle_bet(p, 1_000_000, 5_000_000).is_err());
+    assert_eq!(get_player_stats(p), PlayerLifetimeStats::default());
+}
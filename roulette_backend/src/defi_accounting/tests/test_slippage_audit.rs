@@ -5,7 +5,6 @@
 // 2. Pool reserve is NOT increased
 // 3. System remains solvent (Assets == Liabilities)
 
-use std::cell::RefCell;
 use candid::Nat;
 
 // Mock State to simulate the canister's memory
@@ -245,7 +244,7 @@ fn test_race_condition_orphans_funds() {
 // This addresses the P2 comment: "Test Only Simulates, Doesn't Execute Real Code Path"
 #[cfg(test)]
 mod integration_tests {
-    use super::*;
+    
     
     #[test]
     fn test_integration_force_credit_balance_system() {
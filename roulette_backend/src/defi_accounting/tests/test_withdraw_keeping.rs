@@ -0,0 +1,35 @@
+// Tests for withdraw_keeping amount calculation (boundary around the minimum withdrawal)
+
+use crate::defi_accounting::accounting::{calculate_withdraw_keeping, min_withdraw};
+use crate::defi_accounting::types::GameError;
+
+#[test]
+fn test_keep_amount_must_be_below_balance() {
+    let balance = 5 * min_withdraw();
+    assert!(calculate_withdraw_keeping(balance, balance).is_err());
+    assert!(calculate_withdraw_keeping(balance, balance + 1).is_err());
+    assert!(calculate_withdraw_keeping(0, 0).is_err());
+}
+
+#[test]
+fn test_remainder_below_min_withdraw_rejected() {
+    let keep = 2 * min_withdraw();
+    let balance = keep + min_withdraw() - 1;
+    assert_eq!(
+        calculate_withdraw_keeping(balance, keep),
+        Err(GameError::BelowMinimum { amount: min_withdraw() - 1, minimum: min_withdraw() })
+    );
+}
+
+#[test]
+fn test_remainder_exactly_min_withdraw_allowed() {
+    let keep = 2 * min_withdraw();
+    let balance = keep + min_withdraw();
+    assert_eq!(calculate_withdraw_keeping(balance, keep), Ok(min_withdraw()));
+}
+
+#[test]
+fn test_keep_zero_withdraws_full_balance() {
+    let balance = 3 * min_withdraw() + 17;
+    assert_eq!(calculate_withdraw_keeping(balance, 0), Ok(balance));
+}
@@ -79,7 +79,7 @@ fn test_audit_entry_all_variants_roundtrip() {
         AuditEvent::ParentFeeCredited { amount: 100 },
         AuditEvent::ParentFeeFallback { amount: 100, reason: "Test reason".to_string() },
         AuditEvent::SystemInfo { message: "Test info".to_string() },
-        AuditEvent::BalanceCredited { user: test_principal, amount: 100, new_balance: 200 },
+        AuditEvent::BalanceCredited { user: test_principal, amount: 100, new_balance: 200, block_index: Some(7) },
         AuditEvent::SlippageProtectionTriggered {
             user: test_principal,
             deposit_amount: 1000,
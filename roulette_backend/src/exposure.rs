@@ -0,0 +1,37 @@
+// Largest total potential payout any single spin has carried (stored in stable memory)
+//
+// Risk telemetry for tuning table limits and pool sizing. Updated once per settled spin.
+
+use crate::defi_accounting::memory_ids::MAX_EXPOSURE_MEMORY_ID;
+use crate::{MEMORY_MANAGER, Memory};
+use ic_stable_structures::memory_manager::MemoryId;
+use ic_stable_structures::StableCell;
+use std::cell::RefCell;
+
+thread_local! {
+    static MAX_EXPOSURE: RefCell<StableCell<u64, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(MAX_EXPOSURE_MEMORY_ID))),
+            0
+        )
+    );
+}
+
+pub fn get_max_exposure_seen() -> u64 {
+    MAX_EXPOSURE.with(|e| *e.borrow().get())
+}
+
+/// Raise the high-water mark if this spin's maximum possible payout exceeds it
+pub fn record_exposure(exposure: u64) {
+    MAX_EXPOSURE.with(|e| {
+        let mut cell = e.borrow_mut();
+        if exposure > *cell.get() {
+            cell.set(exposure);
+        }
+    });
+}
+
+/// Start a fresh measurement window (admin check is done by the caller)
+pub fn reset_max_exposure() {
+    MAX_EXPOSURE.with(|e| e.borrow_mut().set(0));
+}
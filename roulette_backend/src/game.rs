@@ -3,11 +3,13 @@
 use crate::types::*;
 use crate::board::*;
 use crate::defi_accounting::{self as accounting, liquidity_pool};
+use crate::limits;
+use crate::prison;
 use candid::Principal;
-use ic_cdk::management_canister::raw_rand;
+use crate::vrf::{raw_rand_with_retries, VRF_RETRIES};
 use sha2::{Sha256, Digest};
 
-const MAX_BETS_PER_SPIN: usize = 20;
+pub const MAX_BETS_PER_SPIN: usize = 20;
 const MAX_PAYOUT_RATIO: u64 = 36; // Straight-up pays 35:1 + original = 36x
 
 /// Get maximum bet allowed based on house balance
@@ -19,27 +21,17 @@ pub fn get_max_bet() -> u64 {
 
 /// Execute a spin with real ckUSDT betting
 pub async fn spin_with_betting(bets: Vec<Bet>, caller: Principal) -> Result<SpinResult, String> {
-    // 1. Validate inputs
-    if bets.is_empty() {
-        return Err("No bets placed".to_string());
-    }
-    if bets.len() > MAX_BETS_PER_SPIN {
-        return Err(format!("Maximum {} bets per spin", MAX_BETS_PER_SPIN));
-    }
+    accounting::require_authenticated(caller)?;
 
-    // 2. Validate each bet and calculate total
-    let mut total_bet: u64 = 0;
-    for bet in &bets {
-        validate_bet(bet)?;
-        if bet.amount < MIN_BET {
-            return Err(format!("Minimum bet is 0.01 USDT ({} units)", MIN_BET));
-        }
-        total_bet = total_bet.checked_add(bet.amount)
-            .ok_or("Total bet overflow")?;
-    }
+    // 1-2. Validate inputs and calculate total, then settle duplicates as single bets
+    let total_bet = validate_bets(&bets)?;
+    let bets = merge_duplicate_bets(&bets)?;
 
     // 3. Calculate maximum possible payout to check house can cover
-    let max_possible_payout = calculate_max_possible_payout(&bets)?;
+    // Imprisoned stakes from the previous spin may also be returned this spin
+    let max_possible_payout = calculate_max_possible_payout(&bets)?
+        .checked_add(prison::get_imprisoned_total(caller))
+        .ok_or("Total payout overflow")?;
     let max_allowed = accounting::get_max_allowed_payout();
     if max_possible_payout > max_allowed {
         return Err(format!(
@@ -50,7 +42,7 @@ pub async fn spin_with_betting(bets: Vec<Bet>, caller: Principal) -> Result<Spin
     }
 
     // 4. Get VRF randomness from IC (async call - execution may suspend here)
-    let random_bytes = raw_rand().await
+    let random_bytes = raw_rand_with_retries(VRF_RETRIES).await
         .map_err(|e| format!("Randomness failed: {:?}", e))?;
 
     if random_bytes.len() < 8 {
@@ -73,24 +65,29 @@ pub async fn spin_with_betting(bets: Vec<Bet>, caller: Principal) -> Result<Spin
     let winning_number = bytes_to_number(&random_bytes);
     let color = get_color(winning_number);
 
-    // 9. Evaluate each bet
-    let bet_results: Vec<BetResult> = bets.iter()
-        .map(|bet| evaluate_bet(bet, winning_number))
-        .collect();
+    // 9. Evaluate each bet, resolving any bets imprisoned on the previous spin
+    let prisoners = prison::take_imprisoned_bets(caller);
+    let spin = evaluate_spin(&bets, &prisoners, winning_number, &prison::get_zero_rule());
 
     // 10. Calculate totals
-    let total_payout: u64 = bet_results.iter().map(|r| r.payout).sum();
+    let total_payout = spin.total_payout;
     let net_result = total_payout as i64 - total_bet as i64;
 
     // 11. Credit payout to user
     let current_balance = accounting::get_balance(caller);
-    let new_balance = current_balance.checked_add(total_payout)
-        .ok_or("Balance overflow when adding winnings")?;
+    let new_balance = match current_balance.checked_add(total_payout) {
+        Some(b) => b,
+        None => {
+            prison::imprison_bets(caller, prisoners);
+            return Err("Balance overflow when adding winnings".to_string());
+        }
+    };
     accounting::update_balance(caller, new_balance)?;
 
     // 12. Settle with pool
-    if let Err(e) = liquidity_pool::settle_bet(total_bet, total_payout) {
-        // CRITICAL: Rollback if pool settlement fails
+    if let Err(e) = liquidity_pool::settle_bet(caller, total_bet, total_payout) {
+        // CRITICAL: Rollback if pool settlement fails (held bets stay held)
+        prison::imprison_bets(caller, prisoners);
         let refund_balance = current_balance.checked_add(total_bet)
             .ok_or("Refund calculation overflow")?;
         accounting::update_balance(caller, refund_balance)?;
@@ -99,17 +96,125 @@ pub async fn spin_with_betting(bets: Vec<Bet>, caller: Principal) -> Result<Spin
         return Err(format!("House settlement failed. Bet refunded. Error: {}", e));
     }
 
+    crate::defi_accounting::game_history::record_game(
+        caller, total_bet, total_payout,
+        format!("Landed on {} ({:?})", winning_number, color),
+        ic_cdk::api::time(),
+    );
+    crate::streaks::record_spin(winning_number);
+    crate::exposure::record_exposure(max_possible_payout);
+
+    // 13. Hold even-money bets lost to zero for the next spin
+    prison::imprison_bets(caller, spin.imprisoned.clone());
+
     Ok(SpinResult {
         winning_number,
         color,
-        bets: bet_results,
+        bets: spin.bet_results,
         total_bet,
         total_payout,
         net_result,
         randomness_hash,
+        imprisoned_bets: spin.imprisoned,
+        prison_results: spin.prison_results,
     })
 }
 
+/// Outcome of evaluating one spin's bets and any previously imprisoned bets
+pub(crate) struct SpinEvaluation {
+    pub bet_results: Vec<BetResult>,
+    pub prison_results: Vec<BetResult>,
+    pub imprisoned: Vec<Bet>,
+    pub total_payout: u64,
+}
+
+/// Evaluate bets against the winning number under the given zero rule.
+///
+/// Under `ZeroRule::EnPrison`, even-money bets that lose to zero are returned in
+/// `imprisoned` (payout 0 for now). Previously imprisoned bets pay back their stake
+/// only if this spin's number matches them; they never earn winnings.
+pub(crate) fn evaluate_spin(bets: &[Bet], prisoners: &[Bet], winning: u8, rule: &ZeroRule) -> SpinEvaluation {
+    let bet_results: Vec<BetResult> = bets.iter()
+        .map(|bet| evaluate_bet(bet, winning))
+        .collect();
+
+    let imprisoned: Vec<Bet> = if winning == 0 && *rule == ZeroRule::EnPrison {
+        bets.iter().filter(|b| prison::is_even_money(&b.bet_type)).cloned().collect()
+    } else {
+        Vec::new()
+    };
+
+    let prison_results: Vec<BetResult> = prisoners.iter()
+        .map(|bet| {
+            let won = evaluate_bet(bet, winning).won;
+            BetResult {
+                bet_type: bet.bet_type.clone(),
+                amount: bet.amount,
+                won,
+                payout: if won { bet.amount } else { 0 },
+            }
+        })
+        .collect();
+
+    let total_payout = bet_results.iter()
+        .chain(prison_results.iter())
+        .fold(0u64, |acc, r| acc.saturating_add(r.payout));
+
+    SpinEvaluation { bet_results, prison_results, imprisoned, total_payout }
+}
+
+/// Validate a full bet list (count, per-bet rules, minimum amount, table limits)
+/// Returns the total amount wagered. Shared by `spin` and preset saving.
+///
+/// Duplicates are merged first (see `merge_duplicate_bets`), so the count limit applies
+/// to the bets as submitted while the table limits apply to the combined stake per position.
+pub fn validate_bets(bets: &[Bet]) -> Result<u64, String> {
+    if bets.is_empty() {
+        return Err("No bets placed".to_string());
+    }
+    if bets.len() > MAX_BETS_PER_SPIN {
+        return Err(format!("Maximum {} bets per spin", MAX_BETS_PER_SPIN));
+    }
+
+    let table_limits = limits::get_table_limits();
+    let mut total_bet: u64 = 0;
+    for (index, bet) in merge_duplicate_bets(bets)?.iter().enumerate() {
+        validate_bet(bet)?;
+        if bet.amount < MIN_BET {
+            return Err(format!("Minimum bet is 0.01 USDT ({} units)", MIN_BET));
+        }
+        limits::check_bet_limits(index, bet, &table_limits)?;
+        total_bet = total_bet.checked_add(bet.amount)
+            .ok_or("Total bet overflow")?;
+    }
+    Ok(total_bet)
+}
+
+/// Merge bets on the same position into one bet with the summed amount, kept at the
+/// position of its first occurrence. Splits match in either order (`Split(1, 2)` and
+/// `Split(2, 1)` cover the same numbers). Bets on different positions are never merged,
+/// even when they overlap (e.g. Red and Odd): each is its own bet for limits and payouts.
+pub fn merge_duplicate_bets(bets: &[Bet]) -> Result<Vec<Bet>, String> {
+    let mut merged: Vec<Bet> = Vec::with_capacity(bets.len());
+    for bet in bets {
+        match merged.iter_mut().find(|m| same_position(&m.bet_type, &bet.bet_type)) {
+            Some(existing) => {
+                existing.amount = existing.amount.checked_add(bet.amount)
+                    .ok_or("Total bet overflow")?;
+            }
+            None => merged.push(bet.clone()),
+        }
+    }
+    Ok(merged)
+}
+
+fn same_position(a: &BetType, b: &BetType) -> bool {
+    match (a, b) {
+        (BetType::Split(a1, a2), BetType::Split(b1, b2)) => (a1, a2) == (b1, b2) || (a1, a2) == (b2, b1),
+        _ => a == b,
+    }
+}
+
 /// Calculate the maximum possible payout for a set of bets
 /// This is used to ensure house can cover worst-case scenario
 fn calculate_max_possible_payout(bets: &[Bet]) -> Result<u64, String> {
@@ -143,6 +248,54 @@ fn get_payout_multiplier(bet_type: &BetType) -> u64 {
 }
 
 
+/// Bet categories as listed by `get_payouts`, each with a representative bet
+fn edge_categories() -> [(&'static str, BetType); 10] {
+    [
+        ("Straight", BetType::Straight(17)),
+        ("Split", BetType::Split(1, 2)),
+        ("Street", BetType::Street(1)),
+        ("Corner", BetType::Corner(1)),
+        ("Six Line", BetType::SixLine(1)),
+        ("Column", BetType::Column(1)),
+        ("Dozen", BetType::Dozen(1)),
+        ("Red/Black", BetType::Red),
+        ("Even/Odd", BetType::Even),
+        ("Low/High", BetType::Low),
+    ]
+}
+
+/// House edge (fraction of stake) of a single bet under the given zero rule.
+///
+/// Computed exactly by enumerating all 37 outcomes through `evaluate_spin`, so it always
+/// matches the payout logic. An imprisoned stake is valued at its chance of being
+/// returned on the following spin.
+pub fn effective_house_edge(bet_type: &BetType, rule: &ZeroRule) -> f64 {
+    const STAKE: u64 = 1_000_000;
+    let bet = [Bet { bet_type: bet_type.clone(), amount: STAKE }];
+
+    // Expected value of a held stake: returned only if the next spin matches the bet
+    let prisoner_value = (0..=36u8)
+        .map(|n| evaluate_spin(&[], &bet, n, rule).total_payout)
+        .sum::<u64>() as f64 / 37.0;
+
+    let expected_return: f64 = (0..=36u8)
+        .map(|n| {
+            let spin = evaluate_spin(&bet, &[], n, rule);
+            spin.total_payout as f64 + spin.imprisoned.len() as f64 * prisoner_value
+        })
+        .sum::<f64>() / 37.0;
+
+    1.0 - expected_return / STAKE as f64
+}
+
+/// Effective house edge in percent for every bet category under the given zero rule
+pub fn effective_edges(rule: &ZeroRule) -> Vec<(String, f64)> {
+    edge_categories()
+        .iter()
+        .map(|(name, bet_type)| (name.to_string(), effective_house_edge(bet_type, rule) * 100.0))
+        .collect()
+}
+
 /// Validate a single bet
 fn validate_bet(bet: &Bet) -> Result<(), String> {
     if bet.amount == 0 {
@@ -196,6 +349,30 @@ fn validate_bet(bet: &Bet) -> Result<(), String> {
     Ok(())
 }
 
+/// Most winning numbers `simulate_spins` draws per call
+pub const MAX_SIMULATED_SPINS: u32 = 1_000;
+
+/// Draw up to MAX_SIMULATED_SPINS winning numbers for distribution audits (no bets, no
+/// balances). One `raw_rand` call seeds them all: spin i maps SHA256(vrf_bytes || i)
+/// through `bytes_to_number`, the same mapping a live spin applies to its VRF bytes.
+pub async fn simulate_spins(count: u32) -> Result<Vec<u8>, String> {
+    let random_bytes = raw_rand_with_retries(VRF_RETRIES).await
+        .map_err(|e| format!("Randomness failed: {:?}", e))?;
+    if random_bytes.len() < 8 {
+        return Err("Insufficient randomness".to_string());
+    }
+    Ok(simulated_numbers(&random_bytes, count.min(MAX_SIMULATED_SPINS)))
+}
+
+fn simulated_numbers(vrf_bytes: &[u8], count: u32) -> Vec<u8> {
+    (0..count).map(|i| {
+        let mut hasher = Sha256::new();
+        hasher.update(vrf_bytes);
+        hasher.update(i.to_be_bytes());
+        bytes_to_number(&hasher.finalize())
+    }).collect()
+}
+
 /// Convert random bytes to a number 0-36
 /// Uses first 8 bytes as u64, mod 37 for fair distribution
 /// Bias is negligible: 37 divides into 2^64 almost evenly
@@ -251,6 +428,30 @@ fn evaluate_bet(bet: &Bet, winning: u8) -> BetResult {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_max_exposure_high_water_mark() {
+        use crate::exposure::{get_max_exposure_seen, record_exposure, reset_max_exposure};
+
+        // Inside and outside bets together: 36x + 2x + 3x
+        let bets = vec![
+            Bet { bet_type: BetType::Straight(17), amount: 1_000_000 },
+            Bet { bet_type: BetType::Red, amount: 5_000_000 },
+            Bet { bet_type: BetType::Dozen(2), amount: 2_000_000 },
+        ];
+        let exposure = calculate_max_possible_payout(&bets).unwrap();
+        assert_eq!(exposure, 36_000_000 + 10_000_000 + 6_000_000);
+
+        record_exposure(exposure);
+        assert_eq!(get_max_exposure_seen(), exposure);
+
+        let small = vec![Bet { bet_type: BetType::Black, amount: 1_000_000 }];
+        record_exposure(calculate_max_possible_payout(&small).unwrap());
+        assert_eq!(get_max_exposure_seen(), exposure, "A smaller spin keeps the high-water mark");
+
+        reset_max_exposure();
+        assert_eq!(get_max_exposure_seen(), 0);
+    }
+
     #[test]
     fn test_validate_straight() {
         let bet = Bet { bet_type: BetType::Straight(17), amount: 100 };
@@ -301,6 +502,66 @@ mod tests {
         assert!(validate_bet(&invalid_dozen).is_err());
     }
 
+    #[test]
+    fn test_validate_bets_list() {
+        assert!(validate_bets(&[]).is_err());
+
+        let ok = vec![
+            Bet { bet_type: BetType::Red, amount: MIN_BET },
+            Bet { bet_type: BetType::Split(1, 2), amount: MIN_BET },
+        ];
+        assert_eq!(validate_bets(&ok), Ok(2 * MIN_BET));
+
+        let too_small = vec![Bet { bet_type: BetType::Red, amount: MIN_BET - 1 }];
+        assert!(validate_bets(&too_small).is_err());
+
+        let too_many: Vec<Bet> = (0..=MAX_BETS_PER_SPIN)
+            .map(|_| Bet { bet_type: BetType::Red, amount: MIN_BET })
+            .collect();
+        assert!(validate_bets(&too_many).is_err());
+    }
+
+    #[test]
+    fn test_duplicate_straight_bets_merged() {
+        let bets = vec![
+            Bet { bet_type: BetType::Straight(17), amount: MIN_BET },
+            Bet { bet_type: BetType::Red, amount: MIN_BET },
+            Bet { bet_type: BetType::Straight(17), amount: 2 * MIN_BET },
+            Bet { bet_type: BetType::Split(2, 1), amount: MIN_BET },
+            Bet { bet_type: BetType::Split(1, 2), amount: MIN_BET },
+        ];
+        let merged = merge_duplicate_bets(&bets).unwrap();
+        assert_eq!(merged.len(), 3);
+        assert_eq!((merged[0].bet_type.clone(), merged[0].amount), (BetType::Straight(17), 3 * MIN_BET));
+        assert_eq!((merged[2].bet_type.clone(), merged[2].amount), (BetType::Split(2, 1), 2 * MIN_BET));
+        assert_eq!(validate_bets(&bets), Ok(6 * MIN_BET));
+
+        // One merged bet pays like a single stake of the combined amount
+        let spin = evaluate_spin(&merged, &[], 17, &ZeroRule::Standard);
+        assert_eq!(spin.bet_results[0].payout, 36 * 3 * MIN_BET);
+        assert_eq!(spin.total_payout, evaluate_spin(&bets, &[], 17, &ZeroRule::Standard).total_payout);
+    }
+
+    #[test]
+    fn test_duplicate_outside_bets_summed_against_limit() {
+        let limits = TableLimits { inside_min: MIN_BET, inside_max: 100 * MIN_BET, outside_min: MIN_BET, outside_max: 100 * MIN_BET };
+        crate::limits::set_table_limits(limits).unwrap();
+
+        let split_red = vec![
+            Bet { bet_type: BetType::Red, amount: 60 * MIN_BET },
+            Bet { bet_type: BetType::Red, amount: 60 * MIN_BET },
+        ];
+        let err = validate_bets(&split_red).unwrap_err();
+        assert!(err.contains("Bet 1 (Red) of 1200000 exceeds the outside maximum"), "{}", err);
+
+        // Overlapping but different positions are separate bets, each within the limit
+        let red_and_odd = vec![
+            Bet { bet_type: BetType::Red, amount: 60 * MIN_BET },
+            Bet { bet_type: BetType::Odd, amount: 60 * MIN_BET },
+        ];
+        assert_eq!(validate_bets(&red_and_odd), Ok(120 * MIN_BET));
+    }
+
     #[test]
     fn test_evaluate_straight_win() {
         let bet = Bet { bet_type: BetType::Straight(17), amount: 100 };
@@ -387,4 +648,55 @@ mod tests {
         let bytes = [0, 0, 0, 0, 0, 0, 0, 36, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
         assert_eq!(bytes_to_number(&bytes), 36);
     }
+
+    #[test]
+    fn test_simulated_spins_are_uniform() {
+        // 74_000 spins from 74 seeds: 2_000 expected per number
+        let mut counts = [0u64; 37];
+        for seed in 0..74u8 {
+            let numbers = simulated_numbers(&[seed; 32], MAX_SIMULATED_SPINS);
+            assert_eq!(numbers.len(), MAX_SIMULATED_SPINS as usize);
+            for n in numbers {
+                counts[n as usize] += 1;
+            }
+        }
+        let expected = 2_000.0;
+        let chi_square: f64 = counts.iter()
+            .map(|&c| (c as f64 - expected).powi(2) / expected)
+            .sum();
+        // 36 degrees of freedom: 67.99 is the p = 0.001 critical value
+        assert!(chi_square < 67.99, "chi-square {} over counts {:?}", chi_square, counts);
+
+        // Same seed, same draw: the audit sample is reproducible
+        assert_eq!(simulated_numbers(&[9; 32], 50), simulated_numbers(&[9; 32], 50));
+    }
+
+    #[test]
+    fn test_standard_rule_edges() {
+        let edges = effective_edges(&ZeroRule::Standard);
+        assert_eq!(edges.len(), 10);
+        for (name, edge) in edges {
+            assert!((edge - 100.0 / 37.0).abs() < 1e-9, "{}: {}", name, edge);
+        }
+    }
+
+    #[test]
+    fn test_en_prison_halves_even_money_edge() {
+        // Zero holds the stake; it comes back with probability 18/37 => edge 19/37^2
+        let en_prison = 100.0 * 19.0 / (37.0 * 37.0);
+        for (name, edge) in effective_edges(&ZeroRule::EnPrison) {
+            let expected = match name.as_str() {
+                "Red/Black" | "Even/Odd" | "Low/High" => en_prison,
+                _ => 100.0 / 37.0,
+            };
+            assert!((edge - expected).abs() < 1e-9, "{}: {} vs {}", name, edge, expected);
+        }
+    }
+
+    #[test]
+    fn test_edge_categories_match_payout_table() {
+        for (_, bet_type) in edge_categories() {
+            assert!(validate_bet(&Bet { bet_type, amount: MIN_BET }).is_ok());
+        }
+    }
 }
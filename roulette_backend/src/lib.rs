@@ -7,6 +7,7 @@
 //! **House Edge:**
 //! - European roulette: 1/37 ≈ 2.70% (zero gives house edge on all bets)
 //! - All payouts calculated fairly with this edge built in
+//! - Optional en prison rule (admin toggle) halves the edge on even-money bets
 //!
 //! **Transparency & Fairness:**
 //! - Randomness: IC VRF (raw_rand) - no fallback
@@ -27,6 +28,12 @@ mod defi_accounting;
 mod types;
 mod game;
 mod board;
+mod limits;
+mod presets;
+mod prison;
+mod streaks;
+mod exposure;
+mod vrf;
 
 pub use types::*;
 use board::{RED_NUMBERS, BLACK_NUMBERS};
@@ -49,13 +56,19 @@ thread_local! {
 #[init]
 fn init() {
     ic_cdk::println!("Roulette Backend Initialized with DeFi Accounting - European Roulette (2.70% house edge)");
+
+    // Fresh canisters start with the default solvency margin; upgrades keep theirs
+    defi_accounting::solvency::init_solvency_margin();
+
     defi_accounting::accounting::start_parent_withdrawal_timer();
     defi_accounting::accounting::start_balance_reconciliation_timer();
     defi_accounting::start_stats_timer();
+    defi_accounting::rebates::start_rebate_timer();
 
     // Initialize cached balance on fresh install using a one-shot timer
     ic_cdk_timers::set_timer(std::time::Duration::ZERO, async {
         defi_accounting::accounting::refresh_canister_balance().await;
+        defi_accounting::accounting::refresh_token_info().await;
         ic_cdk::println!("Init: balance cache initialized");
     });
 }
@@ -70,6 +83,7 @@ fn post_upgrade() {
     defi_accounting::accounting::start_parent_withdrawal_timer();
     defi_accounting::accounting::start_balance_reconciliation_timer();
     defi_accounting::start_stats_timer();
+    defi_accounting::rebates::start_rebate_timer();
 
     // Initialize cached balance immediately after upgrade
     ic_cdk_timers::set_timer(std::time::Duration::ZERO, async {
@@ -85,19 +99,7 @@ fn post_upgrade() {
 // ============================================================================
 
 fn is_canister_solvent() -> bool {
-    let pool_reserve = defi_accounting::liquidity_pool::get_pool_reserve();
-    let total_deposits = defi_accounting::accounting::calculate_total_deposits_internal();
-    let canister_balance = defi_accounting::accounting::get_cached_canister_balance_internal();
-
-    let obligations = match pool_reserve.checked_add(total_deposits) {
-        Some(o) => o,
-        None => {
-            ic_cdk::println!("CRITICAL: Obligations overflow u64::MAX");
-            return false;
-        }
-    };
-
-    canister_balance >= obligations
+    defi_accounting::solvency::is_canister_solvent()
 }
 
 // ============================================================================
@@ -109,11 +111,90 @@ fn is_canister_solvent() -> bool {
 #[update]
 async fn spin(bets: Vec<Bet>) -> Result<SpinResult, String> {
     if !is_canister_solvent() {
-        return Err("Game temporarily paused - insufficient funds.".to_string());
+        return Err(defi_accounting::types::GameError::Insolvent.into());
     }
     game::spin_with_betting(bets, ic_cdk::api::msg_caller()).await
 }
 
+/// Deposit via ICRC-2 and spin in one call. If the spin fails, the deposit stays in the balance.
+#[update]
+async fn deposit_and_play(deposit_amount: u64, bets: Vec<Bet>) -> Result<SpinResult, String> {
+    if !is_canister_solvent() {
+        return Err(defi_accounting::types::GameError::Insolvent.into());
+    }
+    let caller = ic_cdk::api::msg_caller();
+    defi_accounting::accounting::deposit_and_play(
+        defi_accounting::accounting::deposit_internal(caller, deposit_amount, None),
+        game::spin_with_betting(bets, caller),
+    ).await
+}
+
+/// Save (or overwrite) a named bet preset for the caller
+#[update]
+fn save_bet_preset(name: String, bets: Vec<Bet>) -> Result<(), String> {
+    presets::save_bet_preset(ic_cdk::api::msg_caller(), name, bets)
+}
+
+/// Delete one of the caller's bet presets
+#[update]
+fn delete_bet_preset(name: String) -> Result<(), String> {
+    presets::delete_bet_preset(ic_cdk::api::msg_caller(), &name)
+}
+
+/// Get all bet presets saved by the caller
+#[query]
+fn get_bet_presets() -> Vec<(String, Vec<Bet>)> {
+    presets::get_bet_presets(ic_cdk::api::msg_caller())
+}
+
+/// Spin using a saved preset (re-validated against current house limits)
+#[update]
+async fn spin_preset(name: String) -> Result<SpinResult, String> {
+    if !is_canister_solvent() {
+        return Err(defi_accounting::types::GameError::Insolvent.into());
+    }
+    let caller = ic_cdk::api::msg_caller();
+    let bets = presets::get_preset_bets(caller, &name)?;
+    game::spin_with_betting(bets, caller).await
+}
+
+/// Draw up to 1000 winning numbers with the live spin's number mapping, so the uniform
+/// distribution over 0-36 can be checked on-chain. Not a bet: no balance is touched.
+#[update]
+async fn simulate_spins(count: u32) -> Result<Vec<u8>, String> {
+    game::simulate_spins(count).await
+}
+
+/// Even-money bets the caller has held by en prison, resolved on their next spin
+#[query]
+fn get_my_imprisoned_bets() -> Vec<Bet> {
+    prison::get_imprisoned_bets(ic_cdk::api::msg_caller())
+}
+
+/// Get the active zero rule (Standard or EnPrison)
+#[query]
+fn get_zero_rule() -> ZeroRule {
+    prison::get_zero_rule()
+}
+
+/// Get the per-bet table limits for inside and outside bets
+#[query]
+fn get_table_limits() -> TableLimits {
+    limits::get_table_limits()
+}
+
+/// Current runs of consecutive reds, blacks, evens, odds, lows, highs and zeros
+#[query]
+fn get_current_streaks() -> StreakInfo {
+    streaks::get_current_streaks()
+}
+
+/// Largest maximum possible payout (all bets plus imprisoned stakes) of any settled spin
+#[query]
+fn get_max_exposure_seen() -> u64 {
+    exposure::get_max_exposure_seen()
+}
+
 /// Get maximum bet allowed (based on house balance)
 #[query]
 fn get_max_bet() -> u64 {
@@ -125,23 +206,28 @@ fn get_max_bet() -> u64 {
 // =============================================================================
 
 #[update]
-async fn deposit(amount: u64) -> Result<u64, String> {
-    defi_accounting::accounting::deposit(amount).await
+async fn deposit(amount: u64, idempotency_key: Option<[u8; 16]>) -> Result<defi_accounting::types::DepositReceipt, String> {
+    defi_accounting::accounting::deposit(amount, idempotency_key).await.map_err(|e| e.to_string())
 }
 
 #[update]
 async fn withdraw_all() -> Result<u64, String> {
-    defi_accounting::accounting::withdraw_all().await
+    defi_accounting::accounting::withdraw_all().await.map_err(|e| e.to_string())
+}
+
+#[update]
+async fn withdraw_keeping(keep_amount: u64) -> Result<u64, defi_accounting::types::GameError> {
+    defi_accounting::accounting::withdraw_keeping(keep_amount).await
 }
 
 #[update]
 async fn retry_withdrawal() -> Result<u64, String> {
-    defi_accounting::accounting::retry_withdrawal().await
+    defi_accounting::accounting::retry_withdrawal().await.map_err(|e| e.to_string())
 }
 
 #[update]
 fn abandon_withdrawal() -> Result<u64, String> {
-    defi_accounting::accounting::abandon_withdrawal()
+    defi_accounting::accounting::abandon_withdrawal().map_err(|e| e.to_string())
 }
 
 #[query]
@@ -149,6 +235,11 @@ fn get_my_withdrawal_status() -> Option<defi_accounting::types::PendingWithdrawa
     defi_accounting::accounting::get_withdrawal_status()
 }
 
+#[query]
+fn get_my_withdrawal_detail() -> Option<defi_accounting::types::WithdrawalStatusDetail> {
+    defi_accounting::accounting::get_withdrawal_status_detail()
+}
+
 #[query]
 fn get_balance(principal: Principal) -> u64 {
     defi_accounting::query::get_balance(principal)
@@ -169,6 +260,27 @@ fn get_max_allowed_payout() -> u64 {
     defi_accounting::query::get_max_allowed_payout()
 }
 
+#[query]
+fn get_token_info() -> defi_accounting::types::TokenInfo {
+    defi_accounting::query::get_token_info()
+}
+
+#[query]
+fn get_solvency_status() -> defi_accounting::types::SolvencyStatus {
+    defi_accounting::solvency::get_solvency_status()
+}
+
+#[query]
+fn get_my_lifetime_stats() -> defi_accounting::types::PlayerLifetimeStats {
+    defi_accounting::player_stats::get_player_stats(ic_cdk::api::msg_caller())
+}
+
+/// The caller's most recent settled games, newest first (at most 50 are kept)
+#[query]
+fn get_my_recent_games(limit: u32) -> Vec<defi_accounting::types::GameHistoryEntry> {
+    defi_accounting::game_history::get_recent_games(ic_cdk::api::msg_caller(), limit)
+}
+
 // =============================================================================
 // LIQUIDITY POOL ENDPOINTS
 // =============================================================================
@@ -188,6 +300,28 @@ fn get_pool_stats() -> defi_accounting::liquidity_pool::PoolStats {
     defi_accounting::query::get_pool_stats()
 }
 
+#[query]
+fn get_target_reserve() -> u64 {
+    defi_accounting::liquidity_pool::get_target_reserve()
+}
+
+#[query]
+fn get_rebate_config() -> defi_accounting::types::RebateConfig {
+    defi_accounting::rebates::get_rebate_config()
+}
+
+/// Caller's loyalty rebate accrual since their last rebate
+#[query]
+fn get_my_rebate_account() -> defi_accounting::types::RebateAccount {
+    defi_accounting::rebates::get_rebate_account(ic_cdk::api::msg_caller())
+}
+
+/// True while an admin has paused betting (e.g. during a migration)
+#[query]
+fn is_betting_paused() -> bool {
+    defi_accounting::pool_drain::is_betting_paused()
+}
+
 #[query]
 fn get_lp_position(principal: Principal) -> defi_accounting::liquidity_pool::LPPosition {
     defi_accounting::query::get_lp_position(principal)
@@ -237,6 +371,11 @@ fn admin_get_orphaned_funds_report_full() -> Result<defi_accounting::types::Orph
     defi_accounting::admin_query::get_orphaned_funds_report_full()
 }
 
+#[query]
+fn admin_reconcile_user(user: candid::Principal) -> Result<defi_accounting::types::ReconcileReport, String> {
+    defi_accounting::admin_query::reconcile_user(user)
+}
+
 #[query]
 fn admin_get_all_balances(offset: u64, limit: u64) -> Result<Vec<defi_accounting::types::UserBalance>, String> {
     defi_accounting::admin_query::get_all_balances(offset, limit)
@@ -267,6 +406,167 @@ fn admin_get_audit_log_count() -> Result<u64, String> {
     defi_accounting::admin_query::get_audit_log_count()
 }
 
+#[query]
+fn admin_get_audit_retention() -> Result<u64, String> {
+    defi_accounting::admin_query::get_audit_retention()
+}
+
+#[update]
+fn admin_set_audit_retention(limit: u64) -> Result<(), String> {
+    defi_accounting::admin_query::set_audit_retention(limit)
+}
+
+#[query]
+fn admin_get_parent_config() -> Result<defi_accounting::types::ParentWithdrawalConfig, String> {
+    defi_accounting::admin_query::get_parent_config()
+}
+
+#[update]
+fn admin_set_parent_config(threshold: u64, recipient: types::Account) -> Result<(), String> {
+    defi_accounting::admin_query::set_parent_config(threshold, recipient)
+}
+
+#[update]
+fn admin_set_betting_paused(paused: bool) -> Result<(), String> {
+    defi_accounting::admin_query::set_betting_paused(paused)
+}
+
+#[update]
+async fn admin_initiate_pool_drain(recipient: types::Account) -> Result<u64, String> {
+    // Imprisoned stakes are owed back from the pool on the player's next spin
+    let imprisoned = prison::imprisoned_player_count();
+    if imprisoned > 0 {
+        return Err(format!("{} players have imprisoned bets; resolve them before draining the pool", imprisoned));
+    }
+    defi_accounting::admin_query::initiate_pool_drain(recipient).await
+}
+
+#[query]
+fn admin_get_pending_drain() -> Result<Option<defi_accounting::types::PendingDrain>, String> {
+    defi_accounting::admin_query::get_pending_drain()
+}
+
+#[update]
+async fn admin_retry_pool_drain() -> Result<u64, String> {
+    defi_accounting::admin_query::retry_pool_drain().await
+}
+
+#[update]
+fn admin_resolve_pool_drain(landed: bool) -> Result<u64, String> {
+    defi_accounting::admin_query::resolve_pool_drain(landed)
+}
+
+#[update]
+fn admin_set_solvency_margin_bp(margin_bp: u64) -> Result<(), String> {
+    defi_accounting::admin_query::set_solvency_margin_bp(margin_bp)
+}
+
+#[update]
+fn admin_set_target_reserve(target: u64) -> Result<(), String> {
+    defi_accounting::admin_query::set_target_reserve(target)
+}
+
+#[update]
+fn admin_distribute_excess() -> Result<u64, String> {
+    defi_accounting::admin_query::distribute_excess()
+}
+
+#[update]
+fn admin_set_rebate_config(config: defi_accounting::types::RebateConfig) -> Result<(), String> {
+    defi_accounting::admin_query::set_rebate_config(config)
+}
+
+#[update]
+fn admin_process_rebates() -> Result<u64, String> {
+    defi_accounting::admin_query::process_rebates()
+}
+
+#[update]
+async fn admin_set_ledger_canister_id(ledger: candid::Principal) -> Result<defi_accounting::types::TokenInfo, String> {
+    defi_accounting::admin_query::set_ledger_canister_id(ledger).await
+}
+
+#[query]
+fn get_ledger_canister_id() -> candid::Principal {
+    defi_accounting::accounting::get_ledger_canister_id()
+}
+
+/// Select the zero rule for even-money bets (rules are mutually exclusive)
+#[update]
+fn admin_set_zero_rule(rule: ZeroRule) -> Result<(), String> {
+    defi_accounting::admin_query::require_admin()?;
+    prison::set_zero_rule(rule);
+    Ok(())
+}
+
+/// Set per-bet min/max limits for inside and outside bets
+#[update]
+fn admin_set_table_limits(limits: TableLimits) -> Result<(), String> {
+    defi_accounting::admin_query::require_admin()?;
+    limits::set_table_limits(limits)
+}
+
+/// Reset the max exposure high-water mark to 0
+#[update]
+fn admin_reset_max_exposure() -> Result<(), String> {
+    defi_accounting::admin_query::require_admin()?;
+    exposure::reset_max_exposure();
+    Ok(())
+}
+
+#[query]
+fn admin_get_event_subscriber() -> Result<Option<candid::Principal>, String> {
+    defi_accounting::admin_query::get_event_subscriber()
+}
+
+#[update]
+fn admin_set_event_subscriber(canister: candid::Principal) -> Result<(), String> {
+    defi_accounting::admin_query::set_event_subscriber(canister)
+}
+
+#[update]
+fn admin_clear_event_subscriber() -> Result<(), String> {
+    defi_accounting::admin_query::clear_event_subscriber()
+}
+
+/// Every admin-settable parameter as one candid blob, to clone onto another canister
+/// or restore later with `admin_import_config`
+#[query]
+fn admin_export_config() -> Result<types::GameConfigBlob, String> {
+    defi_accounting::admin_query::require_admin()?;
+    Ok(export_game_config())
+}
+
+/// Restore a blob from `admin_export_config`. Nothing changes unless every setting is valid.
+#[update]
+fn admin_import_config(blob: types::GameConfigBlob) -> Result<(), String> {
+    defi_accounting::admin_query::require_admin()?;
+    import_game_config(&blob, ic_cdk::api::time())
+}
+
+fn export_game_config() -> types::GameConfigBlob {
+    let config = types::GameConfig {
+        game: env!("CARGO_PKG_NAME").to_string(),
+        accounting: defi_accounting::config::export_config(),
+        zero_rule: prison::get_zero_rule(),
+        table_limits: limits::get_table_limits(),
+    };
+    candid::encode_one(config).expect("CRITICAL: Failed to encode GameConfig")
+}
+
+fn import_game_config(blob: &[u8], now: u64) -> Result<(), String> {
+    let config: types::GameConfig = candid::decode_one(blob)
+        .map_err(|e| format!("Invalid config blob: {}", e))?;
+    if config.game != env!("CARGO_PKG_NAME") {
+        return Err(format!("Config blob is for {}, not {}", config.game, env!("CARGO_PKG_NAME")));
+    }
+    defi_accounting::config::validate_config(&config.accounting)?;
+    limits::validate_table_limits(&config.table_limits)?;
+    defi_accounting::config::apply_config(config.accounting, now)?;
+    prison::set_zero_rule(config.zero_rule);
+    limits::set_table_limits(config.table_limits)
+}
+
 // =============================================================================
 // STATISTICS ENDPOINTS
 // =============================================================================
@@ -281,6 +581,17 @@ fn get_pool_apy(days: Option<u32>) -> defi_accounting::ApyInfo {
     defi_accounting::get_apy_info(days)
 }
 
+#[query]
+fn get_pool_apy_breakdown(days: Option<u32>) -> defi_accounting::ApyBreakdown {
+    defi_accounting::get_apy_breakdown(days)
+}
+
+/// (day_timestamp, reserve per share) for the last `days` days, to judge LP entry and exit timing
+#[query]
+fn get_share_price_history(days: u32) -> Vec<(u64, f64)> {
+    defi_accounting::get_share_price_history(days)
+}
+
 #[query]
 fn get_stats_range(start_ts: u64, end_ts: u64) -> Vec<defi_accounting::DailySnapshot> {
     defi_accounting::get_snapshots_range(start_ts, end_ts)
@@ -291,6 +602,27 @@ fn get_stats_count() -> u64 {
     defi_accounting::get_snapshot_count()
 }
 
+#[query]
+fn get_stats_summary() -> defi_accounting::types::StatsSummary {
+    defi_accounting::query::get_stats_summary("roulette")
+}
+
+/// Candid interface / stable layout version reported by `get_version_info`
+const SCHEMA_VERSION: u32 = 1;
+
+/// Game name, schema version, enabled features and build commit, so clients can adapt
+#[query]
+fn get_version_info() -> defi_accounting::types::VersionInfo {
+    let mut features = vec!["bet_presets".to_string()];
+    if prison::get_zero_rule() == ZeroRule::EnPrison {
+        features.push("en_prison".to_string());
+    }
+    if limits::get_table_limits() != TableLimits::default() {
+        features.push("table_limits".to_string());
+    }
+    defi_accounting::query::get_version_info("roulette", SCHEMA_VERSION, features)
+}
+
 // ============================================================================
 // ROULETTE-SPECIFIC QUERY ENDPOINTS
 // ============================================================================
@@ -361,6 +693,13 @@ fn get_payouts() -> Vec<PayoutInfo> {
     ]
 }
 
+/// Effective house edge (percent) per bet category under the active zero rule.
+/// Unlike `get_payouts`, this reflects optional rules such as en prison.
+#[query]
+fn get_effective_edges() -> Vec<(String, f64)> {
+    game::effective_edges(&prison::get_zero_rule())
+}
+
 /// Greet a player
 #[query]
 fn greet(name: String) -> String {
@@ -0,0 +1,131 @@
+// Table limits per bet category (inside vs outside), stored in stable memory
+//
+// Limits apply to each bet individually, on top of the global MIN_BET floor and the
+// house payout limit. Presets are checked again at spin time, so lowering a limit
+// also applies to presets saved earlier.
+
+use crate::types::*;
+use crate::defi_accounting::memory_ids::TABLE_LIMITS_MEMORY_ID;
+use crate::{MEMORY_MANAGER, Memory};
+use ic_stable_structures::memory_manager::MemoryId;
+use ic_stable_structures::StableCell;
+use std::cell::RefCell;
+
+thread_local! {
+    static TABLE_LIMITS: RefCell<StableCell<TableLimits, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(TABLE_LIMITS_MEMORY_ID))),
+            TableLimits::default()
+        )
+    );
+}
+
+/// Straight, Split, Street, Corner and SixLine are inside bets; everything else is outside
+pub fn is_inside_bet(bet_type: &BetType) -> bool {
+    matches!(
+        bet_type,
+        BetType::Straight(_) | BetType::Split(_, _) | BetType::Street(_) | BetType::Corner(_) | BetType::SixLine(_)
+    )
+}
+
+pub fn get_table_limits() -> TableLimits {
+    TABLE_LIMITS.with(|l| l.borrow().get().clone())
+}
+
+pub fn validate_table_limits(limits: &TableLimits) -> Result<(), String> {
+    for (category, min, max) in [
+        ("inside", limits.inside_min, limits.inside_max),
+        ("outside", limits.outside_min, limits.outside_max),
+    ] {
+        if min < MIN_BET {
+            return Err(format!("{} minimum cannot be below {} units", category, MIN_BET));
+        }
+        if min > max {
+            return Err(format!("{} minimum {} exceeds maximum {}", category, min, max));
+        }
+    }
+    Ok(())
+}
+
+/// Replace the table limits (admin check is done by the caller)
+pub fn set_table_limits(limits: TableLimits) -> Result<(), String> {
+    validate_table_limits(&limits)?;
+    TABLE_LIMITS.with(|l| l.borrow_mut().set(limits));
+    Ok(())
+}
+
+/// Check one bet against its category's limits. `index` is the bet's position in the spin.
+pub fn check_bet_limits(index: usize, bet: &Bet, limits: &TableLimits) -> Result<(), String> {
+    let (category, min, max) = if is_inside_bet(&bet.bet_type) {
+        ("inside", limits.inside_min, limits.inside_max)
+    } else {
+        ("outside", limits.outside_min, limits.outside_max)
+    };
+    if bet.amount < min {
+        return Err(format!(
+            "Bet {} ({:?}) of {} is below the {} minimum of {}",
+            index + 1, bet.bet_type, bet.amount, category, min
+        ));
+    }
+    if bet.amount > max {
+        return Err(format!(
+            "Bet {} ({:?}) of {} exceeds the {} maximum of {}",
+            index + 1, bet.bet_type, bet.amount, category, max
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::validate_bets;
+
+    fn limits() -> TableLimits {
+        TableLimits {
+            inside_min: 5 * MIN_BET,
+            inside_max: 100 * MIN_BET,
+            outside_min: MIN_BET,
+            outside_max: 1_000 * MIN_BET,
+        }
+    }
+
+    #[test]
+    fn test_inside_bet_below_inside_minimum() {
+        set_table_limits(limits()).unwrap();
+        let bets = vec![
+            Bet { bet_type: BetType::Red, amount: MIN_BET },
+            Bet { bet_type: BetType::Straight(17), amount: 4 * MIN_BET },
+        ];
+        let err = validate_bets(&bets).unwrap_err();
+        assert!(err.contains("Bet 2 (Straight(17))"), "{}", err);
+        assert!(err.contains("below the inside minimum"), "{}", err);
+
+        // The same amount is fine as an outside bet
+        assert!(validate_bets(&[Bet { bet_type: BetType::Dozen(1), amount: 4 * MIN_BET }]).is_ok());
+    }
+
+    #[test]
+    fn test_outside_bet_above_outside_maximum() {
+        set_table_limits(limits()).unwrap();
+        let bets = vec![Bet { bet_type: BetType::Black, amount: 1_000 * MIN_BET + 1 }];
+        let err = validate_bets(&bets).unwrap_err();
+        assert!(err.contains("Bet 1 (Black)"), "{}", err);
+        assert!(err.contains("exceeds the outside maximum"), "{}", err);
+
+        assert!(validate_bets(&[Bet { bet_type: BetType::Black, amount: 1_000 * MIN_BET }]).is_ok());
+    }
+
+    #[test]
+    fn test_limit_settings_validated() {
+        assert_eq!(get_table_limits(), TableLimits::default());
+        let mut bad = limits();
+        bad.inside_min = MIN_BET - 1;
+        assert!(set_table_limits(bad).is_err());
+
+        let mut inverted = limits();
+        inverted.outside_min = inverted.outside_max + 1;
+        assert!(set_table_limits(inverted).is_err());
+        assert_eq!(get_table_limits(), TableLimits::default());
+    }
+}
@@ -0,0 +1,163 @@
+// Named bet presets (per-principal, stored in stable memory)
+
+use crate::types::*;
+use crate::game::validate_bets;
+use crate::defi_accounting::memory_ids::BET_PRESETS_MEMORY_ID;
+use crate::{MEMORY_MANAGER, Memory};
+use candid::Principal;
+use ic_stable_structures::memory_manager::MemoryId;
+use ic_stable_structures::StableBTreeMap;
+use std::cell::RefCell;
+
+pub const MAX_PRESETS_PER_USER: usize = 10;
+pub const MAX_PRESET_NAME_LEN: usize = 32;
+
+thread_local! {
+    static BET_PRESETS: RefCell<StableBTreeMap<Principal, BetPresetList, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(BET_PRESETS_MEMORY_ID)))
+        )
+    );
+}
+
+fn validate_preset_name(name: &str) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("Preset name cannot be empty".to_string());
+    }
+    if name.len() > MAX_PRESET_NAME_LEN {
+        return Err(format!("Preset name too long (max {} bytes)", MAX_PRESET_NAME_LEN));
+    }
+    Ok(())
+}
+
+/// Save (or overwrite) a named preset for the caller.
+/// Bets are validated with the same rules as `spin` so a saved preset is always spinnable
+/// (subject to the house limit at spin time).
+pub fn save_bet_preset(caller: Principal, name: String, bets: Vec<Bet>) -> Result<(), String> {
+    if caller == Principal::anonymous() {
+        return Err("Anonymous principal cannot save presets".to_string());
+    }
+    validate_preset_name(&name)?;
+    validate_bets(&bets)?;
+
+    BET_PRESETS.with(|presets| {
+        let mut presets = presets.borrow_mut();
+        let mut list = presets.get(&caller).unwrap_or_default();
+
+        if let Some(existing) = list.presets.iter_mut().find(|p| p.name == name) {
+            existing.bets = bets;
+        } else {
+            if list.presets.len() >= MAX_PRESETS_PER_USER {
+                return Err(format!("Maximum {} presets per user", MAX_PRESETS_PER_USER));
+            }
+            list.presets.push(BetPreset { name, bets });
+        }
+
+        presets.insert(caller, list);
+        Ok(())
+    })
+}
+
+/// Delete a named preset. Returns an error if it does not exist.
+pub fn delete_bet_preset(caller: Principal, name: &str) -> Result<(), String> {
+    BET_PRESETS.with(|presets| {
+        let mut presets = presets.borrow_mut();
+        let mut list = presets.get(&caller).ok_or("Preset not found")?;
+        let before = list.presets.len();
+        list.presets.retain(|p| p.name != name);
+        if list.presets.len() == before {
+            return Err("Preset not found".to_string());
+        }
+
+        if list.presets.is_empty() {
+            presets.remove(&caller);
+        } else {
+            presets.insert(caller, list);
+        }
+        Ok(())
+    })
+}
+
+/// All presets for a principal as (name, bets) pairs, in save order
+pub fn get_bet_presets(caller: Principal) -> Vec<(String, Vec<Bet>)> {
+    BET_PRESETS.with(|presets| {
+        presets.borrow()
+            .get(&caller)
+            .map(|list| list.presets.into_iter().map(|p| (p.name, p.bets)).collect())
+            .unwrap_or_default()
+    })
+}
+
+/// Look up the bets of a single preset
+pub fn get_preset_bets(caller: Principal, name: &str) -> Result<Vec<Bet>, String> {
+    BET_PRESETS.with(|presets| {
+        presets.borrow()
+            .get(&caller)
+            .and_then(|list| list.presets.into_iter().find(|p| p.name == name))
+            .map(|p| p.bets)
+            .ok_or_else(|| format!("Preset '{}' not found", name))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(id: u8) -> Principal {
+        Principal::from_slice(&[id; 29])
+    }
+
+    fn red_bet() -> Vec<Bet> {
+        vec![Bet { bet_type: BetType::Red, amount: MIN_BET }]
+    }
+
+    #[test]
+    fn test_save_and_get_preset() {
+        let u = user(1);
+        save_bet_preset(u, "reds".to_string(), red_bet()).unwrap();
+
+        let presets = get_bet_presets(u);
+        assert_eq!(presets.len(), 1);
+        assert_eq!(presets[0].0, "reds");
+        assert_eq!(get_preset_bets(u, "reds").unwrap().len(), 1);
+        assert!(get_preset_bets(user(2), "reds").is_err());
+    }
+
+    #[test]
+    fn test_save_overwrites_same_name() {
+        let u = user(3);
+        save_bet_preset(u, "mix".to_string(), red_bet()).unwrap();
+        let two = vec![
+            Bet { bet_type: BetType::Black, amount: MIN_BET },
+            Bet { bet_type: BetType::Straight(17), amount: MIN_BET },
+        ];
+        save_bet_preset(u, "mix".to_string(), two).unwrap();
+
+        assert_eq!(get_bet_presets(u).len(), 1);
+        assert_eq!(get_preset_bets(u, "mix").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_preset_cap() {
+        let u = user(4);
+        for i in 0..MAX_PRESETS_PER_USER {
+            save_bet_preset(u, format!("p{}", i), red_bet()).unwrap();
+        }
+        assert!(save_bet_preset(u, "overflow".to_string(), red_bet()).is_err());
+
+        delete_bet_preset(u, "p0").unwrap();
+        assert!(save_bet_preset(u, "overflow".to_string(), red_bet()).is_ok());
+    }
+
+    #[test]
+    fn test_preset_validation_at_save() {
+        let u = user(5);
+        let bad_split = vec![Bet { bet_type: BetType::Split(1, 5), amount: MIN_BET }];
+        assert!(save_bet_preset(u, "bad".to_string(), bad_split).is_err());
+        assert!(save_bet_preset(u, "empty".to_string(), vec![]).is_err());
+        assert!(save_bet_preset(u, "".to_string(), red_bet()).is_err());
+        assert!(save_bet_preset(u, "x".repeat(MAX_PRESET_NAME_LEN + 1), red_bet()).is_err());
+        assert!(save_bet_preset(Principal::anonymous(), "reds".to_string(), red_bet()).is_err());
+        assert!(get_bet_presets(u).is_empty());
+    }
+}
@@ -0,0 +1,157 @@
+// En prison zero rule (per-principal imprisoned bets, stored in stable memory)
+//
+// When enabled and zero hits, even-money bets are not lost: the stake is held
+// for the player's next spin. If that spin's number matches the bet, the stake
+// is returned (no winnings); otherwise it is lost, including on a second zero.
+//
+// The held stake is settled to the pool when imprisoned and paid back out of the
+// pool on release, so pool accounting never sees an "in-between" amount.
+
+use crate::types::*;
+use crate::defi_accounting::memory_ids::{IMPRISONED_BETS_MEMORY_ID, ZERO_RULE_MEMORY_ID};
+use crate::{MEMORY_MANAGER, Memory};
+use candid::Principal;
+use ic_stable_structures::memory_manager::MemoryId;
+use ic_stable_structures::{StableBTreeMap, StableCell};
+use std::cell::RefCell;
+
+thread_local! {
+    static IMPRISONED_BETS: RefCell<StableBTreeMap<Principal, ImprisonedBets, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(IMPRISONED_BETS_MEMORY_ID)))
+        )
+    );
+
+    static ZERO_RULE: RefCell<StableCell<ZeroRule, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(ZERO_RULE_MEMORY_ID))),
+            ZeroRule::default()
+        )
+    );
+}
+
+/// Red/Black, Even/Odd and Low/High are the only bets eligible for en prison
+pub fn is_even_money(bet_type: &BetType) -> bool {
+    matches!(
+        bet_type,
+        BetType::Red | BetType::Black | BetType::Even | BetType::Odd | BetType::Low | BetType::High
+    )
+}
+
+pub fn get_zero_rule() -> ZeroRule {
+    ZERO_RULE.with(|r| r.borrow().get().clone())
+}
+
+/// Switch the active zero rule. Bets already imprisoned are still resolved on the
+/// player's next spin even if en prison is turned off.
+pub fn set_zero_rule(rule: ZeroRule) {
+    ZERO_RULE.with(|r| r.borrow_mut().set(rule));
+}
+
+/// Bets currently held for a principal
+pub fn get_imprisoned_bets(caller: Principal) -> Vec<Bet> {
+    IMPRISONED_BETS.with(|m| m.borrow().get(&caller).map(|p| p.bets).unwrap_or_default())
+}
+
+/// Total stake that could be returned to a principal on their next spin
+pub fn get_imprisoned_total(caller: Principal) -> u64 {
+    get_imprisoned_bets(caller).iter().fold(0u64, |acc, b| acc.saturating_add(b.amount))
+}
+
+/// Number of principals with bets held in prison
+pub fn imprisoned_player_count() -> u64 {
+    IMPRISONED_BETS.with(|m| m.borrow().len())
+}
+
+/// Remove and return a principal's held bets so they can be resolved
+pub fn take_imprisoned_bets(caller: Principal) -> Vec<Bet> {
+    IMPRISONED_BETS.with(|m| m.borrow_mut().remove(&caller).map(|p| p.bets).unwrap_or_default())
+}
+
+/// Hold bets for a principal's next spin (appends to anything already held)
+pub fn imprison_bets(caller: Principal, bets: Vec<Bet>) {
+    if bets.is_empty() {
+        return;
+    }
+    IMPRISONED_BETS.with(|m| {
+        let mut map = m.borrow_mut();
+        let mut held = map.get(&caller).unwrap_or_default();
+        held.bets.extend(bets);
+        map.insert(caller, held);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::evaluate_spin;
+
+    fn user(id: u8) -> Principal {
+        Principal::from_slice(&[id; 29])
+    }
+
+    fn red(amount: u64) -> Bet {
+        Bet { bet_type: BetType::Red, amount }
+    }
+
+    /// Spin once at zero with en prison enabled and hold the resulting bets
+    fn imprison_on_zero(u: Principal) {
+        set_zero_rule(ZeroRule::EnPrison);
+        let spin = evaluate_spin(
+            &[red(MIN_BET), Bet { bet_type: BetType::Straight(17), amount: MIN_BET }],
+            &take_imprisoned_bets(u),
+            0,
+            &get_zero_rule(),
+        );
+        assert_eq!(spin.imprisoned.len(), 1, "Only the even-money bet is imprisoned");
+        assert!(spin.bet_results.iter().all(|r| r.payout == 0));
+        imprison_bets(u, spin.imprisoned);
+        assert_eq!(get_imprisoned_total(u), MIN_BET);
+    }
+
+    #[test]
+    fn test_imprison_then_win() {
+        let u = user(1);
+        imprison_on_zero(u);
+
+        // 1 is red: held stake is returned, no winnings
+        let spin = evaluate_spin(&[red(MIN_BET)], &take_imprisoned_bets(u), 1, &get_zero_rule());
+        assert_eq!(spin.prison_results.len(), 1);
+        assert!(spin.prison_results[0].won);
+        assert_eq!(spin.prison_results[0].payout, MIN_BET);
+        assert_eq!(spin.total_payout, 2 * MIN_BET + MIN_BET);
+        assert!(get_imprisoned_bets(u).is_empty());
+    }
+
+    #[test]
+    fn test_imprison_then_lose() {
+        let u = user(2);
+        imprison_on_zero(u);
+
+        // 2 is black: held stake is lost
+        let spin = evaluate_spin(&[red(MIN_BET)], &take_imprisoned_bets(u), 2, &get_zero_rule());
+        assert!(!spin.prison_results[0].won);
+        assert_eq!(spin.prison_results[0].payout, 0);
+        assert_eq!(spin.total_payout, 0);
+        assert!(get_imprisoned_bets(u).is_empty());
+    }
+
+    #[test]
+    fn test_second_zero_loses_held_bet() {
+        let u = user(3);
+        imprison_on_zero(u);
+
+        let spin = evaluate_spin(&[red(MIN_BET)], &take_imprisoned_bets(u), 0, &get_zero_rule());
+        assert!(!spin.prison_results[0].won);
+        // The new even-money bet is imprisoned in its turn
+        assert_eq!(spin.imprisoned.len(), 1);
+    }
+
+    #[test]
+    fn test_standard_rule_loses_on_zero() {
+        set_zero_rule(ZeroRule::Standard);
+        let spin = evaluate_spin(&[red(MIN_BET)], &[], 0, &get_zero_rule());
+        assert!(spin.imprisoned.is_empty());
+        assert_eq!(spin.total_payout, 0);
+    }
+}
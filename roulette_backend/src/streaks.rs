@@ -0,0 +1,90 @@
+// Result streaks for "trends" displays (table-wide, stored in stable memory)
+//
+// One set of counters for the whole wheel, updated after every settled spin.
+
+use crate::types::*;
+use crate::board::get_color;
+use crate::defi_accounting::memory_ids::STREAKS_MEMORY_ID;
+use crate::{MEMORY_MANAGER, Memory};
+use ic_stable_structures::memory_manager::MemoryId;
+use ic_stable_structures::StableCell;
+use std::cell::RefCell;
+
+thread_local! {
+    static STREAKS: RefCell<StableCell<StreakInfo, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(STREAKS_MEMORY_ID))),
+            StreakInfo::default()
+        )
+    );
+}
+
+pub fn get_current_streaks() -> StreakInfo {
+    STREAKS.with(|s| s.borrow().get().clone())
+}
+
+/// Extend the runs the winning number belongs to and reset the others
+pub fn record_spin(winning_number: u8) {
+    STREAKS.with(|s| {
+        let mut cell = s.borrow_mut();
+        let mut streaks = cell.get().clone();
+        apply_spin(&mut streaks, winning_number);
+        cell.set(streaks);
+    });
+}
+
+fn apply_spin(streaks: &mut StreakInfo, n: u8) {
+    let color = get_color(n);
+    let bump = |count: &mut u32, hit: bool| *count = if hit { count.saturating_add(1) } else { 0 };
+
+    bump(&mut streaks.red, color == Color::Red);
+    bump(&mut streaks.black, color == Color::Black);
+    bump(&mut streaks.even, n != 0 && n.is_multiple_of(2));
+    bump(&mut streaks.odd, n != 0 && !n.is_multiple_of(2));
+    bump(&mut streaks.low, (1..=18).contains(&n));
+    bump(&mut streaks.high, (19..=36).contains(&n));
+    bump(&mut streaks.zero, n == 0);
+    streaks.last_number = Some(n);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_red_black_streaks_update_and_reset() {
+        // 1, 3, 5 are red; 2 is black
+        for n in [1, 3, 5] {
+            record_spin(n);
+        }
+        let streaks = get_current_streaks();
+        assert_eq!(streaks.red, 3);
+        assert_eq!(streaks.black, 0);
+        assert_eq!(streaks.odd, 3);
+        assert_eq!(streaks.low, 3);
+        assert_eq!(streaks.last_number, Some(5));
+
+        record_spin(2);
+        let streaks = get_current_streaks();
+        assert_eq!(streaks.red, 0, "Black ends the red run");
+        assert_eq!(streaks.black, 1);
+        assert_eq!(streaks.even, 1);
+        assert_eq!(streaks.odd, 0);
+        assert_eq!(streaks.low, 4);
+    }
+
+    #[test]
+    fn test_zero_breaks_every_even_money_run() {
+        for n in [20, 22, 0] {
+            record_spin(n);
+        }
+        let streaks = get_current_streaks();
+        assert_eq!(streaks, StreakInfo { zero: 1, last_number: Some(0), ..StreakInfo::default() });
+
+        record_spin(0);
+        assert_eq!(get_current_streaks().zero, 2);
+        record_spin(36);
+        let streaks = get_current_streaks();
+        assert_eq!((streaks.zero, streaks.red, streaks.even, streaks.high), (0, 1, 1, 1));
+    }
+}
@@ -1,7 +1,10 @@
 // European Roulette Type Definitions
 
 use candid::{CandidType, Deserialize, Principal};
+use ic_stable_structures::storable::Bound;
+use ic_stable_structures::Storable;
 use serde::Serialize;
+use std::borrow::Cow;
 
 // =============================================================================
 // CONSTANTS (Required by defi_accounting)
@@ -16,7 +19,7 @@ pub const CKUSDT_TRANSFER_FEE: u64 = 10_000; // 0.01 USDT
 // ICRC-2 TYPES (Required by defi_accounting)
 // =============================================================================
 
-#[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
 pub struct Account {
     pub owner: Principal,
     pub subaccount: Option<[u8; 32]>,
@@ -88,7 +91,7 @@ pub enum Color {
     Black,
 }
 
-#[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
 pub enum BetType {
     // Inside bets
     Straight(u8),           // Single number 0-36
@@ -131,6 +134,8 @@ pub struct SpinResult {
     pub total_payout: u64,
     pub net_result: i64,    // total_payout - total_bet (can be negative)
     pub randomness_hash: String,
+    pub imprisoned_bets: Vec<Bet>,          // Even-money bets held by en prison this spin
+    pub prison_results: Vec<BetResult>,     // Previously imprisoned bets resolved this spin
 }
 
 #[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
@@ -145,3 +150,174 @@ pub struct PayoutInfo {
     pub payout_multiplier: u8,
     pub description: String,
 }
+
+// =============================================================================
+// BET PRESETS
+// =============================================================================
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
+pub struct BetPreset {
+    pub name: String,
+    pub bets: Vec<Bet>,
+}
+
+/// All presets saved by a single principal (stored as one stable map value)
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, Default)]
+pub struct BetPresetList {
+    pub presets: Vec<BetPreset>,
+}
+
+impl Storable for BetPresetList {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(candid::encode_one(self).expect("CRITICAL: Failed to encode BetPresetList"))
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        self.to_bytes().into_owned()
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        candid::decode_one(&bytes).expect("CRITICAL: Failed to decode BetPresetList from stable storage")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// =============================================================================
+// ZERO RULES (EN PRISON)
+// =============================================================================
+
+/// What happens to even-money bets when zero hits.
+/// Only one rule can be active at a time.
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq, Eq, Default)]
+pub enum ZeroRule {
+    #[default]
+    Standard,   // Even-money bets lose on zero
+    EnPrison,   // Even-money bets are held for one more spin
+}
+
+impl Storable for ZeroRule {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(candid::encode_one(self).expect("CRITICAL: Failed to encode ZeroRule"))
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        self.to_bytes().into_owned()
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        candid::decode_one(&bytes).expect("CRITICAL: Failed to decode ZeroRule from stable storage")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// Even-money bets held for a principal until their next spin
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, Default)]
+pub struct ImprisonedBets {
+    pub bets: Vec<Bet>,
+}
+
+impl Storable for ImprisonedBets {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(candid::encode_one(self).expect("CRITICAL: Failed to encode ImprisonedBets"))
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        self.to_bytes().into_owned()
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        candid::decode_one(&bytes).expect("CRITICAL: Failed to decode ImprisonedBets from stable storage")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// =============================================================================
+// TABLE LIMITS
+// =============================================================================
+
+/// Per-bet min/max by category. Defaults: MIN_BET minimum, no table maximum
+/// (the house payout limit still applies).
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct TableLimits {
+    pub inside_min: u64,
+    pub inside_max: u64,
+    pub outside_min: u64,
+    pub outside_max: u64,
+}
+
+impl Default for TableLimits {
+    fn default() -> Self {
+        Self {
+            inside_min: MIN_BET,
+            inside_max: u64::MAX,
+            outside_min: MIN_BET,
+            outside_max: u64::MAX,
+        }
+    }
+}
+
+impl Storable for TableLimits {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(candid::encode_one(self).expect("CRITICAL: Failed to encode TableLimits"))
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        self.to_bytes().into_owned()
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        candid::decode_one(&bytes).expect("CRITICAL: Failed to decode TableLimits from stable storage")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// =============================================================================
+// RESULT STREAKS
+// =============================================================================
+
+/// Current run length of consecutive spins for each even-money outcome and zero.
+/// Zero breaks every even-money run; each count resets when its outcome misses.
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct StreakInfo {
+    pub red: u32,
+    pub black: u32,
+    pub even: u32,
+    pub odd: u32,
+    pub low: u32,
+    pub high: u32,
+    pub zero: u32,
+    pub last_number: Option<u8>,
+}
+
+impl Storable for StreakInfo {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(candid::encode_one(self).expect("CRITICAL: Failed to encode StreakInfo"))
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        self.to_bytes().into_owned()
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        candid::decode_one(&bytes).expect("CRITICAL: Failed to decode StreakInfo from stable storage")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// Candid-encoded `GameConfig`, as exported by `admin_export_config`
+pub type GameConfigBlob = Vec<u8>;
+
+/// Every admin-settable parameter of this game
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct GameConfig {
+    /// Crate name of the exporting game: a blob only imports into the same game
+    pub game: String,
+    pub accounting: crate::defi_accounting::types::AccountingConfig,
+    pub zero_rule: ZeroRule,
+    pub table_limits: TableLimits,
+}